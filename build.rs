@@ -4,12 +4,9 @@ use serde::Deserialize;
 
 const CONFIG_FILE: &'static str = "build_config.hjson";
 
-const FONT_FILE: &'static str = "OpenSans.ttf";
-
 #[derive(Deserialize)]
 struct Config {
     app_version: [u8;3],
-    include_default_font: bool,
     enable_debugging_features: bool,
 }
 
@@ -29,22 +26,6 @@ fn main() {
     // Errors if the environment variable doesn't exist.
     let build_type = std::env::var("PROFILE").unwrap();
 
-    let font_path = cwd.join("src").join(FONT_FILE);
-    // Download and include the default font if specified in the build config.
-    // It should only get downloaded if the file doesn't exist though.
-    if config.include_default_font {
-        if !font_path.exists() {
-            let font_url = "http://fonts.gstatic.com/s/opensans/v10/cJZKeOuBrn4kERxqtaUH3SZ2oysoEQEeKwjgmXLRnTc.ttf";
-            let font_bytes = {
-                let response = reqwest::blocking::get(font_url).expect("couldn't reach url of default font");
-
-                response.bytes().expect("couldn't download default font")
-            };
-            write_to_file(font_path, font_bytes.to_vec()).expect("couldn't write data of default font to file");
-        }
-        println!("cargo:rustc-cfg=default_font");
-    }
-
     if config.enable_debugging_features {
         println!("cargo:rustc-cfg=debug_features")
     }
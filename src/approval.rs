@@ -0,0 +1,58 @@
+use std::io::{ IsTerminal, Write };
+use std::path::Path;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::config::Config;
+use crate::parse::{ self, Parser };
+
+/// Asks once before a document referencing remote URLs or Lua scripts gets loaded, listing all of
+/// them, so presenters aren't surprised by network calls from a downloaded deck.
+///
+/// Resources starting with any of the `approved_resources` in the user configuration don't need
+/// to be approved (e.g. `https://cdn.example.com/` or `lua:` for all scripts). Scripts don't need
+/// to be approved in safe mode, since they don't run then. Without a terminal to ask on, the
+/// document only gets loaded if all of it's resources are approved in the configuration.
+///
+/// Returns an error if loading the document was declined.
+pub fn check(document: &Path, config: &Config) -> anyhow::Result<()> {
+    let contents = std::fs::read_to_string(document)?;
+    let Some(mut parser) = parse::get_parser(document) else {
+        return Ok(())
+    };
+    // Invalid documents get reported when loading them
+    let resources = parser.external_resources(&contents).unwrap_or_default();
+
+    let unapproved = resources.iter()
+        .filter(|resource| !(config.safe_mode && resource.starts_with(parse::LUA_PREFIX)))
+        .filter(|resource| !config.approved_resources.iter().any(|approved| resource.starts_with(approved.as_str())))
+        .collect::<Vec<_>>();
+    if unapproved.is_empty() {
+        return Ok(())
+    }
+
+    // Everything gets written to stderr, as the logger isn't initialized yet and stdout may be
+    // the output of the command (e.g. 'inspect --json')
+    eprintln!("{} references these remote resources and scripts:", document.display());
+    for resource in unapproved.iter() {
+        eprintln!("\t{resource}");
+    }
+
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("loading {} needs approval (add the resources to approved_resources in the user configuration)", document.display());
+    }
+
+    eprint!("Load it anyway? [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    match answer.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => {
+            eprintln!("Loading {} with {} approved resources", document.display(), unapproved.len());
+            Ok(())
+        },
+        _ => anyhow::bail!("loading {} was declined", document.display())
+    }
+}
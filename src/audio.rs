@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use rodio::{ Decoder, OutputStream, OutputStreamHandle, Sink };
+
+/// Sound effects configured in the document, played when navigating between slides.
+#[derive(Debug, Clone, Default)]
+pub struct SoundEffects {
+    /// Played when advancing to the next slide.
+    pub next: Option<String>,
+    /// Played when returning to the previous slide.
+    pub previous: Option<String>
+}
+
+/// Plays audio files on the default output device.
+/// 
+/// Only one file can be played at a time; starting a new one stops the previous one. Short sound
+/// effects are the exception, as they get played on top of it.
+pub struct AudioPlayer {
+    /// The output stream needs to be kept alive for as long as audio should be played.
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    sink: Option<Sink>,
+    muted: bool
+}
+
+impl AudioPlayer {
+    /// Opens the default audio output device.
+    pub fn new() -> anyhow::Result<Self> {
+        let (stream, handle) = OutputStream::try_default()?;
+
+        Ok(AudioPlayer { _stream: stream, handle, sink: None, muted: false })
+    }
+
+    /// Starts playing the audio file at the given path, stopping any currently playing audio.
+    pub fn play<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<()> {
+        self.stop();
+
+        let source = Decoder::new(BufReader::new(File::open(path)?))?;
+        let sink = Sink::try_new(&self.handle)?;
+        if self.muted {
+            sink.set_volume(0.0);
+        }
+        sink.append(source);
+
+        self.sink = Some(sink);
+        Ok(())
+    }
+
+    /// Plays a sound effect on top of any currently playing audio.
+    /// 
+    /// Does nothing while muted.
+    pub fn play_effect<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<()> {
+        if self.muted {
+            return Ok(())
+        }
+
+        let source = Decoder::new(BufReader::new(File::open(path)?))?;
+        let sink = Sink::try_new(&self.handle)?;
+        sink.append(source);
+        // Keeps playing after the handle got dropped
+        sink.detach();
+
+        Ok(())
+    }
+
+    /// Mutes or unmutes all audio.
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        if let Some(sink) = &self.sink {
+            sink.set_volume(if self.muted { 0.0 } else { 1.0 });
+        }
+    }
+
+    /// Stops the currently playing audio.
+    pub fn stop(&mut self) {
+        if let Some(sink) = self.sink.take() {
+            sink.stop();
+        }
+    }
+
+    /// Returns whether audio is currently being played.
+    pub fn is_playing(&self) -> bool {
+        self.sink.as_ref().map(|sink| !sink.empty()).unwrap_or(false)
+    }
+}
@@ -0,0 +1,94 @@
+//! Benchmarks for text layout, expression evaluation and rendering.
+//! 
+//! Criterion needs a library target to benchmark against, which this crate doesn't have, so the
+//! benchmarks run as the `bench` command instead. Only compiled with the `benchmarks` feature.
+
+use std::collections::HashMap;
+use std::time::{ Duration, Instant };
+
+use opengl_graphics::GlGraphics;
+
+use crate::presentation::{ self, Presentation };
+use crate::presentation::renderable::BaseProperties;
+use crate::presentation::util::{ self, ResExprType, DEFAULT_CONTEXT };
+
+/// The amount of slides in the synthetic deck.
+pub const SYNTHETIC_SLIDES: usize = 50;
+
+/// The amount of objects on each slide of the synthetic deck.
+pub const SYNTHETIC_OBJECTS_PER_SLIDE: usize = 40;
+
+/// The resolution slides get rendered at.
+const RENDER_SIZE: (u32, u32) = (1280, 720);
+
+/// A line of text using all kinds of formatting.
+const FORMATTED_LINE: &'static str = "Some **bold** and *italic* text, `1;0;0`red text``, ~48*h/720~larger text~~ and some more plain text to wrap";
+
+/// Generates a large document with many text and shape objects for benchmarking.
+pub fn synthetic_deck(slides: usize, objects_per_slide: usize) -> String {
+    let mut deck = String::from("{\n    fonts: {},\n    slides: [\n");
+    for slide in 0..slides {
+        deck.push_str("        {\n            background: [1, 1, 1],\n            content: [\n");
+        for object in 0..objects_per_slide {
+            let (x, y) = (object % 8, object / 8);
+            let entry = match object % 2 {
+                0 => format!("{{ type: \"Text\", pos: \"{}%;{}%+sin(t+{object})*2%\", size: \"12%;2%\", alignment: \"TOP_LEFT\", color: \"#202020\", font: \"Default\", text: [\"Slide {slide}\", \"{FORMATTED_LINE}\"], z: 1 }}", x * 12, y * 20),
+                _ => format!("{{ type: \"RoundedRect\", pos: \"{}%;{}%\", size: \"10%;15%\", alignment: \"TOP_LEFT\", color: \"0.9;0.9;1;1\", corner_rounding: \"1%\", z: 0 }}", x * 12, y * 20)
+            };
+            deck.push_str(&format!("                {entry},\n"));
+        }
+        deck.push_str("            ]\n        },\n");
+    }
+    deck.push_str("    ]\n}\n");
+    deck
+}
+
+/// Runs a function repeatedly and prints the mean, fastest and slowest time of a single run.
+fn measure<F: FnMut()>(name: &str, iterations: usize, mut f: F) {
+    let mut times: Vec<Duration> = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f();
+        times.push(start.elapsed());
+    }
+
+    let total: Duration = times.iter().sum();
+    let mean = total / iterations.max(1) as u32;
+    let min = times.iter().min().copied().unwrap_or_default();
+    let max = times.iter().max().copied().unwrap_or_default();
+    println!("{name:<32} mean {mean:>12.3?}   min {min:>12.3?}   max {max:>12.3?}   ({iterations} runs)");
+}
+
+/// Runs all benchmarks against a loaded presentation, which should be the [`synthetic_deck`].
+/// 
+/// Needs the fonts to be initialized.
+pub fn run(presentation: &Presentation, opengl: &mut GlGraphics) {
+    let fonts = &*crate::FONTS.get().unwrap();
+
+    measure("Text::parse", 1000, || {
+        let base = BaseProperties::new("0;0", "50%;3%", "0;0;0;1", "TOP_LEFT").map_err(|_|()).unwrap();
+        let text = presentation::Text::new(base, vec![FORMATTED_LINE; 10], "Default".to_owned(), fonts, HashMap::new(), "LEFT");
+        std::hint::black_box(text.map_err(|_|()).unwrap());
+    });
+
+    measure("text measuring", 1000, || {
+        let mut font = fonts.get("Default").unwrap().borrow_mut();
+        for size in [12.0, 24.0, 48.0] {
+            std::hint::black_box(font.base_font.size(FORMATTED_LINE, size));
+        }
+    });
+
+    let expr = util::res_dependent_expr("50%-h+h*easeOutPow(t-1,3)", DEFAULT_CONTEXT.clone(), ResExprType::WidthBased).map_err(|_|()).unwrap();
+    let object = HashMap::new();
+    let mut time = 0.0;
+    measure("expression evaluation", 100000, || {
+        time += 0.001;
+        std::hint::black_box(expr.evaluate(1280.0, 720.0, time, &object).ok());
+    });
+
+    let mut slide = 0;
+    measure("full-slide rendering", presentation.slide_count() * 4, || {
+        std::hint::black_box(presentation.render_thumbnail(slide, RENDER_SIZE, opengl));
+        slide = (slide + 1) % presentation.slide_count();
+    });
+}
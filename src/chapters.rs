@@ -0,0 +1,122 @@
+use std::fs::File;
+use std::io::{ BufWriter, Write };
+use std::path::Path;
+use std::time::Instant;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+/// The frame rate the timecodes of EDL files are written in.
+const EDL_FRAME_RATE: u64 = 30;
+
+/// The format chapter markers get written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChapterFormat {
+    /// One `MM:SS Title` line per chapter, as used in YouTube video descriptions.
+    YouTube,
+    /// A CMX 3600 edit decision list with one event per chapter, which most video editors can
+    /// import as markers.
+    Edl
+}
+
+impl ChapterFormat {
+    /// Chooses the format based on the extension of the given file: `.edl` files get an EDL,
+    /// all other files YouTube chapters.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("edl")) {
+            true => ChapterFormat::Edl,
+            false => ChapterFormat::YouTube
+        }
+    }
+}
+
+/// Writes a timestamped log of slide changes while presenting, so recordings of the talk can be
+/// split into chapters afterwards.
+///
+/// Timestamps are measured from the creation of the log, so the recording should be started
+/// at the same time as the viewer.
+pub struct ChapterLog {
+    writer: BufWriter<File>,
+    format: ChapterFormat,
+    start: Instant,
+    /// The slide shown in the current chapter, it's title and the time it started at in seconds.
+    current: Option<(usize, String, f64)>,
+    /// The number of the next EDL event.
+    events: usize
+}
+
+impl ChapterLog {
+    /// Creates the log at the given path, replacing any existing file.
+    pub fn create(path: &Path, title: Option<&str>) -> anyhow::Result<Self> {
+        let format = ChapterFormat::from_path(path);
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        if format == ChapterFormat::Edl {
+            writeln!(writer, "TITLE: {}", title.unwrap_or("Presentation"))?;
+            writeln!(writer, "FCM: NON-DROP FRAME\n")?;
+            writer.flush()?;
+        }
+
+        Ok(ChapterLog { writer, format, start: Instant::now(), current: None, events: 1 })
+    }
+
+    /// Starts a new chapter if the shown slide changed.
+    ///
+    /// YouTube chapters get written right away, so they survive crashes. EDL events need to know
+    /// their end, so they get written once the next chapter starts (or the log gets dropped).
+    pub fn slide_shown(&mut self, slide: usize, title: String) -> anyhow::Result<()> {
+        if self.current.as_ref().is_some_and(|(current, _, _)| *current == slide) {
+            return Ok(())
+        }
+
+        let now = self.start.elapsed().as_secs_f64();
+        match self.format {
+            ChapterFormat::YouTube => {
+                writeln!(self.writer, "{} {title}", youtube_timestamp(now))?;
+            },
+            ChapterFormat::Edl => self.write_edl_event(now)?
+        }
+        self.writer.flush()?;
+
+        self.current = Some((slide, title, now));
+        Ok(())
+    }
+
+    /// Writes the current chapter as an EDL event ending at the given time.
+    fn write_edl_event(&mut self, end: f64) -> anyhow::Result<()> {
+        if let Some((_, title, start)) = &self.current {
+            let (start, end) = (edl_timecode(*start), edl_timecode(end));
+            writeln!(self.writer, "{:03}  AX       V     C        {start} {end} {start} {end}", self.events)?;
+            writeln!(self.writer, "* FROM CLIP NAME: {title}\n")?;
+            self.events += 1;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ChapterLog {
+    fn drop(&mut self) {
+        if self.format == ChapterFormat::Edl {
+            let now = self.start.elapsed().as_secs_f64();
+            if let Err(e) = self.write_edl_event(now).and_then(|_| Ok(self.writer.flush()?)) {
+                log_err!("Writing the last chapter failed: {e}");
+            }
+        }
+    }
+}
+
+/// Formats a time in seconds as `MM:SS`, or `H:MM:SS` for times of an hour or longer.
+fn youtube_timestamp(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    match seconds / 3600 {
+        0 => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{hours}:{:02}:{:02}", seconds / 60 % 60, seconds % 60)
+    }
+}
+
+/// Formats a time in seconds as a `HH:MM:SS:FF` timecode at [`EDL_FRAME_RATE`].
+fn edl_timecode(seconds: f64) -> String {
+    let frames = (seconds * EDL_FRAME_RATE as f64).round() as u64;
+    let seconds = frames / EDL_FRAME_RATE;
+    format!("{:02}:{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60, frames % EDL_FRAME_RATE)
+}
@@ -0,0 +1,111 @@
+use crate::presentation::Slide;
+
+/// A ready-made slide bundled into the binary, which can be instantiated by the `generate`
+/// command and the editor.
+///
+/// The source of a component is a HJSON slide containing the placeholders `${background}`,
+/// `${text}`, `${accent}` and `${font}`, which get replaced with the document's [`Style`].
+/// Components showing a heading (like the section divider) contain `${heading}` in it's place.
+pub struct Component {
+    /// The name the component is referred to by on the command line.
+    pub name: &'static str,
+    /// The human readable name of the component, shown in the editor.
+    pub title: &'static str,
+    source: &'static str
+}
+
+/// The placeholder text of headings in components inserted by the user.
+const DEFAULT_HEADING: &'static str = "Section name";
+
+/// The name of the component used for automatically generated section dividers.
+pub const SECTION_DIVIDER: &'static str = "section";
+
+/// All bundled components.
+pub const COMPONENTS: &[Component] = &[
+    Component { name: "title", title: "Title slide", source: include_str!("title.hjson") },
+    Component { name: "section", title: "Section divider", source: include_str!("section.hjson") },
+    Component { name: "two_columns", title: "Two columns", source: include_str!("two_columns.hjson") },
+    Component { name: "quote", title: "Quote", source: include_str!("quote.hjson") }
+];
+
+/// The colors of a document, set in the `palette`-field of it's metadata.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    /// The color of the slides' backgrounds.
+    pub background: String,
+    /// The color of regular text.
+    pub text: String,
+    /// The color used for highlighting, e.g. for subtitles and dividers.
+    pub accent: String
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette { background: "#ffffff".to_owned(), text: "#202020".to_owned(), accent: "#3070c0".to_owned() }
+    }
+}
+
+/// The palette and font components get instantiated with.
+#[derive(Debug, Clone)]
+pub struct Style {
+    pub palette: Palette,
+    /// The name of a font registered in the document.
+    pub font: String
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style { palette: Palette::default(), font: "Default".to_owned() }
+    }
+}
+
+impl Component {
+    /// Returns the component with the given name.
+    pub fn find(name: &str) -> Option<&'static Component> {
+        COMPONENTS.iter().find(|component| component.name == name)
+    }
+
+    /// Returns the HJSON source of the component's slide with the placeholders replaced by the
+    /// given style.
+    pub fn instantiate(&self, style: &Style) -> String {
+        self.instantiate_with_heading(style, DEFAULT_HEADING)
+    }
+
+    /// Returns the HJSON source of the component's slide with the placeholders replaced by the
+    /// given style and heading.
+    /// 
+    /// The heading gets inserted into strings as is, so it mustn't contain quotes.
+    pub fn instantiate_with_heading(&self, style: &Style, heading: &str) -> String {
+        self.source.trim_end()
+            .replace("${heading}", heading)
+            .replace("${background}", &style.palette.background)
+            .replace("${text}", &style.palette.text)
+            .replace("${accent}", &style.palette.accent)
+            .replace("${font}", &style.font)
+    }
+
+    /// Creates the component's slide using the given style.
+    ///
+    /// Needs the fonts to be initialized.
+    pub fn slide(&self, style: &Style) -> anyhow::Result<Slide> {
+        let document = format!("{{ slides: [ {} ] }}", self.instantiate(style));
+        let slide_data = crate::parse::parse_document_bytes(document.as_bytes())?.pop()
+            .ok_or(anyhow::anyhow!("component \"{}\" doesn't contain a slide", self.name))?;
+
+        Ok(crate::engine::build_slide(slide_data, None))
+    }
+}
+
+/// Creates a HJSON document consisting of the given components, with the style's palette stored
+/// in it's metadata.
+pub fn document(components: &[&Component], style: &Style) -> String {
+    let slides = components.iter()
+        .map(|component| component.instantiate(style).lines().map(|line| format!("        {line}")).collect::<Vec<_>>().join("\n"))
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    format!(
+        "{{\n    meta: {{\n        palette: {{\n            background: \"{}\",\n            text: \"{}\",\n            accent: \"{}\"\n        }}\n    }},\n    fonts: {{}},\n    slides: [\n{slides}\n    ]\n}}\n",
+        style.palette.background, style.palette.text, style.palette.accent
+    )
+}
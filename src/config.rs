@@ -0,0 +1,671 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use piston::Key;
+use serde::Deserialize;
+
+use crate::lint::Severity;
+use crate::export::{ ImageFormat, SlideSelection };
+use crate::render::color_filter::ColorFilter;
+
+/// The name of the user configuration file.
+const CONFIG_FILE: &'static str = "config.hjson";
+
+/// The name of the directory containing the user configuration file.
+const CONFIG_DIR: &'static str = "apresentation";
+
+/// Per-user settings of the application.
+///
+/// Gets loaded from `~/.config/apresentation/config.hjson` (or the platform's equivalent) and
+/// can be overridden using command line flags. Any field missing from the file keeps its default
+/// value.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// The initial resolution of the window.
+    pub window_size: (u32, u32),
+    /// Whether vertical sync should be enabled.
+    pub vsync: bool,
+    /// The index of the monitor the window should be opened on and that gets used for fullscreen
+    /// mode. Uses the current monitor if unset.
+    pub monitor: Option<usize>,
+    /// (Part of) the name of the monitor the window should be opened on and that gets used for
+    /// fullscreen mode. Takes precedence over `monitor`, as indices aren't stable on some
+    /// multi-monitor setups.
+    pub monitor_name: Option<String>,
+    /// How fullscreen mode takes over the monitor.
+    pub fullscreen_mode: FullscreenMode,
+    /// The windowing backend used on Linux. Chosen by winit if unset.
+    pub backend: Option<WindowBackend>,
+    /// The keys bound to each action.
+    pub keybindings: Keybindings,
+    /// The theme used for the editor's UI.
+    pub editor_theme: EditorTheme,
+    /// The color (RGB) highlighting selected and hovered elements of the editor's UI. Uses the
+    /// theme's color if unset.
+    pub editor_accent: Option<[u8; 3]>,
+    /// The factor the editor's UI gets scaled by, e.g. for high-DPI screens and projectors.
+    pub editor_scale: f32,
+    /// Whether the viewer's window should stay on top of all other windows.
+    pub always_on_top: bool,
+    /// Whether the viewer's window should be opened without a title bar and borders.
+    pub frameless: bool,
+    /// Whether the viewer's window should be created with a transparent framebuffer.
+    ///
+    /// This also skips drawing the slides' backgrounds, so only the foreground objects are
+    /// visible (e.g. for compositing the presentation as an overlay in OBS).
+    pub transparent: bool,
+    /// A file or named pipe the viewer's rendered slides get written to as raw RGBA video. There's
+    /// no native NDI, Spout or Syphon output, but the stream can be published as one using ffmpeg.
+    pub video_output: Option<PathBuf>,
+    /// The amount of slides on each page of exported handouts.
+    pub handout_slides_per_page: usize,
+    /// Whether exported handouts should contain lines for taking notes next to each slide.
+    pub handout_note_lines: bool,
+    /// Whether exported handouts should show the speaker notes of each slide next to it.
+    pub handout_speaker_notes: bool,
+    /// The slides exported by `export`. All slides get exported if unset.
+    pub export_slides: Option<SlideSelection>,
+    /// The image format slides get exported as. Chosen by the extension of `export_file_name` if
+    /// unset.
+    pub export_format: Option<ImageFormat>,
+    /// The format documents get parsed as, by the file extension of a registered format (see
+    /// [`crate::parse::register_parser`]). Chosen by the extension of each document if unset.
+    pub document_format: Option<String>,
+    /// The factor the window size gets scaled by for the resolution of exported slides.
+    pub export_scale: f64,
+    /// The template of the file names of exported slides, relative to the document. Can contain
+    /// `{n}`, `{title}`, `{name}` and `{ext}` (see [`crate::export::images::file_name`]).
+    pub export_file_name: String,
+    /// Whether `export` keeps running and exports the slides that changed whenever the document
+    /// gets saved.
+    pub export_watch: bool,
+    /// Whether `merge` inserts a section divider titled after each merged document before it's
+    /// slides.
+    pub merge_dividers: bool,
+    /// The language whose text variants get shown initially.
+    pub language: Option<String>,
+    /// (Part of) the name of the MIDI input port the viewer listens on. Uses the first available
+    /// port if unset.
+    pub midi_port: Option<String>,
+    /// Whether the bounding boxes of all objects get drawn on top of the slides initially.
+    pub debug_outlines: bool,
+    /// The color vision deficiency simulated by the viewer and editor initially.
+    pub color_filter: ColorFilter,
+    /// Whether the viewer automatically switches to the next slide once the current slide's
+    /// duration has passed. Always enabled when writing to a video output.
+    pub auto_advance: bool,
+    /// Whether the editor underlines misspelled words.
+    pub spellcheck: bool,
+    /// The directory containing the dictionaries used for spell checking (e.g. `en_US.dic`).
+    pub dictionary_dir: Option<PathBuf>,
+    /// The speaking rate used for estimating how long presenting each slide takes.
+    pub words_per_minute: f64,
+    /// Whether `inspect` prints JSON instead of a human readable listing.
+    pub inspect_json: bool,
+    /// Whether `fmt` rewrites the file in place instead of printing the formatted document.
+    pub format_write: bool,
+    /// Whether `fmt` may rewrite files containing comments, which get lost when formatting.
+    pub format_strip_comments: bool,
+    /// Whether `golden` records the rendered slides as their new reference images instead of
+    /// comparing them (only available with the `golden_tests` feature).
+    pub golden_bless: bool,
+    /// The git revision (or `rehearsal`, for the last time the viewer presented the document)
+    /// `inspect` and the editor mark the slides changed since (see [`crate::review`]).
+    pub changed_since: Option<String>,
+    /// Whether documents get opened in safe mode, for decks from untrusted sources. Lua code
+    /// doesn't run (objects using it get replaced with a warning) and no data gets fetched over
+    /// the network.
+    pub safe_mode: bool,
+    /// Remote URLs and Lua scripts documents can reference without asking before loading them,
+    /// matched by their start (see [`crate::approval::check`]).
+    pub approved_resources: Vec<String>,
+    /// Whether text using undefined placeholders fails to load, instead of showing a marker.
+    pub strict_placeholders: bool,
+    /// How long the plain colored backgrounds of consecutive slides get faded into each other, in
+    /// seconds, for documents that don't specify a transition.
+    pub background_fade: f64,
+    /// The maximum amount of frames rendered per second. Unlimited if unset.
+    ///
+    /// Mostly useful with vsync disabled, as some projector setups behave better with a frame
+    /// limiter than with vertical sync.
+    pub max_fps: Option<u64>,
+    /// Whether the viewer periodically logs how many frames were dropped.
+    pub frame_stats: bool,
+    /// Whether the viewer starts on the slide that was shown when it was last closed.
+    pub resume: bool,
+    /// Whether pressing Esc closes the window.
+    pub exit_on_esc: bool,
+    /// Whether quitting the viewer using Esc needs to be confirmed by pressing it a second time.
+    /// The quit keys always need to be pressed twice and Ctrl+Q always quits right away.
+    pub confirm_quit: bool,
+    /// The effects the viewer draws around the mouse cursor.
+    pub pointer_effects: PointerEffects,
+    /// A file the viewer logs the slide changes to as chapter markers, for splitting recordings
+    /// of the talk into chapters. Gets written as an EDL if it ends with `.edl`, otherwise as
+    /// YouTube chapters.
+    pub chapters: Option<PathBuf>,
+    /// A file or named pipe the viewer reads short messages for the presenter from, one per line
+    /// (e.g. "5 minutes left"). They're shown on the window for a few seconds, but never
+    /// published to the video output.
+    pub messages: Option<PathBuf>,
+    /// Whether the viewer starts showing the teleprompter (the current slide's notes in large,
+    /// automatically scrolling text) instead of the slides. The notes scroll at
+    /// [`Config::words_per_minute`].
+    pub teleprompter: bool,
+    /// Whether the viewer registers as an MPRIS media player on the D-Bus session bus, so media
+    /// keys can navigate the slides. Only available on Linux.
+    pub media_controls: bool,
+    /// Dynamic data exposed to expressions and placeholders, e.g. for lobby displays.
+    pub data: DataProviders,
+    /// The thresholds and severities of the rules checked by `validate` and the editor.
+    pub lint: LintConfig,
+    /// Scheduled documents and automatic restarts of the viewer, for signage displays.
+    pub kiosk: KioskConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            window_size: (1280, 720),
+            vsync: false,
+            monitor: None,
+            monitor_name: None,
+            fullscreen_mode: FullscreenMode::Borderless,
+            backend: None,
+            keybindings: Keybindings::default(),
+            editor_theme: EditorTheme::Dark,
+            editor_accent: None,
+            editor_scale: 1.0,
+            always_on_top: false,
+            frameless: false,
+            transparent: false,
+            video_output: None,
+            handout_slides_per_page: 6,
+            handout_note_lines: false,
+            handout_speaker_notes: false,
+            export_slides: None,
+            export_format: None,
+            document_format: None,
+            export_scale: 1.0,
+            export_file_name: "{name}-{n}.{ext}".to_owned(),
+            export_watch: false,
+            merge_dividers: false,
+            language: None,
+            midi_port: None,
+            debug_outlines: false,
+            color_filter: ColorFilter::None,
+            auto_advance: false,
+            spellcheck: true,
+            dictionary_dir: None,
+            words_per_minute: 130.0,
+            inspect_json: false,
+            format_write: false,
+            format_strip_comments: false,
+            golden_bless: false,
+            changed_since: None,
+            safe_mode: false,
+            approved_resources: Vec::new(),
+            strict_placeholders: false,
+            background_fade: 0.3,
+            max_fps: None,
+            frame_stats: false,
+            resume: false,
+            exit_on_esc: true,
+            confirm_quit: false,
+            pointer_effects: PointerEffects::default(),
+            chapters: None,
+            messages: None,
+            teleprompter: false,
+            media_controls: true,
+            data: DataProviders::default(),
+            lint: LintConfig::default(),
+            kiosk: KioskConfig::default(),
+        }
+    }
+}
+
+/// Effects drawn on top of the slides following the mouse cursor, e.g. for making mouse-driven
+/// walkthroughs easier to follow on recordings.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PointerEffects {
+    /// Whether a fading trail follows the cursor.
+    pub trail: bool,
+    /// Whether clicking shows a ripple growing from the cursor.
+    pub ripple: bool,
+    /// The color of the trail and the ripples (RGBA, from 0 to 1).
+    pub color: [f32; 4],
+    /// How long each part of the trail stays visible, in seconds.
+    pub trail_duration: f64,
+    /// How long a ripple takes to fade out, in seconds.
+    pub ripple_duration: f64,
+    /// The radius a ripple grows to, relative to the height of the window.
+    pub ripple_radius: f64,
+}
+
+impl Default for PointerEffects {
+    fn default() -> Self {
+        PointerEffects {
+            trail: false,
+            ripple: false,
+            color: [1.0, 0.25, 0.2, 0.8],
+            trail_duration: 0.4,
+            ripple_duration: 0.5,
+            ripple_radius: 0.05,
+        }
+    }
+}
+
+/// The keys bound to each action of the viewer.
+///
+/// Key names are the same as the variants of [`piston::Key`] (e.g. `"Right"`, `"D"` or `"F11"`).
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Keybindings {
+    pub next_slide: Vec<Key>,
+    pub previous_slide: Vec<Key>,
+    pub fullscreen: Vec<Key>,
+    pub screenshot: Vec<Key>,
+    pub captions: Vec<Key>,
+    pub mute: Vec<Key>,
+    pub language: Vec<Key>,
+    pub debug_outlines: Vec<Key>,
+    /// Cycles through simulating color vision deficiencies and grayscale.
+    pub color_filter: Vec<Key>,
+    /// Pauses or resumes the slide time.
+    pub pause_time: Vec<Key>,
+    pub scrub_forward: Vec<Key>,
+    pub scrub_backward: Vec<Key>,
+    /// Replays the current slide's animations from the start.
+    pub restart_slide: Vec<Key>,
+    /// Pauses the slide time and advances it by a single frame.
+    pub step_frame: Vec<Key>,
+    /// Cycles through running the slide time at full, half and a tenth of the speed.
+    pub slow_motion: Vec<Key>,
+    /// Keys toggling the visibility of all objects with a given id.
+    pub toggle_objects: HashMap<String, Vec<Key>>,
+    /// Keys jumping to the slide with a given bookmark.
+    pub bookmarks: HashMap<String, Vec<Key>>,
+    /// Keys jumping to the first, second, third, ... bookmarked slide of the presentation.
+    pub bookmark_slots: Vec<Key>,
+    /// Closes the viewer once one of these keys gets pressed a second time, as a single stray
+    /// key press shouldn't end a talk.
+    pub quit: Vec<Key>,
+    /// Shows or hides the teleprompter.
+    pub teleprompter: Vec<Key>,
+    /// Makes the teleprompter scroll faster, only while it's shown.
+    pub teleprompter_faster: Vec<Key>,
+    /// Makes the teleprompter scroll slower, only while it's shown.
+    pub teleprompter_slower: Vec<Key>,
+    /// Shows or hides the overview of all slides.
+    pub overview: Vec<Key>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Keybindings {
+            next_slide: vec![Key::D, Key::Right],
+            previous_slide: vec![Key::A, Key::Left],
+            fullscreen: vec![Key::F11],
+            screenshot: vec![Key::F12],
+            captions: vec![Key::C],
+            mute: vec![Key::M],
+            language: vec![Key::L],
+            debug_outlines: vec![Key::F3],
+            color_filter: vec![Key::F8],
+            pause_time: vec![Key::P],
+            scrub_forward: vec![Key::Period],
+            scrub_backward: vec![Key::Comma],
+            restart_slide: vec![Key::Home],
+            step_frame: vec![Key::F6],
+            slow_motion: vec![Key::F7],
+            toggle_objects: HashMap::new(),
+            bookmarks: HashMap::new(),
+            bookmark_slots: vec![Key::D1, Key::D2, Key::D3, Key::D4, Key::D5, Key::D6, Key::D7, Key::D8, Key::D9],
+            quit: vec![Key::Q],
+            teleprompter: vec![Key::T],
+            teleprompter_faster: vec![Key::Up],
+            teleprompter_slower: vec![Key::Down],
+            overview: vec![Key::O],
+        }
+    }
+}
+
+/// The data providers available to expressions and placeholders (see
+/// [`crate::presentation::data`]). None of them are enabled by default.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct DataProviders {
+    /// The latitude and longitude (in degrees, north and east are positive) used for computing
+    /// the times of sunrise and sunset.
+    pub location: Option<(f64, f64)>,
+    /// Values fetched from JSON documents, by the name they're accessible by (`data_NAME`).
+    pub values: HashMap<String, DataSource>,
+}
+
+/// A JSON document a data value gets fetched from.
+#[derive(Deserialize, Clone, Debug)]
+pub struct DataSource {
+    /// The path of a local file or a `http://` or `https://` URL.
+    pub url: String,
+    /// A JSON pointer (e.g. `/current/temperature`) selecting the value inside the document.
+    /// Selects the whole document if empty.
+    #[serde(default)]
+    pub pointer: String,
+    /// How often the value gets fetched again, in seconds.
+    #[serde(default = "DataSource::default_interval")]
+    pub interval: f64,
+}
+
+impl DataSource {
+    fn default_interval() -> f64 {
+        60.0
+    }
+}
+
+/// The thresholds of the lint rules and the severity of each rule (see [`crate::lint`]).
+///
+/// Documents can override the severities in the `lint`-field of their metadata.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LintConfig {
+    /// The smallest font size allowed by `small_text`, in pixels on a 1080p screen.
+    pub min_text_size: f64,
+    /// The lowest contrast ratio between text and it's background allowed by `low_contrast`.
+    pub min_contrast: f64,
+    /// How much `upscaled_image` allows images to be scaled up.
+    pub max_upscale: f64,
+    /// The severity of each rule by it's name, rules missing here are warnings.
+    pub rules: HashMap<String, Severity>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig {
+            min_text_size: 24.0,
+            min_contrast: 4.5,
+            max_upscale: 2.0,
+            rules: HashMap::new(),
+        }
+    }
+}
+
+/// The settings of the viewer's watchdog for unattended signage displays (see
+/// [`crate::kiosk`]). Nothing gets scheduled or restarted by default.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct KioskConfig {
+    /// The documents shown at different times of the day. The document given on the command line
+    /// only gets shown if this is empty.
+    pub schedule: Vec<ScheduledDocument>,
+    /// The amount of hours after which the viewer restarts itself.
+    pub restart_after: Option<f64>,
+    /// The amount of render errors within a minute after which the viewer restarts itself.
+    pub max_render_errors: Option<u32>,
+}
+
+/// A document shown by the viewer from a time of the day on, until the next scheduled document.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ScheduledDocument {
+    /// The time of the day the document gets shown from (`HH:MM`, in local time).
+    pub from: String,
+    /// The path of the document.
+    pub document: PathBuf,
+}
+
+/// How fullscreen mode takes over the monitor.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FullscreenMode {
+    /// A borderless window covering the whole monitor.
+    Borderless,
+    /// Switches the monitor's video mode. Not supported on Wayland, where no fullscreen mode gets
+    /// entered instead.
+    Exclusive
+}
+
+/// A windowing backend available on Linux.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowBackend {
+    X11,
+    Wayland
+}
+
+impl WindowBackend {
+    /// Returns the name winit uses for the backend.
+    pub fn name(&self) -> &'static str {
+        match self {
+            WindowBackend::X11 => "x11",
+            WindowBackend::Wayland => "wayland"
+        }
+    }
+}
+
+/// The theme of the editor's UI.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EditorTheme {
+    Dark,
+    Light
+}
+
+impl Config {
+    /// Returns the path of the user configuration file.
+    ///
+    /// Returns [`None`] if no configuration directory could be determined.
+    pub fn path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(base.join(CONFIG_DIR).join(CONFIG_FILE))
+    }
+
+    /// Loads the user configuration file.
+    ///
+    /// Returns the default configuration if the file doesn't exist.
+    pub fn load() -> anyhow::Result<Config> {
+        let path = match Self::path() {
+            Some(p) if p.exists() => p,
+            _ => return Ok(Config::default())
+        };
+
+        let contents = std::fs::read_to_string(&path)?;
+
+        deser_hjson::from_str(contents.as_str())
+            .map_err(|e| anyhow::anyhow!("invalid config file at {}: {e}", path.display()))
+    }
+
+    /// Overrides the loaded settings with the supplied command line flags.
+    ///
+    /// Supported flags:
+    /// - `--size WIDTHxHEIGHT`
+    /// - `--vsync` / `--no-vsync`
+    /// - `--monitor INDEX|NAME`
+    /// - `--fullscreen borderless|exclusive`
+    /// - `--backend x11|wayland`
+    /// - `--theme dark|light`
+    /// - `--accent RRGGBB`
+    /// - `--ui-scale FACTOR`
+    /// - `--always-on-top`
+    /// - `--frameless`
+    /// - `--transparent`
+    /// - `--overlay` (shorthand for `--always-on-top --frameless`)
+    /// - `--video-output PATH`
+    /// - `--per-page N`
+    /// - `--notes`
+    /// - `--speaker-notes`
+    /// - `--slides 3,5-9`
+    /// - `--format png|jpg|webp`
+    /// - `--scale FACTOR`
+    /// - `--file-name TEMPLATE`
+    /// - `--watch`
+    /// - `--dividers`
+    /// - `--input-format FORMAT`
+    /// - `--language CODE`
+    /// - `--midi-port NAME`
+    /// - `--debug-outlines`
+    /// - `--color-filter none|protanopia|deuteranopia|tritanopia|grayscale`
+    /// - `--auto-advance`
+    /// - `--no-spellcheck`
+    /// - `--dictionaries PATH`
+    /// - `--wpm N`
+    /// - `--json`
+    /// - `--write`
+    /// - `--strip-comments`
+    /// - `--bless`
+    /// - `--changed-since REVISION|rehearsal`
+    /// - `--safe`
+    /// - `--strict-placeholders`
+    /// - `--background-fade SECONDS`
+    /// - `--max-fps N` (0 removes the limit)
+    /// - `--frame-stats`
+    /// - `--resume`
+    /// - `--no-esc-exit`
+    /// - `--confirm-quit`
+    /// - `--pointer-trail`
+    /// - `--click-ripple`
+    /// - `--chapters PATH`
+    /// - `--messages PATH`
+    /// - `--teleprompter`
+    /// - `--no-media-controls`
+    pub fn apply_args(&mut self, args: &[String]) -> anyhow::Result<()> {
+        let mut iter = args.iter();
+
+        while let Some(flag) = iter.next() {
+            let mut value = || iter.next().ok_or(anyhow::anyhow!("missing value for flag '{flag}'"));
+
+            match flag.as_str() {
+                "--size" => {
+                    let size = value()?;
+                    let (w, h) = size.split_once('x').ok_or(anyhow::anyhow!("invalid window size '{size}' (expected WIDTHxHEIGHT)"))?;
+                    self.window_size = (w.parse()?, h.parse()?);
+                },
+                "--vsync" => self.vsync = true,
+                "--no-vsync" => self.vsync = false,
+                "--monitor" => {
+                    let monitor = value()?;
+                    match monitor.parse() {
+                        Ok(index) => {
+                            self.monitor = Some(index);
+                            self.monitor_name = None;
+                        },
+                        Err(_) => self.monitor_name = Some(monitor.clone())
+                    }
+                },
+                "--fullscreen" => self.fullscreen_mode = match value()?.as_str() {
+                    "borderless" => FullscreenMode::Borderless,
+                    "exclusive" => FullscreenMode::Exclusive,
+                    other => anyhow::bail!("unknown fullscreen mode '{other}'")
+                },
+                "--backend" => self.backend = Some(match value()?.as_str() {
+                    "x11" => WindowBackend::X11,
+                    "wayland" => WindowBackend::Wayland,
+                    other => anyhow::bail!("unknown windowing backend '{other}'")
+                }),
+                "--theme" => self.editor_theme = match value()?.as_str() {
+                    "dark" => EditorTheme::Dark,
+                    "light" => EditorTheme::Light,
+                    other => anyhow::bail!("unknown theme '{other}'")
+                },
+                "--accent" => {
+                    let hex = value()?;
+                    let rgb = u32::from_str_radix(hex.trim_start_matches('#'), 16).ok().filter(|_| hex.trim_start_matches('#').len() == 6)
+                        .ok_or(anyhow::anyhow!("invalid accent color '{hex}' (expected RRGGBB)"))?;
+                    self.editor_accent = Some([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8]);
+                },
+                "--ui-scale" => {
+                    let scale: f32 = value()?.parse()?;
+                    if !(scale > 0.0) {
+                        anyhow::bail!("the UI scale needs to be positive");
+                    }
+                    self.editor_scale = scale;
+                },
+                "--always-on-top" => self.always_on_top = true,
+                "--frameless" => self.frameless = true,
+                "--transparent" => self.transparent = true,
+                "--overlay" => {
+                    self.always_on_top = true;
+                    self.frameless = true;
+                },
+                "--video-output" => self.video_output = Some(PathBuf::from(value()?)),
+                "--per-page" => self.handout_slides_per_page = value()?.parse()?,
+                "--notes" => self.handout_note_lines = true,
+                "--speaker-notes" => self.handout_speaker_notes = true,
+                "--slides" => self.export_slides = Some(SlideSelection::try_from(value()?.clone())?),
+                "--format" => {
+                    let name = value()?;
+                    self.export_format = Some(ImageFormat::from_name(name).ok_or(anyhow::anyhow!("invalid image format '{name}' (expected png, jpg or webp)"))?);
+                },
+                "--scale" => {
+                    let scale: f64 = value()?.parse()?;
+                    if !(scale > 0.0) {
+                        anyhow::bail!("the export scale needs to be positive");
+                    }
+                    self.export_scale = scale;
+                },
+                "--file-name" => self.export_file_name = value()?.clone(),
+                "--watch" => self.export_watch = true,
+                "--dividers" => self.merge_dividers = true,
+                "--input-format" => {
+                    let name = value()?.to_lowercase();
+                    let formats = crate::parse::registered_formats();
+                    if !formats.contains(&name) {
+                        anyhow::bail!("unknown document format '{name}' (expected one of: {})", formats.join(", "));
+                    }
+                    self.document_format = Some(name);
+                },
+                "--language" => self.language = Some(value()?.clone()),
+                "--midi-port" => self.midi_port = Some(value()?.clone()),
+                "--debug-outlines" => self.debug_outlines = true,
+                "--color-filter" => {
+                    let name = value()?;
+                    self.color_filter = ColorFilter::from_name(name).ok_or(anyhow::anyhow!("invalid color filter '{name}' (expected none, protanopia, deuteranopia, tritanopia or grayscale)"))?;
+                },
+                "--auto-advance" => self.auto_advance = true,
+                "--no-spellcheck" => self.spellcheck = false,
+                "--dictionaries" => self.dictionary_dir = Some(PathBuf::from(value()?)),
+                "--wpm" => {
+                    let wpm: f64 = value()?.parse()?;
+                    if !(wpm > 0.0) {
+                        anyhow::bail!("the speaking rate needs to be positive");
+                    }
+                    self.words_per_minute = wpm;
+                },
+                "--json" => self.inspect_json = true,
+                "--write" => self.format_write = true,
+                "--strip-comments" => self.format_strip_comments = true,
+                "--bless" => self.golden_bless = true,
+                "--changed-since" => self.changed_since = Some(value()?.clone()),
+                "--safe" => self.safe_mode = true,
+                "--strict-placeholders" => self.strict_placeholders = true,
+                "--background-fade" => {
+                    let duration: f64 = value()?.parse()?;
+                    if !(duration >= 0.0) {
+                        anyhow::bail!("the background fade duration can't be negative");
+                    }
+                    self.background_fade = duration;
+                },
+                "--max-fps" => self.max_fps = match value()?.parse()? {
+                    0 => None,
+                    fps => Some(fps)
+                },
+                "--frame-stats" => self.frame_stats = true,
+                "--resume" => self.resume = true,
+                "--no-esc-exit" => self.exit_on_esc = false,
+                "--confirm-quit" => self.confirm_quit = true,
+                "--pointer-trail" => self.pointer_effects.trail = true,
+                "--click-ripple" => self.pointer_effects.ripple = true,
+                "--chapters" => self.chapters = Some(PathBuf::from(value()?)),
+                "--messages" => self.messages = Some(PathBuf::from(value()?)),
+                "--teleprompter" => self.teleprompter = true,
+                "--no-media-controls" => self.media_controls = false,
+                _ => anyhow::bail!("unknown flag '{flag}'")
+            }
+        }
+
+        Ok(())
+    }
+}
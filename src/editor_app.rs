@@ -1,10 +1,9 @@
-use std::collections::HashMap;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::collections::{ HashMap, HashSet };
+use std::path::{ Path, PathBuf };
 use std::time::Instant;
 
 use opengl_graphics::{ GlGraphics, OpenGL, Texture, Filter };
-use piston::{RenderArgs, UpdateArgs, ButtonArgs, Button, ButtonState, Key};
+use piston::{RenderArgs, UpdateArgs, ButtonArgs, Button, ButtonState, Key, MouseButton};
 use piston_window::PistonWindow;
 
 use egui::{ RawInput, FullOutput, Context, TextureId };
@@ -12,13 +11,81 @@ use egui::{ RawInput, FullOutput, Context, TextureId };
 #[allow(unused)]
 use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
 
-use super::util::{ PanickingOption, AssumeThreadSafe };
+use super::util::PanickingOption;
 use super::presentation;
+use super::engine;
+use super::components;
+use super::lint;
+use super::review;
+use super::render::slide_view::SlideView;
+use super::render::color_filter::ColorFilter;
+use super::spellcheck::{ self, SpellChecker };
 
 // Gets used for automatic links in comments.
 #[allow(unused)]
 use crate::presentation::Renderable;
-use crate::presentation::renderable::BaseProperties;
+
+/// The color of the outline showing the current slide's safe area.
+const SAFE_AREA_GUIDE_COLOR: [f32; 4] = [1.0, 0.2, 0.6, 0.8];
+
+/// The color of the line beneath misspelled words.
+const SPELLING_UNDERLINE_COLOR: [f32; 4] = [0.9, 0.1, 0.1, 0.9];
+
+/// The color of the outline around the object selected in the outline panel.
+const SELECTED_OBJECT_COLOR: [f32; 4] = [0.1, 0.5, 1.0, 1.0];
+
+/// The zoom factor of the slide preview when it's fit to the window, leaving space for the UI.
+const PREVIEW_FIT_ZOOM: f64 = 0.5;
+
+/// The position of an object inside the presentation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ObjectLocation {
+    slide: usize,
+    z_index: i16,
+    /// The index of the object inside it's layer.
+    index: usize
+}
+
+/// A change made through the outline panel, applied after the UI has been built.
+enum OutlineAction {
+    Select(ObjectLocation),
+    /// Moves an object to the layer with the given z-index.
+    Move(ObjectLocation, i16),
+    /// Copies the current slide into the editor's clipboard.
+    CopySlide,
+    /// Copies the selected object into the editor's clipboard.
+    CopyObject(ObjectLocation),
+    /// Pastes the content of the editor's clipboard into the current deck.
+    Paste,
+    /// Switches to the deck with the given index.
+    SwitchDeck(usize),
+    /// Inserts a bundled component after the current slide.
+    InsertComponent(&'static components::Component),
+    /// Checks the current deck with the lint rules and shows the findings.
+    Lint
+}
+
+/// A presentation file opened in a tab of the editor.
+struct Deck {
+    path: PathBuf,
+    /// The deck's presentation while another deck is shown.
+    /// 
+    /// The shown deck's presentation, slide time and selection are stored in the [`AppData`]
+    /// directly and get swapped with the ones stored here when switching tabs.
+    presentation: presentation::Presentation,
+    time: f64,
+    selected: Option<ObjectLocation>,
+    /// The content hashes of the deck's slides at the baseline set using `--changed-since`, if
+    /// there is one. Slides whose hash isn't part of it get marked as changed in the outline.
+    baseline: Option<HashSet<u64>>
+}
+
+/// Slides or objects copied in the editor, which can be pasted into any open deck.
+enum EditorClipboard {
+    Slide(presentation::Slide),
+    /// An object and the z-index of the layer it was copied from.
+    Object(Box<dyn Renderable>, i16)
+}
 
 pub struct Application {
     pub opengl_version: OpenGL,
@@ -50,8 +117,17 @@ pub struct AppData {
     /// Only enabled in debug relases or with the 'debug_features' feature-flag.
     #[cfg(any(debug_features))]
     frames: u32,
-    /// Captures the state for the left/A, right/D and F11 keys.
+    /// Captures the state for the previous slide, next slide and fullscreen keys.
     last_press: (bool, bool, bool),
+    /// Whether a control key is currently being held down.
+    ctrl_held: bool,
+    /// Whether a shift key is currently being held down.
+    shift_held: bool,
+    /// Whether the outlines of the objects get drawn over the previewed slide.
+    debug_outlines: bool,
+    /// The changes requested with keyboard shortcuts, applied together with the ones made in the
+    /// UI.
+    pending_actions: Vec<OutlineAction>,
     /// Contains all the data passed from the application to [`egui`].
     egui_input: RawInput,
     /// Contains all the data passed from [`egui`] to the application.
@@ -62,73 +138,47 @@ pub struct AppData {
     egui_textures: HashMap<TextureId, Texture>,
 
     egui_time: f64,
+    /// The zoomable and pannable view onto the previewed slide.
+    view: SlideView,
+    /// The last known position of the mouse cursor in window coordinates.
+    cursor: [f64; 2],
+    /// The spell checker for the document's language.
+    /// 
+    /// Is [`None`] if spell checking is disabled or no dictionary is available.
+    spellchecker: Option<SpellChecker>,
+    /// The misspelled words on the current slide and the suggestions for them.
+    misspellings: Vec<(String, Vec<String>)>,
+    /// The object selected in the outline panel.
+    selected: Option<ObjectLocation>,
+    /// The last time the font files were checked for changes.
+    last_font_check: Instant,
+    /// All opened presentation files, in the order of their tabs.
+    decks: Vec<Deck>,
+    /// The index of the currently shown deck.
+    current_deck: usize,
+    /// The slide or object copied last.
+    clipboard: Option<EditorClipboard>,
+    /// The findings of the last lint pass, shown until the window gets closed.
+    lint_findings: Option<Vec<lint::Finding>>,
+    /// The color vision deficiency simulated on the previewed slide.
+    color_filter: ColorFilter,
+    /// The texture the filtered frame gets drawn with, reused between frames.
+    color_filter_texture: Option<Texture>,
 }
 impl AppData {
-    pub fn create(filepath: String) -> AppData {
-        use crate::parse::{ self, Parser };
-
-        // Read the contents of the presentation file
-        let filecontents: String = std::fs::read_to_string(filepath.as_str()).unwrap();
-
-        // Create an instance of a parser (which parser gets instantiated depends on the file extension)
-        let mut parser = parse::get_parser(filepath.as_str()).expect("No parser found for file type!");
+    pub fn create(filepaths: Vec<String>) -> AppData {
+        // All decks share the font registry, so they get loaded together
+        let mut documents = engine::load_documents(&filepaths.iter().map(|path| path.as_str()).collect::<Vec<_>>()).into_iter();
+        let presentation = documents.next().expect("no presentation file to edit").presentation;
 
-        let document_fonts = parser.parse_fonts(filecontents.as_str()).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
-        crate::FONTS.set({
-            let mut map = HashMap::new();
+        let baseline = crate::CONFIG.get().unwrap().changed_since.as_deref().map(review::Baseline::from_name);
+        let baseline_hashes = |path: &str| baseline.as_ref().and_then(|baseline| baseline.hashes(Path::new(path))
+            .map_err(|e| log_warn!("Can't mark the slides of {path} changed since {}: {e}", baseline.describe())).ok());
 
-            // Adds the default font in case it was included into the binary at compile time.
-            #[cfg(default_font)]
-            {
-                let bytes = include_bytes!("OpenSans.ttf") as &[u8];
-
-                // let face = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default()).expect("couldn't parse default font's data");
-
-                let base_font = crate::render::font::Font::from_bytes(bytes.to_vec(), 0, "Default (bundled)".to_owned()).expect("couldn't parse default font's data");
-                let bold_font = crate::render::font::Font::from_bytes(bytes.to_vec(), 0, "Default (bundled)".to_owned()).expect("couldn't parse default font's data");
-
-                map.insert("Default".to_owned(), Rc::new(RefCell::new(presentation::TextFont { base_font, bold_font })));
-            }
-
-            for (name, path) in document_fonts {
-                map.insert(name, Rc::new(RefCell::new(presentation::renderable::TextFont::new(path.0, path.1))));
-            }
-
-            AssumeThreadSafe(map)
-        }).ok().expect("error initializing fonts");
-
-        let document = parser.parse(filecontents.as_str()).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
-
-        let mut presentation = presentation::Presentation::new();
-
-        for slide_data in document {
-            let mut slide = presentation::Slide::new(slide_data.background);
-            for (z, content) in slide_data.content {
-                for renderable in content {
-                    slide.add_boxed(renderable, z);
-                }
-            }
-            presentation.add_slide(slide);
-        }
-
-        // Adds an 'End of presentation' slide. This can only be done when including the default
-        // font though, as the text needs a font to render itself.
-        #[cfg(default_font)]
-        {
-            let bg = presentation::ColoredRect::new(BaseProperties::new("0;0", "w;h", "0;0;0;1", "TOP_LEFT").map_err(|_|()).unwrap());
-            let mut last_slide = presentation::Slide::new(Box::new(bg) as Box<dyn presentation::Renderable>);
-
-            let text = presentation::Text::new(
-                BaseProperties::new("0;0","w;4%","1;1;1;1","TOP_LEFT").map_err(|_|()).unwrap(),
-                vec!["End of presentation"],
-                "Default".to_owned(),
-                &*crate::FONTS.get().unwrap(),
-                HashMap::new(),
-                "LEFT").map_err(|_|()).unwrap();
-            last_slide.add(text, 0);
-
-            presentation.add_slide(last_slide);
-        }
+        // The first deck is shown initially, so it's entry only keeps the file's path
+        let decks = filepaths.iter().zip(std::iter::once(presentation::Presentation::new()).chain(documents.map(|d| d.presentation)))
+            .map(|(path, presentation)| Deck { path: PathBuf::from(path), presentation, time: 0.0, selected: None, baseline: baseline_hashes(path) })
+            .collect();
 
         AppData {
             presentation,
@@ -139,15 +189,62 @@ impl AppData {
             #[cfg(any(debug_features))]
             frames: 0,
             last_press: (false, false, false),
+            ctrl_held: false,
+            shift_held: false,
+            debug_outlines: crate::CONFIG.get().unwrap().debug_outlines,
+            pending_actions: Vec::new(),
             egui_input: Default::default(),
             egui_output: Default::default(),
-            egui_context: Default::default(),
+            egui_context: {
+                let context = Context::default();
+                context.set_visuals(editor_visuals(crate::CONFIG.get().unwrap()));
+                context.set_pixels_per_point(crate::CONFIG.get().unwrap().editor_scale);
+                context
+            },
             egui_textures: HashMap::new(),
-            egui_time: 0.0
+            egui_time: 0.0,
+            view: SlideView::new(PREVIEW_FIT_ZOOM),
+            cursor: [0.0, 0.0],
+            spellchecker: match crate::CONFIG.get().unwrap().spellcheck {
+                true => {
+                    let directory = crate::CONFIG.get().unwrap().dictionary_dir.clone().unwrap_or(spellcheck::DEFAULT_DICTIONARY_DIR.into());
+                    let language = presentation::language().unwrap_or("en".to_owned());
+                    SpellChecker::for_language(directory, &language)
+                        .map_err(|e| log_warn!("Spell checking is disabled: {e}")).ok()
+                },
+                false => None
+            },
+            misspellings: Vec::new(),
+            selected: None,
+            last_font_check: Instant::now(),
+            decks,
+            current_deck: 0,
+            clipboard: None,
+            lint_findings: None,
+            color_filter: crate::CONFIG.get().unwrap().color_filter,
+            color_filter_texture: None
         }
     }
 }
 
+/// Returns the style of the editor's UI for the theme and accent color in the user configuration.
+fn editor_visuals(config: &crate::config::Config) -> egui::Visuals {
+    let mut visuals = match config.editor_theme {
+        crate::config::EditorTheme::Dark => egui::Visuals::dark(),
+        crate::config::EditorTheme::Light => egui::Visuals::light()
+    };
+
+    if let Some([r, g, b]) = config.editor_accent {
+        let accent = egui::Color32::from_rgb(r, g, b);
+        visuals.selection.bg_fill = accent;
+        visuals.hyperlink_color = accent;
+        visuals.widgets.hovered.bg_stroke.color = accent;
+        visuals.widgets.active.bg_fill = accent;
+    }
+
+    visuals
+}
+
 impl Application {
     /// Creates the application's data.
     /// 
@@ -156,26 +253,15 @@ impl Application {
         Application { opengl_version, opengl_backend: PanickingOption::None, data: PanickingOption::None, resolution: (-1.0,-1.0) }
     }
     /// Initializes all the data and state of the application.
-    pub fn init<Str: Into<String>>(&mut self, title: Str, resolution: (u32, u32), vsync: bool, resizable: bool, decoration: bool, filepath: String) -> PistonWindow {
-        // Initialize the logging backend
-        pretty_env_logger::try_init_timed_custom_env("LOG").unwrap();
-
-        // Create the window
-        let window = piston::window::WindowSettings::new(title.into(), [resolution.0,resolution.1])
-            .graphics_api(self.opengl_version)
-            .exit_on_esc(true)
-            .vsync(vsync)
-            .resizable(resizable)
-            .decorated(decoration)
-            .samples(0)
-            .srgb(true)
-            .build()
-            .unwrap();
+    pub fn init<Str: Into<String>>(&mut self, title: Str, resolution: (u32, u32), vsync: bool, resizable: bool, decoration: bool, filepaths: Vec<String>) -> PistonWindow {
+        let title: String = title.into();
+        let window = engine::create_window(title.as_str(), self.opengl_version, resolution, vsync, resizable, decoration, false, crate::CONFIG.get().unwrap().exit_on_esc, true);
         // Create the OpenGL context
         self.opengl_backend = PanickingOption::Some(GlGraphics::new(self.opengl_version));
 
         // Create the application's data
-        self.data = PanickingOption::Some(AppData::create(filepath));
+        self.data = PanickingOption::Some(AppData::create(filepaths));
+        engine::set_document_title(&window, &title, &self.data.presentation);
 
         self.resolution = (resolution.0 as f64, resolution.1 as f64);
 
@@ -200,7 +286,7 @@ impl Application {
 
         // Draw the presentation
         self.opengl_backend.draw(args.viewport(), |c, gl| {
-            use graphics::{ Transformed, Graphics };
+            use graphics::Graphics;
             use opengl_graphics::{ TextureSettings, Wrap };
 
             // We need to set a local variable here to copy the value, because we already mutably
@@ -208,13 +294,54 @@ impl Application {
             // the value into the function call below, which we aren't allowed to do.
             let time = self.data.time;
 
-            let ctx = c.trans(self.resolution.0*0.25, self.resolution.1*0.25).scale(0.5,0.5);
+            let ctx = self.data.view.transform(c, [self.resolution.0, self.resolution.1]);
 
             self.data.presentation.render(time, ctx, gl);
 
+            // Simulate color vision deficiencies before drawing the editor's guides and UI
+            self.data.color_filter.apply_to_frame(&mut self.data.color_filter_texture, c, gl);
+
+            if self.data.debug_outlines {
+                self.data.presentation.render_debug_outlines(time, ctx, gl);
+            }
+
+            // Underline misspelled words
+            if let Some(checker) = &mut self.data.spellchecker {
+                let mut misspellings: Vec<(String, Vec<String>)> = Vec::new();
+                for (word, rect) in presentation::selection::words() {
+                    if let Some(suggestions) = checker.check(&word) {
+                        let y = rect[1] + rect[3];
+                        graphics::line(SPELLING_UNDERLINE_COLOR, 1.5, [rect[0], y, rect[0] + rect[2], y], ctx.transform, gl);
+
+                        if !misspellings.iter().any(|(w, _)| *w == word) {
+                            misspellings.push((word, suggestions.clone()));
+                        }
+                    }
+                }
+                self.data.misspellings = misspellings;
+            }
+
+            // Outline the object selected in the outline panel
+            let view_size = c.get_view_size();
+            if let Some(location) = self.data.selected.filter(|l| l.slide == self.data.presentation.current_slide_index()) {
+                let bounds = self.data.presentation.current_slide()
+                    .and_then(|slide| slide.object(location.z_index, location.index))
+                    .and_then(|object| object.bounds(time, view_size).ok());
+                if let Some((rect, _)) = bounds {
+                    graphics::Rectangle::new_border(SELECTED_OBJECT_COLOR, 2.0).draw(rect, &c.draw_state, ctx.transform, gl);
+                }
+            }
+
+            // Draw the guide for the slide's safe area
+            if let Some(rect) = self.data.presentation.current_safe_area(view_size[0], view_size[1]) {
+                graphics::Rectangle::new_border(SAFE_AREA_GUIDE_COLOR, 2.0).draw(rect, &c.draw_state, ctx.transform, gl);
+            }
+
             let gui = &self.data.egui_output;
 
             let tris = self.data.egui_context.tessellate(gui.shapes.clone());
+            // The UI gets laid out in points, which are scaled up to pixels here
+            let ui_scale = self.data.egui_context.pixels_per_point();
             let mut to_add = HashMap::new();
             let mut to_remove = Vec::new();
 
@@ -256,7 +383,7 @@ impl Application {
                         gl.tri_list_uv_c(&c.draw_state, self.data.egui_textures.get(&m.texture_id).unwrap(), |c| {
                             let verts = indices.iter().map(|i|{
                                 let p = &vertices[*i as usize].pos;
-                                [p.x,p.y]
+                                [p.x*ui_scale,p.y*ui_scale]
                             }).collect::<Vec<[f32;2]>>();
                             let uv = indices.iter().map(|i|{
                                 let p = &vertices[*i as usize].uv;
@@ -292,55 +419,406 @@ impl Application {
 
         self.data.egui_time += args.dt;
 
+        // Reload fonts whose files changed, so edited fonts show up without restarting
+        if self.data.last_font_check.elapsed().as_secs_f64() >= presentation::renderable::FONT_CHECK_INTERVAL {
+            self.data.last_font_check = Instant::now();
+            for name in presentation::renderable::reload_fonts(false) {
+                log_info!("Reloaded font '{name}'");
+            }
+        }
+
         let mut input = std::mem::replace(&mut self.data.egui_input, Default::default());
         input.time = Some(self.data.egui_time);
 
-        let out = self.data.egui_context.run(input, |c| self.ui(c));
+        let ui_scale = self.data.egui_context.pixels_per_point();
+        input.pixels_per_point = Some(ui_scale);
+        input.screen_rect = Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(self.resolution.0 as f32, self.resolution.1 as f32) / ui_scale));
+
+        // The context is reference counted, so it can be cloned to let the UI mutate the app's data
+        let context = self.data.egui_context.clone();
+        let out = context.run(input, |c| self.ui(c));
         self.data.egui_output = out;
     }
 
     pub fn resize(&mut self, new_res: (u32, u32)) {
         self.resolution = (new_res.0 as f64, new_res.1 as f64);
+        self.data.view.fit();
+    }
+
+    /// Remembers the position of the mouse cursor, so zooming keeps the point beneath it in place.
+    pub fn mouse_moved(&mut self, position: [f64; 2]) {
+        self.data.cursor = position;
+        let ui_scale = self.data.egui_context.pixels_per_point();
+        self.data.egui_input.events.push(egui::Event::PointerMoved(egui::pos2(position[0] as f32, position[1] as f32) / ui_scale));
+    }
+
+    /// Zooms the slide preview when scrolling vertically and pans it when scrolling horizontally.
+    pub fn scroll(&mut self, amount: [f64; 2]) {
+        let data = &mut *self.data;
+        data.view.scroll(amount, data.cursor, [self.resolution.0, self.resolution.1]);
     }
 
     /// Checks for input and updates the applications state accordingly.
+    /// 
+    /// The keys for switching slides, fullscreen and the debug outlines are taken from the user
+    /// configuration, like in the viewer.
     pub fn input(&mut self, args: &ButtonArgs) -> bool {
         use egui::Event;
 
-        todo!();
+        // Pass mouse buttons on to the UI
+        if let Button::Mouse(button) = args.button {
+            let button = match button {
+                MouseButton::Left => egui::PointerButton::Primary,
+                MouseButton::Right => egui::PointerButton::Secondary,
+                MouseButton::Middle => egui::PointerButton::Middle,
+                _ => return false
+            };
+            let cursor = self.data.cursor;
+            let ui_scale = self.data.egui_context.pixels_per_point();
+            self.data.egui_input.events.push(Event::PointerButton {
+                pos: egui::pos2(cursor[0] as f32, cursor[1] as f32) / ui_scale,
+                button,
+                pressed: matches!(args.state, ButtonState::Press),
+                modifiers: Default::default()
+            });
+            return false
+        }
 
-        match (args.button, args.state, self.data.last_press) {
-            (Button::Keyboard(Key::A | Key::Left), ButtonState::Press, (false, _, _)) => {
+        let key = match args.button {
+            Button::Keyboard(key) => key,
+            _ => return false
+        };
+        let bindings = &crate::CONFIG.get().unwrap().keybindings;
+
+        match key {
+            Key::LCtrl | Key::RCtrl => self.data.ctrl_held = args.state == ButtonState::Press,
+            Key::LShift | Key::RShift => self.data.shift_held = args.state == ButtonState::Press,
+            _ => {}
+        }
+
+        // Shortcuts with the control key do the same as the buttons of the outline and the tabs
+        if self.data.ctrl_held {
+            if args.state == ButtonState::Press {
+                let data = &mut *self.data;
+                match key {
+                    // Ctrl+0 fits the preview into the window again
+                    Key::D0 => data.view.fit(),
+                    // Ctrl+Tab and Ctrl+Shift+Tab switch to the next and previous tab
+                    Key::Tab => {
+                        let count = data.decks.len();
+                        let offset = if data.shift_held { count - 1 } else { 1 };
+                        data.pending_actions.push(OutlineAction::SwitchDeck((data.current_deck + offset) % count));
+                    },
+                    // Ctrl+C copies the selected object, or the current slide if nothing is selected
+                    Key::C => data.pending_actions.push(match data.selected {
+                        Some(location) => OutlineAction::CopyObject(location),
+                        None => OutlineAction::CopySlide
+                    }),
+                    Key::V => data.pending_actions.push(OutlineAction::Paste),
+                    _ => {}
+                }
+            }
+            return false
+        }
+
+        match (args.state, self.data.last_press) {
+            (ButtonState::Press, (false, _, _)) if bindings.previous_slide.contains(&key) => {
                 self.data.presentation.previous_slide();
                 self.data.time = 0.0;
                 self.data.last_press.0 = true;
             },
-            (Button::Keyboard(Key::A | Key::Left), ButtonState::Release, (true, _, _)) => {
+            (ButtonState::Release, (true, _, _)) if bindings.previous_slide.contains(&key) => {
                 self.data.last_press.0 = false;
             },
 
-            (Button::Keyboard(Key::D | Key::Right), ButtonState::Press, (_, false, _)) => {
+            (ButtonState::Press, (_, false, _)) if bindings.next_slide.contains(&key) => {
                 self.data.presentation.next_slide();
                 self.data.time = 0.0;
                 self.data.last_press.1 = true;
             },
-            (Button::Keyboard(Key::D | Key::Right), ButtonState::Release, (_, true, _)) => {
+            (ButtonState::Release, (_, true, _)) if bindings.next_slide.contains(&key) => {
                 self.data.last_press.1 = false;
             },
-            (Button::Keyboard(Key::F11), ButtonState::Press, (_, _, false)) => {
+            (ButtonState::Press, (_, _, false)) if bindings.fullscreen.contains(&key) => {
                 self.data.last_press.2 = true;
                 return true
             },
-            (Button::Keyboard(Key::F11), ButtonState::Release, (_, _, true)) => {
+            (ButtonState::Release, (_, _, true)) if bindings.fullscreen.contains(&key) => {
                 self.data.last_press.2 = false;
             },
+            (ButtonState::Press, _) if bindings.debug_outlines.contains(&key) => {
+                self.data.debug_outlines = !self.data.debug_outlines;
+            },
+            // Escape clears the selection of the outline
+            (ButtonState::Press, _) if key == Key::Escape => {
+                self.data.selected = None;
+            },
             _ => {}
         }
 
         false
     }
 
-    pub fn ui(&self, ctx: &Context) {
+    pub fn ui(&mut self, ctx: &Context) {
+        let mut actions = std::mem::take(&mut self.data.pending_actions);
+
+        // One tab for every opened deck
+        if self.data.decks.len() > 1 {
+            egui::TopBottomPanel::top("decks").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for (index, deck) in self.data.decks.iter().enumerate() {
+                        let name = deck.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or(deck.path.display().to_string());
+                        if ui.selectable_label(index == self.data.current_deck, name).clicked() {
+                            actions.push(OutlineAction::SwitchDeck(index));
+                        }
+                    }
+                });
+            });
+        }
+
+        let mut color_filter = self.data.color_filter;
+        egui::SidePanel::left("outline").show(ctx, |ui| {
+            if ui.button("Reload fonts").clicked() {
+                let reloaded = presentation::renderable::reload_fonts(true);
+                log_info!("Reloaded {} fonts", reloaded.len());
+            }
+            if ui.button("Lint").clicked() {
+                actions.push(OutlineAction::Lint);
+            }
+            egui::ComboBox::from_label("Color vision")
+                .selected_text(color_filter.name())
+                .show_ui(ui, |ui| {
+                    for filter in ColorFilter::ALL {
+                        ui.selectable_value(&mut color_filter, filter, filter.name());
+                    }
+                });
+            ui.horizontal(|ui| {
+                if ui.button("Copy slide").clicked() {
+                    actions.push(OutlineAction::CopySlide);
+                }
+                if ui.add_enabled(self.data.selected.is_some(), egui::Button::new("Copy object")).clicked() {
+                    actions.extend(self.data.selected.map(OutlineAction::CopyObject));
+                }
+                if ui.add_enabled(self.data.clipboard.is_some(), egui::Button::new("Paste")).clicked() {
+                    actions.push(OutlineAction::Paste);
+                }
+                ui.menu_button("Insert", |ui| {
+                    for component in components::COMPONENTS {
+                        if ui.button(component.title).clicked() {
+                            actions.push(OutlineAction::InsertComponent(component));
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
+            egui::ScrollArea::vertical().show(ui, |ui| self.outline(ui, &mut actions));
+        });
+        self.data.color_filter = color_filter;
+
+        for action in actions {
+            match action {
+                OutlineAction::Select(location) => {
+                    if location.slide != self.data.presentation.current_slide_index() {
+                        self.data.presentation.go_to_slide(location.slide);
+                        self.data.time = 0.0;
+                    }
+                    self.data.selected = Some(location);
+                },
+                OutlineAction::Move(location, z_index) => {
+                    let index = self.data.presentation.slide_mut(location.slide)
+                        .and_then(|slide| slide.move_object(location.z_index, location.index, z_index));
+                    self.data.selected = index.map(|index| ObjectLocation { z_index, index, ..location });
+                },
+                OutlineAction::CopySlide => {
+                    self.data.clipboard = self.data.presentation.current_slide().cloned().map(EditorClipboard::Slide);
+                },
+                OutlineAction::CopyObject(location) => {
+                    self.data.clipboard = self.data.presentation.slide(location.slide)
+                        .and_then(|slide| slide.object(location.z_index, location.index))
+                        .map(|object| EditorClipboard::Object(object.copy(), location.z_index));
+                },
+                OutlineAction::Paste => self.paste(),
+                OutlineAction::SwitchDeck(index) => self.switch_deck(index),
+                OutlineAction::InsertComponent(component) => {
+                    match component.slide(self.data.presentation.style()) {
+                        Ok(slide) => {
+                            let index = self.data.presentation.current_slide_index() + 1;
+                            self.data.presentation.insert_slide(index, slide);
+                            self.data.presentation.go_to_slide(index);
+                            self.data.time = 0.0;
+                            self.data.selected = None;
+                        },
+                        Err(e) => log_err!("Inserting the component \"{}\" failed: {e}", component.name)
+                    }
+                },
+                OutlineAction::Lint => {
+                    self.data.lint_findings = Some(lint::run(&self.data.presentation, &crate::CONFIG.get().unwrap().lint));
+                }
+            }
+        }
 
+        if let Some(findings) = &self.data.lint_findings {
+            let mut open = true;
+            let mut selected = None;
+            egui::Window::new("Lint").open(&mut open).show(ctx, |ui| {
+                if findings.is_empty() {
+                    ui.label("No problems found");
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    // Clicking a finding selects the object causing it
+                    for finding in findings.iter() {
+                        let color = match finding.severity {
+                            lint::Severity::Error => ui.visuals().error_fg_color,
+                            _ => ui.visuals().warn_fg_color
+                        };
+                        if ui.add(egui::Label::new(egui::RichText::new(finding.to_string()).color(color)).sense(egui::Sense::click())).clicked() {
+                            let (z_index, index) = finding.object;
+                            selected = Some(ObjectLocation { slide: finding.slide, z_index, index });
+                        }
+                    }
+                });
+            });
+
+            if let Some(location) = selected {
+                if location.slide != self.data.presentation.current_slide_index() {
+                    self.data.presentation.go_to_slide(location.slide);
+                    self.data.time = 0.0;
+                }
+                self.data.selected = Some(location);
+            }
+            if !open {
+                self.data.lint_findings = None;
+            }
+        }
+
+        if !self.data.misspellings.is_empty() {
+            egui::Window::new("Spelling").show(ctx, |ui| {
+                for (word, suggestions) in self.data.misspellings.iter() {
+                    match suggestions.is_empty() {
+                        true => ui.label(format!("{word}: no suggestions")),
+                        false => ui.label(format!("{word}: {}", suggestions.join(", ")))
+                    };
+                }
+            });
+        }
+    }
+
+    /// Pastes the copied slide after the current slide or the copied object into the current
+    /// slide, then selects it.
+    fn paste(&mut self) {
+        let data = &mut *self.data;
+        let current = data.presentation.current_slide_index();
+
+        match &data.clipboard {
+            Some(EditorClipboard::Slide(slide)) => {
+                data.presentation.insert_slide(current + 1, slide.clone());
+                data.presentation.go_to_slide(current + 1);
+                data.time = 0.0;
+                data.selected = None;
+            },
+            Some(EditorClipboard::Object(object, z_index)) => {
+                if let Some(slide) = data.presentation.slide_mut(current) {
+                    slide.add_boxed(object.copy(), *z_index);
+                    let index = slide.layers().find(|(z, _)| z == z_index).map(|(_, objects)| objects.len() - 1).unwrap_or(0);
+                    data.selected = Some(ObjectLocation { slide: current, z_index: *z_index, index });
+                }
+            },
+            None => {}
+        }
     }
-}
\ No newline at end of file
+
+    /// Shows the deck with the given index, keeping the state of the previously shown one.
+    fn switch_deck(&mut self, index: usize) {
+        let data = &mut *self.data;
+        if index == data.current_deck || index >= data.decks.len() {
+            return
+        }
+
+        // Store the shown deck in it's tab, then take the new deck out of it's tab
+        for deck in [data.current_deck, index] {
+            let deck = &mut data.decks[deck];
+            std::mem::swap(&mut data.presentation, &mut deck.presentation);
+            std::mem::swap(&mut data.time, &mut deck.time);
+            std::mem::swap(&mut data.selected, &mut deck.selected);
+        }
+        data.current_deck = index;
+        data.misspellings.clear();
+        data.lint_findings = None;
+        data.view.fit();
+    }
+
+    /// Builds the outline panel listing all slides and their objects.
+    /// 
+    /// Clicking an object selects it, the checkbox next to objects with an id toggles their
+    /// visibility and dragging an object onto another one moves it into that object's layer.
+    fn outline(&self, ui: &mut egui::Ui, actions: &mut Vec<OutlineAction>) {
+        let mut rows: Vec<(ObjectLocation, egui::Rect)> = Vec::new();
+        let mut dropped = None;
+
+        let baseline = self.data.decks[self.data.current_deck].baseline.as_ref();
+
+        for slide_index in 0..self.data.presentation.slide_count() {
+            let slide = self.data.presentation.slide(slide_index).unwrap();
+            let title = match slide.title() {
+                Some(title) => format!("{}. {title}", slide_index + 1),
+                None => format!("Slide {}", slide_index + 1)
+            };
+
+            // Slides changed since the baseline get highlighted, so reviewers can focus on them
+            let changed = baseline.is_some_and(|hashes| slide.content_hash().is_some_and(|hash| !hashes.contains(&hash)));
+            let title = match changed {
+                true => egui::RichText::new(format!("{title} (changed)")).color(ui.visuals().warn_fg_color),
+                false => egui::RichText::new(title)
+            };
+
+            egui::CollapsingHeader::new(title)
+                .id_source(("outline_slide", slide_index))
+                .default_open(slide_index == self.data.presentation.current_slide_index())
+                .show(ui, |ui| {
+                    for (z_index, objects) in slide.layers() {
+                        for (index, object) in objects.iter().enumerate() {
+                            let location = ObjectLocation { slide: slide_index, z_index, index };
+
+                            ui.horizontal(|ui| {
+                                if let Some(id) = object.id() {
+                                    let mut visible = presentation::object_visible(id);
+                                    if ui.checkbox(&mut visible, "").changed() {
+                                        presentation::set_object_visible(id, visible);
+                                    }
+                                }
+
+                                let mut text = egui::RichText::new(match object.id() {
+                                    Some(id) => format!("{} \"{id}\" (z={z_index})", object.type_name()),
+                                    None => format!("{} (z={z_index})", object.type_name())
+                                });
+                                if self.data.selected == Some(location) {
+                                    text = text.strong();
+                                }
+
+                                let response = ui.add(egui::Label::new(text).sense(egui::Sense::click_and_drag()));
+                                if response.clicked() {
+                                    actions.push(OutlineAction::Select(location));
+                                }
+                                if response.dragged() {
+                                    ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                                }
+                                if response.drag_released() {
+                                    dropped = Some(location);
+                                }
+                                rows.push((location, response.rect));
+                            });
+                        }
+                    }
+                });
+        }
+
+        // Move a dropped object into the layer of the object it was dropped onto
+        let pointer = ui.ctx().pointer_latest_pos();
+        if let (Some(source), Some(pointer)) = (dropped, pointer) {
+            let target = rows.iter().find(|(_, rect)| rect.contains(pointer)).map(|(location, _)| *location);
+            if let Some(target) = target.filter(|t| t.slide == source.slide && t.z_index != source.z_index) {
+                actions.push(OutlineAction::Move(source, target.z_index));
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use opengl_graphics::OpenGL;
+use piston_window::{ PistonWindow, Events, EventSettings };
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::util::AssumeThreadSafe;
+use crate::presentation;
+use crate::parse::SlideData;
+use crate::components::Style;
+use crate::presentation::renderable::BaseProperties;
+use crate::audio::SoundEffects;
+use crate::midi::MidiBinding;
+
+/// Everything loaded from a document file.
+pub struct Document {
+    /// The slides of the document, followed by an 'End of presentation' slide.
+    pub presentation: presentation::Presentation,
+    /// The sound effects configured in the document.
+    pub sounds: SoundEffects,
+    /// The MIDI bindings configured in the document, with their Lua handlers not compiled yet.
+    pub midi_bindings: Vec<MidiBinding>
+}
+
+/// The icon of the application's windows.
+const ICON: &'static [u8] = include_bytes!("../assets/icon.png");
+
+/// Initializes the logging backend and creates a window with an OpenGL context.
+///
+/// Windows that only get shown later on (e.g. after loading a document) or not at all get created
+/// hidden using `visible`, so they don't flash up on the screen.
+pub fn create_window<Str: Into<String>>(title: Str, opengl_version: OpenGL, resolution: (u32, u32), vsync: bool, resizable: bool, decoration: bool, transparent: bool, exit_on_esc: bool, visible: bool) -> PistonWindow {
+    // Initialize the logging backend
+    pretty_env_logger::try_init_timed_custom_env("LOG").unwrap();
+
+    let settings = piston::window::WindowSettings::new(title.into(), [resolution.0,resolution.1])
+        .graphics_api(opengl_version)
+        .exit_on_esc(exit_on_esc)
+        .vsync(vsync)
+        .resizable(resizable)
+        .decorated(decoration)
+        .transparent(transparent)
+        .samples(0)
+        .srgb(true);
+
+    // Piston's settings can't create hidden windows, so the window gets built from winit's
+    // builder instead
+    let builder = winit::window::WindowBuilder::new()
+        .with_title(settings.get_title())
+        .with_inner_size(winit::dpi::LogicalSize::new(resolution.0 as f64, resolution.1 as f64))
+        .with_resizable(resizable)
+        .with_decorations(decoration)
+        .with_transparent(transparent)
+        .with_visible(visible);
+    let event_loop = winit::event_loop::EventLoopBuilder::with_user_event().build();
+    let window = glutin_window::GlutinWindow::from_raw(&settings, event_loop, builder).unwrap();
+    let window = PistonWindow::new(opengl_version, settings.get_samples(), window);
+
+    match image::load_from_memory(ICON) {
+        Ok(icon) => {
+            let icon = icon.to_rgba8();
+            let (width, height) = icon.dimensions();
+            window.window.window.set_window_icon(winit::window::Icon::from_rgba(icon.into_raw(), width, height).ok());
+        },
+        Err(e) => log_err!("Loading the window icon failed: {e}")
+    }
+
+    window
+}
+
+/// Shows the title of the document in front of the application's title in the window's title
+/// bar, if the document has one.
+pub fn set_document_title(window: &PistonWindow, app_title: &str, presentation: &presentation::Presentation) {
+    if let Some(title) = presentation.title() {
+        window.window.window.set_title(&format!("{title} - {app_title}"));
+    }
+}
+
+/// Creates the event loop of an application's window.
+///
+/// Renders as many frames as possible, unless a frame limit is set in the user configuration.
+pub fn events() -> Events {
+    let mut settings = EventSettings::new();
+    settings.lazy = false;
+    settings.bench_mode = false;
+    settings.max_fps = crate::CONFIG.get().unwrap().max_fps.unwrap_or(std::u64::MAX);
+    Events::new(settings)
+}
+
+/// Loads the bundled default font and the given fonts of a document into [`crate::FONTS`].
+///
+/// Can only be called once.
+pub fn init_fonts(document_fonts: HashMap<String, (String, String)>) {
+    crate::FONTS.set({
+        let mut map = HashMap::new();
+
+        // Adds the default font bundled into the binary.
+        map.insert("Default".to_owned(), Rc::new(RefCell::new(presentation::TextFont::fallback())));
+
+        for (name, font) in presentation::renderable::TextFont::load_all(document_fonts) {
+            map.insert(name, Rc::new(RefCell::new(font)));
+        }
+
+        AssumeThreadSafe(map)
+    }).ok().expect("error initializing fonts");
+}
+
+/// Loads the document at the given path, including it's fonts.
+///
+/// Errors in the document get reported through the parser's error handling, which exits the
+/// application.
+pub fn load_document(filepath: &str) -> Document {
+    load_documents(&[filepath]).remove(0)
+}
+
+/// Loads multiple documents sharing one font registry.
+///
+/// The fonts of all documents get loaded first; if multiple documents register a font with the
+/// same name, the first document's font gets used by all of them.
+pub fn load_documents(filepaths: &[&str]) -> Vec<Document> {
+    use crate::parse::{ self, Parser };
+
+    // Read the contents of the presentation files
+    let contents = filepaths.iter()
+        .map(|filepath| std::fs::read_to_string(filepath).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut fonts = HashMap::new();
+    // The names of the fonts registered by each document
+    let mut font_names = Vec::new();
+    for (filepath, filecontents) in filepaths.iter().zip(contents.iter()) {
+        // Create an instance of a parser (which parser gets instantiated depends on the file extension)
+        let mut parser = parse::get_parser(filepath).expect("No parser found for file type!");
+
+        let document_fonts = parser.parse_fonts(filecontents.as_str()).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+        font_names.push(document_fonts.keys().cloned().collect::<Vec<_>>());
+        for (name, files) in document_fonts {
+            fonts.entry(name).or_insert(files);
+        }
+    }
+    init_fonts(fonts);
+
+    filepaths.iter().zip(contents.iter()).zip(font_names)
+        .map(|((filepath, filecontents), font_names)| parse_document(filepath, filecontents, font_names))
+        .collect()
+}
+
+/// Loads a document again after it changed, once it's fonts were loaded by [`load_document`].
+///
+/// Fonts that were added to the document since then aren't available. Errors in the document get
+/// reported through the parser's error handling, like when loading it.
+pub fn reload_document(filepath: &str) -> Document {
+    use crate::parse::{ self, Parser };
+
+    let contents = std::fs::read_to_string(filepath).unwrap();
+    let mut parser = parse::get_parser(filepath).expect("No parser found for file type!");
+    let font_names = parser.parse_fonts(contents.as_str()).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() })
+        .into_keys().collect::<Vec<_>>();
+
+    // Errors of the previous version shouldn't hide the same errors of the reloaded one
+    presentation::clear_logged_render_errors();
+
+    parse_document(filepath, &contents, font_names)
+}
+
+/// Parses a document whose fonts were already loaded.
+/// 
+/// `font_names` are the names of the fonts registered by the document, in the order they're
+/// defined in.
+fn parse_document(filepath: &str, filecontents: &str, font_names: Vec<String>) -> Document {
+    use crate::parse::{ self, Parser };
+
+    let mut parser = parse::get_parser(filepath).expect("No parser found for file type!");
+
+    let document = parser.parse(filecontents).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+    let captions = parser.parse_captions(filecontents).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+    let sounds = parser.parse_sounds(filecontents).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+    let midi_bindings = parser.parse_midi(filecontents).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+    let meta = parser.parse_meta(filecontents).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+
+    let mut presentation = presentation::Presentation::new();
+    presentation.set_captions(captions);
+    presentation.set_title(meta.title);
+    presentation.set_seed(meta.seed);
+    presentation.set_lint_severities(meta.lint);
+    presentation.set_locale(meta.locale);
+    // Components inserted into the document use it's own font if it doesn't choose one
+    presentation.set_style(Style {
+        palette: meta.palette,
+        font: meta.font.or(font_names.into_iter().next()).unwrap_or("Default".to_owned())
+    });
+
+    // The background of the previous slide, used by slides inheriting it
+    let mut previous_background: Option<Rc<dyn presentation::Renderable>> = None;
+
+    for slide_data in document {
+        let slide = build_slide(slide_data, previous_background.take());
+        previous_background = Some(slide.background().clone());
+        presentation.add_slide(slide);
+    }
+
+    // Adds an 'End of presentation' slide using the bundled default font.
+    {
+        let bg = presentation::ColoredRect::new(BaseProperties::new("0;0", "w;h", "0;0;0;1", "TOP_LEFT").map_err(|_|()).unwrap());
+        let mut last_slide = presentation::Slide::new(Box::new(bg) as Box<dyn presentation::Renderable>);
+
+        let text = presentation::Text::new(
+            BaseProperties::new("0;0","w;4%","1;1;1;1","TOP_LEFT").map_err(|_|()).unwrap(),
+            vec!["End of presentation"],
+            "Default".to_owned(),
+            &*crate::FONTS.get().unwrap(),
+            HashMap::new(),
+            "LEFT").map_err(|_|()).unwrap();
+        last_slide.add(text, 0);
+        last_slide.set_generated(true);
+
+        presentation.add_slide(last_slide);
+    }
+
+    Document { presentation, sounds, midi_bindings }
+}
+
+/// Creates a slide from it's parsed data.
+/// 
+/// Slides inheriting the background use `previous_background`, if there is one.
+pub fn build_slide(slide_data: SlideData, previous_background: Option<Rc<dyn presentation::Renderable>>) -> presentation::Slide {
+    let mut slide = match (slide_data.background, previous_background) {
+        (None, Some(background)) => presentation::Slide::with_shared_background(background),
+        (background, _) => {
+            if background.is_none() {
+                log_warn!("The first slide can't inherit a background, using the default background instead");
+            }
+            presentation::Slide::new(background)
+        }
+    };
+    slide.set_narration(slide_data.narration);
+    slide.set_safe_area(slide_data.safe_area);
+    slide.set_duration(slide_data.duration);
+    slide.set_title(slide_data.title);
+    slide.set_bookmark(slide_data.bookmark);
+    slide.set_transition(slide_data.transition);
+    slide.set_notes(slide_data.notes);
+    slide.set_content_hash(Some(slide_data.content_hash));
+    for (z, content) in slide_data.content {
+        for renderable in content {
+            slide.add_boxed(renderable, z);
+        }
+    }
+    slide
+}
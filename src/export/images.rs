@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::hash::{ Hash, Hasher };
+use std::path::{ Path, PathBuf };
+
+use opengl_graphics::GlGraphics;
+use serde::Deserialize;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::presentation::Presentation;
+
+/// The file formats slides can be exported as.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Png,
+    Jpg,
+    Webp
+}
+
+impl ImageFormat {
+    /// Returns the format with the given name or file extension.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpg),
+            "webp" => Some(ImageFormat::Webp),
+            _ => None
+        }
+    }
+
+    /// Returns the file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpg => "jpg",
+            ImageFormat::Webp => "webp"
+        }
+    }
+}
+
+/// A selection of slides by their numbers, starting at 1 (e.g. `3,5-9`).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct SlideSelection(Vec<(usize, usize)>);
+
+impl SlideSelection {
+    /// Returns whether the slide with the given index (starting at 0) is selected.
+    pub fn contains(&self, index: usize) -> bool {
+        self.0.iter().any(|(first, last)| (*first..=*last).contains(&(index + 1)))
+    }
+}
+
+impl TryFrom<String> for SlideSelection {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> anyhow::Result<Self> {
+        value.split(',')
+            .map(|part| -> anyhow::Result<(usize, usize)> {
+                let part = part.trim();
+                let (first, last) = part.split_once('-').unwrap_or((part, part));
+                let range = (first.trim().parse::<usize>()?, last.trim().parse::<usize>()?);
+                match range.0 >= 1 && range.0 <= range.1 {
+                    true => Ok(range),
+                    false => anyhow::bail!("invalid slide range '{part}'")
+                }
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(SlideSelection)
+            .map_err(|e| anyhow::anyhow!("invalid slide selection '{value}' (expected e.g. 3,5-9): {e}"))
+    }
+}
+
+/// Options for exporting slides as images.
+#[derive(Debug, Clone)]
+pub struct ImageExportOptions {
+    /// The slides that get exported. All slides get exported if unset.
+    pub slides: Option<SlideSelection>,
+    /// The file format of the images. Gets chosen by the extension of the file name template if
+    /// unset, falling back to PNG.
+    pub format: Option<ImageFormat>,
+    /// The resolution the slides get rendered at, before scaling.
+    pub slide_size: (u32, u32),
+    /// The factor the resolution gets scaled by.
+    pub scale: f64,
+    /// The template of the file names (see [`file_name`]).
+    pub file_name: String
+}
+
+/// Returns the file name of an exported slide from a template.
+///
+/// The template can contain `{n}` (the slide's number, starting at 1), `{title}` (the slide's
+/// title, or `slide` if it has none), `{name}` (the name of the document without it's extension)
+/// and `{ext}` (the extension of the image format). Characters that aren't allowed in file names
+/// get removed from the title.
+pub fn file_name(template: &str, number: usize, title: Option<&str>, name: &str, format: ImageFormat) -> String {
+    let title = title.unwrap_or("slide").chars()
+        .filter(|c| !matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') && !c.is_control())
+        .collect::<String>();
+
+    template
+        .replace("{n}", &number.to_string())
+        .replace("{title}", title.trim())
+        .replace("{name}", name)
+        .replace("{ext}", format.extension())
+}
+
+/// Exports the selected slides of a presentation as images into a directory.
+///
+/// Returns the paths of the written files.
+pub fn export_images<P: AsRef<Path>>(presentation: &Presentation, opengl: &mut GlGraphics, options: &ImageExportOptions, name: &str, directory: P) -> anyhow::Result<Vec<PathBuf>> {
+    export_changed_images(presentation, opengl, options, name, directory, &mut HashMap::new())
+}
+
+/// Exports the selected slides of a presentation as images into a directory, skipping the slides
+/// whose image didn't change since they were last exported.
+///
+/// `exported` holds a hash of the image last written to each path, and gets updated with the
+/// written images. Returns the paths of the written files.
+pub fn export_changed_images<P: AsRef<Path>>(presentation: &Presentation, opengl: &mut GlGraphics, options: &ImageExportOptions, name: &str, directory: P, exported: &mut HashMap<PathBuf, u64>) -> anyhow::Result<Vec<PathBuf>> {
+    let format = options.format
+        .or_else(|| Path::new(&options.file_name).extension().and_then(|e| ImageFormat::from_name(&e.to_string_lossy())))
+        .unwrap_or(ImageFormat::Png);
+    let size = (
+        ((options.slide_size.0 as f64 * options.scale).round() as u32).max(1),
+        ((options.slide_size.1 as f64 * options.scale).round() as u32).max(1)
+    );
+
+    let mut written = Vec::new();
+    for index in (0..presentation.document_slide_count()).filter(|i| options.slides.as_ref().map(|s| s.contains(*i)).unwrap_or(true)) {
+        let image = presentation.render_thumbnail(index, size, opengl)
+            .ok_or(anyhow::anyhow!("slide #{index} doesn't exist"))?;
+
+        let title = presentation.slide(index).and_then(|s| s.title());
+        let path = directory.as_ref().join(file_name(&options.file_name, index + 1, title, name, format));
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        image.as_raw().hash(&mut hasher);
+        let hash = hasher.finish();
+        if exported.get(&path) == Some(&hash) {
+            continue
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // JPEG has no alpha channel
+        match format {
+            ImageFormat::Png => image.save_with_format(&path, image::ImageFormat::Png)?,
+            ImageFormat::Jpg => image::DynamicImage::ImageRgba8(image).to_rgb8().save_with_format(&path, image::ImageFormat::Jpeg)?,
+            ImageFormat::Webp => image.save_with_format(&path, image::ImageFormat::WebP)?
+        }
+        log_info!("Exported slide {} to {}", index + 1, path.display());
+        exported.insert(path.clone(), hash);
+        written.push(path);
+    }
+
+    Ok(written)
+}
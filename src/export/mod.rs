@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use opengl_graphics::GlGraphics;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::presentation::Presentation;
+
+pub mod pdf;
+pub mod images;
+
+use pdf::{ PdfWriter, PdfPage, A4_SIZE };
+pub use images::{ export_images, export_changed_images, ImageExportOptions, ImageFormat, SlideSelection };
+
+/// Options for exporting handouts.
+#[derive(Debug, Clone)]
+pub struct HandoutOptions {
+    /// The amount of slides on each page.
+    pub slides_per_page: usize,
+    /// Whether lines for taking notes should be drawn next to each slide.
+    pub note_lines: bool,
+    /// Whether the speaker notes of each slide should be printed next to it, in place of the
+    /// note lines.
+    pub speaker_notes: bool,
+    /// The resolution the slides get rendered at.
+    pub slide_size: (u32, u32)
+}
+
+/// The margin around the page's content and between slides, in PDF units.
+const MARGIN: f64 = 36.0;
+
+/// The distance between two note lines, in PDF units.
+const NOTE_LINE_SPACING: f64 = 18.0;
+
+/// The font size of speaker notes, in PDF units.
+const SPEAKER_NOTES_FONT_SIZE: f64 = 10.0;
+
+/// The amount of pixels per PDF unit speaker notes get rendered with.
+const SPEAKER_NOTES_RESOLUTION: f64 = 3.0;
+
+/// Exports the presentation as a printable PDF handout with multiple slides per page.
+/// 
+/// Slides get laid out in a grid. With note lines, there's only a single column of slides, with
+/// the lines next to each slide. Each slide gets tagged with an alternative text (containing it's
+/// title, text and the alternative texts of it's images) and bookmarked using it's title.
+pub fn export_handout<P: AsRef<Path>>(presentation: &Presentation, opengl: &mut GlGraphics, options: &HandoutOptions, path: P) -> anyhow::Result<()> {
+    let per_page = options.slides_per_page.max(1);
+    let columns = match options.note_lines || options.speaker_notes {
+        true => 1,
+        false => (per_page as f64).sqrt().ceil() as usize
+    };
+    let rows = (per_page + columns - 1) / columns;
+
+    // The size of a single cell of the grid
+    let cell_width = (A4_SIZE.0 - MARGIN * (columns + 1) as f64) / columns as f64;
+    let cell_height = (A4_SIZE.1 - MARGIN * (rows + 1) as f64) / rows as f64;
+
+    // The space available for the slide inside a cell
+    let slide_area = match options.note_lines || options.speaker_notes {
+        true => (cell_width / 2.0 - MARGIN / 2.0, cell_height),
+        false => (cell_width, cell_height)
+    };
+    let aspect = options.slide_size.0 as f64 / options.slide_size.1 as f64;
+    let (slide_width, slide_height) = if slide_area.0 / slide_area.1 > aspect {
+        (slide_area.1 * aspect, slide_area.1)
+    } else {
+        (slide_area.0, slide_area.0 / aspect)
+    };
+
+    let mut writer = PdfWriter::new();
+    let mut page = PdfPage::new(A4_SIZE);
+
+    for slide in 0..presentation.document_slide_count() {
+        let thumbnail = presentation.render_thumbnail(slide, options.slide_size, opengl)
+            .ok_or(anyhow::anyhow!("slide #{slide} doesn't exist"))?;
+        let image = writer.add_image(&thumbnail)?;
+
+        let cell = slide % per_page;
+        let x = MARGIN + (cell % columns) as f64 * (cell_width + MARGIN);
+        let y = MARGIN + (cell / columns) as f64 * (cell_height + MARGIN);
+
+        let title = presentation.slide(slide).and_then(|s| s.title()).map(|t| t.to_owned())
+            .unwrap_or(format!("Slide {}", slide + 1));
+
+        let rect = [x, y, slide_width, slide_height];
+        page.draw_figure(image, rect, &slide_alt_text(presentation, slide, &title));
+        page.draw_rect(rect);
+        writer.add_bookmark(&title, &page, y);
+
+        let notes = presentation.slide(slide).and_then(|s| s.notes()).filter(|_| options.speaker_notes);
+        if let Some(notes) = notes {
+            let rect = [x + slide_area.0 + MARGIN, y, cell_width - slide_area.0 - MARGIN, slide_height];
+            let image = writer.add_image(&render_speaker_notes(notes, (rect[2], rect[3]), opengl))?;
+            page.draw_figure(image, rect, notes);
+        } else if options.note_lines {
+            let lines_x = x + slide_area.0 + MARGIN;
+            let mut line_y = y + NOTE_LINE_SPACING;
+            while line_y <= y + slide_height {
+                page.draw_line((lines_x, line_y), (x + cell_width, line_y));
+                line_y += NOTE_LINE_SPACING;
+            }
+        }
+
+        if cell == per_page - 1 {
+            writer.add_page(std::mem::replace(&mut page, PdfPage::new(A4_SIZE)));
+        }
+    }
+
+    // Add the last, partially filled page
+    if presentation.document_slide_count() % per_page != 0 {
+        writer.add_page(page);
+    }
+
+    writer.write(path.as_ref())?;
+    log_info!("Exported handout to {}", path.as_ref().display());
+    Ok(())
+}
+
+/// Describes a slide for the alternative text of it's image in exported documents.
+fn slide_alt_text(presentation: &Presentation, slide: usize, title: &str) -> String {
+    let mut parts = vec![title.to_owned()];
+    if let Some(slide) = presentation.slide(slide) {
+        parts.extend(slide.text_lines().cloned());
+        parts.extend(slide.alt_texts().map(|alt| format!("Image: {alt}")));
+    }
+    parts.join("\n")
+}
+
+/// Renders speaker notes with the default font as black text on a white image covering an area
+/// of the given size in PDF units.
+/// 
+/// Lines get wrapped at the width of the area. Lines that don't fit into it's height anymore get
+/// left out.
+fn render_speaker_notes(notes: &str, area: (f64, f64), opengl: &mut GlGraphics) -> image::RgbaImage {
+    use graphics::Transformed;
+
+    let size = ((area.0 * SPEAKER_NOTES_RESOLUTION).ceil().max(1.0) as u32, (area.1 * SPEAKER_NOTES_RESOLUTION).ceil().max(1.0) as u32);
+    let font_size = SPEAKER_NOTES_FONT_SIZE * SPEAKER_NOTES_RESOLUTION;
+    let line_height = font_size * 1.3;
+
+    let fonts = crate::FONTS.get().unwrap();
+    let mut font = fonts.get("Default").unwrap().borrow_mut();
+
+    // Wrap the lines of the notes at word boundaries
+    let mut lines = Vec::new();
+    for paragraph in notes.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = match line.is_empty() {
+                true => word.to_owned(),
+                false => format!("{line} {word}")
+            };
+            if !line.is_empty() && font.base_font.size(candidate.as_str(), font_size).0 > size.0 as f64 {
+                lines.push(std::mem::replace(&mut line, word.to_owned()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+
+    let fitting = ((size.1 as f64 / line_height).floor() as usize).min(lines.len());
+    if fitting < lines.len() {
+        log_warn!("Speaker notes don't fit next to their slide, leaving out {} lines", lines.len() - fitting);
+    }
+
+    crate::render::offscreen::render_offscreen(opengl, size, |c, gl| {
+        graphics::clear([1.0, 1.0, 1.0, 1.0], gl);
+        for (row, line) in lines[..fitting].iter().enumerate().filter(|(_, line)| !line.is_empty()) {
+            font.base_font.draw(line.as_str(), font_size, (0.0, 0.0, 0.0, 1.0), false, &c.trans(0.0, row as f64 * line_height), gl);
+        }
+    })
+}
@@ -0,0 +1,217 @@
+use std::io::Write;
+use std::path::Path;
+
+use image::RgbaImage;
+
+/// The size of an A4 page in PDF units (1/72 inch).
+pub const A4_SIZE: (f64, f64) = (595.0, 842.0);
+
+/// A minimal writer for PDF documents consisting of pages with images and lines.
+/// 
+/// Images get embedded as JPEG data, so no external PDF library is needed. Images drawn with an
+/// alternative text get tagged as figures, so screen readers can describe them.
+pub struct PdfWriter {
+    /// The bodies of all objects of the document. Object `n` is stored at index `n-1`.
+    objects: Vec<Vec<u8>>,
+    /// The object ids of all pages.
+    pages: Vec<usize>,
+    /// The object ids of the structure elements of all tagged figures, grouped by page.
+    figures: Vec<Vec<usize>>,
+    /// The title, page index and vertical position (in PDF coordinates) of all bookmarks.
+    bookmarks: Vec<(String, usize, f64)>
+}
+
+/// The contents of a single page.
+pub struct PdfPage {
+    size: (f64, f64),
+    content: String,
+    images: Vec<(String, usize)>,
+    /// The alternative texts of all tagged figures on the page, indexed by their marked content
+    /// id.
+    figures: Vec<String>
+}
+
+/// Encodes a string as a PDF text string (UTF-16BE with a byte order mark).
+fn text_string(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        hex.push_str(&format!("{unit:04X}"));
+    }
+    hex.push('>');
+    hex
+}
+
+impl PdfPage {
+    /// Creates an empty page of the given size.
+    pub fn new(size: (f64, f64)) -> PdfPage {
+        PdfPage { size, content: String::new(), images: Vec::new(), figures: Vec::new() }
+    }
+
+    /// Draws an image like [`PdfPage::draw_image`], tagging it as a figure with an alternative
+    /// text.
+    pub fn draw_figure(&mut self, image: usize, rect: [f64; 4], alt: &str) {
+        self.content.push_str(&format!("/Figure << /MCID {} >> BDC\n", self.figures.len()));
+        self.draw_image(image, rect);
+        self.content.push_str("EMC\n");
+        self.figures.push(alt.to_owned());
+    }
+
+    /// Draws an image previously added using [`PdfWriter::add_image`].
+    /// 
+    /// The rectangle is given as `[x, y, width, height]`, measured from the top left corner of
+    /// the page.
+    pub fn draw_image(&mut self, image: usize, rect: [f64; 4]) {
+        let name = format!("Im{}", self.images.len());
+        let y = self.size.1 - rect[1] - rect[3];
+        self.content.push_str(&format!("q {} 0 0 {} {} {} cm /{name} Do Q\n", rect[2], rect[3], rect[0], y));
+        self.images.push((name, image));
+    }
+
+    /// Draws a thin gray line between two points, measured from the top left corner of the page.
+    pub fn draw_line(&mut self, from: (f64, f64), to: (f64, f64)) {
+        self.content.push_str(&format!("0.6 G 0.5 w {} {} m {} {} l S\n", from.0, self.size.1 - from.1, to.0, self.size.1 - to.1));
+    }
+
+    /// Draws the outline of a rectangle given as `[x, y, width, height]`, measured from the top
+    /// left corner of the page.
+    pub fn draw_rect(&mut self, rect: [f64; 4]) {
+        let y = self.size.1 - rect[1] - rect[3];
+        self.content.push_str(&format!("0.6 G 0.5 w {} {} {} {} re S\n", rect[0], y, rect[2], rect[3]));
+    }
+}
+
+impl PdfWriter {
+    /// The id of the document's catalog object.
+    const CATALOG_ID: usize = 1;
+    /// The id of the document's page tree object.
+    const PAGES_ID: usize = 2;
+
+    /// The id of the document's structure tree root object.
+    const STRUCT_TREE_ID: usize = 3;
+
+    pub fn new() -> PdfWriter {
+        // The catalog, page tree and structure tree get written when finishing the document, but
+        // their ids need to be reserved.
+        PdfWriter { objects: vec![Vec::new(), Vec::new(), Vec::new()], pages: Vec::new(), figures: Vec::new(), bookmarks: Vec::new() }
+    }
+
+    /// Adds a bookmark pointing to a position on the page that gets added next.
+    /// 
+    /// The position is measured from the top of the page.
+    pub fn add_bookmark(&mut self, title: &str, page: &PdfPage, y: f64) {
+        self.bookmarks.push((title.to_owned(), self.pages.len(), page.size.1 - y));
+    }
+
+    fn add_object(&mut self, body: Vec<u8>) -> usize {
+        self.objects.push(body);
+        self.objects.len()
+    }
+
+    fn add_stream(&mut self, dict: String, data: &[u8]) -> usize {
+        let mut body = format!("<< {dict} /Length {} >>\nstream\n", data.len()).into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\nendstream");
+        self.add_object(body)
+    }
+
+    /// Adds an image to the document, returning it's id for drawing it on pages.
+    pub fn add_image(&mut self, image: &RgbaImage) -> anyhow::Result<usize> {
+        let rgb = image::DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+
+        let mut jpeg = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 90)
+            .encode(rgb.as_raw(), rgb.width(), rgb.height(), image::ColorType::Rgb8)?;
+
+        Ok(self.add_stream(
+            format!("/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode", rgb.width(), rgb.height()),
+            &jpeg))
+    }
+
+    /// Adds a page to the end of the document.
+    pub fn add_page(&mut self, page: PdfPage) {
+        let content = self.add_stream(String::new(), page.content.as_bytes());
+
+        let images = page.images.iter().map(|(name, id)| format!("/{name} {id} 0 R")).collect::<Vec<_>>().join(" ");
+        let id = self.add_object(format!(
+            "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources << /XObject << {images} >> >> /Contents {content} 0 R /StructParents {} >>",
+            Self::PAGES_ID, page.size.0, page.size.1, self.figures.len()).into_bytes());
+
+        // Add a structure element for each of the page's figures
+        let figures = page.figures.iter().enumerate().map(|(mcid, alt)| {
+            self.add_object(format!("<< /Type /StructElem /S /Figure /P {} 0 R /Pg {id} 0 R /K {mcid} /Alt {} >>", Self::STRUCT_TREE_ID, text_string(alt)).into_bytes())
+        }).collect::<Vec<_>>();
+
+        self.pages.push(id);
+        self.figures.push(figures);
+    }
+
+    /// Adds the document outline containing all bookmarks, returning it's id.
+    fn add_outline(&mut self) -> Option<usize> {
+        if self.bookmarks.is_empty() {
+            return None
+        }
+
+        // The ids of the outline's items directly follow the id of the outline itself
+        let outline = self.add_object(Vec::new());
+        let first = outline + 1;
+        let last = outline + self.bookmarks.len();
+
+        for (i, (title, page, y)) in std::mem::take(&mut self.bookmarks).into_iter().enumerate() {
+            let id = first + i;
+            let mut item = format!("<< /Title {} /Parent {outline} 0 R /Dest [{} 0 R /XYZ 0 {y} null]", text_string(&title), self.pages[page]);
+            if id > first {
+                item.push_str(&format!(" /Prev {} 0 R", id - 1));
+            }
+            if id < last {
+                item.push_str(&format!(" /Next {} 0 R", id + 1));
+            }
+            item.push_str(" >>");
+            self.add_object(item.into_bytes());
+        }
+
+        self.objects[outline-1] = format!("<< /Type /Outlines /First {first} 0 R /Last {last} 0 R /Count {} >>", last - outline).into_bytes();
+        Some(outline)
+    }
+
+    /// Writes the document to a file.
+    pub fn write<P: AsRef<Path>>(mut self, path: P) -> anyhow::Result<()> {
+        let outline = match self.add_outline() {
+            Some(id) => format!(" /Outlines {id} 0 R /PageMode /UseOutlines"),
+            None => String::new()
+        };
+        self.objects[Self::CATALOG_ID-1] = format!(
+            "<< /Type /Catalog /Pages {} 0 R /StructTreeRoot {} 0 R /MarkInfo << /Marked true >>{outline} >>",
+            Self::PAGES_ID, Self::STRUCT_TREE_ID).into_bytes();
+
+        // The structure tree contains all figures, the parent tree maps each page's marked
+        // content ids to them
+        let elements = self.figures.iter().flatten().map(|id| format!("{id} 0 R")).collect::<Vec<_>>().join(" ");
+        let parent_tree = self.figures.iter().enumerate().map(|(page, figures)| {
+            format!("{page} [{}]", figures.iter().map(|id| format!("{id} 0 R")).collect::<Vec<_>>().join(" "))
+        }).collect::<Vec<_>>().join(" ");
+        self.objects[Self::STRUCT_TREE_ID-1] = format!("<< /Type /StructTreeRoot /K [{elements}] /ParentTree << /Nums [{parent_tree}] >> >>").into_bytes();
+
+        let kids = self.pages.iter().map(|id| format!("{id} 0 R")).collect::<Vec<_>>().join(" ");
+        self.objects[Self::PAGES_ID-1] = format!("<< /Type /Pages /Kids [{kids}] /Count {} >>", self.pages.len()).into_bytes();
+
+        let mut out: Vec<u8> = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+
+        for (i, body) in self.objects.iter().enumerate() {
+            offsets.push(out.len());
+            write!(out, "{} 0 obj\n", i+1)?;
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref_offset = out.len();
+        write!(out, "xref\n0 {}\n0000000000 65535 f \n", self.objects.len()+1)?;
+        for offset in offsets {
+            write!(out, "{offset:010} 00000 n \n")?;
+        }
+        write!(out, "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n", self.objects.len()+1, Self::CATALOG_ID)?;
+
+        std::fs::write(path, out)?;
+        Ok(())
+    }
+}
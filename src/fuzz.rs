@@ -0,0 +1,146 @@
+//! A small mutation fuzzer for the document, expression and text markup parsers.
+//! 
+//! Feeds the files of a corpus directory and random mutations of them into each target and
+//! collects inputs that make a target panic. Only compiled with the `fuzzing` feature.
+
+use std::collections::HashMap;
+use std::panic::{ self, AssertUnwindSafe };
+use std::path::{ Path, PathBuf };
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::presentation;
+use crate::presentation::renderable::BaseProperties;
+use crate::presentation::util::{ self, ResExprType };
+
+/// The amount of mutations generated from each input of the corpus.
+pub const DEFAULT_MUTATIONS: usize = 2000;
+
+/// A parser that gets fuzzed.
+pub struct Target {
+    pub name: &'static str,
+    /// Parses the input. Errors are fine, only panics count as failures.
+    pub run: fn(&[u8])
+}
+
+/// All fuzzed parsers.
+pub const TARGETS: [Target; 3] = [
+    Target { name: "document", run: fuzz_document },
+    Target { name: "expression", run: fuzz_expression },
+    Target { name: "markup", run: fuzz_markup }
+];
+
+fn fuzz_document(data: &[u8]) {
+    let _ = crate::parse::parse_document_bytes(data);
+}
+
+fn fuzz_expression(data: &[u8]) {
+    let expr = match std::str::from_utf8(data) {
+        Ok(expr) => expr,
+        Err(_) => return
+    };
+    if let Ok(expr) = util::res_dependent_expr(expr, util::DEFAULT_CONTEXT.clone(), ResExprType::WidthBased) {
+        let _ = expr.evaluate(1280.0, 720.0, 1.0, &HashMap::new());
+    }
+}
+
+fn fuzz_markup(data: &[u8]) {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(_) => return
+    };
+    let base = BaseProperties::new("0;0", "w;4%", "0;0;0;1", "TOP_LEFT").map_err(|_|()).unwrap();
+    let _ = presentation::Text::new(base, text.lines().collect(), "Default".to_owned(), &*crate::FONTS.get().unwrap(), HashMap::new(), "LEFT");
+}
+
+/// A xorshift random number generator, so runs are reproducible without extra dependencies.
+struct Rng(u64);
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+/// Bytes that are especially likely to confuse the parsers.
+const INTERESTING_BYTES: &[u8] = b"{}[]:,\"'#%;*~_`\\\t\n0123456789wht-+/^()";
+
+/// Randomly changes, inserts, removes or duplicates some bytes of an input.
+fn mutate(input: &[u8], rng: &mut Rng) -> Vec<u8> {
+    let mut data = input.to_vec();
+    for _ in 0..1 + rng.below(4) {
+        let position = rng.below(data.len() + 1);
+        match rng.below(5) {
+            0 if position < data.len() => data[position] = rng.next() as u8,
+            1 => data.insert(position, INTERESTING_BYTES[rng.below(INTERESTING_BYTES.len())]),
+            2 if position < data.len() => { data.remove(position); },
+            3 if position < data.len() => {
+                let end = (position + 1 + rng.below(16)).min(data.len());
+                let chunk = data[position..end].to_vec();
+                data.splice(position..position, chunk);
+            },
+            _ => data.truncate(position)
+        }
+    }
+    data
+}
+
+/// Runs a target on an input, returning whether it panicked.
+fn panics(target: &Target, data: &[u8]) -> bool {
+    panic::catch_unwind(AssertUnwindSafe(|| (target.run)(data))).is_err()
+}
+
+/// Fuzzes all targets using the files of a corpus directory as seeds.
+/// 
+/// Inputs that make a target panic get written to `<corpus>/crashes/<target>-<n>`, their paths
+/// get returned.
+pub fn run<P: AsRef<Path>>(corpus: P, mutations: usize, seed: u64) -> anyhow::Result<Vec<PathBuf>> {
+    let corpus = corpus.as_ref();
+    let crash_dir = corpus.join("crashes");
+
+    let mut inputs = Vec::new();
+    for entry in std::fs::read_dir(corpus)? {
+        let path = entry?.path();
+        if path.is_file() {
+            inputs.push(std::fs::read(&path)?);
+        }
+    }
+    if inputs.is_empty() {
+        inputs.push(Vec::new());
+    }
+
+    // Panics are expected, so the default hook would only flood the output
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut rng = Rng(seed.max(1));
+    let mut crashes = Vec::new();
+    for target in TARGETS.iter() {
+        for input in inputs.iter() {
+            for i in 0..=mutations {
+                let data = match i {
+                    0 => input.clone(),
+                    _ => mutate(input, &mut rng)
+                };
+
+                if panics(target, &data) {
+                    std::fs::create_dir_all(&crash_dir)?;
+                    let path = crash_dir.join(format!("{}-{}", target.name, crashes.len()));
+                    std::fs::write(&path, &data)?;
+                    crashes.push(path);
+                }
+            }
+        }
+        log_info!("Fuzzed the {} parser, {} crashing inputs so far", target.name, crashes.len());
+    }
+
+    panic::set_hook(hook);
+    Ok(crashes)
+}
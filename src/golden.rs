@@ -0,0 +1,96 @@
+//! Golden image regression checks for the renderer.
+//! 
+//! Renders every slide of a fixture document and compares it against a stored reference image,
+//! so changes to the renderer (e.g. to text layout or alignment) can't silently change how slides
+//! look. The reference images get committed next to their fixtures and are recorded (or updated
+//! after intended changes) using `--bless`. Only compiled with the `golden_tests` feature.
+
+use std::path::{ Path, PathBuf };
+
+use opengl_graphics::GlGraphics;
+use image::RgbaImage;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::presentation::Presentation;
+
+/// The resolution slides get rendered at for comparing them.
+pub const GOLDEN_SIZE: (u32, u32) = (640, 360);
+
+/// The largest difference of a color channel between two pixels that still counts as equal.
+/// 
+/// Absorbs small differences in anti-aliasing between drivers.
+const PIXEL_TOLERANCE: u8 = 8;
+
+/// The largest fraction of differing pixels a slide may have before it counts as changed.
+pub const DEFAULT_TOLERANCE: f64 = 0.001;
+
+/// Returns the directory containing the reference images of a fixture document.
+/// 
+/// For `tests/golden/text.hjson` that's `tests/golden/text/`.
+pub fn reference_dir<P: AsRef<Path>>(fixture: P) -> PathBuf {
+    fixture.as_ref().with_extension("")
+}
+
+/// Returns the fraction of pixels that differ between two images, or [`None`] if their sizes
+/// don't match.
+pub fn difference(actual: &RgbaImage, reference: &RgbaImage) -> Option<f64> {
+    if actual.dimensions() != reference.dimensions() {
+        return None
+    }
+
+    let differing = actual.pixels().zip(reference.pixels())
+        .filter(|(a, b)| a.0.iter().zip(b.0.iter()).any(|(a, b)| a.abs_diff(*b) > PIXEL_TOLERANCE))
+        .count();
+
+    Some(differing as f64 / (actual.width() as f64 * actual.height() as f64))
+}
+
+/// Renders all slides of a presentation and compares them with the reference images in a
+/// directory (`slide-0.png`, `slide-1.png`, ...).
+/// 
+/// For slides that differ by more than `tolerance` (a fraction of pixels), the rendered image gets
+/// written next to the reference as `slide-N.actual.png`. Returns the indices of those slides and
+/// of the slides without a reference image. If `bless` is set, the rendered slides get saved as
+/// the new reference images instead.
+pub fn check<P: AsRef<Path>>(presentation: &Presentation, opengl: &mut GlGraphics, references: P, tolerance: f64, bless: bool) -> anyhow::Result<Vec<usize>> {
+    let references = references.as_ref();
+    std::fs::create_dir_all(references)?;
+
+    let mut failed = Vec::new();
+    for index in 0..presentation.document_slide_count() {
+        let actual = match presentation.render_thumbnail(index, GOLDEN_SIZE, opengl) {
+            Some(image) => image,
+            None => continue
+        };
+
+        let reference_path = references.join(format!("slide-{index}.png"));
+        if bless {
+            actual.save(&reference_path)?;
+            log_warn!("Recorded reference image {}", reference_path.display());
+            continue
+        }
+        if !reference_path.exists() {
+            log_err!("Slide #{index} has no reference image {} (record it using --bless)", reference_path.display());
+            actual.save(references.join(format!("slide-{index}.actual.png")))?;
+            failed.push(index);
+            continue
+        }
+
+        let reference = image::open(&reference_path)?.to_rgba8();
+        match difference(&actual, &reference) {
+            Some(diff) if diff <= tolerance => log_info!("Slide #{index} matches {}", reference_path.display()),
+            result => {
+                match result {
+                    Some(diff) => log_err!("Slide #{index} differs from {} ({:.3}% of pixels)", reference_path.display(), diff * 100.0),
+                    None => log_err!("Slide #{index} has a different size than {}", reference_path.display())
+                }
+                actual.save(references.join(format!("slide-{index}.actual.png")))?;
+                failed.push(index);
+            }
+        }
+    }
+
+    Ok(failed)
+}
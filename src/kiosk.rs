@@ -0,0 +1,123 @@
+use std::path::{ Path, PathBuf };
+use std::time::Instant;
+
+use chrono::NaiveTime;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::config::KioskConfig;
+
+/// The interval in seconds at which the watchdog checks the schedule and the render errors.
+const CHECK_INTERVAL: f64 = 1.0;
+
+/// The time span in seconds render errors get counted over before the count starts again.
+const ERROR_WINDOW: f64 = 60.0;
+
+/// Restarts the viewer for unattended signage displays.
+///
+/// The viewer gets restarted with another document whenever the schedule in the user
+/// configuration switches to it, after it has been running for the configured amount of hours and
+/// when objects failed to render too often within a minute. Restarting starts a new process of the
+/// application, so everything (including fonts and the Lua instance) gets loaded from scratch.
+pub struct Watchdog {
+    /// The scheduled documents by the time of day they get shown from, sorted by that time.
+    schedule: Vec<(NaiveTime, PathBuf)>,
+    /// The amount of seconds after which the viewer gets restarted.
+    restart_after: Option<f64>,
+    /// The amount of render errors within [`ERROR_WINDOW`] after which the viewer gets restarted.
+    max_render_errors: Option<u32>,
+    start: Instant,
+    last_check: Instant,
+    /// The start of the current error window and the render error count at that time.
+    error_window: (Instant, u32)
+}
+
+impl Watchdog {
+    /// Creates the watchdog from the user configuration.
+    ///
+    /// Returns [`None`] if neither a schedule nor any restart conditions are configured.
+    pub fn new(config: &KioskConfig) -> anyhow::Result<Option<Self>> {
+        if config.schedule.is_empty() && config.restart_after.is_none() && config.max_render_errors.is_none() {
+            return Ok(None)
+        }
+
+        let mut schedule = config.schedule.iter()
+            .map(|entry| {
+                let time = NaiveTime::parse_from_str(&entry.from, "%H:%M")
+                    .map_err(|_| anyhow::anyhow!("invalid time of day '{}' in the kiosk schedule (expected HH:MM)", entry.from))?;
+                Ok((time, entry.document.clone()))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        schedule.sort_by_key(|(time, _)| *time);
+
+        let now = Instant::now();
+        Ok(Some(Watchdog {
+            schedule,
+            restart_after: config.restart_after.map(|hours| hours * 3600.0),
+            max_render_errors: config.max_render_errors,
+            start: now,
+            last_check: now,
+            error_window: (now, crate::presentation::render_error_count())
+        }))
+    }
+
+    /// Returns the document scheduled for the current time of day.
+    ///
+    /// Before the first scheduled time of a day, the document scheduled last on the day before is
+    /// still shown. Returns [`None`] if the schedule is empty.
+    pub fn scheduled_document(&self) -> Option<&Path> {
+        let now = chrono::Local::now().time();
+        self.schedule.iter().rev()
+            .find(|(time, _)| *time <= now)
+            .or(self.schedule.last())
+            .map(|(_, document)| document.as_path())
+    }
+
+    /// Checks whether the viewer showing `document` should be restarted.
+    ///
+    /// Returns the document the restarted viewer should show.
+    pub fn check(&mut self, document: &Path) -> Option<PathBuf> {
+        if self.last_check.elapsed().as_secs_f64() < CHECK_INTERVAL {
+            return None
+        }
+        self.last_check = Instant::now();
+
+        let scheduled = self.scheduled_document().unwrap_or(document).to_path_buf();
+        if scheduled != document {
+            log_info!("Switching to the scheduled document {}", scheduled.display());
+            return Some(scheduled)
+        }
+
+        if self.restart_after.is_some_and(|seconds| self.start.elapsed().as_secs_f64() >= seconds) {
+            log_info!("Restarting the viewer after {:.1} hours", self.start.elapsed().as_secs_f64() / 3600.0);
+            return Some(scheduled)
+        }
+
+        let errors = crate::presentation::render_error_count();
+        let (window_start, window_errors) = self.error_window;
+        if self.max_render_errors.is_some_and(|max| errors.wrapping_sub(window_errors) >= max) {
+            log_err!("Restarting the viewer after {} render errors", errors.wrapping_sub(window_errors));
+            return Some(scheduled)
+        }
+        if window_start.elapsed().as_secs_f64() >= ERROR_WINDOW {
+            self.error_window = (Instant::now(), errors);
+        }
+
+        None
+    }
+}
+
+/// Starts a new viewer process showing `document`, passing on the original command line flags.
+///
+/// `args` are the arguments the application was started with. The current process should exit
+/// afterwards.
+pub fn restart(args: &[String], document: &Path) -> anyhow::Result<()> {
+    let executable = std::env::current_exe()?;
+    std::process::Command::new(executable)
+        .arg("view")
+        .arg(document)
+        .args(&args[3..])
+        .spawn()?;
+    Ok(())
+}
@@ -0,0 +1,254 @@
+//! Checks documents for common readability problems, used by the `validate` command and the
+//! editor.
+//!
+//! Every rule has a severity, which is set in the `lint` section of the user configuration and
+//! can be overridden by documents in the `lint`-field of their metadata, e.g.
+//! `lint: { small_text: "error", low_contrast: "off" }`.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::config::LintConfig;
+use crate::presentation::{ Presentation, Renderable, THUMBNAIL_TIME };
+use crate::presentation::util::{ self, ExprEval };
+
+/// The screen size slides get checked at, so the thresholds don't depend on the window.
+const VIEW_SIZE: [f64; 2] = [1920.0, 1080.0];
+
+/// How many pixels objects can reach beyond the slide or text beyond it's width without being
+/// reported, so rounding errors don't cause findings.
+const TOLERANCE: f64 = 1.0;
+
+/// How serious a rule's findings are.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The rule doesn't get checked.
+    Off,
+    /// Findings get reported, but don't fail validation.
+    Warn,
+    /// Findings fail validation.
+    Error
+}
+
+impl Severity {
+    /// Parses a severity from it's name as used in documents and the configuration.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Severity::Off),
+            "warn" => Some(Severity::Warn),
+            "error" => Some(Severity::Error),
+            _ => None
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Off => write!(f, "off"),
+            Severity::Warn => write!(f, "warning"),
+            Severity::Error => write!(f, "error")
+        }
+    }
+}
+
+/// The checks done by the lint pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// Text with a font size below [`LintConfig::min_text_size`].
+    SmallText,
+    /// Text whose contrast to a plain colored background is below [`LintConfig::min_contrast`].
+    LowContrast,
+    /// Images scaled up by more than [`LintConfig::max_upscale`].
+    UpscaledImage,
+    /// Objects reaching beyond the edges of the slide.
+    OutOfBounds,
+    /// Text with words too long to be wrapped at the object's width.
+    TextOverflow
+}
+
+impl Rule {
+    /// All rules, in the order they get checked in.
+    pub const ALL: [Rule; 5] = [Rule::SmallText, Rule::LowContrast, Rule::UpscaledImage, Rule::OutOfBounds, Rule::TextOverflow];
+
+    /// Returns the name the rule is configured by.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::SmallText => "small_text",
+            Rule::LowContrast => "low_contrast",
+            Rule::UpscaledImage => "upscaled_image",
+            Rule::OutOfBounds => "out_of_bounds",
+            Rule::TextOverflow => "text_overflow"
+        }
+    }
+}
+
+/// A problem found on a slide.
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub rule: Rule,
+    pub severity: Severity,
+    /// The index of the slide the problem is on.
+    pub slide: usize,
+    /// The layer and index of the object causing the problem.
+    pub object: (i16, usize),
+    pub message: String
+}
+
+impl Display for Finding {
+    /// Shows the slide by it's number (starting at 1), as it's meant for authors.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: slide {}: {} [{}]", self.severity, self.slide + 1, self.message, self.rule.name())
+    }
+}
+
+/// Checks all slides of the presentation as they look at [`THUMBNAIL_TIME`] on a 1080p screen,
+/// which is the resolution slides are designed for.
+///
+/// Objects whose properties can't be evaluated get skipped, as they already fail when rendering.
+pub fn run(presentation: &Presentation, config: &LintConfig) -> Vec<Finding> {
+    let severities: HashMap<&str, Severity> = Rule::ALL.iter()
+        .map(|rule| {
+            let severity = presentation.lint_severities().get(rule.name())
+                .or(config.rules.get(rule.name()))
+                .copied()
+                .unwrap_or(Severity::Warn);
+            (rule.name(), severity)
+        })
+        .collect();
+    let enabled = |rule: Rule| severities[rule.name()] != Severity::Off;
+
+    let mut findings = Vec::new();
+    for index in 0..presentation.document_slide_count() {
+        let slide = match presentation.slide(index) {
+            Some(s) => s,
+            None => continue
+        };
+
+        // Expressions need the same state as when the slide gets rendered
+        util::set_random_seed(index, presentation.seed());
+        util::set_safe_area(slide.safe_area());
+
+        let background = slide.plain_background_color(THUMBNAIL_TIME, VIEW_SIZE);
+
+        for (z_index, objects) in slide.layers() {
+            for (object_index, object) in objects.iter().enumerate() {
+                let name = match object.id() {
+                    Some(id) => format!("{} \"{id}\"", object.type_name()),
+                    None => object.type_name().to_owned()
+                };
+                let mut report = |rule: Rule, message: String| findings.push(Finding {
+                    rule, severity: severities[rule.name()], slide: index, object: (z_index, object_index), message: format!("{name}: {message}")
+                });
+
+                if object.type_name() == "Text" {
+                    if enabled(Rule::SmallText) {
+                        if let Some(size) = font_size(&**object).filter(|size| *size < config.min_text_size) {
+                            report(Rule::SmallText, format!("font size of {size:.0}px is below {:.0}px", config.min_text_size));
+                        }
+                    }
+
+                    if enabled(Rule::LowContrast) {
+                        let ratio = background.zip(color(&**object)).map(|(background, color)| contrast_ratio(color, background));
+                        if let Some(ratio) = ratio.filter(|ratio| *ratio < config.min_contrast) {
+                            report(Rule::LowContrast, format!("contrast ratio of {ratio:.2}:1 to the background is below {:.1}:1", config.min_contrast));
+                        }
+                    }
+
+                    if enabled(Rule::TextOverflow) {
+                        if let Some((width, max_width)) = text_overflow(&**object) {
+                            report(Rule::TextOverflow, format!("text is {width:.0}px wide, but wraps at {max_width:.0}px (a word is too long)"));
+                        }
+                    }
+                }
+
+                if enabled(Rule::OutOfBounds) {
+                    if let Some(distance) = out_of_bounds(&**object) {
+                        report(Rule::OutOfBounds, format!("reaches {distance:.0}px beyond the edge of the slide"));
+                    }
+                }
+
+                if enabled(Rule::UpscaledImage) {
+                    if let Some(scale) = upscale(&**object).filter(|scale| *scale > config.max_upscale) {
+                        report(Rule::UpscaledImage, format!("image is scaled up {scale:.1}x, more than {:.1}x", config.max_upscale));
+                    }
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+/// Evaluates the expressions of an object's property.
+fn evaluate<const N: usize>(object: &dyn Renderable, property: &util::ExprVector<N>) -> Option<[f64; N]> {
+    let object_repr = object.to_lua(crate::LUA_INSTANCE.get().unwrap()).ok()?;
+    property.evaluate_arr(VIEW_SIZE[0], VIEW_SIZE[1], THUMBNAIL_TIME, &object_repr).ok()?
+        .try_map(|e| match e {
+            ExprEval::F64(f) => Some(f),
+            ExprEval::String(_) => None
+        })
+}
+
+/// Returns the default font size of a text object, which is the height in it's size.
+fn font_size(object: &dyn Renderable) -> Option<f64> {
+    evaluate(object, &object.get_base_properties().size).map(|size| size[1])
+}
+
+/// Returns the color of an object.
+fn color(object: &dyn Renderable) -> Option<[f64; 4]> {
+    evaluate(object, &object.get_base_properties().color)
+}
+
+/// Returns how much an image is scaled up along it's more stretched axis.
+fn upscale(object: &dyn Renderable) -> Option<f64> {
+    let (width, height) = object.source_size().filter(|(w, h)| *w > 0 && *h > 0)?;
+    let (bounds, _) = object.bounds(THUMBNAIL_TIME, VIEW_SIZE).ok()?;
+    Some((bounds[2].abs() / width as f64).max(bounds[3].abs() / height as f64))
+}
+
+/// Returns how far an object reaches beyond the slide at most, if it does.
+fn out_of_bounds(object: &dyn Renderable) -> Option<f64> {
+    let (bounds, _) = object.bounds(THUMBNAIL_TIME, VIEW_SIZE).ok()?;
+    let (left, right) = (bounds[0].min(bounds[0] + bounds[2]), bounds[0].max(bounds[0] + bounds[2]));
+    let (top, bottom) = (bounds[1].min(bounds[1] + bounds[3]), bounds[1].max(bounds[1] + bounds[3]));
+
+    let distance = [-left, -top, right - VIEW_SIZE[0], bottom - VIEW_SIZE[1]].into_iter().fold(0.0, f64::max);
+    Some(distance).filter(|distance| *distance > TOLERANCE)
+}
+
+/// Returns the width of a text object's laid out lines and the width they get wrapped at, if
+/// the lines are wider.
+fn text_overflow(object: &dyn Renderable) -> Option<(f64, f64)> {
+    let max_width = evaluate(object, &object.get_base_properties().size)?[0];
+    let (width, _) = object.measure(VIEW_SIZE[0], VIEW_SIZE[1], THUMBNAIL_TIME).ok()?;
+    Some((width, max_width)).filter(|(width, max_width)| width - max_width > TOLERANCE)
+}
+
+/// Calculates the WCAG contrast ratio of a (possibly transparent) color drawn over an opaque
+/// background.
+fn contrast_ratio(color: [f64; 4], background: [f64; 4]) -> f64 {
+    let alpha = color[3].clamp(0.0, 1.0);
+    let blended = [0, 1, 2].map(|i| color[i] * alpha + background[i] * (1.0 - alpha));
+
+    let (a, b) = (relative_luminance(blended), relative_luminance([background[0], background[1], background[2]]));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Calculates the relative luminance of a color in the sRGB color space.
+fn relative_luminance(color: [f64; 3]) -> f64 {
+    let linear = color.map(|c| {
+        let c = c.clamp(0.0, 1.0);
+        match c <= 0.04045 {
+            true => c / 12.92,
+            false => ((c + 0.055) / 1.055).powf(2.4)
+        }
+    });
+    0.2126 * linear[0] + 0.7152 * linear[1] + 0.0722 * linear[2]
+}
@@ -14,14 +14,38 @@ use std::sync::{ OnceLock, RwLock };
 use std::rc::Rc;
 use opengl_graphics::OpenGL;
 use piston::input::*;
-use piston_window::{ PistonWindow, Events, EventSettings };
+use piston_window::PistonWindow;
 use mlua::{ Lua, StdLib, LuaOptions };
 
 mod viewer_app;
 mod editor_app;
+mod engine;
+mod components;
+mod state;
+mod chapters;
+mod lint;
+mod kiosk;
+mod approval;
+mod review;
+mod config;
+mod audio;
+mod midi;
+mod messages;
+#[cfg(target_os = "linux")]
+mod mpris;
+mod spellcheck;
 mod util;
 mod render;
+mod export;
 mod parse;
+#[cfg(feature = "golden_tests")]
+mod golden;
+#[cfg(feature = "benchmarks")]
+mod bench;
+#[cfg(feature = "fuzzing")]
+mod fuzz;
+#[cfg(feature = "render_tracing")]
+mod profiling;
 
 mod presentation;
 
@@ -35,21 +59,103 @@ const APPLICATION_VERSION: &'static str = include_str!("version");
 // them is used on multiple threads concurrently, things will go VERY bad.
 pub static LUA_INSTANCE: OnceLock<AssumeThreadSafe<Lua>> = OnceLock::new();
 pub static FONTS: OnceLock<AssumeThreadSafe<HashMap<String, Rc<RefCell<presentation::TextFont>>>>> = OnceLock::new();
+/// The user configuration, merged with the command line flags.
+pub static CONFIG: OnceLock<config::Config> = OnceLock::new();
+
+/// Returns the monitor configured in the user configuration, if it exists.
+///
+/// A monitor selected by it's name takes precedence over one selected by it's index.
+fn configured_monitor(window: &PistonWindow) -> Option<winit::monitor::MonitorHandle> {
+    let config = CONFIG.get()?;
+    if let Some(name) = &config.monitor_name {
+        let monitor = window.window.window.available_monitors().find(|monitor| monitor.name().is_some_and(|n| n.contains(name.as_str())));
+        if monitor.is_none() {
+            log::warn!("There's no monitor named '{name}' (available: {})", window.window.window.available_monitors().filter_map(|m| m.name()).collect::<Vec<_>>().join(", "));
+        }
+        return monitor
+    }
+    window.window.window.available_monitors().nth(config.monitor?)
+}
+
+/// Toggles between windowed and fullscreen mode, using the configured monitor if there is one.
+///
+/// Exclusive fullscreen switches to the monitor's video mode with the highest resolution and
+/// refresh rate.
+fn toggle_fullscreen(window: &mut PistonWindow) {
+    if window.window.window.fullscreen().is_some() {
+        window.window.window.set_fullscreen(None);
+        return
+    }
+
+    let monitor = configured_monitor(window).or_else(|| window.window.window.current_monitor());
+    let fullscreen = match CONFIG.get().unwrap().fullscreen_mode {
+        config::FullscreenMode::Borderless => Some(winit::window::Fullscreen::Borderless(monitor)),
+        config::FullscreenMode::Exclusive => monitor
+            .and_then(|monitor| monitor.video_modes().max_by_key(|mode| (mode.size().width * mode.size().height, mode.refresh_rate_millihertz())))
+            .map(winit::window::Fullscreen::Exclusive)
+    };
+    window.window.window.set_fullscreen(fullscreen);
+}
+
+/// Returns the refresh rate of the monitor the window is currently on, in Hz.
+fn current_refresh_rate(window: &PistonWindow) -> Option<f64> {
+    window.window.window.current_monitor()
+        .and_then(|monitor| monitor.refresh_rate_millihertz())
+        .map(|millihertz| millihertz as f64 / 1000.0)
+}
+
+/// Moves the window onto the configured monitor.
+fn move_to_configured_monitor(window: &mut PistonWindow) {
+    if let Some(monitor) = configured_monitor(window) {
+        window.window.window.set_outer_position(monitor.position());
+    }
+}
 
 fn run_viewer(args: Vec<String>) -> anyhow::Result<()> {
     let mut application = viewer_app::Application::create(OpenGL::V3_2);
 
-    let mut window: PistonWindow = application.init(format!("APresentation Viewer - {}",APPLICATION_VERSION), (1280,720), false, true, true, args[2].clone());
+    let config = CONFIG.get().unwrap();
+
+    // A kiosk schedule replaces the document given on the command line
+    let mut watchdog = kiosk::Watchdog::new(&config.kiosk)?;
+    let document = watchdog.as_ref().and_then(|w| w.scheduled_document()).map(|d| d.display().to_string()).unwrap_or(args[2].clone());
+
+    let mut window: PistonWindow = application.init(format!("APresentation Viewer - {}",APPLICATION_VERSION), config.window_size, config.vsync, true, !config.frameless, config.transparent, document.clone());
+    move_to_configured_monitor(&mut window);
+    application.set_refresh_rate(current_refresh_rate(&window));
+
+    // A video output that can't be opened gets reported before anything is shown
+    if let Some(path) = &config.video_output {
+        let output = render::output::RawVideoOutput::new(path).map_err(|e| anyhow::anyhow!("Opening the video output at {} failed: {e}", path.display()))?;
+        application.set_video_output(Box::new(output));
+    }
+
+    // The window only gets shown after the first slide was rendered once, so the audience doesn't
+    // see it half loaded
+    let size = window.window.window.inner_size();
+    application.warm_up((size.width.max(1), size.height.max(1)));
+    window.window.window.set_visible(true);
+
+    // Presenting the document for long enough or until closing the viewer counts as rehearsing
+    // it, for reviewing the slides changed since
+    let shown = std::time::Instant::now();
+    let mut rehearsed = false;
+    let mut restarting = false;
+    let record_rehearsal = |application: &viewer_app::Application| {
+        if let Err(e) = review::record_rehearsal(std::path::Path::new(&document), &application.data.presentation) {
+            log::warn!("Saving the rehearsal of {document} failed: {e}");
+        }
+    };
+
+    if config.always_on_top {
+        window.window.window.set_window_level(winit::window::WindowLevel::AlwaysOnTop);
+    }
 
     let mut fullscreen;
 
-    let mut events = Events::new({
-        let mut settings = EventSettings::new();
-        settings.lazy = false;
-        settings.bench_mode = false;
-        settings.max_fps = std::u64::MAX;
-        settings
-    });
+    let mut events = engine::events();
+
+    let mut cursor_visible = true;
 
     while let Some(e) = events.next(&mut window) {
         if let Some(args) = e.render_args() {
@@ -59,33 +165,101 @@ fn run_viewer(args: Vec<String>) -> anyhow::Result<()> {
         if let Some(args) = e.update_args() {
             application.update(&args);
         }
+
+        if let Some(position) = e.mouse_cursor_args() {
+            application.mouse_moved(position);
+        }
+
+        if let Some(text) = e.text_args() {
+            application.text_input(&text);
+        }
+
+        if let Some(amount) = e.mouse_scroll_args() {
+            application.scroll(amount);
+        }
         
         if let Some(args) = e.button_args() {
             fullscreen = application.input(&args);
 
             if fullscreen {
-                window.window.window.set_fullscreen(match window.window.window.fullscreen().is_none() { true => Some(winit::window::Fullscreen::Borderless(None)), false => None });
+                toggle_fullscreen(&mut window);
+                application.set_refresh_rate(current_refresh_rate(&window));
+
+                // Hide the cursor right away when switching to fullscreen mode
+                if window.window.window.fullscreen().is_some() {
+                    application.hide_cursor();
+                }
+            }
+        }
+
+        if !rehearsed && shown.elapsed().as_secs_f64() >= review::MIN_REHEARSAL_TIME {
+            record_rehearsal(&application);
+            rehearsed = true;
+        }
+
+        if application.should_quit() {
+            piston::Window::set_should_close(&mut window, true);
+        }
+
+        if let Some(next) = watchdog.as_mut().and_then(|w| w.check(std::path::Path::new(&document))) {
+            match kiosk::restart(&args, &next) {
+                Ok(()) => {
+                    piston::Window::set_should_close(&mut window, true);
+                    restarting = true;
+                },
+                Err(e) => {
+                    log::error!("Restarting the viewer failed, disabling the kiosk watchdog: {e}");
+                    watchdog = None;
+                }
             }
         }
+
+        // Only update the cursor's visibility when it actually changed
+        if application.cursor_visible() != cursor_visible {
+            cursor_visible = !cursor_visible;
+            window.window.window.set_cursor_visible(cursor_visible);
+        }
+    }
+
+    // The kiosk watchdog restarting the viewer isn't a rehearsal being finished
+    if !rehearsed && !restarting {
+        record_rehearsal(&application);
+    }
+
+    Ok(())
+}
+
+fn run_generate(args: Vec<String>) -> anyhow::Result<()> {
+    let names = args[3..].iter().take_while(|arg| !arg.starts_with("--")).collect::<Vec<_>>();
+
+    // Without any components, the commented template gets generated
+    if names.is_empty() {
+        std::fs::write(&args[2], include_str!("template.hjson"))?;
+        return Ok(())
     }
 
+    let components = names.iter()
+        .map(|name| components::Component::find(name).ok_or(anyhow::anyhow!("unknown component \"{name}\" (available: {})", components::COMPONENTS.iter().map(|c| c.name).collect::<Vec<_>>().join(", "))))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    std::fs::write(&args[2], components::document(&components, &components::Style::default()))?;
+
     Ok(())
 }
 
 fn run_editor(args: Vec<String>) -> anyhow::Result<()> {
     let mut application = editor_app::Application::create(OpenGL::V3_2);
 
-    let mut window: PistonWindow = application.init(format!("APresentation Editor - {}",APPLICATION_VERSION), (1280,720), false, true, true, args[2].clone());
+    let config = CONFIG.get().unwrap();
+
+    // Every path before the first flag gets opened in it's own tab
+    let documents = args[2..].iter().take_while(|arg| !arg.starts_with("--")).cloned().collect::<Vec<_>>();
+
+    let mut window: PistonWindow = application.init(format!("APresentation Editor - {}",APPLICATION_VERSION), config.window_size, config.vsync, true, true, documents);
+    move_to_configured_monitor(&mut window);
 
     let mut fullscreen;
 
-    let mut events = Events::new({
-        let mut settings = EventSettings::new();
-        settings.lazy = false;
-        settings.bench_mode = false;
-        settings.max_fps = std::u64::MAX;
-        settings
-    });
+    let mut events = engine::events();
 
     while let Some(e) = events.next(&mut window) {
         if let Some(args) = e.render_args() {
@@ -99,12 +273,20 @@ fn run_editor(args: Vec<String>) -> anyhow::Result<()> {
         if let Some(args) = e.resize_args() {
             application.resize((args.draw_size[0],args.draw_size[1]))
         }
+
+        if let Some(position) = e.mouse_cursor_args() {
+            application.mouse_moved(position);
+        }
+
+        if let Some(amount) = e.mouse_scroll_args() {
+            application.scroll(amount);
+        }
         
         if let Some(args) = e.button_args() {
             fullscreen = application.input(&args);
 
             if fullscreen {
-                window.window.window.set_fullscreen(match window.window.window.fullscreen().is_none() { true => Some(winit::window::Fullscreen::Borderless(None)), false => None });
+                toggle_fullscreen(&mut window);
             }
         }
     }
@@ -112,27 +294,395 @@ fn run_editor(args: Vec<String>) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn run_handout_export(args: Vec<String>) -> anyhow::Result<()> {
+    let mut application = viewer_app::Application::create(OpenGL::V3_2);
+
+    let config = CONFIG.get().unwrap();
+
+    // The window is only needed for it's OpenGL context
+    let _window: PistonWindow = application.init(format!("APresentation Export - {}",APPLICATION_VERSION), config.window_size, false, false, true, false, args[2].clone());
+
+    let options = export::HandoutOptions {
+        slides_per_page: config.handout_slides_per_page,
+        note_lines: config.handout_note_lines,
+        speaker_notes: config.handout_speaker_notes,
+        slide_size: config.window_size
+    };
+
+    let output = std::path::Path::new(&args[2]).with_extension("pdf");
+    export::export_handout(&application.data.presentation, &mut application.opengl_backend, &options, output)
+}
+
+fn run_image_export(args: Vec<String>) -> anyhow::Result<()> {
+    let mut application = viewer_app::Application::create(OpenGL::V3_2);
+
+    let config = CONFIG.get().unwrap();
+
+    // The window is only needed for it's OpenGL context
+    let _window: PistonWindow = application.init(format!("APresentation Export - {}",APPLICATION_VERSION), config.window_size, false, false, true, false, args[2].clone());
+
+    let options = export::ImageExportOptions {
+        slides: config.export_slides.clone(),
+        format: config.export_format,
+        slide_size: config.window_size,
+        scale: config.export_scale,
+        file_name: config.export_file_name.clone()
+    };
+
+    let document = std::path::Path::new(&args[2]);
+    let name = document.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or("presentation".to_owned());
+    let directory = document.parent().unwrap_or(std::path::Path::new("."));
+    let mut exported = std::collections::HashMap::new();
+    let written = export::export_changed_images(&application.data.presentation, &mut application.opengl_backend, &options, &name, directory, &mut exported)?;
+    println!("Exported {} slides", written.len());
+
+    if !config.export_watch {
+        return Ok(())
+    }
+
+    const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+    println!("Watching {} for changes (press Ctrl+C to stop)", document.display());
+    let mut modified = std::fs::metadata(document)?.modified()?;
+    loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        // Editors often replace the file while saving, so it can be missing for a moment
+        match std::fs::metadata(document).and_then(|m| m.modified()) {
+            Ok(time) if time != modified => modified = time,
+            _ => continue
+        }
+
+        // Errors in the document panic, which shouldn't stop watching it
+        let reloaded = match std::panic::catch_unwind(|| engine::reload_document(&args[2])) {
+            Ok(reloaded) => reloaded,
+            Err(_) => {
+                log::error!("Reloading {} failed, waiting for the next change", document.display());
+                continue
+            }
+        };
+
+        let written = export::export_changed_images(&reloaded.presentation, &mut application.opengl_backend, &options, &name, directory, &mut exported)?;
+        println!("Exported {} changed slides", written.len());
+    }
+}
+
+fn run_merge(args: Vec<String>) -> anyhow::Result<()> {
+    use parse::Parser;
+
+    let config = CONFIG.get().unwrap();
+
+    let output = std::path::Path::new(&args[2]);
+    let paths = args[3..].iter().take_while(|arg| !arg.starts_with("--")).map(std::path::PathBuf::from).collect::<Vec<_>>();
+    if paths.is_empty() {
+        anyhow::bail!("no documents to merge");
+    }
+    let contents = paths.iter().map(std::fs::read_to_string).collect::<Result<Vec<_>, _>>()?;
+    let documents = contents.iter().map(String::as_str).zip(paths.iter().map(|p| p.as_path())).collect::<Vec<_>>();
+
+    let mut parser = parse::get_parser(output).ok_or(anyhow::anyhow!("No parser found for file type!"))?;
+    let merged = parser.merge(&documents, config.merge_dividers).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+    std::fs::write(output, merged)?;
+    println!("Merged {} documents into {}", documents.len(), output.display());
+
+    Ok(())
+}
+
+fn run_inspect(args: Vec<String>) -> anyhow::Result<()> {
+    let mut application = viewer_app::Application::create(OpenGL::V3_2);
+
+    let config = CONFIG.get().unwrap();
+
+    // The window is only needed for it's OpenGL context
+    let _window: PistonWindow = application.init(format!("APresentation Inspector - {}",APPLICATION_VERSION), config.window_size, false, false, true, false, args[2].clone());
+
+    let presentation = &application.data.presentation;
+    let words_per_minute = config.words_per_minute;
+
+    // The slides changed since the baseline, if one was given
+    let baseline = config.changed_since.as_deref().map(review::Baseline::from_name);
+    let changed = match &baseline {
+        Some(baseline) => Some(review::changed_slides(presentation, &baseline.hashes(std::path::Path::new(&args[2]))?)),
+        None => None
+    };
+
+    if config.inspect_json {
+        use parse::Parser;
+
+        // The presentation only keeps the loaded fonts, so their files are read from the document
+        let contents = std::fs::read_to_string(&args[2])?;
+        let mut parser = parse::get_parser(&args[2]).ok_or(anyhow::anyhow!("No parser found for file type!"))?;
+        let fonts = parser.parse_fonts(&contents).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+
+        let slides = (0..presentation.document_slide_count()).filter_map(|index| presentation.slide(index)).enumerate().map(|(index, slide)| {
+            let objects = slide.layers().flat_map(|(z, objects)| objects.iter().map(move |object| serde_json::json!({
+                "type": object.type_name(),
+                "id": object.id(),
+                "z": z,
+                "text": object.raw_text(),
+                "alt": object.alt_text(),
+                "asset": object.asset_path()
+            }))).collect::<Vec<_>>();
+
+            serde_json::json!({
+                "index": index,
+                "title": slide.title(),
+                "bookmark": slide.bookmark(),
+                "duration": slide.duration(),
+                "estimated_duration": slide.estimated_speaking_time(words_per_minute),
+                "words": slide.word_count(),
+                "notes": slide.notes(),
+                "hash": slide.content_hash().map(review::format_hash),
+                "changed": changed.as_ref().map(|changed| changed.contains(&index)),
+                "objects": objects,
+                "assets": slide.asset_paths().collect::<Vec<_>>()
+            })
+        }).collect::<Vec<_>>();
+
+        let mut assets = (0..presentation.document_slide_count())
+            .filter_map(|index| presentation.slide(index))
+            .flat_map(|slide| slide.asset_paths())
+            .chain(fonts.values().flat_map(|(regular, bold)| [regular.as_str(), bold.as_str()]))
+            .collect::<Vec<_>>();
+        assets.sort();
+        assets.dedup();
+
+        let document = serde_json::json!({
+            "title": presentation.title(),
+            "slides": slides,
+            "fonts": fonts,
+            "assets": assets,
+            "words": presentation.word_count(),
+            "duration": (0..presentation.document_slide_count()).filter_map(|index| presentation.slide(index)?.duration()).sum::<f64>(),
+            "estimated_duration": presentation.estimated_duration(words_per_minute),
+            "words_per_minute": words_per_minute,
+            "changed_since": config.changed_since
+        });
+        println!("{}", serde_json::to_string_pretty(&document)?);
+        return Ok(())
+    }
+
+    for index in 0..presentation.document_slide_count() {
+        let slide = match presentation.slide(index) {
+            Some(s) => s,
+            None => continue
+        };
+
+        let marker = match changed.as_ref().is_some_and(|changed| changed.contains(&index)) {
+            true => " (changed)",
+            false => ""
+        };
+        // Slides are numbered from one, like in the viewer's overview and the slide numbers
+        match slide.title() {
+            Some(title) => println!("Slide #{}: {title}{marker}", index + 1),
+            None => println!("Slide #{}{marker}", index + 1)
+        }
+        if let Some(duration) = slide.duration() {
+            println!("\tDuration: {duration}s");
+        }
+        println!("\tWords: {} (about {:.0}s of speaking)", slide.word_count(), slide.estimated_speaking_time(words_per_minute));
+        for line in slide.text_lines() {
+            println!("\tText: {line}");
+        }
+        for alt in slide.alt_texts() {
+            println!("\tImage: {alt}");
+        }
+    }
+
+    let seconds = presentation.estimated_duration(words_per_minute).round() as u64;
+    println!("Total: {} words, about {}:{:02} minutes at {words_per_minute} words per minute", presentation.word_count(), seconds / 60, seconds % 60);
+    if let (Some(baseline), Some(changed)) = (&baseline, &changed) {
+        println!("{} of {} slides changed since {}", changed.len(), presentation.document_slide_count(), baseline.describe());
+    }
+
+    Ok(())
+}
+
+fn run_validate(args: Vec<String>) -> anyhow::Result<()> {
+    let mut application = viewer_app::Application::create(OpenGL::V3_2);
+
+    let config = CONFIG.get().unwrap();
+
+    // The window is only needed for it's OpenGL context
+    let _window: PistonWindow = application.init(format!("APresentation Validator - {}",APPLICATION_VERSION), config.window_size, false, false, true, false, args[2].clone());
+
+    let findings = lint::run(&application.data.presentation, &config.lint);
+    for finding in findings.iter() {
+        println!("{finding}");
+    }
+
+    let errors = findings.iter().filter(|finding| finding.severity == lint::Severity::Error).count();
+    println!("{} problems found ({errors} errors)", findings.len());
+    if errors > 0 {
+        anyhow::bail!("validation of {} failed", args[2]);
+    }
+
+    Ok(())
+}
+
+fn run_format(args: Vec<String>) -> anyhow::Result<()> {
+    use parse::Parser;
+
+    let contents = std::fs::read_to_string(&args[2])?;
+    let mut parser = parse::get_parser(&args[2]).ok_or(anyhow::anyhow!("No parser found for file type!"))?;
+
+    let formatted = parser.format(&contents).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+
+    let config = CONFIG.get().unwrap();
+    if !config.format_write {
+        print!("{formatted}");
+        return Ok(())
+    }
+
+    // Formatting loses all comments, so they only get removed from the file when asked to
+    if parser.has_comments(&contents) && !config.format_strip_comments {
+        anyhow::bail!("{} contains comments, which would be removed by formatting it (use --strip-comments to rewrite it anyway)", args[2]);
+    }
+    if formatted != contents {
+        std::fs::write(&args[2], formatted)?;
+        println!("Formatted {}", args[2]);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "golden_tests")]
+fn run_golden(args: Vec<String>) -> anyhow::Result<()> {
+    let mut application = viewer_app::Application::create(OpenGL::V3_2);
+
+    let config = CONFIG.get().unwrap();
+
+    // The window is only needed for it's OpenGL context
+    let _window: PistonWindow = application.init(format!("APresentation Golden Tests - {}",APPLICATION_VERSION), config.window_size, false, false, true, false, args[2].clone());
+
+    let references = golden::reference_dir(&args[2]);
+    let failed = golden::check(&application.data.presentation, &mut application.opengl_backend, &references, golden::DEFAULT_TOLERANCE, config.golden_bless)?;
+    if !failed.is_empty() {
+        anyhow::bail!("slides {failed:?} of {} differ from their reference images or have none", args[2]);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "benchmarks")]
+fn run_benchmarks(_args: Vec<String>) -> anyhow::Result<()> {
+    let mut application = viewer_app::Application::create(OpenGL::V3_2);
+
+    let config = CONFIG.get().unwrap();
+
+    // The benchmarks run against a generated deck, which gets loaded like any other document
+    let path = env::temp_dir().join("apresentation-benchmark.hjson");
+    std::fs::write(&path, bench::synthetic_deck(bench::SYNTHETIC_SLIDES, bench::SYNTHETIC_OBJECTS_PER_SLIDE))?;
+
+    // The window is only needed for it's OpenGL context
+    let _window: PistonWindow = application.init(format!("APresentation Benchmarks - {}",APPLICATION_VERSION), config.window_size, false, false, true, false, path.display().to_string());
+
+    bench::run(&application.data.presentation, &mut application.opengl_backend);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[cfg(feature = "fuzzing")]
+fn run_fuzzer(args: Vec<String>) -> anyhow::Result<()> {
+    pretty_env_logger::try_init_timed_custom_env("LOG")?;
+
+    // The markup parser needs the fonts, the bundled default font is enough
+    engine::init_fonts(HashMap::new());
+
+    let seed = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+    println!("Fuzzing with seed {seed}");
+
+    let crashes = fuzz::run(&args[2], fuzz::DEFAULT_MUTATIONS, seed)?;
+    for path in crashes.iter() {
+        println!("Crashing input: {}", path.display());
+    }
+    if !crashes.is_empty() {
+        anyhow::bail!("found {} crashing inputs", crashes.len());
+    }
+
+    Ok(())
+}
+
 fn usage() {
-    println!("Usage:\n\ta_presentation.exe view [PATH_TO_FILE]\t\t- Opens a file for viewing\n\ta_presentation.exe generate [PATH_TO_FILE]\t- Generates a template for easier creation of presentations");
+    println!("Usage:\n\ta_presentation.exe view [PATH_TO_FILE] [FLAGS]\t\t- Opens a file for viewing\n\ta_presentation.exe generate [PATH_TO_FILE] [COMPONENT]...\t- Generates a template for easier creation of presentations, or a document made of the bundled components (title, section, two_columns, quote)\n\ta_presentation.exe handout [PATH_TO_FILE] [FLAGS]\t- Exports a printable PDF handout next to the file\n\ta_presentation.exe export [PATH_TO_FILE] [FLAGS]\t- Exports slides as images next to the file\n\ta_presentation.exe merge [OUTPUT] [PATH_TO_FILE]... [FLAGS]\t- Merges multiple files into one, keeping the order of their slides\n\ta_presentation.exe inspect [PATH_TO_FILE]\t\t- Lists the titles, text, word counts and image descriptions of all slides (as JSON with --json)\n\ta_presentation.exe validate [PATH_TO_FILE]\t\t- Checks all slides for small text, low contrast and upscaled images, failing on rules set to \"error\"\n\ta_presentation.exe fmt [PATH_TO_FILE] [FLAGS]\t\t- Prints a file in a canonical layout (without comments), or rewrites it using --write\n\ta_presentation.exe edit [PATH_TO_FILE]... [FLAGS]\t- Opens one or more files in the editor, each in it's own tab");
+    println!("Flags:\n\t--size WIDTHxHEIGHT\t- Sets the initial window size\n\t--vsync / --no-vsync\t- Enables or disables vertical sync\n\t--monitor INDEX|NAME\t- Selects the monitor to open the window on, by it's index or (part of) it's name\n\t--fullscreen MODE\t- Uses borderless or exclusive fullscreen mode\n\t--backend x11|wayland\t- Selects the windowing backend on Linux\n\t--theme dark|light\t- Sets the theme of the editor\n\t--accent RRGGBB\t\t- Sets the accent color of the editor\n\t--ui-scale FACTOR\t- Scales the editor's UI, e.g. for high-DPI screens\n\t--always-on-top\t\t- Keeps the viewer's window above all other windows\n\t--frameless\t\t- Opens the viewer's window without a title bar and borders\n\t--transparent\t\t- Creates the viewer's window with a transparent framebuffer\n\t--overlay\t\t- Shorthand for --always-on-top --frameless\n\t--video-output PATH\t- Writes the rendered slides as raw RGBA video to a file or named pipe (e.g. for ffmpeg to publish as an NDI source)\n\t--per-page N\t\t- Sets the amount of slides on each page of a handout\n\t--notes\t\t\t- Adds lines for taking notes next to each slide of a handout\n\t--speaker-notes\t\t- Prints the speaker notes of each slide next to it on a handout\n\t--slides 3,5-9\t\t- Selects the exported slides by their numbers\n\t--format png|jpg|webp\t- Sets the image format of exported slides\n\t--scale FACTOR\t\t- Scales the resolution of exported slides relative to the window size\n\t--watch\t\t\t- Keeps exporting the slides that changed whenever the file is saved\n\t--file-name TEMPLATE\t- Sets the file names of exported slides, using {{n}}, {{title}}, {{name}} and {{ext}} (default: {{name}}-{{n}}.{{ext}})\n\t--dividers\t\t- Inserts a section divider titled after each merged file before it's slides\n\t--input-format FORMAT\t- Parses files as the given format (e.g. hjson) regardless of their extension\n\t--language CODE\t\t- Selects the language of text with multiple language variants\n\t--midi-port NAME\t- Selects the MIDI input port used by the viewer\n\t--debug-outlines\t- Draws the bounding boxes of all objects (toggled using F3 in the viewer)\n\t--color-filter NAME\t- Simulates protanopia, deuteranopia, tritanopia or grayscale over the slides (cycled using F8 in the viewer)\n\t--auto-advance\t\t- Switches to the next slide once the current slide's duration has passed\n\t--no-spellcheck\t\t- Disables underlining misspelled words in the editor\n\t--dictionaries PATH\t- Sets the directory containing the dictionaries used for spell checking\n\t--wpm N\t\t\t- Sets the speaking rate used for estimating the duration of slides\n\t--json\t\t\t- Makes inspect print JSON (slides, objects, fonts, assets and durations)\n\t--write\t\t\t- Makes fmt rewrite the file instead of printing the formatted document\n\t--strip-comments\t- Allows fmt --write to rewrite files containing comments, removing them\n\t--changed-since REV\t- Marks the slides changed since a git revision (or since the viewer last presented the file, using 'rehearsal') in inspect and the editor\n\t--safe\t\t\t- Opens untrusted files without running Lua code (showing a warning instead) or fetching data over the network\n\t--strict-placeholders\t- Fails loading text that uses undefined placeholders\n\t--background-fade SECONDS - Sets how long plain colored slide backgrounds get faded into each other (0 disables it)\n\t--max-fps N\t\t- Limits the amount of frames rendered per second (0 removes the limit)\n\t--frame-stats\t\t- Periodically logs the amount of dropped frames in the viewer\n\t--resume\t\t- Continues on the slide that was shown when the viewer was last closed\n\t--no-esc-exit\t\t- Stops Esc from closing the window\n\t--confirm-quit\t\t- Asks for pressing Esc twice before quitting the viewer (Q always needs to be pressed twice)\n\t--pointer-trail\t\t- Draws a fading trail behind the mouse cursor in the viewer\n\t--click-ripple\t\t- Shows a ripple around the mouse cursor when clicking in the viewer\n\t--chapters PATH\t\t- Logs the slide changes while presenting as YouTube chapters (or an EDL for .edl files)\n\t--messages PATH\t\t- Shows each line written to a file or named pipe as a message to the presenter, which isn't published to the video output\n\t--teleprompter\t\t- Shows the current slide's notes as scrolling text at the --wpm rate instead of the slides (toggled using T in the viewer)\n\t--no-media-controls\t- Stops the viewer from registering as a media player on D-Bus (Linux only)");
+    println!("Defaults for these flags can be set in the user configuration file ({}).", config::Config::path().map(|p| p.display().to_string()).unwrap_or("unavailable".to_owned()));
 }
 
 fn main() -> anyhow::Result<()> {
 
     let args = env::args().collect::<Vec<String>>();
 
-    if args.len()!=3 {
+    if args.len()<3 {
         usage();
         return Ok(())
     }
 
+    // The registered formats need to be known for parsing the --input-format flag
+    parse::register_builtin_parsers();
+
+    // The trace only gets written completely once the guard is dropped at the end of main
+    #[cfg(feature = "render_tracing")]
+    let _trace_guard = profiling::init();
+
+    let mut config = config::Config::load().unwrap_or_else(|e| {
+        println!("Error loading user configuration, using defaults instead: {e}");
+        config::Config::default()
+    });
+    // The editor and merge accept multiple files and generate a list of components, so their flags
+    // start after the last of them
+    let flags = match args[1].as_str() {
+        "edit" | "generate" | "merge" => args[2..].iter().position(|arg| arg.starts_with("--")).map(|i| i + 2).unwrap_or(args.len()),
+        _ => 3
+    };
+    if let Err(e) = config.apply_args(&args[flags..]) {
+        println!("Error: {e}");
+        usage();
+        return Ok(())
+    }
+    // Winit picks the windowing backend on Linux from this variable when creating the first window
+    if let Some(backend) = config.backend {
+        env::set_var("WINIT_UNIX_BACKEND", backend.name());
+    }
+    presentation::set_language(config.language.clone());
+    CONFIG.set(config).map_err(|_|anyhow::anyhow!("Setting the CONFIG static failed!"))?;
+    presentation::data::start();
+
     LUA_INSTANCE.set(AssumeThreadSafe(Lua::new_with(StdLib::TABLE | StdLib::STRING | StdLib::MATH, LuaOptions::new()).unwrap())).map_err(|_|anyhow::anyhow!("Setting the LUA_INSTANCE static failed!"))?;
 
     let lua = LUA_INSTANCE.get().unwrap();
 
+    // Functions for showing and hiding objects by their id
+    lua.globals().set("show_object", lua.create_function(|_, id: String| Ok(presentation::set_object_visible(&id, true)))?)?;
+    lua.globals().set("hide_object", lua.create_function(|_, id: String| Ok(presentation::set_object_visible(&id, false)))?)?;
+    lua.globals().set("toggle_object", lua.create_function(|_, id: String| Ok(presentation::toggle_object(&id)))?)?;
+    lua.globals().set("object_visible", lua.create_function(|_, id: String| Ok(presentation::object_visible(&id)))?)?;
+
+    // Documents referencing remote resources or scripts need approval before they get loaded
+    let documents = match args[1].as_str() {
+        "view" | "handout" | "export" | "inspect" | "validate" => &args[2..3],
+        "edit" => &args[2..flags],
+        _ => &args[0..0]
+    };
+    for document in documents {
+        approval::check(std::path::Path::new(document), CONFIG.get().unwrap())?;
+    }
+
     match args[1].clone().as_str() {
         "view" => run_viewer(args)?,
-        "generate" => std::fs::write(&args[2], include_str!("template.hjson"))?,
+        "generate" => run_generate(args)?,
         "edit" => run_editor(args)?,
+        "handout" => run_handout_export(args)?,
+        "export" => run_image_export(args)?,
+        "merge" => run_merge(args)?,
+        "inspect" => run_inspect(args)?,
+        "validate" => run_validate(args)?,
+        "fmt" => run_format(args)?,
+        #[cfg(feature = "golden_tests")]
+        "golden" => run_golden(args)?,
+        #[cfg(feature = "benchmarks")]
+        "bench" => run_benchmarks(args)?,
+        #[cfg(feature = "fuzzing")]
+        "fuzz" => run_fuzzer(args)?,
         _ => usage()
     }
     Ok(())
@@ -0,0 +1,76 @@
+use std::fs::File;
+use std::io::{ BufRead, BufReader, Seek, SeekFrom };
+use std::path::Path;
+use std::sync::mpsc::{ self, Receiver };
+use std::time::Duration;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+/// How long to wait before checking the file for new messages again after reaching it's end.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Receives short messages for the presenter (like "5 minutes left") from a file or named pipe.
+///
+/// Every line written to the file is a message. The file gets read on a background thread, so
+/// a named pipe without a writer doesn't block the viewer. Lines written before the viewer was
+/// started aren't shown.
+pub struct MessageListener {
+    receiver: Receiver<String>
+}
+
+impl MessageListener {
+    /// Starts reading messages from the file at the given path.
+    pub fn open(path: &Path) -> MessageListener {
+        let (sender, receiver) = mpsc::channel();
+        let path = path.to_owned();
+
+        std::thread::spawn(move || {
+            let mut reader = match Self::open_at_end(&path) {
+                Ok(reader) => reader,
+                Err(e) => {
+                    log_err!("Opening the presenter messages at {} failed: {e}", path.display());
+                    return
+                }
+            };
+
+            let mut line = String::new();
+            loop {
+                line.clear();
+                match reader.read_line(&mut line) {
+                    // Wait for more lines to be appended, or for the next writer of a named pipe
+                    Ok(0) => std::thread::sleep(POLL_INTERVAL),
+                    Ok(_) => {
+                        let message = line.trim();
+                        if !message.is_empty() && sender.send(message.to_owned()).is_err() {
+                            // The viewer was closed
+                            return
+                        }
+                    },
+                    Err(e) => {
+                        log_err!("Reading the presenter messages from {} failed: {e}", path.display());
+                        return
+                    }
+                }
+            }
+        });
+
+        MessageListener { receiver }
+    }
+
+    /// Opens the file, skipping the lines it already contains.
+    ///
+    /// Named pipes can't be seeked and are read from the start.
+    fn open_at_end(path: &Path) -> std::io::Result<BufReader<File>> {
+        let mut file = File::open(path)?;
+        if file.metadata()?.is_file() {
+            file.seek(SeekFrom::End(0))?;
+        }
+        Ok(BufReader::new(file))
+    }
+
+    /// Returns all messages received since the last call.
+    pub fn poll(&self) -> impl Iterator<Item = String> + '_ {
+        self.receiver.try_iter()
+    }
+}
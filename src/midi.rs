@@ -0,0 +1,110 @@
+use std::sync::mpsc::{ self, Receiver };
+
+use midir::{ MidiInput, MidiInputConnection };
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+/// The kind of a MIDI message that can be bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiKind {
+    /// A key getting pressed (Note On). The value is the key's velocity.
+    Note,
+    /// A knob, fader or button changing it's value (Control Change).
+    ControlChange
+}
+
+/// A MIDI message received from a controller.
+#[derive(Debug, Clone, Copy)]
+pub struct MidiMessage {
+    pub kind: MidiKind,
+    /// The note or controller number.
+    pub number: u8,
+    pub value: u8
+}
+
+impl MidiMessage {
+    /// Parses a raw MIDI message, ignoring anything other than Note On and Control Change.
+    fn parse(bytes: &[u8]) -> Option<MidiMessage> {
+        let (status, number, value) = match bytes {
+            [status, number, value, ..] => (*status, *number, *value),
+            _ => return None
+        };
+
+        let kind = match status & 0xF0 {
+            // A Note On with zero velocity is a Note Off
+            0x90 if value > 0 => MidiKind::Note,
+            0xB0 => MidiKind::ControlChange,
+            _ => return None
+        };
+
+        Some(MidiMessage { kind, number, value })
+    }
+
+    /// Returns whether the message is a press of a key or button.
+    /// 
+    /// Control changes count as pressed when their value is in the upper half of the range.
+    pub fn is_press(&self) -> bool {
+        match self.kind {
+            MidiKind::Note => true,
+            MidiKind::ControlChange => self.value >= 64
+        }
+    }
+}
+
+/// What happens when a bound MIDI message is received.
+#[derive(Debug, Clone)]
+pub enum MidiAction {
+    NextSlide,
+    PreviousSlide,
+    /// Lua code that gets run with the message's number and value as arguments (accessible using
+    /// `local number, value = ...`).
+    Lua(String)
+}
+
+/// A MIDI message bound to an action, as configured in the document.
+#[derive(Debug, Clone)]
+pub struct MidiBinding {
+    pub kind: MidiKind,
+    pub number: u8,
+    pub action: MidiAction
+}
+
+/// Receives messages from a MIDI input port.
+pub struct MidiListener {
+    /// The connection needs to be kept alive for as long as messages should be received.
+    _connection: MidiInputConnection<()>,
+    receiver: Receiver<MidiMessage>
+}
+
+impl MidiListener {
+    /// Connects to the first input port whose name contains `port_name`, or the first available
+    /// port if no name is given.
+    pub fn connect(port_name: Option<&str>) -> anyhow::Result<MidiListener> {
+        let input = MidiInput::new("APresentation")?;
+
+        let ports = input.ports();
+        let port = ports.iter()
+            .find(|port| match port_name {
+                Some(name) => input.port_name(port).map(|n| n.contains(name)).unwrap_or(false),
+                None => true
+            })
+            .ok_or(anyhow::anyhow!("no matching MIDI input port found"))?;
+
+        log_info!("Connecting to MIDI input {}", input.port_name(port).unwrap_or("(unknown)".to_owned()));
+
+        let (sender, receiver) = mpsc::channel();
+        let connection = input.connect(port, "apresentation-input", move |_, bytes, _| {
+            if let Some(message) = MidiMessage::parse(bytes) {
+                let _ = sender.send(message);
+            }
+        }, ()).map_err(|e| anyhow::anyhow!("couldn't connect to MIDI input: {e}"))?;
+
+        Ok(MidiListener { _connection: connection, receiver })
+    }
+
+    /// Returns all messages received since the last call.
+    pub fn poll(&self) -> impl Iterator<Item = MidiMessage> + '_ {
+        self.receiver.try_iter()
+    }
+}
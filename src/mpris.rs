@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{ self, Receiver, Sender };
+
+use zbus::dbus_interface;
+use zbus::blocking::{ Connection, ConnectionBuilder };
+use zbus::zvariant::{ OwnedValue, Value };
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+/// The object path all MPRIS interfaces are served at.
+const OBJECT_PATH: &'static str = "/org/mpris/MediaPlayer2";
+
+/// A command received from a media player controller (e.g. the desktop's media keys).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaCommand {
+    NextSlide,
+    PreviousSlide,
+    /// Pauses the slide time.
+    Pause,
+    /// Resumes the slide time.
+    Play,
+    /// Pauses or resumes the slide time.
+    PlayPause,
+    Quit
+}
+
+/// The `org.mpris.MediaPlayer2` interface, describing the application.
+struct Root {
+    sender: Sender<MediaCommand>
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {}
+
+    fn quit(&self) {
+        let _ = self.sender.send(MediaCommand::Quit);
+    }
+
+    #[dbus_interface(property)]
+    fn can_quit(&self) -> bool { true }
+
+    #[dbus_interface(property)]
+    fn can_raise(&self) -> bool { false }
+
+    #[dbus_interface(property)]
+    fn has_track_list(&self) -> bool { false }
+
+    #[dbus_interface(property)]
+    fn identity(&self) -> String { "APresentation".to_owned() }
+
+    #[dbus_interface(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> { Vec::new() }
+
+    #[dbus_interface(property)]
+    fn supported_mime_types(&self) -> Vec<String> { Vec::new() }
+}
+
+/// The `org.mpris.MediaPlayer2.Player` interface, mapping the playback controls onto the slides.
+///
+/// Each slide is presented as a track, and playing or pausing runs or holds the slide time.
+struct Player {
+    sender: Sender<MediaCommand>,
+    paused: bool,
+    title: String
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    fn next(&self) {
+        let _ = self.sender.send(MediaCommand::NextSlide);
+    }
+
+    fn previous(&self) {
+        let _ = self.sender.send(MediaCommand::PreviousSlide);
+    }
+
+    fn pause(&self) {
+        let _ = self.sender.send(MediaCommand::Pause);
+    }
+
+    fn play(&self) {
+        let _ = self.sender.send(MediaCommand::Play);
+    }
+
+    fn play_pause(&self) {
+        let _ = self.sender.send(MediaCommand::PlayPause);
+    }
+
+    fn stop(&self) {
+        let _ = self.sender.send(MediaCommand::Pause);
+    }
+
+    #[dbus_interface(property)]
+    fn playback_status(&self) -> String {
+        match self.paused {
+            true => "Paused".to_owned(),
+            false => "Playing".to_owned()
+        }
+    }
+
+    #[dbus_interface(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        let mut metadata = HashMap::new();
+        metadata.insert("xesam:title".to_owned(), Value::from(self.title.clone()).into());
+        metadata
+    }
+
+    #[dbus_interface(property)]
+    fn rate(&self) -> f64 { 1.0 }
+
+    #[dbus_interface(property)]
+    fn minimum_rate(&self) -> f64 { 1.0 }
+
+    #[dbus_interface(property)]
+    fn maximum_rate(&self) -> f64 { 1.0 }
+
+    #[dbus_interface(property)]
+    fn can_go_next(&self) -> bool { true }
+
+    #[dbus_interface(property)]
+    fn can_go_previous(&self) -> bool { true }
+
+    #[dbus_interface(property)]
+    fn can_play(&self) -> bool { true }
+
+    #[dbus_interface(property)]
+    fn can_pause(&self) -> bool { true }
+
+    #[dbus_interface(property)]
+    fn can_seek(&self) -> bool { false }
+
+    #[dbus_interface(property)]
+    fn can_control(&self) -> bool { true }
+}
+
+/// Exposes the viewer as an MPRIS media player on the D-Bus session bus, so desktop media keys
+/// and remote control tools can navigate the presentation.
+pub struct MediaControls {
+    /// The connection needs to be kept alive for as long as commands should be received.
+    connection: Connection,
+    receiver: Receiver<MediaCommand>,
+    /// The state last published to the bus.
+    published: (bool, String)
+}
+
+impl MediaControls {
+    /// Connects to the session bus and registers the media player.
+    ///
+    /// Every viewer process gets it's own bus name, so multiple viewers can be controlled
+    /// separately.
+    pub fn connect() -> anyhow::Result<MediaControls> {
+        let (sender, receiver) = mpsc::channel();
+
+        let name = format!("org.mpris.MediaPlayer2.apresentation.instance{}", std::process::id());
+        let connection = ConnectionBuilder::session()?
+            .name(name.as_str())?
+            .serve_at(OBJECT_PATH, Root { sender: sender.clone() })?
+            .serve_at(OBJECT_PATH, Player { sender, paused: false, title: String::new() })?
+            .build()?;
+
+        log_info!("Registered as media player {name} on the session bus");
+
+        Ok(MediaControls { connection, receiver, published: (false, String::new()) })
+    }
+
+    /// Returns all commands received since the last call.
+    pub fn poll(&self) -> impl Iterator<Item = MediaCommand> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Publishes whether the slide time is paused and the title of the current slide, notifying
+    /// controllers if either changed.
+    pub fn publish(&mut self, paused: bool, title: &str) -> anyhow::Result<()> {
+        if self.published.0 == paused && self.published.1 == title {
+            return Ok(())
+        }
+        self.published = (paused, title.to_owned());
+
+        let player = self.connection.object_server().interface::<_, Player>(OBJECT_PATH)?;
+        zbus::block_on(async {
+            let mut state = player.get_mut().await;
+            state.paused = paused;
+            state.title = title.to_owned();
+            state.playback_status_changed(player.signal_context()).await?;
+            state.metadata_changed(player.signal_context()).await
+        })?;
+
+        Ok(())
+    }
+}
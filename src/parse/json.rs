@@ -1,6 +1,7 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::hash::Hash;
-use std::path::PathBuf;
+use std::path::{ Path, PathBuf };
 use std::fmt::Debug;
 
 use serde::Deserialize;
@@ -9,7 +10,9 @@ use serde::de::Visitor;
 #[allow(unused)]
 use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
 
-use super::{ Parser, SlideData };
+use indexmap::IndexMap;
+
+use super::{ Parser, SlideData, Metadata };
 
 pub struct JSONParser;
 impl Parser for JSONParser {
@@ -21,12 +24,264 @@ impl Parser for JSONParser {
         Ok(document.0)
     }
 
-    fn parse_fonts<'a>(&mut self, contents: &'a str) -> Result<HashMap<String, (String, String)>, Self::Error> {
+    fn parse_fonts<'a>(&mut self, contents: &'a str) -> Result<IndexMap<String, (String, String)>, Self::Error> {
         let fonts: DocumentFonts = deser_hjson::from_str(contents)?;
 
         Ok(fonts.0)
     }
 
+    fn parse_captions<'a>(&mut self, contents: &'a str) -> Result<Option<CaptionTrack>, Self::Error> {
+        let captions: DocumentCaptions = deser_hjson::from_str(contents)?;
+
+        Ok(captions.0)
+    }
+
+    fn parse_sounds<'a>(&mut self, contents: &'a str) -> Result<SoundEffects, Self::Error> {
+        let sounds: DocumentSounds = deser_hjson::from_str(contents)?;
+
+        Ok(sounds.0)
+    }
+
+    fn parse_midi<'a>(&mut self, contents: &'a str) -> Result<Vec<MidiBinding>, Self::Error> {
+        let midi: DocumentMidi = deser_hjson::from_str(contents)?;
+
+        Ok(midi.0)
+    }
+
+    fn parse_meta<'a>(&mut self, contents: &'a str) -> Result<Metadata, Self::Error> {
+        let meta: DocumentMeta = deser_hjson::from_str(contents)?;
+
+        Ok(meta.0)
+    }
+
+    fn format<'a>(&mut self, contents: &'a str) -> Result<String, Self::Error> {
+        let document: JSONValue = deser_hjson::from_str(contents)?;
+
+        let mut output = String::new();
+        write_hjson(&mut output, &document, None, 0);
+        output.push('\n');
+        Ok(output)
+    }
+
+    fn has_comments(&self, contents: &str) -> bool {
+        let chars = contents.chars().collect::<Vec<_>>();
+        let starts_with = |i: usize, s: &str| s.chars().enumerate().all(|(j, c)| chars.get(i + j) == Some(&c));
+
+        let mut i = 0;
+        // Whether the next character starts the value of a key, which may be a quoteless string
+        let mut value = false;
+        while i < chars.len() {
+            let c = chars[i];
+            if value && (c == ' ' || c == '\t') {
+                i += 1;
+                continue
+            }
+            let after_key = std::mem::take(&mut value);
+
+            match c {
+                '#' => return true,
+                '/' if starts_with(i, "//") || starts_with(i, "/*") => return true,
+                '\'' if starts_with(i, "'''") => {
+                    i += 3;
+                    while i < chars.len() && !starts_with(i, "'''") {
+                        i += 1;
+                    }
+                    i += 2;
+                },
+                '"' | '\'' => {
+                    i += 1;
+                    while i < chars.len() && chars[i] != c {
+                        i += if chars[i] == '\\' { 2 } else { 1 };
+                    }
+                },
+                ':' => value = true,
+                // Quoteless strings last until the end of the line, including any `#` or `//` in
+                // them, but keywords can be followed by a comment
+                c if after_key && !"{[-0123456789\n".contains(c) => {
+                    let end = chars[i..].iter().position(|c| *c == '\n').map(|n| i + n).unwrap_or(chars.len());
+                    let text = chars[i..end].iter().collect::<String>();
+                    let keyword = ["true", "false", "null"].iter()
+                        .any(|keyword| text.strip_prefix(keyword).is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace)));
+                    if !keyword {
+                        i = end;
+                    }
+                },
+                _ => ()
+            }
+            i += 1;
+        }
+
+        false
+    }
+
+    fn merge<'a>(&mut self, documents: &[(&'a str, &Path)], dividers: bool) -> Result<String, Self::Error> {
+        // Alias for more compact and more readable code
+        let err = <Self::Error as serde::de::Error>::custom;
+
+        let mut merged: IndexMap<String, JSONValue> = IndexMap::new();
+        let mut fonts: IndexMap<String, JSONValue> = IndexMap::new();
+        let mut templates: IndexMap<String, JSONValue> = IndexMap::new();
+        let mut slides: Vec<JSONValue> = Vec::new();
+
+        for (index, (contents, path)) in documents.iter().enumerate() {
+            let document: JSONValue = deser_hjson::from_str(contents)?;
+            let mut document: IndexMap<String, JSONValue> = document.try_into()
+                .map_err(|_|err(format!("base object of {} isn't a map", path.display())))?;
+
+            // Fonts registered with the same files only get registered once, fonts with a name
+            // that's already taken by other files get renamed
+            let document_fonts: IndexMap<String, JSONValue> = match document.shift_remove("fonts") {
+                Some(json) => json.try_into().map_err(|_|err("field \"fonts\" needs to be a dictionary of tuples of two file paths"))?,
+                None => IndexMap::new()
+            };
+            let mut font_renames: HashMap<String, String> = HashMap::new();
+            let mut renamed_fonts: IndexMap<String, JSONValue> = IndexMap::new();
+            let names = document_fonts.keys().cloned().collect::<Vec<_>>();
+            for name in names {
+                let files = document_fonts[&name].clone();
+
+                let existing = match fonts.get(&name) == Some(&files) {
+                    true => Some(name.clone()),
+                    false => fonts.iter().filter(|(_, f)| **f == files).map(|(n, _)| n.clone()).min()
+                };
+                let unique = existing.unwrap_or_else(|| {
+                    let unique = unique_name(&name, index, &fonts);
+                    fonts.insert(unique.clone(), files.clone());
+                    unique
+                });
+                if unique != name {
+                    log_info!("Font \"{name}\" of {} is called \"{unique}\" in the merged document", path.display());
+                    font_renames.insert(name, unique.clone());
+                }
+                renamed_fonts.insert(unique, files);
+            }
+
+            let mut json = JSONValue::Object(document);
+            rename_references(&mut json, &["font", "base_font"], &font_renames);
+            let mut document = match json {
+                JSONValue::Object(document) => document,
+                _ => unreachable!()
+            };
+
+            // Templates only get renamed if their name is already taken by a different template
+            let document_templates: IndexMap<String, JSONValue> = match document.shift_remove("templates") {
+                Some(json) => json.try_into().map_err(|_|err("field \"templates\" needs to be a dictionary of objects"))?,
+                None => IndexMap::new()
+            };
+            let mut template_renames: HashMap<String, String> = HashMap::new();
+            let mut added_templates = Vec::new();
+            let names = document_templates.keys().cloned().collect::<Vec<_>>();
+            for name in names {
+                let template = document_templates[&name].clone();
+                if templates.get(&name) == Some(&template) {
+                    continue
+                }
+                let unique = unique_name(&name, index, &templates);
+                if unique != name {
+                    log_info!("Template \"{name}\" of {} is called \"{unique}\" in the merged document", path.display());
+                    template_renames.insert(name, unique.clone());
+                }
+                templates.insert(unique.clone(), template);
+                added_templates.push(unique);
+            }
+            for name in added_templates {
+                rename_references(templates.get_mut(&name).unwrap(), &["extends"], &template_renames);
+            }
+
+            // The document's variables get moved into it's slides, since other documents can
+            // define the same variables differently
+            let variables: IndexMap<String, JSONValue> = match document.shift_remove("variables") {
+                Some(json) => json.try_into().map_err(|_|err("field \"variables\" needs to be an object"))?,
+                None => IndexMap::new()
+            };
+
+            // Defaults for slides are kept for the first document, the slides of other documents
+            // get their own defaults if they differ
+            let mut slide_defaults = Vec::new();
+            for key in ["safe_area", "transition"] {
+                match (document.shift_remove(key), index) {
+                    (Some(json), 0) => { merged.insert(key.to_owned(), json); },
+                    (Some(json), _) if merged.get(key) != Some(&json) => slide_defaults.push((key, json)),
+                    (None, i) if i > 0 && merged.contains_key(key) => log_warn!("The slides of {} use the {key} of the first document in the merged document", path.display()),
+                    _ => {}
+                }
+            }
+
+            let document_slides: Vec<JSONValue> = document.shift_remove("slides")
+                .ok_or(err(format!("required field \"slides\" is missing in {}", path.display())))?
+                .try_into().map_err(|_|err("field \"slides\" must be an array"))?;
+
+            if dividers {
+                let title = document.get("meta").and_then(|meta| meta.as_object()).and_then(|meta| meta.get("title")).and_then(JSONValue::as_str)
+                    .map(str::to_owned)
+                    .unwrap_or(path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default());
+
+                // The divider uses the palette and font of the document it's inserted before
+                let mut style = IndexMap::from([
+                    ("section_dividers".to_owned(), JSONValue::Bool(true)),
+                    ("fonts".to_owned(), JSONValue::Object(renamed_fonts))
+                ]);
+                if let Some(meta) = document.get("meta") {
+                    style.insert("meta".to_owned(), meta.clone());
+                }
+                let mut divider = Document::section_divider_template::<Self::Error>(&style)?.unwrap();
+                divider.insert("variables".to_owned(), JSONValue::Object(IndexMap::from([("section".to_owned(), JSONValue::String(title.clone()))])));
+                divider.entry("title".to_owned()).or_insert(JSONValue::String(title));
+                slides.push(JSONValue::Object(divider));
+            }
+
+            for mut slide in document_slides {
+                rename_references(&mut slide, &["extends"], &template_renames);
+                let mut slide: IndexMap<String, JSONValue> = slide.try_into().map_err(|_|err("contents of \"slides\" array need to be objects"))?;
+
+                if !variables.is_empty() {
+                    let mut merged_variables = variables.clone();
+                    if let Some(JSONValue::Object(overrides)) = slide.shift_remove("variables") {
+                        merged_variables.extend(overrides);
+                    }
+                    slide.insert("variables".to_owned(), JSONValue::Object(merged_variables));
+                }
+                for (key, json) in slide_defaults.iter() {
+                    slide.entry(key.to_string()).or_insert(json.clone());
+                }
+                slides.push(JSONValue::Object(slide));
+            }
+
+            // Everything else (e.g. the metadata and sound effects) is kept from the first
+            // document that has it
+            for (key, json) in document {
+                match merged.get(&key) {
+                    Some(existing) if *existing != json => log_warn!("Field \"{key}\" of {} differs from the one of an earlier document and gets dropped", path.display()),
+                    Some(_) => {},
+                    None => { merged.insert(key, json); }
+                }
+            }
+        }
+
+        if !fonts.is_empty() {
+            merged.insert("fonts".to_owned(), JSONValue::Object(fonts));
+        }
+        if !templates.is_empty() {
+            merged.insert("templates".to_owned(), JSONValue::Object(templates));
+        }
+        merged.insert("slides".to_owned(), JSONValue::Array(slides));
+
+        let mut output = String::new();
+        write_hjson(&mut output, &JSONValue::Object(merged), None, 0);
+        output.push('\n');
+        Ok(output)
+    }
+
+    fn external_resources<'a>(&mut self, contents: &'a str) -> Result<Vec<String>, Self::Error> {
+        let document: JSONValue = deser_hjson::from_str(contents)?;
+
+        let mut resources = Vec::new();
+        collect_external_resources(&document, &mut resources, 0);
+        resources.sort();
+        resources.dedup();
+        Ok(resources)
+    }
+
     fn handle_error(&self, err: Self::Error) {
         use deser_hjson::{ Error, ErrorCode };
         match err {
@@ -105,7 +360,44 @@ pub enum JSONValue {
     Number(f64),
     String(String),
     Array(Vec<JSONValue>),
-    Object(HashMap<String, JSONValue>)
+    Object(IndexMap<String, JSONValue>)
+}
+impl JSONValue {
+    /// Borrows the string, if the value is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JSONValue::String(s) => Some(s),
+            _ => None
+        }
+    }
+    /// Returns the number, if the value is one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JSONValue::Number(n) => Some(*n),
+            _ => None
+        }
+    }
+    /// Returns the boolean, if the value is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JSONValue::Bool(b) => Some(*b),
+            _ => None
+        }
+    }
+    /// Borrows the elements, if the value is an array.
+    pub fn as_array(&self) -> Option<&Vec<JSONValue>> {
+        match self {
+            JSONValue::Array(vec) => Some(vec),
+            _ => None
+        }
+    }
+    /// Borrows the fields, if the value is an object.
+    pub fn as_object(&self) -> Option<&IndexMap<String, JSONValue>> {
+        match self {
+            JSONValue::Object(map) => Some(map),
+            _ => None
+        }
+    }
 }
 impl TryInto<bool> for JSONValue {
     type Error = JSONValue;
@@ -147,10 +439,10 @@ impl TryInto<Vec<JSONValue>> for JSONValue {
         }
     }
 }
-impl TryInto<HashMap<String,JSONValue>> for JSONValue {
+impl TryInto<IndexMap<String, JSONValue>> for JSONValue {
     type Error = JSONValue;
 
-    fn try_into(self) -> Result<HashMap<String,JSONValue>, Self::Error> {
+    fn try_into(self) -> Result<IndexMap<String, JSONValue>, Self::Error> {
         match self {
             JSONValue::Object(s) => Ok(s),
             _ => Err(self)
@@ -300,7 +592,7 @@ impl<'de> Visitor<'de> for JSONValue {
     fn visit_unit<E>(self) -> Result<Self::Value, E>
         where
             E: serde::de::Error, {
-        Ok(JSONValue::Object(HashMap::new()))
+        Ok(JSONValue::Object(IndexMap::new()))
     }
 
     fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
@@ -322,7 +614,7 @@ impl<'de> Visitor<'de> for JSONValue {
     fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
         where
             A: serde::de::MapAccess<'de>, {
-        let mut hashmap = HashMap::new();
+        let mut hashmap = IndexMap::new();
         while let Some((key, value)) = map.next_entry::<String, JSONValue>()? {
             hashmap.insert(key, value);
         }
@@ -338,13 +630,20 @@ impl<'de> Deserialize<'de> for JSONValue {
 }
 
 use crate::presentation::renderable::*;
-use crate::presentation::util::PropertyError;
+use crate::presentation::util::{ PropertyError, take_safe_mode_refusal };
+use crate::presentation::{ Narration, CaptionTrack, Transition };
+use crate::presentation::locale::Locale;
+use crate::audio::SoundEffects;
+use crate::midi::{ MidiBinding, MidiKind, MidiAction };
+use crate::components::{ Palette, Style, Component, SECTION_DIVIDER };
+use crate::lint::Severity;
+use crate::render::blend::BlendMode;
 
 /// Helper struct with functions for parsing the JSON-document
 #[derive(Debug)]
 pub struct Document(pub Vec<SlideData>);
 impl Document {
-    fn parse_base_properties<E: serde::de::Error>(map: &HashMap<String, JSONValue>, renderable_type: String) -> Result<BaseProperties, E> {
+    fn parse_base_properties<E: serde::de::Error>(map: &IndexMap<String, JSONValue>, renderable_type: String) -> Result<BaseProperties, E> {
         let err = serde::de::Error::custom;
 
         let merr = |renderable: String, property: Option<String>, desc: String| move |e: PropertyError|{
@@ -358,47 +657,33 @@ impl Document {
             }
         };
 
-        let pos: String = get_value_alternates(map, vec!["pos", "position"])?.clone().try_into().map_err(|_|err("position needs to be a string"))?;
-        let size: String = get_value_alternates(map, vec!["size"])?.clone().try_into().map_err(|_|err("size needs to be a string"))?;
+        let pos = get_value_alternates(map, vec!["pos", "position"])?.as_str().ok_or(err("position needs to be a string"))?;
+        let size = get_value_alternates(map, vec!["size"])?.as_str().ok_or(err("size needs to be a string"))?;
         let col: String = {
-            let str: String = get_value_alternates(map, vec!["col", "color", "colour"])?.clone().try_into().map_err(|_|err("color needs to be a string"))?;
-            if str.starts_with('#') {
-                // If the color string starts with a '#', we don't have a pair of expressions, but
-                // a hexadecimal color value instead.
-
-                // Do some rudimentary checks if the format is correct (as to not throw confusing
-                // error messages at the user)
-                if str.len()!=7 && str.len()!=9 {
-                    // The string needs to be of length 7 (#RRGGBB) or 9 (#RRGGBBAA), otherwise it
-                    // is invalid
-                    Err(err("invalid hexadecimal color format"))?;
-                }
-
-                let red_hex: String = str[1..3].to_lowercase();
-                let green_hex: String = str[3..5].to_lowercase();
-                let blue_hex: String = str[5..7].to_lowercase();
-                let alpha_hex: String = if str.len()==9 { str[7..9].to_lowercase() } else { "ff".to_owned() };
-
-                let red = u8::from_str_radix(&red_hex, 16).map_err(|_|err("invalid hexadecimal color value"))? as f32;
-                let green = u8::from_str_radix(&green_hex, 16).map_err(|_|err("invalid hexadecimal color value"))? as f32;
-                let blue = u8::from_str_radix(&blue_hex, 16).map_err(|_|err("invalid hexadecimal color value"))? as f32;
-                let alpha = u8::from_str_radix(&alpha_hex, 16).map_err(|_|err("invalid hexadecimal color value"))? as f32;
-
-                format!("{};{};{};{}",red/255.0,green/255.0,blue/255.0,alpha/255.0)
-            } else {
-                // If the color string doesn't start with a '#', we do have a pair of expressions.
-
-                str
-            }
+            let str = get_value_alternates(map, vec!["col", "color", "colour"])?.as_str().ok_or(err("color needs to be a string"))?;
+            parse_color(str.to_owned())?
         };
 
-        let alignment: String = get_value_alternates(map, vec!["align", "alignment"])?.clone().try_into().map_err(|_|err("alignment needs to be a string"))?;
+        let alignment = get_value_alternates(map, vec!["align", "alignment"])?.as_str().ok_or(err("alignment needs to be a string"))?;
+
+        let mut base = BaseProperties::new(pos, size, col, alignment).map_err(merr(renderable_type, None, "Invalid alignment or invalid expression count!".to_owned()))?;
 
-        BaseProperties::new(pos, size, col, alignment).map_err(merr(renderable_type, None, "Invalid alignment or invalid expression count!".to_owned()))
+        // The blend mode is optional
+        if let Some(json) = map.get("blend") {
+            base.blend = json.as_str().and_then(BlendMode::from_name)
+                .ok_or(err("invalid blend mode (expected \"alpha\", \"add\", \"multiply\" or \"screen\")"))?;
+        }
+        Ok(base)
     }
 
     /// Parses the document to get a [`Vec`] of [`SlideData`]s
-    pub fn slides_from_json<E: serde::de::Error>(data: &HashMap<String, JSONValue>) -> Result<SlideData, E> {
+    /// 
+    /// `variables` are the document's variables, which the slide's own `variables`-field can
+    /// override (see [`Document::substitute_variables`]).
+    /// 
+    /// Takes ownership of the slide's data, so the objects can be moved out of it instead of being
+    /// copied.
+    pub fn slides_from_json<E: serde::de::Error>(mut data: IndexMap<String, JSONValue>, templates: &IndexMap<String, JSONValue>, variables: &IndexMap<String, JSONValue>) -> Result<SlideData, E> {
         // Helper function for creating a general error message for the background being invalid.
         let err_bg_invalid = ||serde::de::Error::custom("field \"background\" is invalid");
 
@@ -418,46 +703,96 @@ impl Document {
 
         let never_err = "Error that shouldn't happen! Report this!".to_owned();
 
+        // The slide's variables override the document's ones; the document's variables only get
+        // copied if there are any overrides
+        let variables: Cow<IndexMap<String, JSONValue>> = match data.shift_remove("variables") {
+            Some(json) => {
+                let overrides: IndexMap<String, JSONValue> = json.try_into().map_err(|_|err("field \"variables\" needs to be an object"))?;
+                let mut variables = variables.clone();
+                variables.extend(overrides);
+                Cow::Owned(variables)
+            },
+            None => Cow::Borrowed(variables)
+        };
+        let mut data = match Self::substitute_variables(JSONValue::Object(data), &variables) {
+            JSONValue::Object(data) => data,
+            _ => unreachable!()
+        };
+
+        // Included files get expanded before hashing, so changing them marks the slide as changed
+        // as well
+        if let Some(JSONValue::Array(vec)) = data.get_mut("content") {
+            *vec = Self::expand_includes(std::mem::take(vec), 0)?;
+        }
+
+        // The hash covers the slide after substituting variables and the templates it's objects
+        // extend, so changing any of them marks the slide as changed
+        let content_hash = content_hash(&data, templates);
+
         // Parse the background object
-        let background: Box<dyn Renderable>;
-        match data.get("background").ok_or(serde::de::Error::custom("required field \"background\" is missing in slide"))? {
+        let background: Option<Box<dyn Renderable>>;
+        match data.shift_remove("background").ok_or(serde::de::Error::custom("required field \"background\" is missing in slide"))? {
+            // Reuse the previous slide's background object
+            JSONValue::String(s) if s == "inherit" => {
+                background = None;
+            },
+            // Any other string is the path of a video file that gets played looped
+            JSONValue::String(path) => {
+                take_safe_mode_refusal();
+                background = match Video::background(&path) {
+                    Ok(object) => Some(Box::new( object ) as Box<dyn Renderable>),
+                    // Remote videos get replaced with a warning in safe mode
+                    Err(_) if take_safe_mode_refusal() => {
+                        log_warn!("The background video {path} is a remote file and got replaced with a warning in safe mode");
+                        Some(Self::safe_mode_placeholder()?)
+                    },
+                    Err(e) => return Err((merr)("Video".to_owned(),Some("background".to_owned()),"Invalid video file!".to_owned())(e))
+                };
+            },
             // Simplest case: Just an array of RGB-values
             JSONValue::Array(vec) => {
                 // Get the RGB-values from the array
                 //   Errors when the array is to short or when the conversion from `JSONValue` to f64
                 //   fails.
-                let r: f64 = vec.get(0).ok_or((err_bg_invalid)())?.clone().try_into().map_err(|_|(err_bg_invalid)())?;
-                let g: f64 = vec.get(1).ok_or((err_bg_invalid)())?.clone().try_into().map_err(|_|(err_bg_invalid)())?;
-                let b: f64 = vec.get(2).ok_or((err_bg_invalid)())?.clone().try_into().map_err(|_|(err_bg_invalid)())?;
+                let r = vec.get(0).and_then(JSONValue::as_f64).ok_or((err_bg_invalid)())?;
+                let g = vec.get(1).and_then(JSONValue::as_f64).ok_or((err_bg_invalid)())?;
+                let b = vec.get(2).and_then(JSONValue::as_f64).ok_or((err_bg_invalid)())?;
 
                 // Use the RGB-values to create a colored rectangle filling the whole screen
                 let object = ColoredRect::new(BaseProperties::new("0;0", "w;h", format!("{r};{g};{b};1"), "TOP_LEFT").map_err((merr)("Background".to_owned(),None,never_err))?);
 
-                background = Box::new( object ) as Box<dyn Renderable>;
+                background = Some(Box::new( object ) as Box<dyn Renderable>);
             },
             // More complex case: Any renderable object
             JSONValue::Object(hashmap) => {
 
                 // Get the type of the Renderable.
                 //   Used for error messages and actually constructing a Renderable
-                let renderable_type: String = hashmap.get("type").ok_or(err("required field \"type\" missing"))?.clone()
-                    .try_into().map_err(|_|err("field \"type\" needs to be a string"))?;
-
-                let base = Self::parse_base_properties(hashmap, renderable_type.clone())?;
+                let renderable_type = hashmap.get("type").ok_or(err("required field \"type\" missing"))?
+                    .as_str().ok_or(err("field \"type\" needs to be a string"))?;
 
-                let map = hashmap.clone();
+                // Forget refusals of optional properties that got ignored before
+                take_safe_mode_refusal();
+                let base = Self::parse_base_properties::<E>(&hashmap, renderable_type.to_owned());
 
                 // Tries to construct a Renderable object based on the specified type.
                 //   Errors if the specified type doesn't exist, the field is invalid or the
                 //   constructor function failed.
-                let result = match RENDERABLE_FUNCS.get(&renderable_type) {
-                    Some(t) => (t)(map, base),
-                    None => return Err(err("field \"type\" is invalid"))
+                let result = match (RENDERABLE_FUNCS.get(renderable_type), base) {
+                    (Some(t), Ok(base)) => (t)(&hashmap, base),
+                    (None, _) => return Err(err("field \"type\" is invalid")),
+                    (_, Err(e)) => Err(e.to_string())
                 };
 
                 // The error when the constructor function failed occurs here.
                 match result {
-                    Ok(b) => background = b.copy(),
+                    Ok(b) => background = Some(b),
+                    // Backgrounds running Lua code or using remote files get replaced with a
+                    // warning in safe mode
+                    Err(_) if take_safe_mode_refusal() => {
+                        log_warn!("The background ({renderable_type}) uses Lua or a remote file and got replaced with a warning in safe mode");
+                        background = Some(Self::safe_mode_placeholder()?);
+                    },
                     Err(_) => return Err((err_bg_invalid)())
                 }
             },
@@ -466,43 +801,77 @@ impl Document {
         }
 
         // Parse all objects defined in the slide
-        let mut content: HashMap<u8, Vec<Box<dyn Renderable>>> = HashMap::new();
-        match data.get("content").ok_or(serde::de::Error::custom("required field \"content\" is missing in slide"))? {
+        let mut content: HashMap<i16, Vec<Box<dyn Renderable>>> = HashMap::new();
+        match data.shift_remove("content").ok_or(serde::de::Error::custom("required field \"content\" is missing in slide"))? {
             JSONValue::Array(vec) => {
-                // The default for the z-index of an object
-                let z_index_default = JSONValue::Number(0.0);
-
-                for (i, renderable_json) in vec.iter().enumerate() {
-                    let map: HashMap<String, JSONValue> = renderable_json.clone().try_into().map_err(|_|serde::de::Error::custom("field \"content\" must be an array of objects"))?;
+                for (i, renderable_json) in vec.into_iter().enumerate() {
+                    let map: IndexMap<String, JSONValue> = renderable_json.try_into().map_err(|_|serde::de::Error::custom("field \"content\" must be an array of objects"))?;
+                    let map = Self::apply_template(map, templates, 0)?;
+                    // Templates can use variables too, so they get substituted again
+                    let map: IndexMap<String, JSONValue> = Self::substitute_variables(JSONValue::Object(map), &variables).try_into().unwrap();
 
                     // Get the type of the Renderable.
                     //   Used for error messages and actually constructing a Renderable
-                    let renderable_type: String = map.get("type").ok_or(err("required field \"type\" missing"))?.clone()
-                        .try_into().map_err(|_|err("field \"type\" needs to be a string"))?;
+                    let renderable_type = map.get("type").ok_or(err("required field \"type\" missing"))?
+                        .as_str().ok_or(err("field \"type\" needs to be a string"))?;
 
-                    let base = Self::parse_base_properties(&map, renderable_type.clone())?;
+                    // Forget refusals of optional properties that got ignored before
+                    take_safe_mode_refusal();
+                    let base = Self::parse_base_properties::<E>(&map, renderable_type.to_owned());
 
                     // Try to construct a Renderable object based on the specified type.
                     //   Errors if the specified type doesn't exist, the field is invalid or the
                     //   constructor function failed.
-                    let result = (RENDERABLE_FUNCS.get(&renderable_type).ok_or(err("field \"type\" is invalid"))?)(map.clone(), base);
-                    let object = result.map_err(|e|err(format!("invalid contents of renderable object #{i} ({e})").leak()))?;
+                    let constructor = RENDERABLE_FUNCS.get(renderable_type).ok_or(err("field \"type\" is invalid"))?;
+                    let result = base.and_then(|base| (constructor)(&map, base)
+                        .map_err(|e|<E as serde::de::Error>::custom(format!("invalid contents of renderable object #{i} ({e})"))));
+                    let object = match result {
+                        // Objects running Lua code or using remote files get replaced with a warning
+                        // in safe mode
+                        Err(_) if take_safe_mode_refusal() => {
+                            log_warn!("Renderable object #{i} ({renderable_type}) uses Lua or a remote file and got replaced with a warning in safe mode");
+                            Self::safe_mode_placeholder()?
+                        },
+                        result => result?
+                    };
+
+                    // Objects with an id get wrapped, so they can be shown and hidden at runtime
+                    let object = match map.get("id") {
+                        Some(JSONValue::String(id)) => Box::new(Named::new(id.clone(), object)) as Box<dyn Renderable>,
+                        Some(_) => return Err(err("field \"id\" needs to be a string")),
+                        None => object
+                    };
 
                     // Note: The error message just says 'expected an integer' because the number
                     //       gets casted to an integer. You can supply a float in theory though.
-                    let z_index_result: Result<&JSONValue, E> = get_value_alternates(&map, vec!["z_index","z-index","z"]);
-                    let z_index: f64 = z_index_result.unwrap_or(&z_index_default)
-                        .clone().try_into().map_err(|_|serde::de::Error::custom("invalid z-index (expected an integer)"))?;
+                    let z_index: f64 = match get_value_alternates::<_, _, _, E>(&map, vec!["z_index","z-index","z"]) {
+                        Ok(json) => json.as_f64().ok_or(serde::de::Error::custom("invalid z-index (expected an integer)"))?,
+                        Err(_) => 0.0
+                    };
+
+                    let (min_z, max_z) = crate::presentation::slide::Z_INDEX_RANGE;
+                    if z_index < min_z as f64 || z_index > max_z as f64 {
+                        log_warn!("The z-index {z_index} is out of range, clamping it between {min_z} and {max_z}");
+                    }
+                    let mut z_index = z_index.clamp(min_z as f64, max_z as f64) as i16;
+
+                    // Objects on the 'under_background' layer get rendered beneath the slide's
+                    // background, their z-index still orders them among each other
+                    match map.get("layer") {
+                        Some(JSONValue::String(layer)) if layer == "under_background" => z_index -= crate::presentation::slide::UNDER_BACKGROUND_OFFSET,
+                        Some(_) => return Err(err("invalid layer (expected \"under_background\")")),
+                        None => {}
+                    }
                     
                     // Check in the map if a vec for the specified z-index already exists or not
-                    match content.get_mut(&(z_index as u8)) {
+                    match content.get_mut(&z_index) {
                         // If it exists, just push the object to this list
                         Some(list) => {
                             list.push(object);
                         },
                         // If it doesn't exist, create one and then push the object to the list
                         None => {
-                            content.insert(z_index as u8, vec![object]);
+                            content.insert(z_index, vec![object]);
                         }
                     }
                 }
@@ -511,7 +880,238 @@ impl Document {
             _ => return Err((err_bg_invalid)())
         }
 
-        Ok(SlideData { background, content })
+        // Parse the optional narration of the slide
+        let narration = match data.get("narration") {
+            Some(json) => Some(Self::parse_narration(json)?),
+            None => None
+        };
+
+        // Parse the optional safe area of the slide
+        let safe_area = match data.get("safe_area") {
+            Some(json) => Some(parse_aspect_ratio(json.clone())?),
+            None => None
+        };
+
+        // Parse the optional duration of the slide
+        let duration = match data.get("duration") {
+            Some(json) => {
+                let duration = json.as_f64().ok_or(serde::de::Error::custom("field \"duration\" needs to be a number of seconds"))?;
+                if duration <= 0.0 {
+                    return Err(serde::de::Error::custom("field \"duration\" needs to be positive"))
+                }
+                Some(duration)
+            },
+            None => None
+        };
+
+        // Parse the optional title of the slide
+        let title = match data.shift_remove("title") {
+            Some(json) => Some(json.try_into().map_err(|_|serde::de::Error::custom("field \"title\" needs to be a string"))?),
+            None => None
+        };
+
+        // Parse the optional bookmark of the slide
+        let bookmark = match data.shift_remove("bookmark") {
+            Some(json) => Some(json.try_into().map_err(|_|serde::de::Error::custom("field \"bookmark\" needs to be a string"))?),
+            None => None
+        };
+
+        // Parse the optional transition to the slide
+        let transition = match data.get("transition") {
+            Some(json) => Some(parse_transition(json.clone())?),
+            None => None
+        };
+
+        // Parse the optional speaker notes of the slide, which can be split into multiple lines
+        let notes = match data.shift_remove("notes") {
+            Some(JSONValue::String(notes)) => Some(notes),
+            Some(JSONValue::Array(lines)) => Some(lines.into_iter()
+                .map(|line| line.try_into().map_err(|_|serde::de::Error::custom("field \"notes\" needs to be a string or an array of strings")))
+                .collect::<Result<Vec<String>, E>>()?
+                .join("\n")),
+            Some(_) => return Err(serde::de::Error::custom("field \"notes\" needs to be a string or an array of strings")),
+            None => None
+        };
+
+        Ok(SlideData { background, content, narration, safe_area, duration, title, bookmark, transition, notes, content_hash })
+    }
+
+    /// Returns the warning shown in place of objects that got disabled in safe mode.
+    fn safe_mode_placeholder<E: serde::de::Error>() -> Result<Box<dyn Renderable>, E> {
+        let map: IndexMap<String, JSONValue> = deser_hjson::from_str::<JSONValue>(SAFE_MODE_PLACEHOLDER)
+            .map_err(|e|<E as serde::de::Error>::custom(format!("invalid safe mode placeholder: {e}")))?
+            .try_into().map_err(|_|serde::de::Error::custom("invalid safe mode placeholder"))?;
+
+        let base = Self::parse_base_properties(&map, "Text".to_owned())?;
+        (RENDERABLE_FUNCS["Text"])(&map, base).map_err(|e|<E as serde::de::Error>::custom(format!("invalid safe mode placeholder: {e}")))
+    }
+
+    /// Merges an object with the template it extends (using the `extends`-field).
+    /// 
+    /// Fields of the object override the ones of the template. Templates can extend other
+    /// templates themselves.
+    fn apply_template<E: serde::de::Error>(mut map: IndexMap<String, JSONValue>, templates: &IndexMap<String, JSONValue>, depth: usize) -> Result<IndexMap<String, JSONValue>, E> {
+        /// The maximum amount of templates extending each other, so cycles don't recurse forever.
+        const MAX_DEPTH: usize = 16;
+
+        let name = match map.shift_remove("extends") {
+            Some(JSONValue::String(name)) => name,
+            Some(_) => return Err(serde::de::Error::custom("field \"extends\" needs to be the name of a template")),
+            None => return Ok(map)
+        };
+
+        if depth >= MAX_DEPTH {
+            return Err(<E as serde::de::Error>::custom(format!("template \"{name}\" extends too many templates (is there a cycle?)")))
+        }
+
+        let template: IndexMap<String, JSONValue> = templates.get(&name)
+            .ok_or(<E as serde::de::Error>::custom(format!("template \"{name}\" doesn't exist")))?.clone()
+            .try_into().map_err(|_|<E as serde::de::Error>::custom(format!("template \"{name}\" needs to be an object")))?;
+        let mut merged = Self::apply_template(template, templates, depth + 1)?;
+
+        merged.extend(map);
+        Ok(merged)
+    }
+
+    /// Replaces the entries of a slide's content that include other files (e.g.
+    /// `{ include: "objects/footer.hjson" }`) with the object or list of objects in that file.
+    /// 
+    /// Other fields of the entry override the ones of the included objects. Included files can
+    /// include other files themselves.
+    fn expand_includes<E: serde::de::Error>(content: Vec<JSONValue>, depth: usize) -> Result<Vec<JSONValue>, E> {
+        let mut expanded = Vec::with_capacity(content.len());
+        for json in content {
+            let mut map = match json {
+                JSONValue::Object(map) if map.contains_key("include") => map,
+                json => {
+                    expanded.push(json);
+                    continue
+                }
+            };
+
+            let path = match map.shift_remove("include") {
+                Some(JSONValue::String(path)) => path,
+                _ => return Err(serde::de::Error::custom("field \"include\" needs to be a file path"))
+            };
+            if depth >= MAX_INCLUDE_DEPTH {
+                return Err(<E as serde::de::Error>::custom(format!("included file \"{path}\" includes too many files (is there a cycle?)")))
+            }
+
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e|<E as serde::de::Error>::custom(format!("couldn't read included file \"{path}\": {e}")))?;
+            let included: JSONValue = deser_hjson::from_str(&contents)
+                .map_err(|e|<E as serde::de::Error>::custom(format!("invalid included file \"{path}\": {e}")))?;
+            let objects = match included {
+                JSONValue::Array(objects) => objects,
+                object => vec![object]
+            };
+
+            for object in Self::expand_includes::<E>(objects, depth + 1)? {
+                match object {
+                    JSONValue::Object(mut object) => {
+                        object.extend(map.iter().map(|(k, v)| (k.clone(), v.clone())));
+                        expanded.push(JSONValue::Object(object));
+                    },
+                    _ => return Err(<E as serde::de::Error>::custom(format!("included file \"{path}\" needs to contain an object or a list of objects")))
+                }
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Returns the slide inserted at the start of every section, as set by the document's
+    /// `section_dividers`-field.
+    /// 
+    /// It's either `true` for the bundled section divider in the document's palette, or a slide
+    /// that can reference the section's name through the variable `section`.
+    fn section_divider_template<E: serde::de::Error>(document: &IndexMap<String, JSONValue>) -> Result<Option<IndexMap<String, JSONValue>>, E> {
+        let err = serde::de::Error::custom;
+
+        match document.get("section_dividers") {
+            None | Some(JSONValue::Bool(false)) => Ok(None),
+            Some(JSONValue::Object(template)) => Ok(Some(template.clone())),
+            Some(JSONValue::Bool(true)) => {
+                let meta: IndexMap<String, JSONValue> = match document.get("meta") {
+                    Some(json) => json.clone().try_into().map_err(|_|err("field \"meta\" needs to be an object"))?,
+                    None => IndexMap::new()
+                };
+
+                // Like components inserted by the user, the divider uses the document's first font
+                // if it doesn't choose one
+                let font = match meta.get("font") {
+                    Some(json) => json.clone().try_into().map_err(|_|err("the document's font needs to be a string"))?,
+                    None => match document.get("fonts") {
+                        Some(JSONValue::Object(fonts)) => fonts.keys().next().cloned().unwrap_or("Default".to_owned()),
+                        _ => "Default".to_owned()
+                    }
+                };
+                let style = Style { palette: parse_palette::<E>(&meta)?, font };
+
+                // The heading references the section variable, so the name doesn't need escaping
+                let component = Component::find(SECTION_DIVIDER).unwrap();
+                let source = component.instantiate_with_heading(&style, "${section}");
+                let template: JSONValue = deser_hjson::from_str(&source).map_err(|e|err(format!("invalid section divider: {e}")))?;
+                Ok(Some(template.try_into().map_err(|_|err("invalid section divider"))?))
+            },
+            Some(_) => Err(err("field \"section_dividers\" needs to be a boolean or a slide"))
+        }
+    }
+
+    /// Replaces references to variables (`${name}`) in all strings of a JSON value.
+    /// 
+    /// A string consisting of only a reference gets replaced with the variable's value itself,
+    /// so variables can also hold numbers, arrays or objects. References to undefined variables
+    /// are left unchanged.
+    fn substitute_variables(json: JSONValue, variables: &IndexMap<String, JSONValue>) -> JSONValue {
+        if variables.is_empty() {
+            return json
+        }
+
+        match json {
+            JSONValue::String(s) => {
+                let captures = VARIABLE_REGEX.captures(&s);
+                if let Some(value) = captures.filter(|c| c[0].len() == s.len()).and_then(|c| variables.get(&c[1])) {
+                    return value.clone()
+                }
+
+                JSONValue::String(VARIABLE_REGEX.replace_all(&s, |c: &regex::Captures| match variables.get(&c[1]) {
+                    Some(JSONValue::String(value)) => value.clone(),
+                    Some(JSONValue::Number(value)) => value.to_string(),
+                    Some(JSONValue::Bool(value)) => value.to_string(),
+                    _ => c[0].to_owned()
+                }).into_owned())
+            },
+            JSONValue::Array(vec) => JSONValue::Array(vec.into_iter().map(|v| Self::substitute_variables(v, variables)).collect()),
+            // The variables themselves don't get substituted
+            JSONValue::Object(map) => JSONValue::Object(map.into_iter().map(|(k, v)| match k == "variables" {
+                true => (k, v),
+                false => (k, Self::substitute_variables(v, variables))
+            }).collect()),
+            json => json
+        }
+    }
+
+    /// Parses the narration of a slide.
+    /// 
+    /// It can either be a string containing the path to the audio file or an object containing the
+    /// path and optional delays before and after playing the file.
+    fn parse_narration<E: serde::de::Error>(json: &JSONValue) -> Result<Narration, E> {
+        let err = serde::de::Error::custom;
+
+        match json {
+            JSONValue::String(path) => Ok(Narration { path: path.clone(), pre_delay: 0.0, post_delay: 0.0 }),
+            JSONValue::Object(map) => {
+                let path: String = get_value_alternates::<_, _, _, E>(map, vec!["path", "file", "file_path"])?.clone()
+                    .try_into().map_err(|_|err("narration file path needs to be a string"))?;
+                let pre_delay: f64 = map.get("pre_delay").cloned().unwrap_or(JSONValue::Number(0.0))
+                    .try_into().map_err(|_|err("narration pre_delay needs to be a number"))?;
+                let post_delay: f64 = map.get("post_delay").cloned().unwrap_or(JSONValue::Number(0.0))
+                    .try_into().map_err(|_|err("narration post_delay needs to be a number"))?;
+
+                Ok(Narration { path, pre_delay, post_delay })
+            },
+            _ => Err(err("field \"narration\" needs to be a file path or an object"))
+        }
     }
 }
 
@@ -524,21 +1124,76 @@ impl<'de> Deserialize<'de> for Document {
 
         // Parse the document to a map
         //   (JSONValue also acts as a Visitor from the 'serde'-crate for itself)
-        let map: HashMap<String, JSONValue> = deserializer.deserialize_map(JSONValue::Null)?.try_into().map_err(|_|err("base object isn't a map"))?;
+        let mut map: IndexMap<String, JSONValue> = deserializer.deserialize_map(JSONValue::Null)?.try_into().map_err(|_|err("base object isn't a map"))?;
+
+        // The safe area used by slides that don't specify their own
+        let default_safe_area = match map.get("safe_area") {
+            Some(json) => Some(parse_aspect_ratio(json.clone())?),
+            None => None
+        };
+
+        // The transition used by slides that don't specify their own
+        let default_transition = match map.get("transition") {
+            Some(json) => Some(parse_transition(json.clone())?),
+            None => None
+        };
+
+        // The templates objects can extend
+        let templates: IndexMap<String, JSONValue> = match map.shift_remove("templates") {
+            Some(json) => json.try_into().map_err(|_|err("field \"templates\" needs to be a dictionary of objects"))?,
+            None => IndexMap::new()
+        };
 
+        // The variables slides and templates can reference, which slides can override
+        let mut variables: IndexMap<String, JSONValue> = match map.shift_remove("variables") {
+            Some(json) => json.try_into().map_err(|_|err("field \"variables\" needs to be an object"))?,
+            None => IndexMap::new()
+        };
 
         let slides = {
             // Gets the 'slides'-field and checks if it's actually an array
-            let slide_array: Vec<JSONValue> = map.get("slides").ok_or(err("required field \"slides\" is missing"))?.clone()
+            let slide_array: Vec<JSONValue> = map.shift_remove("slides").ok_or(err("required field \"slides\" is missing"))?
                     .try_into().map_err(|_|err("field \"slides\" must be an array"))?;
             
+            // The slide inserted at the start of every section, if enabled
+            let divider_template = Self::section_divider_template::<D::Error>(&map)?;
+
             // Parses the slides contained in the 'slides'-array
             //   Errors if any item in the array isn't an object or any object couldn't get parsed
             //   into a slide.
-            slide_array.into_iter().map(|json_val| {
-                let map: HashMap<String, JSONValue> = json_val.try_into().map_err(|_|err("contents of \"slides\" array need to be objects"))?;
-                Document::slides_from_json(&map)
-            }).collect::< Result<Vec<SlideData>, D::Error> >()?
+            let mut slides = Vec::with_capacity(slide_array.len());
+            let mut current_section: Option<String> = None;
+            // A variable called 'section' defined by the document takes precedence
+            let has_section_variable = variables.contains_key("section");
+            for json_val in slide_array {
+                let map: IndexMap<String, JSONValue> = json_val.try_into().map_err(|_|err("contents of \"slides\" array need to be objects"))?;
+
+                // Slides without a section belong to the section of the previous slide
+                let section: Option<String> = match map.get("section") {
+                    Some(json) => Some(json.as_str().ok_or(err("field \"section\" needs to be a string"))?.to_owned()),
+                    None => None
+                };
+                let starts_section = section.is_some() && section != current_section;
+                current_section = section.or(current_section);
+
+                // The name of the current section is available as the variable 'section'
+                if let (Some(section), true, false) = (&current_section, starts_section, has_section_variable) {
+                    variables.insert("section".to_owned(), JSONValue::String(section.clone()));
+                }
+
+                if let (Some(template), true) = (&divider_template, starts_section) {
+                    let mut divider = Document::slides_from_json::<D::Error>(template.clone(), &templates, &variables)?;
+                    divider.title = divider.title.or(current_section.clone());
+                    slides.push(divider);
+                }
+                slides.push(Document::slides_from_json::<D::Error>(map, &templates, &variables)?);
+            }
+
+            for slide in slides.iter_mut() {
+                slide.safe_area = slide.safe_area.or(default_safe_area);
+                slide.transition = slide.transition.or(default_transition);
+            }
+            slides
         };
 
         Ok(Document(slides))
@@ -546,7 +1201,7 @@ impl<'de> Deserialize<'de> for Document {
 }
 
 #[derive(Debug)]
-pub struct DocumentFonts(pub HashMap<String, (String, String)>);
+pub struct DocumentFonts(pub IndexMap<String, (String, String)>);
 impl<'de> Deserialize<'de> for DocumentFonts {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
@@ -555,16 +1210,16 @@ impl<'de> Deserialize<'de> for DocumentFonts {
         let err = serde::de::Error::custom;
 
         // Get the base object of the document and error if it isn't a map
-        let document: HashMap<String, JSONValue> = deserializer.deserialize_map(JSONValue::Null)?.try_into().map_err(|_|err("base object isn't a map"))?;
+        let document: IndexMap<String, JSONValue> = deserializer.deserialize_map(JSONValue::Null)?.try_into().map_err(|_|err("base object isn't a map"))?;
 
         // Get the 'fonts'-field from the document
         //   Errors if the 'fonts'-field isn't a dictionary containing tuples of two string paths.
         let fonts = {
             // Check if the 'fonts'-field is a dictionary
-            let font_dict: HashMap<String, JSONValue> = document.get("fonts").ok_or(err("required field \"fonts\" is missing"))?.clone()
+            let font_dict: IndexMap<String, JSONValue> = document.get("fonts").ok_or(err("required field \"fonts\" is missing"))?.clone()
                 .try_into().map_err(|_|err("field \"fonts\" needs to be a dictionary of tuples of two file paths"))?;
             // The fonts will be stored here
-            let mut font_list: HashMap<String, (String, String)> = HashMap::new();
+            let mut font_list: IndexMap<String, (String, String)> = IndexMap::new();
 
             // Iterate over all values in the dict, then check if they're tuples of two strings
             for (key, value) in font_dict.into_iter() {
@@ -584,12 +1239,238 @@ impl<'de> Deserialize<'de> for DocumentFonts {
     }
 }
 
+#[derive(Debug)]
+pub struct DocumentCaptions(pub Option<CaptionTrack>);
+impl<'de> Deserialize<'de> for DocumentCaptions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de> {
+        // Alias for more compact and more readable code
+        let err = serde::de::Error::custom;
+
+        let merr = |desc: &'static str| move |e: PropertyError| -> D::Error {
+            let (_, p, desc) = e.syntax_error("Captions", "_", desc);
+            serde::de::Error::custom(format!("error while creating captions ({p}): {desc}"))
+        };
+
+        // Get the base object of the document and error if it isn't a map
+        let document: IndexMap<String, JSONValue> = deserializer.deserialize_map(JSONValue::Null)?.try_into().map_err(|_|err("base object isn't a map"))?;
+
+        // The caption track is optional
+        let captions: IndexMap<String, JSONValue> = match document.get("captions") {
+            Some(json) => json.clone().try_into().map_err(|_|err("field \"captions\" needs to be an object"))?,
+            None => return Ok(DocumentCaptions(None))
+        };
+
+        // Helper for getting optional string fields of the caption style
+        let style = |key: &str, default: &str| -> Result<String, D::Error> {
+            captions.get(key).cloned().unwrap_or(JSONValue::String(default.to_owned()))
+                .try_into().map_err(|_|<D::Error as serde::de::Error>::custom(format!("caption field \"{key}\" needs to be a string")))
+        };
+
+        let font = style("font", "Default")?;
+        let size = style("size", "4%")?;
+        let color = parse_color(style("color", "#ffffff")?)?;
+        let background = parse_color(style("background", "#000000b0")?)?;
+        let strip_height = style("strip_height", "12%")?;
+
+        let strip = ColoredRect::new(
+            BaseProperties::new(format!("0;h-({strip_height})"), format!("w;{strip_height}"), background, "TOP_LEFT")
+                .map_err(merr("Invalid strip height or background color!"))?);
+        let mut track = CaptionTrack::new(Box::new(strip));
+
+        let cues: Vec<JSONValue> = captions.get("cues").cloned().unwrap_or(JSONValue::Array(Vec::new()))
+            .try_into().map_err(|_|err("field \"cues\" of captions needs to be an array"))?;
+
+        for cue in cues {
+            let cue: IndexMap<String, JSONValue> = cue.try_into().map_err(|_|err("captions cues need to be objects"))?;
+
+            let slide: f64 = get_value_alternates::<_, _, _, D::Error>(&cue, vec!["slide"])?.clone().try_into().map_err(|_|err("caption slide index needs to be a number"))?;
+            let start: f64 = get_value_alternates::<_, _, _, D::Error>(&cue, vec!["start"])?.clone().try_into().map_err(|_|err("caption start needs to be a number"))?;
+            let end: f64 = get_value_alternates::<_, _, _, D::Error>(&cue, vec!["end"])?.clone().try_into().map_err(|_|err("caption end needs to be a number"))?;
+            let lines: Vec<String> = match get_value_alternates::<_, _, _, D::Error>(&cue, vec!["text", "lines"])? {
+                JSONValue::String(s) => s.split('\n').map(|l| l.to_owned()).collect(),
+                JSONValue::Array(a) => a.iter().map(|l| l.clone().try_into().map_err(|_|err("caption text needs to be a string or an array of strings"))).collect::<Result<_, D::Error>>()?,
+                _ => return Err(err("caption text needs to be a string or an array of strings"))
+            };
+
+            let text = Text::new(
+                BaseProperties::new(format!("50%;h-({strip_height})/2"), format!("90%;{size}"), color.clone(), "MID_CENTERED")
+                    .map_err(merr("Invalid caption size or color!"))?,
+                lines,
+                font.clone(),
+                &*crate::FONTS.get().ok_or(err("error getting font-list"))?,
+                HashMap::new(),
+                "CENTERED").map_err(merr("Invalid caption text!"))?;
+
+            track.add(slide as usize, start, end, Box::new(text));
+        }
+
+        Ok(DocumentCaptions(Some(track)))
+    }
+}
+
+#[derive(Debug)]
+pub struct DocumentMeta(pub Metadata);
+impl<'de> Deserialize<'de> for DocumentMeta {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de> {
+        // Alias for more compact and more readable code
+        let err = serde::de::Error::custom;
+
+        // Get the base object of the document and error if it isn't a map
+        let document: IndexMap<String, JSONValue> = deserializer.deserialize_map(JSONValue::Null)?.try_into().map_err(|_|err("base object isn't a map"))?;
+
+        // The metadata is optional
+        let meta: IndexMap<String, JSONValue> = match document.get("meta") {
+            Some(json) => json.clone().try_into().map_err(|_|err("field \"meta\" needs to be an object"))?,
+            None => return Ok(DocumentMeta(Metadata::default()))
+        };
+
+        let title = match meta.get("title") {
+            Some(json) => Some(json.clone().try_into().map_err(|_|err("the document's title needs to be a string"))?),
+            None => None
+        };
+
+        let font = match meta.get("font") {
+            Some(json) => Some(json.clone().try_into().map_err(|_|err("the document's font needs to be a string"))?),
+            None => None
+        };
+
+        let palette = parse_palette::<D::Error>(&meta)?;
+
+        let seed = match meta.get("seed") {
+            Some(json) => {
+                let seed: f64 = json.clone().try_into().map_err(|_|err("the document's seed needs to be a number"))?;
+                seed as u64
+            },
+            None => 0
+        };
+
+        let mut lint = HashMap::new();
+        if let Some(json) = meta.get("lint") {
+            let rules: IndexMap<String, JSONValue> = json.clone().try_into().map_err(|_|err("field \"lint\" needs to be an object"))?;
+            for (rule, severity) in rules {
+                let severity: String = severity.try_into().map_err(|_|err("the severities of lint rules need to be strings"))?;
+                let severity = Severity::from_name(&severity).ok_or(err(format!("invalid severity '{severity}' of lint rule '{rule}' (expected off, warn or error)")))?;
+                lint.insert(rule, severity);
+            }
+        }
+
+        let locale = match meta.get("locale") {
+            Some(json) => {
+                let code: String = json.clone().try_into().map_err(|_|err("the document's locale needs to be a string"))?;
+                Locale::from_code(&code).ok_or(err(format!("unknown locale '{code}'")))?
+            },
+            None => Locale::DEFAULT
+        };
+
+        Ok(DocumentMeta(Metadata { title, palette, font, seed, lint, locale }))
+    }
+}
+
+#[derive(Debug)]
+pub struct DocumentSounds(pub SoundEffects);
+impl<'de> Deserialize<'de> for DocumentSounds {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de> {
+        // Alias for more compact and more readable code
+        let err = serde::de::Error::custom;
+
+        // Get the base object of the document and error if it isn't a map
+        let document: IndexMap<String, JSONValue> = deserializer.deserialize_map(JSONValue::Null)?.try_into().map_err(|_|err("base object isn't a map"))?;
+
+        // The sound effects are optional
+        let sounds: IndexMap<String, JSONValue> = match document.get("sounds") {
+            Some(json) => json.clone().try_into().map_err(|_|err("field \"sounds\" needs to be an object"))?,
+            None => return Ok(DocumentSounds(SoundEffects::default()))
+        };
+
+        // Helper for getting the optional file path of a sound effect
+        let sound = |key: &str| -> Result<Option<String>, D::Error> {
+            match sounds.get(key) {
+                Some(json) => json.clone().try_into().map(Some)
+                    .map_err(|_|<D::Error as serde::de::Error>::custom(format!("sound \"{key}\" needs to be a file path"))),
+                None => Ok(None)
+            }
+        };
+
+        Ok(DocumentSounds(SoundEffects {
+            next: sound("next")?,
+            previous: sound("previous")?
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct DocumentMidi(pub Vec<MidiBinding>);
+impl<'de> Deserialize<'de> for DocumentMidi {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de> {
+        // Alias for more compact and more readable code
+        let err = serde::de::Error::custom;
+
+        // Get the base object of the document and error if it isn't a map
+        let document: IndexMap<String, JSONValue> = deserializer.deserialize_map(JSONValue::Null)?.try_into().map_err(|_|err("base object isn't a map"))?;
+
+        // The MIDI bindings are optional
+        let bindings: Vec<JSONValue> = match document.get("midi") {
+            Some(json) => json.clone().try_into().map_err(|_|err("field \"midi\" needs to be an array of bindings"))?,
+            None => return Ok(DocumentMidi(Vec::new()))
+        };
+
+        let mut list = Vec::with_capacity(bindings.len());
+        for (i, binding) in bindings.into_iter().enumerate() {
+            let binding: IndexMap<String, JSONValue> = binding.try_into().map_err(|_|err("MIDI bindings need to be objects"))?;
+
+            // A binding either reacts to a note or a controller
+            let (kind, number) = match (binding.get("note"), binding.get("cc")) {
+                (Some(n), None) => (MidiKind::Note, n),
+                (None, Some(n)) => (MidiKind::ControlChange, n),
+                _ => return Err(<D::Error as serde::de::Error>::custom(format!("MIDI binding #{i} needs either a \"note\" or a \"cc\" field")))
+            };
+            let number: f64 = number.clone().try_into().map_err(|_|<D::Error as serde::de::Error>::custom(format!("invalid note or controller number in MIDI binding #{i}")))?;
+
+            let action = match (binding.get("action"), binding.get("lua")) {
+                (Some(JSONValue::String(action)), None) => match action.as_str() {
+                    "next" | "next_slide" => MidiAction::NextSlide,
+                    "previous" | "previous_slide" => MidiAction::PreviousSlide,
+                    other => return Err(<D::Error as serde::de::Error>::custom(format!("unknown action \"{other}\" in MIDI binding #{i}")))
+                },
+                (None, Some(JSONValue::String(code))) => MidiAction::Lua(code.clone()),
+                _ => return Err(<D::Error as serde::de::Error>::custom(format!("MIDI binding #{i} needs either an \"action\" or a \"lua\" string")))
+            };
+
+            list.push(MidiBinding { kind, number: number as u8, action });
+        }
+
+        Ok(DocumentMidi(list))
+    }
+}
+
 use once_cell::sync::Lazy;
-type FnRenderableParse = Box<dyn Fn(HashMap<String, JSONValue>, BaseProperties) -> Result<Box<dyn Renderable>, String>>;
+
+/// Matches a reference to a variable (e.g. `${accent}`).
+static VARIABLE_REGEX: Lazy<regex::Regex> = Lazy::new(|| regex::Regex::new(r"\$\{(\w+)\}").unwrap());
+
+/// The maximum amount of files including each other, so cycles don't recurse forever.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// The object shown in place of objects that got disabled in safe mode (see
+/// [`crate::presentation::util::take_safe_mode_refusal`]).
+const SAFE_MODE_PLACEHOLDER: &str = r##"{
+    type: "Text", pos: "50%;50%", size: "80%;4%", alignment: "MID_CENTERED", text_align: "CENTERED",
+    color: "#e0a000", font: "Default", text: ["⚠ This object was disabled in safe mode"]
+}"##;
+
+type FnRenderableParse = Box<dyn Fn(&IndexMap<String, JSONValue>, BaseProperties) -> Result<Box<dyn Renderable>, String> + Send + Sync>;
 /// A [`HashMap`] of functions for parsing each type of [`Renderable`].
 /// 
 /// The index defines the name of the type.
-const RENDERABLE_FUNCS: Lazy<HashMap<String, FnRenderableParse>> = Lazy::new(|| {
+static RENDERABLE_FUNCS: Lazy<HashMap<String, FnRenderableParse>> = Lazy::new(|| {
     let mut map = HashMap::new();
     map.insert("ColoredRect".to_owned(), ColoredRect::renderable_func::<deser_hjson::Error>());
     map.insert("Rect".to_owned(), ColoredRect::renderable_func::<deser_hjson::Error>());
@@ -597,13 +1478,175 @@ const RENDERABLE_FUNCS: Lazy<HashMap<String, FnRenderableParse>> = Lazy::new(||
     map.insert("RoundedRect".to_owned(), RoundedRect::renderable_func::<deser_hjson::Error>());
     map.insert("Text".to_owned(), Text::renderable_func::<deser_hjson::Error>());
     map.insert("Image".to_owned(), Image::renderable_func::<deser_hjson::Error>());
+    map.insert("Video".to_owned(), Video::renderable_func::<deser_hjson::Error>());
+    map.insert("Blur".to_owned(), Blur::renderable_func::<deser_hjson::Error>());
+    map.insert("Ticker".to_owned(), Ticker::renderable_func::<deser_hjson::Error>());
     map
 });
 
-/// Helper function for getting a value of a [`HashMap`], allowing it to be stored in multiple alternative keys.
+/// Helper function for converting a color string into a list of four expressions.
+/// 
+/// The string can either already be a list of expressions or a hexadecimal color value (`#RRGGBB`
+/// or `#RRGGBBAA`).
+fn parse_color<E: serde::de::Error>(str: String) -> Result<String, E> {
+    let err = serde::de::Error::custom;
+
+    if str.starts_with('#') {
+        // If the color string starts with a '#', we don't have a pair of expressions, but
+        // a hexadecimal color value instead.
+
+        // Do some rudimentary checks if the format is correct (as to not throw confusing
+        // error messages at the user)
+        if (str.len()!=7 && str.len()!=9) || !str.is_ascii() {
+            // The string needs to be of length 7 (#RRGGBB) or 9 (#RRGGBBAA), otherwise it
+            // is invalid (non-ASCII characters would also make slicing it below panic)
+            Err(err("invalid hexadecimal color format"))?;
+        }
+
+        let red_hex: String = str[1..3].to_lowercase();
+        let green_hex: String = str[3..5].to_lowercase();
+        let blue_hex: String = str[5..7].to_lowercase();
+        let alpha_hex: String = if str.len()==9 { str[7..9].to_lowercase() } else { "ff".to_owned() };
+
+        let red = u8::from_str_radix(&red_hex, 16).map_err(|_|err("invalid hexadecimal color value"))? as f32;
+        let green = u8::from_str_radix(&green_hex, 16).map_err(|_|err("invalid hexadecimal color value"))? as f32;
+        let blue = u8::from_str_radix(&blue_hex, 16).map_err(|_|err("invalid hexadecimal color value"))? as f32;
+        let alpha = u8::from_str_radix(&alpha_hex, 16).map_err(|_|err("invalid hexadecimal color value"))? as f32;
+
+        Ok(format!("{};{};{};{}",red/255.0,green/255.0,blue/255.0,alpha/255.0))
+    } else {
+        // If the color string doesn't start with a '#', we do have a pair of expressions.
+
+        Ok(str)
+    }
+}
+
+/// Helper function for parsing the texture settings of an image.
+/// 
+/// Starts with the default settings and overrides the filtering (`filter`: `"linear"` or
+/// `"nearest"`), mipmap generation (`mipmaps`) and wrap mode (`wrap`: `"clamp"`, `"repeat"`,
+/// `"mirror"` or `"border"`) if the fields are present.
+/// Parses the palette in a document's metadata, colors missing from it keep their default.
+fn parse_palette<E: serde::de::Error>(meta: &IndexMap<String, JSONValue>) -> Result<Palette, E> {
+    let err = serde::de::Error::custom;
+
+    let mut palette = Palette::default();
+    if let Some(json) = meta.get("palette") {
+        let colors: IndexMap<String, JSONValue> = json.clone().try_into().map_err(|_|err("field \"palette\" needs to be an object"))?;
+        for (name, color) in [("background", &mut palette.background), ("text", &mut palette.text), ("accent", &mut palette.accent)] {
+            if let Some(json) = colors.get(name) {
+                let value: String = json.clone().try_into().map_err(|_|err("the colors of the palette need to be strings"))?;
+                *color = parse_color::<E>(value)?;
+            }
+        }
+    }
+    Ok(palette)
+}
+
+fn parse_texture_settings<E: serde::de::Error>(hashmap: &IndexMap<String, JSONValue>) -> Result<opengl_graphics::TextureSettings, E> {
+    use opengl_graphics::{ Filter, Wrap };
+
+    let err = serde::de::Error::custom;
+
+    let mut settings = crate::render::sprite::DEFAULT_TEXTURE_SETTINGS.clone();
+
+    let filter = match hashmap.get("filter") {
+        Some(JSONValue::String(filter)) => match filter.to_lowercase().as_str() {
+            "linear" => Filter::Linear,
+            "nearest" => Filter::Nearest,
+            _ => return Err(err("field \"filter\" needs to be either \"linear\" or \"nearest\""))
+        },
+        Some(_) => return Err(err("field \"filter\" needs to be either \"linear\" or \"nearest\"")),
+        None => Filter::Linear
+    };
+    settings.set_filter(filter);
+
+    match hashmap.get("mipmaps") {
+        Some(JSONValue::Bool(mipmaps)) => {
+            settings.set_generate_mipmap(*mipmaps);
+            settings.set_mipmap(filter);
+        },
+        Some(_) => return Err(err("field \"mipmaps\" needs to be a boolean")),
+        None => {}
+    }
+
+    match hashmap.get("wrap") {
+        Some(JSONValue::String(wrap)) => {
+            let wrap = match wrap.to_lowercase().as_str() {
+                "clamp" => Wrap::ClampToEdge,
+                "repeat" => Wrap::Repeat,
+                "mirror" => Wrap::MirroredRepeat,
+                "border" => Wrap::ClampToBorder,
+                _ => return Err(err("field \"wrap\" needs to be \"clamp\", \"repeat\", \"mirror\" or \"border\""))
+            };
+            settings.set_wrap_u(wrap);
+            settings.set_wrap_v(wrap);
+        },
+        Some(_) => return Err(err("field \"wrap\" needs to be \"clamp\", \"repeat\", \"mirror\" or \"border\"")),
+        None => {}
+    }
+
+    Ok(settings)
+}
+
+/// Helper function for parsing an aspect ratio.
+/// 
+/// It can either be a number (width divided by height) or a string like `"4:3"`.
+fn parse_aspect_ratio<E: serde::de::Error>(json: JSONValue) -> Result<f64, E> {
+    let err = || serde::de::Error::custom("aspect ratio needs to be a number or a string like \"4:3\"");
+
+    let ratio = match json {
+        JSONValue::Number(ratio) => ratio,
+        JSONValue::String(s) => {
+            let (w, h) = s.split_once(':').ok_or(err())?;
+            let w: f64 = w.trim().parse().map_err(|_| err())?;
+            let h: f64 = h.trim().parse().map_err(|_| err())?;
+            w / h
+        },
+        _ => return Err(err())
+    };
+
+    match ratio.is_finite() && ratio > 0.0 {
+        true => Ok(ratio),
+        false => Err(err())
+    }
+}
+
+/// The duration of transitions that don't specify one, in seconds.
+const DEFAULT_TRANSITION_DURATION: f64 = 0.3;
+
+/// Helper function for parsing a slide transition.
+/// 
+/// It can either be the name of the transition (`"fade"`, or `"none"`/`false` to disable it) or an
+/// object like `{ type: "fade", duration: 0.5 }`.
+fn parse_transition<E: serde::de::Error>(json: JSONValue) -> Result<Transition, E> {
+    let err = || serde::de::Error::custom("transition needs to be \"fade\", \"none\" or an object containing it's type and duration");
+
+    let (kind, duration) = match json {
+        JSONValue::Bool(false) => ("none".to_owned(), None),
+        JSONValue::String(kind) => (kind, None),
+        JSONValue::Object(map) => {
+            let kind: String = map.get("type").cloned().ok_or(err())?.try_into().map_err(|_| err())?;
+            let duration: Option<f64> = match map.get("duration") {
+                Some(json) => Some(json.clone().try_into().map_err(|_| err())?),
+                None => None
+            };
+            (kind, duration)
+        },
+        _ => return Err(err())
+    };
+
+    match (kind.as_str(), duration.unwrap_or(DEFAULT_TRANSITION_DURATION)) {
+        ("none" | "cut", _) => Ok(Transition::Cut),
+        ("fade", duration) if duration.is_finite() && duration >= 0.0 => Ok(Transition::Fade { duration }),
+        _ => Err(err())
+    }
+}
+
+/// Helper function for getting a value of a [`IndexMap`], allowing it to be stored in multiple alternative keys.
 /// 
 /// Returns a [`Result<&V, serde::de::Error>`], primarily for usage in implementations of the [`Deserialize`] trait.
-fn get_value_alternates<K, V, Q, E>(map: &HashMap<K, V>, keys: Vec<Q>) -> Result<&V, E>
+fn get_value_alternates<K, V, Q, E>(map: &IndexMap<K, V>, keys: Vec<Q>) -> Result<&V, E>
 where
     K: Hash + Eq + std::fmt::Display,
     Q: Into<K> + std::fmt::Debug + Clone,
@@ -615,19 +1658,254 @@ where
     val.ok_or(serde::de::Error::custom(format!("required parameter unspecified; possible keys: {:?}",keys)))
 }
 
+/// The order keys get written in when formatting a document.
+/// 
+/// Keys that aren't in this list get written after these, in alphabetical order.
+const CANONICAL_KEY_ORDER: &[&str] = &[
+    "meta", "fonts", "captions", "sounds", "midi", "templates", "safe_area", "slides",
+    "title", "bookmark", "duration", "transition", "narration", "notes", "background",
+    "type", "id", "extends", "z", "pos", "position", "size", "col", "color", "colour", "align", "alignment"
+];
+
+/// The maximum length of an array of plain values that still gets written on a single line.
+const MAX_INLINE_ARRAY_LENGTH: usize = 80;
+
+/// Helper function for writing a value in the canonical HJSON layout.
+/// 
+/// Objects get their keys sorted by [`CANONICAL_KEY_ORDER`], get indented with four spaces per
+/// level and colors get normalized. `key` is the key the value is stored under, if any.
+fn write_hjson(output: &mut String, value: &JSONValue, key: Option<&str>, indent: usize) {
+    let indentation = |level: usize| "    ".repeat(level);
+
+    match value {
+        JSONValue::Null => output.push_str("null"),
+        JSONValue::Bool(b) => output.push_str(&b.to_string()),
+        JSONValue::Number(n) => output.push_str(&n.to_string()),
+        JSONValue::String(s) => {
+            let s = match key {
+                Some("col" | "color" | "colour" | "background") => normalize_color(s),
+                _ => s.clone()
+            };
+            output.push_str(&quote_string(&s));
+        },
+        JSONValue::Array(vec) => {
+            let plain = vec.iter().all(|v| !matches!(v, JSONValue::Array(_) | JSONValue::Object(_)));
+            let inline = vec.iter().map(|v| {
+                let mut s = String::new();
+                write_hjson(&mut s, v, key, 0);
+                s
+            }).collect::<Vec<_>>().join(", ");
+
+            if vec.is_empty() {
+                output.push_str("[]");
+            } else if plain && inline.len() <= MAX_INLINE_ARRAY_LENGTH {
+                output.push_str(&format!("[{inline}]"));
+            } else {
+                output.push_str("[\n");
+                for (i, v) in vec.iter().enumerate() {
+                    output.push_str(&indentation(indent + 1));
+                    write_hjson(output, v, key, indent + 1);
+                    output.push_str(if i + 1 < vec.len() { ",\n" } else { "\n" });
+                }
+                output.push_str(&indentation(indent));
+                output.push(']');
+            }
+        },
+        JSONValue::Object(map) => {
+            if map.is_empty() {
+                output.push_str("{}");
+                return
+            }
+
+            let rank = |k: &str| CANONICAL_KEY_ORDER.iter().position(|c| *c == k).unwrap_or(CANONICAL_KEY_ORDER.len());
+            let mut keys = map.keys().collect::<Vec<_>>();
+            keys.sort_by(|a, b| rank(a).cmp(&rank(b)).then(a.cmp(b)));
+
+            output.push_str("{\n");
+            for (i, k) in keys.iter().enumerate() {
+                output.push_str(&indentation(indent + 1));
+                let is_identifier = k.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                    && k.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                match is_identifier {
+                    true => output.push_str(k),
+                    false => output.push_str(&quote_string(k))
+                }
+                output.push_str(": ");
+                write_hjson(output, &map[*k], Some(k.as_str()), indent + 1);
+                output.push_str(if i + 1 < keys.len() { ",\n" } else { "\n" });
+            }
+            output.push_str(&indentation(indent));
+            output.push('}');
+        }
+    }
+}
+
+/// Helper function for collecting the remote URLs in file paths and the Lua scripts of a JSON
+/// value (see [`Parser::external_resources`]).
+/// 
+/// Included files get searched as well, `depth` is the amount of files including each other up to
+/// the value. Included files that can't be read are skipped, they get reported when loading the
+/// document.
+fn collect_external_resources(json: &JSONValue, resources: &mut Vec<String>, depth: usize) {
+    match json {
+        JSONValue::Array(vec) => vec.iter().for_each(|v| collect_external_resources(v, resources, depth)),
+        JSONValue::Object(map) => {
+            for (key, value) in map.iter() {
+                match (key.as_str(), value) {
+                    ("path" | "file" | "file_path" | "include" | "background" | "narration" | "source", JSONValue::String(path)) if path.contains("://") => resources.push(path.clone()),
+                    ("include", JSONValue::String(path)) if depth < MAX_INCLUDE_DEPTH => {
+                        let included = std::fs::read_to_string(path).ok()
+                            .and_then(|contents| deser_hjson::from_str::<JSONValue>(&contents).ok());
+                        if let Some(included) = included {
+                            collect_external_resources(&included, resources, depth + 1);
+                        }
+                    },
+                    ("lua", JSONValue::String(code)) => resources.push(format!("{}{}", super::LUA_PREFIX, code.split_whitespace().collect::<Vec<_>>().join(" "))),
+                    (_, value) => collect_external_resources(value, resources, depth)
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Helper function for renaming the references in the fields `keys` of all objects in a JSON
+/// value (e.g. to fonts or templates).
+/// 
+/// `renames` are the new names by the old ones.
+fn rename_references(json: &mut JSONValue, keys: &[&str], renames: &HashMap<String, String>) {
+    if renames.is_empty() {
+        return
+    }
+
+    match json {
+        JSONValue::Array(vec) => vec.iter_mut().for_each(|v| rename_references(v, keys, renames)),
+        JSONValue::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match value {
+                    JSONValue::String(name) if keys.contains(&key.as_str()) => if let Some(renamed) = renames.get(name) {
+                        *name = renamed.clone();
+                    },
+                    value => rename_references(value, keys, renames)
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Helper function for hashing the content of a slide and the templates it's objects extend.
+/// 
+/// The slide gets hashed in it's canonical layout (see [`write_hjson`]), so reformatting a
+/// document doesn't change the hashes of it's slides. Uses 64 bit FNV-1a, which (unlike the
+/// standard library's hasher) gives the same hash on every run, so hashes can be saved and
+/// compared later.
+fn content_hash(slide: &IndexMap<String, JSONValue>, templates: &IndexMap<String, JSONValue>) -> u64 {
+    let slide = JSONValue::Object(slide.clone());
+
+    let mut extended = Vec::new();
+    collect_extended_templates(&slide, templates, &mut extended);
+    extended.sort();
+
+    let mut text = String::new();
+    write_hjson(&mut text, &slide, None, 0);
+    for name in extended {
+        text.push_str(&format!("\n{}: ", quote_string(&name)));
+        write_hjson(&mut text, &templates[&name], None, 0);
+    }
+
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Helper function for collecting the names of all existing templates a JSON value extends,
+/// including the templates those templates extend.
+fn collect_extended_templates(json: &JSONValue, templates: &IndexMap<String, JSONValue>, names: &mut Vec<String>) {
+    match json {
+        JSONValue::Array(vec) => vec.iter().for_each(|v| collect_extended_templates(v, templates, names)),
+        JSONValue::Object(map) => {
+            for (key, value) in map.iter() {
+                match (key.as_str(), value) {
+                    ("extends", JSONValue::String(name)) => if let (Some(template), false) = (templates.get(name), names.contains(name)) {
+                        names.push(name.clone());
+                        collect_extended_templates(template, templates, names);
+                    },
+                    (_, value) => collect_extended_templates(value, templates, names)
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Helper function for finding a name for an entry of the document with index `document` that
+/// isn't used by any entry of `map` yet.
+/// 
+/// Returns `name` if it isn't taken, otherwise the number of the document gets appended to it.
+fn unique_name(name: &str, document: usize, map: &IndexMap<String, JSONValue>) -> String {
+    let mut unique = name.to_owned();
+    let mut attempt = 1;
+    while map.contains_key(&unique) {
+        unique = match attempt {
+            1 => format!("{name} ({})", document + 1),
+            _ => format!("{name} ({}, {attempt})", document + 1)
+        };
+        attempt += 1;
+    }
+    unique
+}
+
+/// Helper function for writing a string as a quoted JSON string.
+fn quote_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c)
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Helper function for normalizing the format of a color.
+/// 
+/// Hexadecimal colors get lowercased and lose a fully opaque alpha value (`#FF0000FF` becomes
+/// `#ff0000`), lists of expressions lose the whitespace around their separators.
+fn normalize_color(color: &str) -> String {
+    let color = color.trim();
+    if color.starts_with('#') && color.is_ascii() {
+        let lower = color.to_lowercase();
+        match lower.len() == 9 && lower.ends_with("ff") {
+            true => lower[..7].to_owned(),
+            false => lower
+        }
+    } else {
+        color.split(';').map(|part| part.trim()).collect::<Vec<_>>().join(";")
+    }
+}
+
 /// Trait for parsing JSON data into a struct.
 /// 
 /// Also contains some helper functions related to [`Renderable`]s that can be parsed from JSON.
 trait FromJson<'a> {
     /// Parses JSON-data and into itself
-    fn from_json<E: serde::de::Error>(dict: &'a HashMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
+    /// 
+    /// The data is only borrowed while parsing, everything kept by the object gets copied out
+    /// of it.
+    fn from_json<E: serde::de::Error>(dict: &IndexMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
     where Self: Sized;
 
     /// Returns a closure that constructs a Renderable object
     fn renderable_func<E: serde::de::Error>() -> FnRenderableParse
     where Self: Sized + Renderable + 'static {
-        let func = |dict: HashMap<String, JSONValue>, base: BaseProperties| {
-            match Self::from_json::<E>(&*Box::leak(Box::new(dict)), base) {
+        let func = |dict: &IndexMap<String, JSONValue>, base: BaseProperties| {
+            match Self::from_json::<E>(dict, base) {
                 Ok(s) => Ok(Box::new(s) as Box<dyn Renderable>),
                 Err(e) => Err(format!("{e}"))
             }
@@ -638,7 +1916,7 @@ trait FromJson<'a> {
 }
 
 impl<'a> FromJson<'a> for ColoredRect {
-    fn from_json<E: serde::de::Error>(_hashmap: &'a HashMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
+    fn from_json<E: serde::de::Error>(_hashmap: &IndexMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
     where Self: Sized {
         // Create the struct
         Ok(
@@ -648,7 +1926,7 @@ impl<'a> FromJson<'a> for ColoredRect {
 }
 
 impl<'a> FromJson<'a> for RoundedRect {
-    fn from_json<E: serde::de::Error>(hashmap: &'a HashMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
+    fn from_json<E: serde::de::Error>(hashmap: &IndexMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
     where Self: Sized {
 
         let merr = |renderable: &'static str, property: Option<&'static str>, desc: &'static str| move |e: PropertyError|{
@@ -666,17 +1944,29 @@ impl<'a> FromJson<'a> for RoundedRect {
             Err(_) => return Err(serde::de::Error::custom("corner radius needs to be a string"))
         }
 
+        // The number of segments per corner is optional and usually derived from it's size
+        let quality: Option<u32> = match get_value_alternates::<_, _, _, E>(hashmap, vec!["quality", "corner_quality", "segments"]) {
+            Ok(json) => {
+                let quality: f64 = json.clone().try_into().map_err(|_|serde::de::Error::custom("corner quality needs to be a number"))?;
+                if quality < 1.0 {
+                    return Err(serde::de::Error::custom("corner quality needs to be at least 1"))
+                }
+                Some(quality as u32)
+            },
+            Err(_) => None
+        };
+
         // Create the struct
-        Ok(
-            RoundedRect::new(
-                base,
-                corner_rounding).map_err(merr("RoundedRect",Some("corner_rounding"),"Invalid corner rounding!"))?
-        )
+        let mut rect = RoundedRect::new(
+            base,
+            corner_rounding).map_err(merr("RoundedRect",Some("corner_rounding"),"Invalid corner rounding!"))?;
+        rect.set_quality(quality);
+        Ok(rect)
     }
 }
 
 impl<'a> FromJson<'a> for Text<'a> {
-    fn from_json<E: serde::de::Error>(hashmap: &'a HashMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
+    fn from_json<E: serde::de::Error>(hashmap: &IndexMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
     where Self: Sized {
         let err = serde::de::Error::custom;
 
@@ -709,11 +1999,11 @@ impl<'a> FromJson<'a> for Text<'a> {
 
                 let context = &DEFAULT_CONTEXT;
 
-                let placeholder_map: HashMap<String, JSONValue> = placeholders_json.clone().try_into().map_err(|_|err("placeholder list must be a dict"))?;
+                let placeholder_map = placeholders_json.as_object().ok_or(err("placeholder list must be a dict"))?;
                 let mut placeholder_hash_map = HashMap::with_capacity(placeholder_map.len());
                 for (key, json) in placeholder_map {
-                    let expr_string: String = json.try_into().map_err(|_|err("placeholders have to be strings"))?;
-                    placeholder_hash_map.insert(key, TextPlaceholderExpr::parse(expr_string, context));
+                    let expr_string = json.as_str().ok_or(err("placeholders have to be strings"))?;
+                    placeholder_hash_map.insert(key.clone(), TextPlaceholderExpr::parse(expr_string, context));
                 }
                 placeholder_hash_map
             },
@@ -722,34 +2012,74 @@ impl<'a> FromJson<'a> for Text<'a> {
             }
         };
 
-        let mut texts: Vec<String> = Vec::new();
-        match < JSONValue as TryInto<Vec<JSONValue>> >::try_into(get_value_alternates(&hashmap, vec!["text","texts","lines"])?.clone()) {
-            Ok(v) => {
-                for val in v {
-                    match val.try_into() {
-                        Ok(s) => texts.push(s),
-                        Err(_) => return Err(serde::de::Error::custom("text needs to be an array of strings"))
-                    }
+        // Helper for parsing an array of lines of text
+        let parse_lines = |json: &JSONValue| -> Result<Vec<String>, E> {
+            let lines = json.as_array().ok_or(serde::de::Error::custom("text needs to be an array of strings"))?;
+            lines.iter()
+                .map(|line| line.as_str().map(str::to_owned).ok_or(serde::de::Error::custom("text needs to be an array of strings")))
+                .collect()
+        };
+
+        // The text is either an array of lines or an object containing an array of lines for each
+        // language
+        let variants: Vec<(String, Vec<String>)> = match get_value_alternates(&hashmap, vec!["text","texts","lines"])? {
+            JSONValue::Object(languages) => {
+                let mut variants = Vec::with_capacity(languages.len());
+                // The first language in the document is the fallback for missing translations
+                for (language, lines) in languages {
+                    variants.push((language.clone(), parse_lines(lines)?));
                 }
+                variants
             },
-            Err(_) => return Err(serde::de::Error::custom("text needs to be an array of strings"))
-        }
+            json => vec![(String::new(), parse_lines(json)?)]
+        };
+
+        // The background card is optional
+        let background = match get_value_alternates::<_, _, _, E>(hashmap, vec!["background", "card"]) {
+            Ok(json) => {
+                let card: IndexMap<String, JSONValue> = json.clone().try_into().map_err(|_|err("text background needs to be an object"))?;
+
+                // Helper for getting the optional string fields of the card
+                let field = |keys: Vec<&'static str>, default: &str| -> Result<String, E> {
+                    match get_value_alternates::<_, _, _, E>(&card, keys) {
+                        Ok(json) => json.clone().try_into().map_err(|_|err("text background fields need to be strings")),
+                        Err(_) => Ok(default.to_owned())
+                    }
+                };
+
+                Some(TextBackground::new(
+                    parse_color::<E>(field(vec!["col", "color", "colour"], "#000000b0")?)?,
+                    field(vec!["padding"], "0")?,
+                    field(vec!["corner_radius", "radius", "rounding"], "0")?
+                ).map_err(merr("Text",Some("background"),"Invalid background color, padding or corner radius!"))?)
+            },
+            Err(_) => None
+        };
 
         // Create the struct
-        Ok(
-            Text::new(
-                base,
-                texts,
-                font,
-                &*crate::FONTS.get().ok_or(serde::de::Error::custom("error getting font-list"))?,
-                placeholders,
-                text_alignment).map_err(merr("Text",None,"Invalid parameters!"))?
-        )
+        let mut text = Text::with_variants(
+            base,
+            variants,
+            font,
+            &*crate::FONTS.get().ok_or(serde::de::Error::custom("error getting font-list"))?,
+            placeholders,
+            text_alignment).map_err(merr("Text",None,"Invalid parameters!"))?;
+        text.set_background(background);
+
+        // Undefined placeholders only show a marker, unless they're treated as errors
+        if crate::CONFIG.get().is_some_and(|config| config.strict_placeholders) {
+            let undefined = text.undefined_placeholders();
+            if !undefined.is_empty() {
+                return Err(serde::de::Error::custom(format!("text uses undefined placeholders: {}", undefined.join(", "))))
+            }
+        }
+
+        Ok(text)
     }
 }
 
 impl<'a> FromJson<'a> for Image {
-    fn from_json<E: serde::de::Error>(hashmap: &'a HashMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
+    fn from_json<E: serde::de::Error>(hashmap: &IndexMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
     where Self: Sized {
 
         let merr = |renderable: &'static str, property: Option<&'static str>, desc: &'static str| move |e: PropertyError|{
@@ -769,11 +2099,179 @@ impl<'a> FromJson<'a> for Image {
             Err(_) => return Err(serde::de::Error::custom("file path needs to be a string"))
         }
 
+        // The alternative text is optional
+        let alt: Option<String> = match get_value_alternates::<_, _, _, E>(hashmap, vec!["alt", "alt_text"]) {
+            Ok(json) => Some(json.clone().try_into().map_err(|_| serde::de::Error::custom("alt text needs to be a string"))?),
+            Err(_) => None
+        };
+
+        let settings = parse_texture_settings(hashmap)?;
+
         // Create the struct
         Ok(
             Image::new(
                 base,
-                PathBuf::try_from(path).map_err(|_| serde::de::Error::custom("invalid file path specified"))?).map_err(merr("Image", Some("path"), "Invalid file format!"))?
+                PathBuf::try_from(path).map_err(|_| serde::de::Error::custom("invalid file path specified"))?,
+                alt,
+                &settings).map_err(merr("Image", Some("path"), "Invalid file format!"))?
         )
     }
-}
\ No newline at end of file
+}
+impl<'a> FromJson<'a> for Video {
+    fn from_json<E: serde::de::Error>(hashmap: &IndexMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
+    where Self: Sized {
+
+        let merr = |renderable: &'static str, property: Option<&'static str>, desc: &'static str| move |e: PropertyError|{
+            let (r, p, desc) = e.syntax_error(renderable, property.unwrap_or("_"), desc);
+
+            if property.is_some() {
+                serde::de::Error::custom(format!("error while initializing property {p} of {r}: {desc}").as_str())
+            } else {
+                serde::de::Error::custom(format!("error while creating {r}: {desc}").as_str())
+            }
+        };
+
+        // Get the file path and whether the video should loop from the JSON data
+        let path: String;
+        match get_value_alternates(hashmap, vec!["path", "file", "file_path"])?.clone().try_into() {
+            Ok(v) => path = v,
+            Err(_) => return Err(serde::de::Error::custom("file path needs to be a string"))
+        }
+
+        let looped: bool = match hashmap.get("loop") {
+            Some(v) => v.clone().try_into().map_err(|_| serde::de::Error::custom("field \"loop\" needs to be a boolean"))?,
+            None => true
+        };
+
+        // Create the struct
+        Ok(
+            Video::new(
+                base,
+                PathBuf::try_from(path).map_err(|_| serde::de::Error::custom("invalid file path specified"))?,
+                looped).map_err(merr("Video", Some("path"), "Invalid video file!"))?
+        )
+    }
+}
+
+impl<'a> FromJson<'a> for Blur {
+    fn from_json<E: serde::de::Error>(hashmap: &IndexMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
+    where Self: Sized {
+
+        let merr = |renderable: &'static str, property: Option<&'static str>, desc: &'static str| move |e: PropertyError|{
+            let (r, p, desc) = e.syntax_error(renderable, property.unwrap_or("_"), desc);
+
+            if property.is_some() {
+                serde::de::Error::custom(format!("error while initializing property {p} of {r}: {desc}").as_str())
+            } else {
+                serde::de::Error::custom(format!("error while creating {r}: {desc}").as_str())
+            }
+        };
+
+        // The blur radius is optional
+        let radius: String = match get_value_alternates::<_, _, _, E>(hashmap, vec!["radius", "blur_radius"]) {
+            Ok(v) => v.clone().try_into().map_err(|_| serde::de::Error::custom("blur radius needs to be a string"))?,
+            Err(_) => "1%".to_owned()
+        };
+
+        // Create the struct
+        Ok(
+            Blur::new(
+                base,
+                radius).map_err(merr("Blur",Some("radius"),"Invalid blur radius!"))?
+        )
+    }
+}
+
+impl<'a> FromJson<'a> for Ticker {
+    fn from_json<E: serde::de::Error>(hashmap: &IndexMap<String, JSONValue>, base: BaseProperties) -> Result<Self, E>
+    where Self: Sized {
+        let err = serde::de::Error::custom;
+
+        // The entries are either listed directly, read from a text file or fetched from a URL (one
+        // entry per line)
+        let source: Option<String> = match get_value_alternates::<_, _, _, E>(hashmap, vec!["source"]) {
+            Ok(json) => Some(json.clone().try_into().map_err(|_|err("ticker source needs to be a file path or URL"))?),
+            Err(_) => None
+        };
+        let lines: Vec<String> = match &source {
+            // The lines of URLs get fetched in the background once the ticker exists
+            Some(url) if url.contains("://") => {
+                crate::presentation::util::check_remote_path(url).map_err(|_|err("remote ticker sources are disabled in safe mode"))?;
+                Vec::new()
+            },
+            Some(path) => Ticker::split_lines(&std::fs::read_to_string(path).map_err(|e|err(format!("reading ticker source {path} failed: {e}")))?),
+            None => {
+                let json: Vec<JSONValue> = get_value_alternates(hashmap, vec!["text", "entries", "lines"])?.clone().try_into()
+                    .map_err(|_|err("ticker entries need to be an array of strings"))?;
+                json.into_iter()
+                    .map(|line| line.try_into().map_err(|_|err("ticker entries need to be an array of strings")))
+                    .collect::<Result<_, E>>()?
+            }
+        };
+
+        let dwell: f64 = match get_value_alternates::<_, _, _, E>(hashmap, vec!["dwell", "duration"]) {
+            Ok(json) => json.clone().try_into().map_err(|_|err("ticker dwell time needs to be a number"))?,
+            Err(_) => 5.0
+        };
+        if !(dwell > 0.0) {
+            return Err(err("ticker dwell time needs to be positive"))
+        }
+
+        // "fade" fades every entry in and out, "cut" switches instantly
+        let transition: String = match get_value_alternates::<_, _, _, E>(hashmap, vec!["transition"]) {
+            Ok(json) => json.clone().try_into().map_err(|_|err("ticker transition needs to be a string"))?,
+            Err(_) => "fade".to_owned()
+        };
+        let fade: f64 = match transition.as_str() {
+            "fade" => match get_value_alternates::<_, _, _, E>(hashmap, vec!["fade"]) {
+                Ok(json) => json.clone().try_into().map_err(|_|err("ticker fade duration needs to be a number"))?,
+                Err(_) => (dwell * 0.1).min(0.5)
+            },
+            "cut" => 0.0,
+            _ => return Err(err("invalid ticker transition (expected \"fade\" or \"cut\")"))
+        };
+
+        // Every entry is a text object whose alpha gets faded using the time since it was shown
+        let color: String = get_value_alternates::<_, _, _, E>(hashmap, vec!["col", "color", "colour"])?.clone().try_into()
+            .map_err(|_|err("color needs to be a string"))?;
+        let color = parse_color::<E>(color)?;
+        let color = match (fade > 0.0, color.split(';').collect::<Vec<_>>().as_slice()) {
+            (true, [r, g, b, a]) => format!("{r};{g};{b};({a})*clamp(min(t,{dwell}-t)/{fade},0,1)"),
+            (true, _) => return Err(err("fading ticker entries needs a color of four expressions")),
+            (false, _) => color
+        };
+
+        // Fetched lines get turned into entries while presenting, so the ticker keeps the
+        // properties needed for creating them
+        let mut template = hashmap.clone();
+        template.insert("color".to_owned(), JSONValue::String(color));
+        template.shift_remove("col");
+        template.shift_remove("colour");
+        let entry = move |line: &str| -> anyhow::Result<Box<dyn Renderable>> {
+            let mut map = template.clone();
+            map.insert("text".to_owned(), JSONValue::Array(vec![JSONValue::String(line.to_owned())]));
+
+            let entry_base = Document::parse_base_properties::<deser_hjson::Error>(&map, "Ticker".to_owned()).map_err(|e| anyhow::anyhow!("{e}"))?;
+            let text = Text::from_json::<deser_hjson::Error>(&map, entry_base).map_err(|e| anyhow::anyhow!("{e}"))?;
+            Ok(Box::new(text))
+        };
+
+        let entries = lines.iter().map(|line| entry(line).map_err(|e|err(e.to_string()))).collect::<Result<Vec<_>, E>>()?;
+
+        let feed = match source {
+            Some(url) if url.contains("://") => {
+                let refresh: f64 = match get_value_alternates::<_, _, _, E>(hashmap, vec!["refresh"]) {
+                    Ok(json) => json.clone().try_into().map_err(|_|err("ticker refresh interval needs to be a number"))?,
+                    Err(_) => 60.0
+                };
+                Some(TickerFeed::new(url, refresh, Box::new(entry)))
+            },
+            _ => None
+        };
+
+        // Create the struct
+        Ok(
+            Ticker::new(base, entries, lines, dwell, feed)
+        )
+    }
+}
@@ -1,35 +1,264 @@
-use std::fmt::Debug;
+use std::any::Any;
+use std::fmt::{ Debug, Display };
 use std::path::Path;
 use std::collections::HashMap;
+use std::sync::{ Arc, RwLock };
 
-use crate::presentation::Renderable;
+use once_cell::sync::Lazy;
+use indexmap::IndexMap;
+
+use crate::presentation::{ Renderable, Narration, CaptionTrack, Transition };
+use crate::presentation::locale::Locale;
+use crate::audio::SoundEffects;
+use crate::midi::MidiBinding;
+use crate::components::Palette;
+use crate::lint::Severity;
 
 pub mod json;
 
+/// The prefix of Lua scripts listed by [`Parser::external_resources`].
+pub const LUA_PREFIX: &'static str = "lua:";
+
 pub trait Parser {
     type Error: serde::de::Error;
 
     fn parse<'a>(&mut self, contents: &'a str) -> Result<Vec<SlideData>, Self::Error>;
 
-    fn parse_fonts<'a>(&mut self, contents: &'a str) -> Result<HashMap<String, (String, String)>, Self::Error>;
+    /// Parses the fonts registered by the document, in the order they're defined in.
+    fn parse_fonts<'a>(&mut self, contents: &'a str) -> Result<IndexMap<String, (String, String)>, Self::Error>;
+
+    /// Parses the document's caption track, returning [`None`] if the document doesn't have one.
+    /// 
+    /// Needs to be called after the fonts got initialized.
+    fn parse_captions<'a>(&mut self, contents: &'a str) -> Result<Option<CaptionTrack>, Self::Error>;
+
+    /// Parses the sound effects configured in the document.
+    fn parse_sounds<'a>(&mut self, contents: &'a str) -> Result<SoundEffects, Self::Error>;
+
+    /// Parses the MIDI bindings configured in the document.
+    fn parse_midi<'a>(&mut self, contents: &'a str) -> Result<Vec<MidiBinding>, Self::Error>;
+
+    /// Parses the document's metadata (e.g. it's title).
+    fn parse_meta<'a>(&mut self, contents: &'a str) -> Result<Metadata, Self::Error>;
+
+    /// Reprints a document in the parser's canonical layout, so diffs between versions of it stay
+    /// readable.
+    /// 
+    /// Comments don't get preserved.
+    fn format<'a>(&mut self, contents: &'a str) -> Result<String, Self::Error>;
+
+    /// Checks whether a document contains comments, which [`Parser::format`] would remove.
+    fn has_comments(&self, contents: &str) -> bool;
+
+    /// Merges documents into one, keeping the order of their slides.
+    /// 
+    /// `documents` are the contents and paths of the documents. Paths in them are kept as they
+    /// are, as they get resolved against the working directory when loading the merged document
+    /// just like when loading the original ones. Fonts registered by multiple documents only get
+    /// registered once. With `dividers`, a section divider titled after each document gets
+    /// inserted before it's slides.
+    fn merge<'a>(&mut self, documents: &[(&'a str, &Path)], dividers: bool) -> Result<String, Self::Error>;
+
+    /// Lists the remote URLs and the code a document references, which load or run something
+    /// from outside of the document's own files.
+    /// 
+    /// Code gets listed with the prefix [`LUA_PREFIX`] and it's whitespace collapsed. Lua
+    /// expressions can't be told apart from math expressions before parsing them, so only Lua
+    /// scripts (e.g. of MIDI bindings) get listed. Files included by the document get searched as
+    /// well.
+    fn external_resources<'a>(&mut self, contents: &'a str) -> Result<Vec<String>, Self::Error>;
 
     fn handle_error(&self, err: Self::Error);
 }
 
 #[derive(Debug)]
 pub struct SlideData {
-    pub background: Box<dyn Renderable>,
-    pub content: HashMap<u8, Vec<Box<dyn Renderable>>>
+    /// The slide's background object.
+    /// 
+    /// [`None`] if the slide inherits the background of the previous slide.
+    pub background: Option<Box<dyn Renderable>>,
+    pub content: HashMap<i16, Vec<Box<dyn Renderable>>>,
+    pub narration: Option<Narration>,
+    /// The aspect ratio of the slide's safe area, if it has one.
+    pub safe_area: Option<f64>,
+    /// The planned duration of the slide in seconds, if it has one.
+    pub duration: Option<f64>,
+    /// The title of the slide, if it has one.
+    pub title: Option<String>,
+    /// The name the slide can be jumped to by, if it has one.
+    pub bookmark: Option<String>,
+    /// How the presentation switches to the slide, if it differs from the default.
+    pub transition: Option<Transition>,
+    /// The speaker notes of the slide, if it has any.
+    pub notes: Option<String>,
+    /// A hash of the slide's content, which stays the same across runs as long as the slide
+    /// doesn't change (see [`crate::review`]).
+    pub content_hash: u64
+}
+
+/// Information about a document as a whole.
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    /// The title of the document, shown in the window's title bar.
+    pub title: Option<String>,
+    /// The colors bundled components get inserted with.
+    pub palette: Palette,
+    /// The font bundled components get inserted with, if it differs from the document's first
+    /// font.
+    pub font: Option<String>,
+    /// The seed of random numbers in expressions, so documents can choose between different
+    /// "random" layouts.
+    pub seed: u64,
+    /// The severities of lint rules, overriding the ones in the user configuration.
+    pub lint: HashMap<String, Severity>,
+    /// The conventions numbers, dates and times in placeholders get written with.
+    pub locale: Locale
 }
 
 pub use json::JSONParser;
 
-/// Automatically chooses a parser based on the supplied filename and returns it.
+/// Parses the raw bytes of a HJSON document into slides.
+/// 
+/// Malformed input is meant to result in an error, but the parser and the deserialized objects
+/// may still panic on some of it. The fuzzer of the `fuzz` command runs this behind
+/// [`std::panic::catch_unwind`] and keeps the inputs that panic. Needs the fonts to be
+/// initialized.
+pub fn parse_document_bytes(bytes: &[u8]) -> anyhow::Result<Vec<SlideData>> {
+    let contents = std::str::from_utf8(bytes)?;
+    JSONParser.parse(contents).map_err(|e| anyhow::anyhow!("{e}"))
+}
+
+/// A parser of any registered format, with it's error type erased.
+pub type AnyParser = Box<dyn Parser<Error = ParserError>>;
+
+/// Creates a new instance of a registered parser.
+type ParserConstructor = Arc<dyn Fn() -> AnyParser + Send + Sync>;
+
+/// The registered parsers by the file extensions (in lowercase) of the formats they parse.
+static PARSERS: Lazy<RwLock<HashMap<String, ParserConstructor>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// An error of any registered parser.
 /// 
-/// Returns [`None`] if no suitable parser was found.
-pub fn get_parser<P: AsRef<Path>>(file: P) -> Option<impl Parser> {
-    match file.as_ref().extension()?.to_string_lossy().as_ref() {
-        "hjson" | "json" | "json5" => Some(JSONParser),
-        _ => None
+/// Keeps the parser's own error, so the parser can still handle it (see [`Parser::handle_error`]).
+#[derive(Debug)]
+pub struct ParserError {
+    message: String,
+    original: Option<Box<dyn Any + Send + Sync>>
+}
+
+impl Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
     }
+}
+
+impl std::error::Error for ParserError {}
+
+impl serde::de::Error for ParserError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ParserError { message: msg.to_string(), original: None }
+    }
+}
+
+impl ParserError {
+    fn wrap<E: serde::de::Error + Send + Sync + 'static>(error: E) -> Self {
+        ParserError { message: error.to_string(), original: Some(Box::new(error)) }
+    }
+}
+
+/// Erases the error type of a parser, so parsers of all formats can be registered together.
+struct ErasedParser<P>(P);
+
+impl<P: Parser> Parser for ErasedParser<P> where P::Error: Send + Sync + 'static {
+    type Error = ParserError;
+
+    fn parse<'a>(&mut self, contents: &'a str) -> Result<Vec<SlideData>, Self::Error> {
+        self.0.parse(contents).map_err(ParserError::wrap)
+    }
+
+    fn parse_fonts<'a>(&mut self, contents: &'a str) -> Result<IndexMap<String, (String, String)>, Self::Error> {
+        self.0.parse_fonts(contents).map_err(ParserError::wrap)
+    }
+
+    fn parse_captions<'a>(&mut self, contents: &'a str) -> Result<Option<CaptionTrack>, Self::Error> {
+        self.0.parse_captions(contents).map_err(ParserError::wrap)
+    }
+
+    fn parse_sounds<'a>(&mut self, contents: &'a str) -> Result<SoundEffects, Self::Error> {
+        self.0.parse_sounds(contents).map_err(ParserError::wrap)
+    }
+
+    fn parse_midi<'a>(&mut self, contents: &'a str) -> Result<Vec<MidiBinding>, Self::Error> {
+        self.0.parse_midi(contents).map_err(ParserError::wrap)
+    }
+
+    fn parse_meta<'a>(&mut self, contents: &'a str) -> Result<Metadata, Self::Error> {
+        self.0.parse_meta(contents).map_err(ParserError::wrap)
+    }
+
+    fn format<'a>(&mut self, contents: &'a str) -> Result<String, Self::Error> {
+        self.0.format(contents).map_err(ParserError::wrap)
+    }
+
+    fn has_comments(&self, contents: &str) -> bool {
+        self.0.has_comments(contents)
+    }
+
+    fn merge<'a>(&mut self, documents: &[(&'a str, &Path)], dividers: bool) -> Result<String, Self::Error> {
+        self.0.merge(documents, dividers).map_err(ParserError::wrap)
+    }
+
+    fn external_resources<'a>(&mut self, contents: &'a str) -> Result<Vec<String>, Self::Error> {
+        self.0.external_resources(contents).map_err(ParserError::wrap)
+    }
+
+    fn handle_error(&self, err: Self::Error) {
+        let ParserError { message, original } = err;
+        match original.and_then(|original| original.downcast::<P::Error>().ok()) {
+            Some(original) => self.0.handle_error(*original),
+            None => panic!("\nParsing Error:\n\t{}\n", message.replace("\n", "\n\t"))
+        }
+    }
+}
+
+/// Registers a parser for documents with the given file extensions (without the dot), replacing
+/// the parsers registered for them before.
+/// 
+/// `constructor` creates a new instance of the parser whenever a document gets parsed.
+pub fn register_parser<P, F>(extensions: &[&str], constructor: F)
+where P: Parser + 'static, P::Error: Send + Sync + 'static, F: Fn() -> P + Send + Sync + 'static {
+    let constructor: ParserConstructor = Arc::new(move || Box::new(ErasedParser(constructor())) as AnyParser);
+
+    let mut parsers = PARSERS.write().unwrap();
+    for extension in extensions {
+        parsers.insert(extension.to_lowercase(), constructor.clone());
+    }
+}
+
+/// Registers the parsers of the formats supported out of the box.
+/// 
+/// Needs to be called before any documents get parsed.
+pub fn register_builtin_parsers() {
+    register_parser(&["hjson", "json", "json5"], || JSONParser);
+}
+
+/// Returns the file extensions of all registered formats, sorted alphabetically.
+pub fn registered_formats() -> Vec<String> {
+    let mut formats = PARSERS.read().unwrap().keys().cloned().collect::<Vec<_>>();
+    formats.sort();
+    formats
+}
+
+/// Chooses a registered parser based on the supplied filename and returns it.
+/// 
+/// The format set in the user configuration (`document_format`, or the `--input-format` flag)
+/// takes precedence over the file's extension. Returns [`None`] if no suitable parser was found.
+pub fn get_parser<P: AsRef<Path>>(file: P) -> Option<AnyParser> {
+    let format = match crate::CONFIG.get().and_then(|config| config.document_format.clone()) {
+        Some(format) => format,
+        None => file.as_ref().extension()?.to_string_lossy().to_lowercase()
+    };
+
+    let constructor = PARSERS.read().unwrap().get(&format)?.clone();
+    Some(constructor())
 }
\ No newline at end of file
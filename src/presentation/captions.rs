@@ -0,0 +1,60 @@
+use opengl_graphics::GlGraphics;
+use graphics::Context;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use super::renderable::Renderable;
+
+/// A single timed caption of a slide.
+#[derive(Debug)]
+pub struct Caption {
+    /// The index of the slide the caption belongs to.
+    pub slide: usize,
+    /// The time since the slide was switched to at which the caption appears.
+    pub start: f64,
+    /// The time since the slide was switched to at which the caption disappears.
+    pub end: f64,
+    /// The object rendering the caption's text.
+    text: Box<dyn Renderable>
+}
+
+/// A document-wide track of timed captions, rendered as a strip at the bottom of the screen.
+#[derive(Debug)]
+pub struct CaptionTrack {
+    captions: Vec<Caption>,
+    /// The background strip drawn behind any visible caption.
+    strip: Box<dyn Renderable>
+}
+
+impl CaptionTrack {
+    /// Creates an empty caption track using the supplied object as the background strip.
+    pub fn new(strip: Box<dyn Renderable>) -> CaptionTrack {
+        CaptionTrack { captions: Vec::new(), strip }
+    }
+
+    /// Adds a caption to the track.
+    pub fn add(&mut self, slide: usize, start: f64, end: f64, text: Box<dyn Renderable>) {
+        self.captions.push(Caption { slide, start, end, text });
+    }
+
+    /// Renders all captions of the given slide that are visible at the given time.
+    pub fn render(&self, slide: usize, time: f64, context: Context, opengl: &mut GlGraphics) {
+        let mut visible = self.captions.iter()
+            .filter(|c| c.slide == slide && c.start <= time && time < c.end)
+            .peekable();
+
+        if visible.peek().is_none() {
+            return
+        }
+
+        if let Err(e) = self.strip.render(time, context, opengl) {
+            log_err!("Error rendering caption strip: {e}");
+        }
+        for caption in visible {
+            if let Err(e) = caption.text.render(time, context, opengl) {
+                log_err!("Error rendering caption of slide #{}: {e}", caption.slide);
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Light dynamic data for expressions and placeholders, e.g. for presentations running on lobby
+//! displays.
+//!
+//! All providers are opt-in and configured in the `data` section of the user configuration:
+//! - `sunrise` and `sunset` are the local times of today's sunrise and sunset in hours since
+//!   midnight (e.g. `6.5` for 6:30) and `daylight` is 1 between them, otherwise 0. They need a
+//!   `location` and are NaN without one (or during polar day and night).
+//! - `data_NAME` is the value named `NAME` in the configuration, fetched from a JSON document
+//!   and refreshed periodically in the background.
+//!
+//! Expressions can use them as variables, text as placeholders like `{{sunset}}` or
+//! `{{data_temperature}}`.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use chrono::{ Datelike, Timelike };
+use once_cell::sync::Lazy;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::config::DataSource;
+
+/// The names of the sun related variables.
+pub const SUN_VARIABLES: [&'static str; 3] = ["sunrise", "sunset", "daylight"];
+
+/// The prefix of the variables of configured data values.
+pub const VALUE_PREFIX: &'static str = "data_";
+
+/// How long connecting to and reading from a data source may take.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The HTTP client used for fetching documents, shared so connections can be reused.
+static AGENT: Lazy<ureq::Agent> = Lazy::new(|| ureq::AgentBuilder::new().timeout(FETCH_TIMEOUT).build());
+
+/// The latest fetched data values by their name.
+static VALUES: Lazy<RwLock<HashMap<String, serde_json::Value>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// The names of all configured data values, sorted so each one has a fixed index.
+static NAMES: Lazy<Vec<String>> = Lazy::new(|| {
+    let mut names = crate::CONFIG.get().map(|config| config.data.values.keys().cloned().collect::<Vec<_>>()).unwrap_or_default();
+    names.sort();
+    names
+});
+
+/// Starts fetching all configured data values in the background.
+///
+/// Every value gets it's own thread, which refreshes it in the value's interval until the
+/// application exits.
+pub fn start() {
+    let config = crate::CONFIG.get().unwrap();
+
+    for (name, source) in config.data.values.iter() {
+        // Nothing gets fetched over the network in safe mode, so the value stays undefined
+        if config.safe_mode && source.url.contains("://") {
+            log_warn!("Not fetching the data value '{name}' from {} in safe mode", source.url);
+            continue
+        }
+
+        let (name, source) = (name.clone(), source.clone());
+        std::thread::spawn(move || loop {
+            match fetch(&source) {
+                Ok(value) => { VALUES.write().unwrap().insert(name.clone(), value); },
+                Err(e) => log_warn!("Fetching the data value '{name}' from {} failed: {e}", source.url)
+            }
+            std::thread::sleep(Duration::from_secs_f64(source.interval.max(1.0)));
+        });
+    }
+}
+
+/// Returns the index of the data value with the given name, used for accessing it from
+/// expressions.
+pub fn value_index(name: &str) -> Option<usize> {
+    NAMES.iter().position(|n| n == name)
+}
+
+/// Returns the data value with the given index as a number.
+///
+/// NaN if it wasn't fetched yet or isn't a number (strings containing numbers get parsed).
+pub fn number(index: usize) -> f64 {
+    let value = NAMES.get(index).and_then(|name| VALUES.read().unwrap().get(name).cloned());
+    match value {
+        Some(serde_json::Value::Number(n)) => n.as_f64().unwrap_or(f64::NAN),
+        Some(serde_json::Value::String(s)) => s.trim().parse().unwrap_or(f64::NAN),
+        Some(serde_json::Value::Bool(b)) => b as u8 as f64,
+        _ => f64::NAN
+    }
+}
+
+/// Returns the text shown for a placeholder of a data provider, or [`None`] if the placeholder
+/// doesn't belong to one.
+pub fn placeholder_text(name: &str) -> Option<String> {
+    if let Some(name) = name.strip_prefix(VALUE_PREFIX) {
+        value_index(name)?;
+        return Some(match VALUES.read().unwrap().get(name) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+            None => "…".to_owned()
+        })
+    }
+
+    let hours = match name {
+        "sunrise" => sun_times().map(|times| times.0),
+        "sunset" => sun_times().map(|times| times.1),
+        _ => return None
+    };
+
+    // The sun placeholders are only defined with a location
+    crate::CONFIG.get()?.data.location?;
+    Some(match hours {
+        Some(hours) => {
+            let minutes = (hours * 60.0).round() as u32;
+            format!("{:02}:{:02}", minutes / 60 % 24, minutes % 60)
+        },
+        None => "--:--".to_owned()
+    })
+}
+
+/// Returns the value of the sun related variable with the given index in [`SUN_VARIABLES`].
+pub fn sun_variable(index: usize) -> f64 {
+    let (sunrise, sunset) = match sun_times() {
+        Some(times) => times,
+        None => return f64::NAN
+    };
+
+    match index {
+        0 => sunrise,
+        1 => sunset,
+        _ => {
+            let now = chrono::Local::now();
+            let hours = now.hour() as f64 + now.minute() as f64 / 60.0 + now.second() as f64 / 3600.0;
+            (sunrise <= hours && hours < sunset) as u8 as f64
+        }
+    }
+}
+
+/// Returns today's local sunrise and sunset at the configured location in hours since midnight.
+///
+/// Returns [`None`] without a location or when the sun doesn't rise or set today.
+fn sun_times() -> Option<(f64, f64)> {
+    let (latitude, longitude) = crate::CONFIG.get()?.data.location?;
+    let now = chrono::Local::now();
+    let utc_offset = now.offset().local_minus_utc() as f64 / 3600.0;
+
+    // NOAA's approximation of the equation of time and the sun's declination
+    let gamma = 2.0 * std::f64::consts::PI / 365.0 * (now.ordinal() as f64 - 0.5);
+    let equation_of_time = 229.18 * (0.000075 + 0.001868 * gamma.cos() - 0.032077 * gamma.sin()
+        - 0.014615 * (2.0 * gamma).cos() - 0.040849 * (2.0 * gamma).sin());
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos() + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos() + 0.00148 * (3.0 * gamma).sin();
+
+    // The hour angle of the sun at sunrise, accounting for atmospheric refraction
+    let latitude = latitude.to_radians();
+    let cos_hour_angle = 90.833_f64.to_radians().cos() / (latitude.cos() * declination.cos()) - latitude.tan() * declination.tan();
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None
+    }
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+
+    let to_local_hours = |utc_minutes: f64| (utc_minutes / 60.0 + utc_offset).rem_euclid(24.0);
+    Some((
+        to_local_hours(720.0 - 4.0 * (longitude + hour_angle) - equation_of_time),
+        to_local_hours(720.0 - 4.0 * (longitude - hour_angle) - equation_of_time)
+    ))
+}
+
+/// Fetches a data value from it's source and selects the configured part of it.
+fn fetch(source: &DataSource) -> anyhow::Result<serde_json::Value> {
+    let document: serde_json::Value = serde_json::from_str(&fetch_text(&source.url)?)?;
+    document.pointer(&source.pointer).cloned()
+        .ok_or(anyhow::anyhow!("the document doesn't contain '{}'", source.pointer))
+}
+
+/// Reads a document from a `http://` or `https://` URL, or from a local file if it isn't one.
+///
+/// Blocks for up to [`FETCH_TIMEOUT`], so it shouldn't be called on the render thread.
+pub fn fetch_text(url: &str) -> anyhow::Result<String> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Ok(std::fs::read_to_string(url)?)
+    }
+
+    // Redirects get followed and responses with an error status are returned as errors
+    let response = AGENT.get(url).call()?;
+    Ok(response.into_string()?)
+}
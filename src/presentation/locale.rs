@@ -0,0 +1,109 @@
+//! Regional conventions for numbers, dates and times shown by text placeholders, chosen by the
+//! `locale`-field of a document's metadata.
+
+use std::sync::RwLock;
+
+/// How numbers, dates and times get written in a region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    /// The character in front of the decimals of numbers.
+    pub decimal_separator: char,
+    /// The character between groups of thousands, used by the `,` format flag.
+    pub thousands_separator: char,
+    /// The chrono format string of the `date` placeholder.
+    pub date_format: &'static str,
+    /// The chrono format string of the `time` placeholder.
+    pub time_format: &'static str
+}
+
+impl Locale {
+    /// The conventions used by documents without a locale: ISO 8601 dates, 24-hour times and
+    /// numbers with a decimal point.
+    pub const DEFAULT: Locale = Locale::new('.', ',', "%Y-%m-%d", "%H:%M");
+
+    const fn new(decimal_separator: char, thousands_separator: char, date_format: &'static str, time_format: &'static str) -> Locale {
+        Locale { decimal_separator, thousands_separator, date_format, time_format }
+    }
+
+    /// Returns the locale with the given code (e.g. `de`, `en-US` or `pt_BR`).
+    ///
+    /// Codes with a region that isn't known fall back to the locale of their language.
+    pub fn from_code(code: &str) -> Option<Locale> {
+        let code = code.trim().replace('_', "-").to_lowercase();
+        let language = code.split('-').next().unwrap_or_default();
+
+        LOCALES.iter().find(|(c, _)| *c == code)
+            .or_else(|| LOCALES.iter().find(|(c, _)| *c == language))
+            .map(|(_, locale)| *locale)
+    }
+
+    /// Writes a number formatted with a decimal point and comma separated thousands using this
+    /// locale's separators.
+    /// 
+    /// Only the separators of the number itself get replaced (a decimal point followed by a digit
+    /// and commas between digits), so any other characters around it (e.g. a placeholder's fill
+    /// characters) stay as they are.
+    pub fn localize_number(&self, number: &str) -> String {
+        let chars = number.chars().collect::<Vec<_>>();
+        let is_digit = |i: Option<usize>| i.and_then(|i| chars.get(i)).is_some_and(|c| c.is_ascii_digit());
+
+        chars.iter().enumerate().map(|(i, c)| match c {
+            '.' if is_digit(Some(i + 1)) => self.decimal_separator,
+            ',' if is_digit(i.checked_sub(1)) && is_digit(Some(i + 1)) => self.thousands_separator,
+            c => *c
+        }).collect()
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::DEFAULT
+    }
+}
+
+/// All known locales by their lowercase code, languages without a region use their most common
+/// conventions.
+const LOCALES: &[(&str, Locale)] = &[
+    ("en", Locale::new('.', ',', "%m/%d/%Y", "%I:%M %p")),
+    ("en-us", Locale::new('.', ',', "%m/%d/%Y", "%I:%M %p")),
+    ("en-gb", Locale::new('.', ',', "%d/%m/%Y", "%H:%M")),
+    ("en-ie", Locale::new('.', ',', "%d/%m/%Y", "%H:%M")),
+    ("en-au", Locale::new('.', ',', "%d/%m/%Y", "%I:%M %p")),
+    ("en-ca", Locale::new('.', ',', "%Y-%m-%d", "%I:%M %p")),
+    ("de", Locale::new(',', '.', "%d.%m.%Y", "%H:%M")),
+    ("de-ch", Locale::new('.', '\'', "%d.%m.%Y", "%H:%M")),
+    ("fr", Locale::new(',', ' ', "%d/%m/%Y", "%H:%M")),
+    ("fr-ca", Locale::new(',', ' ', "%Y-%m-%d", "%H:%M")),
+    ("fr-ch", Locale::new('.', '\'', "%d.%m.%Y", "%H:%M")),
+    ("es", Locale::new(',', '.', "%d/%m/%Y", "%H:%M")),
+    ("es-mx", Locale::new('.', ',', "%d/%m/%Y", "%H:%M")),
+    ("it", Locale::new(',', '.', "%d/%m/%Y", "%H:%M")),
+    ("nl", Locale::new(',', '.', "%d-%m-%Y", "%H:%M")),
+    ("pt", Locale::new(',', ' ', "%d/%m/%Y", "%H:%M")),
+    ("pt-br", Locale::new(',', '.', "%d/%m/%Y", "%H:%M")),
+    ("da", Locale::new(',', '.', "%d.%m.%Y", "%H.%M")),
+    ("sv", Locale::new(',', ' ', "%Y-%m-%d", "%H:%M")),
+    ("nb", Locale::new(',', ' ', "%d.%m.%Y", "%H:%M")),
+    ("fi", Locale::new(',', ' ', "%d.%m.%Y", "%H.%M")),
+    ("pl", Locale::new(',', ' ', "%d.%m.%Y", "%H:%M")),
+    ("cs", Locale::new(',', ' ', "%d. %m. %Y", "%H:%M")),
+    ("ru", Locale::new(',', ' ', "%d.%m.%Y", "%H:%M")),
+    ("uk", Locale::new(',', ' ', "%d.%m.%Y", "%H:%M")),
+    ("tr", Locale::new(',', '.', "%d.%m.%Y", "%H:%M")),
+    ("ja", Locale::new('.', ',', "%Y/%m/%d", "%H:%M")),
+    ("zh", Locale::new('.', ',', "%Y/%m/%d", "%H:%M")),
+    ("ko", Locale::new('.', ',', "%Y. %m. %d.", "%H:%M")),
+];
+
+/// The locale of the presentation that's currently being rendered.
+static CURRENT: RwLock<Locale> = RwLock::new(Locale::DEFAULT);
+
+/// Sets the locale used by placeholders that get formatted from now on.
+pub fn set_current(locale: Locale) {
+    *CURRENT.write().unwrap() = locale;
+}
+
+/// Returns the locale of the presentation that's currently being rendered.
+pub fn current() -> Locale {
+    *CURRENT.read().unwrap()
+}
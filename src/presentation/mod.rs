@@ -1,26 +1,192 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
 use opengl_graphics::GlGraphics;
 use graphics::Context;
 
+use crate::components::Style;
+use crate::lint::Severity;
+
 pub mod slide;
+pub mod captions;
 pub mod renderable;
 pub mod util;
+pub mod selection;
+pub mod data;
+pub mod locale;
+pub mod placeholders;
 
-pub use slide::Slide;
+pub use slide::{ Slide, Narration, Transition, render_error_count, clear_logged_render_errors };
+pub use captions::CaptionTrack;
 pub use renderable::*;
 
 #[allow(unused)]
 use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
 
+/// A line of text matching a search query.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// The index of the slide containing the line.
+    pub slide: usize,
+    /// The matching line of text.
+    pub line: String
+}
+
+/// The slide time at which thumbnails get rendered.
+/// 
+/// Most entry animations have finished by then, so the thumbnail shows the slide's settled
+/// content.
+pub const THUMBNAIL_TIME: f64 = 5.0;
+
+/// The code of the language whose text variants get shown.
+static LANGUAGE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Sets the language whose text variants get shown.
+/// 
+/// Text objects without a variant in that language (or when it's [`None`]) show their first
+/// variant instead.
+pub fn set_language(language: Option<String>) {
+    *LANGUAGE.write().unwrap() = language;
+}
+
+/// Returns the language whose text variants get shown.
+pub fn language() -> Option<String> {
+    LANGUAGE.read().unwrap().clone()
+}
+
+/// The ids of all objects that are currently hidden.
+static HIDDEN_OBJECTS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Shows or hides all objects with the given id, across all slides.
+pub fn set_object_visible(id: &str, visible: bool) {
+    let mut hidden = HIDDEN_OBJECTS.write().unwrap();
+    hidden.retain(|h| h != id);
+    if !visible {
+        hidden.push(id.to_owned());
+    }
+}
+
+/// Toggles the visibility of all objects with the given id, across all slides.
+pub fn toggle_object(id: &str) {
+    set_object_visible(id, !object_visible(id));
+}
+
+/// Returns whether objects with the given id are currently shown.
+pub fn object_visible(id: &str) -> bool {
+    !HIDDEN_OBJECTS.read().unwrap().iter().any(|h| h == id)
+}
+
 /// Contains all data and state related to rendering the presentation.
 pub struct Presentation {
     slides: Vec<slide::Slide>,
-    current_slide: usize
+    /// The title of the document, if it has one.
+    title: Option<String>,
+    /// The palette and font bundled components get inserted into the document with.
+    style: Style,
+    /// The document's seed for random numbers in expressions.
+    seed: u64,
+    /// The severities of lint rules chosen by the document, by the rules' names.
+    lint_severities: HashMap<String, Severity>,
+    /// The conventions numbers, dates and times in placeholders get written with.
+    locale: locale::Locale,
+    current_slide: usize,
+    /// Whether the slides' backgrounds should be skipped and the screen cleared with a
+    /// transparent color instead.
+    transparent: bool,
+    /// The document's caption track, if it has one.
+    captions: Option<CaptionTrack>,
+    /// Whether the captions are currently shown.
+    show_captions: bool,
+    /// The time the current slide's background has already been shown on previous slides that
+    /// share it.
+    background_time_offset: f64,
+    /// The slide time of the last rendered frame.
+    last_time: f64,
+    /// The time spent on previous slides since the presentation was started.
+    elapsed_time: f64,
+    /// The transition used for slides that don't specify their own.
+    default_transition: Transition,
+    /// The index of the previously shown slide and the time it's background was shown for, while
+    /// it's background gets faded out.
+    fade_from: Option<(usize, f64)>
 }
 
 impl Presentation {
     /// Creates a new Presentation.
     pub fn new() -> Presentation {
-        Presentation { slides: Vec::new(), current_slide: 0 }
+        Presentation { slides: Vec::new(), title: None, style: Style::default(), seed: 0, lint_severities: HashMap::new(), locale: locale::Locale::DEFAULT, current_slide: 0, transparent: false, captions: None, show_captions: true, background_time_offset: 0.0, last_time: 0.0, elapsed_time: 0.0, default_transition: Transition::Cut, fade_from: None }
+    }
+
+    /// Sets whether the presentation gets rendered with a transparent background.
+    /// 
+    /// When enabled, the backgrounds of all slides are skipped and the screen gets cleared with a
+    /// fully transparent color, so only the foreground objects remain visible.
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.transparent = transparent;
+    }
+
+    /// Sets the transition used for slides that don't specify their own.
+    /// 
+    /// Fades only apply to slides whose backgrounds are plain colors covering the whole screen,
+    /// all other slides get switched to instantly.
+    pub fn set_default_transition(&mut self, transition: Transition) {
+        self.default_transition = transition;
+    }
+
+    /// Sets the caption track of the presentation.
+    pub fn set_captions(&mut self, captions: Option<CaptionTrack>) {
+        self.captions = captions;
+    }
+
+    /// Sets the title of the document.
+    pub fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+    }
+
+    /// Returns the title of the document, if it has one.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Sets the seed used for random numbers in expressions, together with the slides' indices.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Returns the seed used for random numbers in expressions.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Sets the severities of lint rules chosen by the document, overriding the user
+    /// configuration.
+    pub fn set_lint_severities(&mut self, severities: HashMap<String, Severity>) {
+        self.lint_severities = severities;
+    }
+
+    /// Sets the conventions numbers, dates and times in placeholders get written with.
+    pub fn set_locale(&mut self, locale: locale::Locale) {
+        self.locale = locale;
+    }
+
+    /// Returns the severities of lint rules chosen by the document.
+    pub fn lint_severities(&self) -> &HashMap<String, Severity> {
+        &self.lint_severities
+    }
+
+    /// Sets the palette and font bundled components get inserted with.
+    pub fn set_style(&mut self, style: Style) {
+        self.style = style;
+    }
+
+    /// Returns the palette and font bundled components get inserted with.
+    pub fn style(&self) -> &Style {
+        &self.style
+    }
+
+    /// Shows or hides the captions.
+    pub fn toggle_captions(&mut self) {
+        self.show_captions = !self.show_captions;
     }
 
     /// Adds a new slide.
@@ -28,10 +194,41 @@ impl Presentation {
         self.slides.push(slide);
     }
 
+    /// Inserts a slide at the given index, moving all slides after it back by one.
+    /// 
+    /// The slide gets added to the end if the index is out of bounds.
+    pub fn insert_slide(&mut self, index: usize, slide: slide::Slide) {
+        let index = index.min(self.slides.len());
+        // Keep showing the same slide
+        if index <= self.current_slide && !self.slides.is_empty() {
+            self.current_slide += 1;
+        }
+        self.slides.insert(index, slide);
+    }
+
+    /// Returns the slide with the given index, if it exists.
+    pub fn slide(&self, index: usize) -> Option<&slide::Slide> {
+        self.slides.get(index)
+    }
+
+    /// Returns a mutable reference to the slide with the given index, if it exists.
+    /// 
+    /// A slide that panicked while rendering gets rendered again, as it may have been fixed.
+    pub fn slide_mut(&mut self, index: usize) -> Option<&mut slide::Slide> {
+        let slide = self.slides.get_mut(index)?;
+        slide.set_error(None);
+        Some(slide)
+    }
+
+    /// Returns the currently shown slide.
+    pub fn current_slide(&self) -> Option<&slide::Slide> {
+        self.slides.get(self.current_slide)
+    }
+
     /// Changes to the next slide or wraps around to the first one if you're already on the last
     /// slide.
     pub fn next_slide(&mut self) {
-        self.current_slide = (self.current_slide + 1) % self.slides.len();
+        self.switch_to((self.current_slide + 1) % self.slides.len());
     }
 
     /// Changes to the previous slide or wraps around to the last one if you're already on the
@@ -39,19 +236,246 @@ impl Presentation {
     pub fn previous_slide(&mut self) {
         let mut new = self.current_slide as isize - 1;
         if new<0 { new = self.slides.len() as isize-1 }
-        self.current_slide = new as usize;
+        self.switch_to(new as usize);
+    }
+
+    /// Switches to another slide, keeping track of how long a shared background has been shown.
+    fn switch_to(&mut self, index: usize) {
+        let shared = match (self.slides.get(self.current_slide), self.slides.get(index)) {
+            (Some(old), Some(new)) => index != self.current_slide && new.shares_background_with(old),
+            _ => false
+        };
+
+        // Compare the time spent on the slide with it's planned duration, for rehearsing
+        if let Some(duration) = self.current_slide().and_then(|slide| slide.duration()) {
+            let difference = self.last_time - duration;
+            log_info!("Pacing: spent {:.1}s on slide #{} (planned {:.1}s, {}{:.1}s)", self.last_time, self.current_slide, duration, if difference >= 0.0 { "+" } else { "" }, difference);
+        }
+
+        self.fade_from = match shared || index == self.current_slide {
+            true => None,
+            false => Some((self.current_slide, self.background_time_offset + self.last_time))
+        };
+
+        self.background_time_offset = match shared {
+            true => self.background_time_offset + self.last_time,
+            false => 0.0
+        };
+        self.elapsed_time += self.last_time;
+        self.last_time = 0.0;
+        self.current_slide = index;
     }
     
+    /// Returns the amount of slides.
+    pub fn slide_count(&self) -> usize {
+        self.slides.len()
+    }
+
+    /// Returns the amount of slides that are part of the document, leaving out the slides added
+    /// by the application after them (like the 'End of presentation' slide).
+    /// 
+    /// Exports and analyses of the document only use the slides up to this index.
+    pub fn document_slide_count(&self) -> usize {
+        self.slides.iter().rposition(|slide| !slide.is_generated()).map(|i| i + 1).unwrap_or(0)
+    }
+
+    /// Returns the amount of words in all text of the document's slides.
+    pub fn word_count(&self) -> usize {
+        self.slides.iter().filter(|slide| !slide.is_generated()).map(|slide| slide.word_count()).sum()
+    }
+
+    /// Estimates how long presenting takes in seconds, using the slides' planned durations where
+    /// they have one and their estimated speaking time otherwise.
+    pub fn estimated_duration(&self, words_per_minute: f64) -> f64 {
+        self.slides.iter()
+            .filter(|slide| !slide.is_generated())
+            .map(|slide| slide.duration().unwrap_or(slide.estimated_speaking_time(words_per_minute)))
+            .sum()
+    }
+
+    /// Returns the index of the currently shown slide.
+    pub fn current_slide_index(&self) -> usize {
+        self.current_slide
+    }
+
+    /// Returns the bookmarks of all slides in the order of the slides.
+    pub fn bookmarks(&self) -> Vec<&str> {
+        self.slides.iter().filter_map(|slide| slide.bookmark()).collect()
+    }
+
+    /// Switches to the first slide with the given bookmark.
+    /// 
+    /// Returns whether such a slide exists.
+    pub fn go_to_bookmark(&mut self, bookmark: &str) -> bool {
+        match self.slides.iter().position(|slide| slide.bookmark() == Some(bookmark)) {
+            Some(index) => {
+                self.switch_to(index);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Switches to the slide with the given index.
+    /// 
+    /// Does nothing if the slide doesn't exist.
+    pub fn go_to_slide(&mut self, index: usize) {
+        if index < self.slides.len() {
+            self.switch_to(index);
+        }
+    }
+
+    /// Returns the safe area of the current slide inside a screen of the given size as
+    /// `[x, y, width, height]`, or [`None`] if the slide doesn't have one.
+    pub fn current_safe_area(&self, width: f64, height: f64) -> Option<[f64; 4]> {
+        let ratio = self.current_slide()?.safe_area()?;
+        Some(util::fit_safe_area(ratio, width, height))
+    }
+
+    /// Returns the codes of all languages used in the presentation, in order of their first
+    /// appearance.
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = Vec::new();
+        for language in self.slides.iter().flat_map(|slide| slide.languages()) {
+            if !languages.iter().any(|l| l == language) {
+                languages.push(language.to_owned());
+            }
+        }
+        languages
+    }
+
+    /// Switches to the next language used in the presentation.
+    pub fn next_language(&self) {
+        let languages = self.languages();
+        if languages.is_empty() {
+            return
+        }
+
+        let next = match language().and_then(|current| languages.iter().position(|l| *l == current)) {
+            Some(index) => (index + 1) % languages.len(),
+            // Without an active language, the first variant is shown, so skip to the second one
+            None => 1 % languages.len()
+        };
+        log_info!("Switched language to {}", languages[next]);
+        set_language(Some(languages[next].clone()));
+    }
+
+    /// Searches the text of all slides for the given query (ignoring case).
+    /// 
+    /// Returns every matching line of text, ordered by slide.
+    pub fn search(&self, query: &str) -> Vec<SearchResult> {
+        let query = query.to_lowercase();
+        if query.is_empty() {
+            return Vec::new()
+        }
+
+        self.slides.iter().enumerate().flat_map(|(index, slide)| {
+            slide.text_lines()
+                .filter(|line| line.to_lowercase().contains(&query))
+                .map(move |line| SearchResult { slide: index, line: line.clone() })
+                .collect::<Vec<_>>()
+        }).collect()
+    }
+
+    /// Renders a slide into an image of the given size.
+    /// 
+    /// Gets used for previews of slides, e.g. in overviews or the editor. The slide gets rendered
+    /// as it looks at [`THUMBNAIL_TIME`]. Returns [`None`] if the slide doesn't exist.
+    /// 
+    /// Must not be called while already drawing with the supplied [`GlGraphics`] instance.
+    pub fn render_thumbnail(&self, slide: usize, size: (u32, u32), opengl: &mut GlGraphics) -> Option<image::RgbaImage> {
+        util::set_random_seed(slide, self.seed);
+        locale::set_current(self.locale);
+        let slide = self.slides.get(slide)?;
+
+        util::set_safe_area(slide.safe_area());
+
+        Some(crate::render::offscreen::render_offscreen(opengl, size, |c, gl| {
+            slide.render(THUMBNAIL_TIME, THUMBNAIL_TIME, c, gl);
+        }))
+    }
+
+    /// Draws the bounding box, pivot point and z-index of every object on the current slide.
+    pub fn render_debug_outlines(&self, time: f64, context: Context, opengl: &mut GlGraphics) {
+        if let Some(slide) = self.current_slide() {
+            slide.render_debug_outlines(time, context, opengl);
+        }
+    }
+
+    /// Returns the background color while fading from the previous slide's background to the
+    /// current one's, if both are plain colors.
+    fn faded_background_color(&self, time: f64, view_size: [f64; 2]) -> Option<[f64; 4]> {
+        let (from, from_time) = self.fade_from?;
+        let duration = match self.current_slide()?.transition().unwrap_or(self.default_transition) {
+            Transition::Fade { duration } if time < duration => duration,
+            _ => return None
+        };
+
+        let old = self.slides.get(from)?.plain_background_color(from_time, view_size)?;
+        let new = self.current_slide()?.plain_background_color(self.background_time_offset + time, view_size)?;
+
+        let t = time / duration;
+        Some([0, 1, 2, 3].map(|i| old[i] + (new[i] - old[i]) * t))
+    }
+
     /// Renders this presentation.
     pub fn render(&mut self, time: f64, context: Context, opengl: &mut GlGraphics) {
-        match self.slides.get(self.current_slide) {
-            Some(slide) => {
-                slide.render(time, context, opengl);
+        self.last_time = time;
+
+        util::set_safe_area(self.current_slide().and_then(|slide| slide.safe_area()));
+        util::set_random_seed(self.current_slide, self.seed);
+        locale::set_current(self.locale);
+        util::set_presentation_time(self.elapsed_time + time);
+
+        selection::begin_frame();
+
+        if let Some(error) = self.current_slide().and_then(|slide| slide.error()) {
+            slide::render_error_card(self.current_slide + 1, error, context, opengl);
+            selection::end_frame();
+            return
+        }
+
+        // A slide that panics gets marked as broken, so the rest of the presentation keeps working
+        let background_color = match self.transparent {
+            true => None,
+            false => self.faded_background_color(time, context.get_view_size())
+        };
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| match self.slides.get(self.current_slide) {
+            Some(slide) if self.transparent => {
+                graphics::clear([0.0, 0.0, 0.0, 0.0], opengl);
+                slide.render_objects(time, context, opengl);
+                true
+            },
+            Some(slide) => match background_color {
+                Some(color) => { slide.render_with_background_color(time, color, context, opengl); true },
+                None => { slide.render(time, self.background_time_offset + time, context, opengl); true }
             },
-            None => {
+            None => false
+        }));
+
+        match result {
+            Ok(true) => {},
+            Ok(false) => {
                 log_err!("Slide #{} doesn't exist! Switching to slide #0...",self.current_slide);
                 self.current_slide = 0;
+                return
+            },
+            Err(panic) => {
+                let error = panic.downcast_ref::<&str>().map(|s| s.to_string())
+                    .or(panic.downcast_ref::<String>().cloned())
+                    .unwrap_or("unknown error".to_owned());
+                log_err!("Slide #{} panicked while rendering and got replaced with an error card: {error}", self.current_slide);
+                slide::count_render_error();
+                slide::render_error_card(self.current_slide + 1, &error, context, opengl);
+                self.slides[self.current_slide].set_error(Some(error));
             }
         }
+
+        selection::end_frame();
+
+        // Render the captions on top of the slide
+        if let Some(captions) = self.captions.as_ref().filter(|_| self.show_captions) {
+            captions.render(self.current_slide, time, context, opengl);
+        }
     }
 }
\ No newline at end of file
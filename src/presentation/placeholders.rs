@@ -0,0 +1,184 @@
+//! The sources text placeholders get their values from.
+//!
+//! Placeholders defined in a text object's `placeholders`-field are evaluated by the object
+//! itself. All other placeholders are looked up in the registered [`PlaceholderSource`]s, in the
+//! order they were registered in. New kinds of live data can be added by registering another
+//! source, without touching the rendering of text.
+
+use std::sync::RwLock;
+
+use chrono::Local;
+use once_cell::sync::Lazy;
+
+use super::renderable::PlaceholderFormat;
+use super::util;
+
+/// What a source gets to know about the placeholder it's asked for.
+pub struct PlaceholderContext<'a> {
+    /// The format written after the placeholder's name.
+    pub format: &'a PlaceholderFormat,
+    /// The size of the screen the text gets rendered on.
+    pub view_size: [f64; 2],
+    /// The time since the slide was started.
+    pub time: f64
+}
+
+/// Provides the values of placeholders, e.g. the current date or data fetched from the internet.
+pub trait PlaceholderSource: Send + Sync {
+    /// Returns the text the placeholder with the given name gets replaced with, or [`None`] if
+    /// this source doesn't provide it.
+    fn text(&self, name: &str, context: &PlaceholderContext) -> Option<String>;
+
+    /// Returns whether this source provides the placeholder with the given name.
+    ///
+    /// Gets used for detecting undefined placeholders when loading a document, so sources whose
+    /// placeholders depend on data that isn't available yet should still return `true`.
+    fn provides(&self, name: &str) -> bool;
+
+    /// Returns whether this source interprets the given format of the placeholder itself, e.g.
+    /// as a chrono format string.
+    ///
+    /// Such formats get passed on verbatim instead of being parsed as a [`PlaceholderFormat`].
+    fn accepts_format(&self, _name: &str, _spec: &str) -> bool {
+        false
+    }
+}
+
+/// All registered sources, the built-in ones first.
+static SOURCES: Lazy<RwLock<Vec<Box<dyn PlaceholderSource>>>> = Lazy::new(|| RwLock::new(vec![
+    Box::new(ClockSource),
+    Box::new(DataSource),
+    Box::new(SystemSource),
+    Box::new(LuaSource)
+]));
+
+/// Adds a source of placeholders, which gets asked after all previously registered sources.
+pub fn register(source: Box<dyn PlaceholderSource>) {
+    SOURCES.write().unwrap().push(source);
+}
+
+/// Returns the text of the placeholder with the given name from the first source providing it.
+pub fn text(name: &str, context: &PlaceholderContext) -> Option<String> {
+    SOURCES.read().unwrap().iter().find_map(|source| source.text(name, context))
+}
+
+/// Returns whether any registered source provides the placeholder with the given name.
+pub fn provided(name: &str) -> bool {
+    SOURCES.read().unwrap().iter().any(|source| source.provides(name))
+}
+
+/// Returns whether a source interprets the given format of the placeholder itself.
+pub fn accepts_format(name: &str, spec: &str) -> bool {
+    SOURCES.read().unwrap().iter().any(|source| source.accepts_format(name, spec))
+}
+
+/// The placeholders `slide_time` and `presentation_time`, showing the time elapsed since the
+/// slide or the presentation was started as `hh:mm:ss`, and `date` and `time`, showing the
+/// current date and time in the document's locale or a chrono format string given as their
+/// format (e.g. `{{date:%d.%m.%Y}}`).
+pub struct ClockSource;
+
+impl ClockSource {
+    /// Formats a duration in seconds as `hh:mm:ss`.
+    fn format_duration(seconds: f64) -> String {
+        let seconds = seconds.max(0.0) as u64;
+        format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    }
+}
+
+impl PlaceholderSource for ClockSource {
+    fn text(&self, name: &str, context: &PlaceholderContext) -> Option<String> {
+        match name {
+            "slide_time" => Some(Self::format_duration(context.time)),
+            "presentation_time" => Some(Self::format_duration(util::presentation_time())),
+            "date" | "time" => {
+                let locale = super::locale::current();
+                let datetime_format = match (name, context.format.spec.as_str()) {
+                    ("date", "") => locale.date_format,
+                    ("time", "") => locale.time_format,
+                    (_, spec) => spec
+                };
+                Some(Local::now().format(datetime_format).to_string())
+            },
+            _ => None
+        }
+    }
+
+    fn provides(&self, name: &str) -> bool {
+        matches!(name, "slide_time" | "presentation_time" | "date" | "time")
+    }
+
+    fn accepts_format(&self, name: &str, spec: &str) -> bool {
+        matches!(name, "date" | "time") && PlaceholderFormat::is_valid_datetime_format(spec)
+    }
+}
+
+/// The placeholders of the data providers configured by the user, which read local files and
+/// fetch HTTP documents (see [`super::data`]).
+pub struct DataSource;
+
+impl PlaceholderSource for DataSource {
+    fn text(&self, name: &str, _context: &PlaceholderContext) -> Option<String> {
+        super::data::placeholder_text(name)
+    }
+
+    fn provides(&self, name: &str) -> bool {
+        super::data::placeholder_text(name).is_some()
+    }
+}
+
+/// Information about the computer the presentation runs on: `os`, `hostname`, `user` and
+/// `cpus` (the amount of logical processors).
+pub struct SystemSource;
+
+impl PlaceholderSource for SystemSource {
+    fn text(&self, name: &str, _context: &PlaceholderContext) -> Option<String> {
+        match name {
+            "os" => Some(std::env::consts::OS.to_owned()),
+            "hostname" => Some(std::env::var("HOSTNAME").or(std::env::var("COMPUTERNAME")).ok()
+                .or_else(|| std::fs::read_to_string("/etc/hostname").ok().map(|name| name.trim().to_owned()))
+                .unwrap_or_default()),
+            "user" => Some(std::env::var("USER").or(std::env::var("USERNAME")).unwrap_or_default()),
+            "cpus" => Some(std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).to_string()),
+            _ => None
+        }
+    }
+
+    fn provides(&self, name: &str) -> bool {
+        matches!(name, "os" | "hostname" | "user" | "cpus")
+    }
+}
+
+/// The placeholders `lua_NAME`, showing the Lua global `NAME`, e.g. a variable set by a MIDI
+/// binding's script. Functions get called without arguments and show their result.
+pub struct LuaSource;
+
+impl LuaSource {
+    /// The prefix of the placeholders of Lua globals.
+    const PREFIX: &'static str = "lua_";
+}
+
+impl PlaceholderSource for LuaSource {
+    fn text(&self, name: &str, _context: &PlaceholderContext) -> Option<String> {
+        let name = name.strip_prefix(Self::PREFIX)?;
+        let lua = crate::LUA_INSTANCE.get()?;
+
+        let value: mlua::Value = lua.globals().get(name).ok()?;
+        let value = match value {
+            mlua::Value::Function(function) => function.call::<_, mlua::Value>(()).ok()?,
+            value => value
+        };
+        Some(match value {
+            mlua::Value::Nil => String::new(),
+            mlua::Value::Boolean(b) => b.to_string(),
+            value => lua.coerce_string(value).ok().flatten()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+    }
+
+    fn provides(&self, name: &str) -> bool {
+        // Globals might only get defined once a script runs
+        name.strip_prefix(Self::PREFIX).is_some_and(|name| !name.is_empty())
+    }
+}
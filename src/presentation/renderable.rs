@@ -10,6 +10,10 @@ use graphics::{ Context, Transformed };
 use graphics;
 
 use super::util; use util::{ ExprVector, Alignment, PropertyError };
+use crate::render::blend::BlendMode;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
 
 /// This trait defines shared behaviour for any object of a slide that should be rendered to the
 /// screen (referred to in this project as `Renderable objects` or `objects`).
@@ -23,6 +27,134 @@ pub trait Renderable: Debug {
     fn copy<'b>(&self) -> Box<dyn Renderable + 'b>;
 
     fn to_lua<'lua>(&self, lua: &'lua mlua::Lua) -> anyhow::Result<HashMap<String, mlua::Value<'lua>>>;
+
+    /// Returns a short name for the type of the object, e.g. for listing it in the editor.
+    fn type_name(&self) -> &'static str;
+
+    /// Returns the id the object can be referred to by, if it has one.
+    fn id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the lines of text displayed by this object, as they were written in the document.
+    /// 
+    /// Gets used for searching through slides. Returns [`None`] for objects without any text.
+    fn raw_text(&self) -> Option<&[String]> {
+        None
+    }
+
+    /// Returns the codes of all languages this object has variants for.
+    fn languages(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    /// Returns the alternative text describing this object for accessibility, if it has one.
+    /// 
+    /// Gets embedded into exported documents.
+    fn alt_text(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the size of the image this object shows in pixels, if it shows one.
+    /// 
+    /// Gets used for detecting images that get scaled up too much.
+    fn source_size(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    /// Returns the path of the file this object shows, if it shows one.
+    /// 
+    /// Gets used for listing the assets of a document.
+    fn asset_path(&self) -> Option<&str> {
+        None
+    }
+
+    /// Evaluates the object's bounding box as `[x, y, width, height]` and it's pivot point.
+    /// 
+    /// Gets used for drawing debug outlines. The default implementation uses the position, size
+    /// and alignment of the object's [`BaseProperties`].
+    fn bounds(&self, time: f64, view_size: [f64; 2]) -> anyhow::Result<([f64; 4], [f64; 2])> {
+        fn expr_to_f(e: util::ExprEval) -> Option<f64> {
+            match e {
+                util::ExprEval::F64(f) => Some(f),
+                util::ExprEval::String(_) => None
+            }
+        }
+
+        let base = self.get_base_properties();
+        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
+
+        let pos = base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let size = base.size.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let alignment: (f64, f64) = base.alignment.into();
+
+        Ok(([pos[0] - size[0] * alignment.0, pos[1] - size[1] * alignment.1, size[0], size[1]], pos))
+    }
+
+    /// Returns the natural size of the object as `(width, height)` on a screen of `w`×`h` pixels
+    /// at the time `t`.
+    /// 
+    /// Gets used by layout containers and for fitting objects into an area. The default
+    /// implementation evaluates the size of the object's [`BaseProperties`]; objects whose
+    /// content has a size of it's own (like laid out text) measure that instead.
+    fn measure(&self, w: f64, h: f64, t: f64) -> anyhow::Result<(f64, f64)> {
+        fn expr_to_f(e: util::ExprEval) -> Option<f64> {
+            match e {
+                util::ExprEval::F64(f) => Some(f),
+                util::ExprEval::String(_) => None
+            }
+        }
+
+        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
+        let size = self.get_base_properties().size.evaluate_arr(w, h, t, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        Ok((size[0], size[1]))
+    }
+
+    /// Evaluates the object as a plain (rounded) rectangle, if it is one.
+    /// 
+    /// Consecutive shapes of the same color get drawn together in a single draw call instead of
+    /// calling [`Renderable::render`] on each of them, as long as they don't need anything else
+    /// the slide does for each object (e.g. a blend mode). Returns [`None`] for any other objects.
+    fn shape_instance(&self, _time: f64, _view_size: [f64; 2]) -> Option<anyhow::Result<ShapeInstance>> {
+        None
+    }
+}
+
+/// A (rounded) rectangle evaluated for a single frame, which can be drawn together with other
+/// shapes of the same color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeInstance {
+    pub color: [f32; 4],
+    /// The rectangle as `[x, y, width, height]`, already offset by the object's alignment.
+    pub rect: [f64; 4],
+    /// The radius of each corner, in the order top left, top right, bottom right, bottom left.
+    pub radii: [f64; 4],
+    /// The number of segments each corner's arc consists of. Gets derived from the on-screen size
+    /// of the largest radius when [`None`].
+    pub quality: Option<u32>
+}
+
+impl ShapeInstance {
+    /// Draws all given shapes with the color of the first one, using as few draw calls as the
+    /// backend allows.
+    pub fn draw_batch(shapes: &[ShapeInstance], context: Context, opengl: &mut GlGraphics) {
+        use graphics::Graphics;
+
+        #[cfg(feature = "render_tracing")]
+        let _span = tracing::info_span!("ShapeInstance::draw_batch", shapes = shapes.len()).entered();
+
+        let Some(first) = shapes.first() else { return };
+        let px_per_unit = crate::render::shapes::pixels_per_unit(&context);
+        let resolution = |shape: &ShapeInstance| shape.quality.unwrap_or_else(|| {
+            let max_radius = shape.radii.iter().fold(0.0_f64, |a, b| a.max(*b));
+            crate::render::shapes::arc_resolution(max_radius * px_per_unit)
+        });
+
+        opengl.tri_list(&context.draw_state, &first.color, |tri| {
+            crate::render::shapes::with_rounded_rectangles_tri_list(
+                shapes.iter().map(|s| (resolution(s), s.rect, s.radii)), context.transform, tri);
+        });
+    }
 }
 
 /// A wrapper for a reference to any object implementing [`Renderable`]
@@ -57,6 +189,14 @@ impl<'a> Renderable for RenderableRef<'a> {
         self.reference.get_base_properties()
     }
 
+    fn type_name(&self) -> &'static str {
+        self.reference.type_name()
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.reference.id()
+    }
+
     fn copy<'b>(&self) -> Box<dyn Renderable + 'b> {
         let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable + 'a) as *mut (dyn Renderable + 'a);
         unsafe {
@@ -68,6 +208,33 @@ impl<'a> Renderable for RenderableRef<'a> {
     fn to_lua<'lua>(&self, lua: &'lua mlua::Lua) -> anyhow::Result<HashMap<String, mlua::Value<'lua>>> {
         self.reference.to_lua(lua)
     }
+
+    fn raw_text(&self) -> Option<&[String]> {
+        self.reference.raw_text()
+    }
+
+    fn languages(&self) -> Vec<&str> {
+        self.reference.languages()
+    }
+
+    fn alt_text(&self) -> Option<&str> {
+        self.reference.alt_text()
+    }
+
+    fn source_size(&self) -> Option<(u32, u32)> {
+        self.reference.source_size()
+    }
+
+    fn asset_path(&self) -> Option<&str> {
+        self.reference.asset_path()
+    }
+
+    fn bounds(&self, time: f64, view_size: [f64; 2]) -> anyhow::Result<([f64; 4], [f64; 2])> {
+        self.reference.bounds(time, view_size)
+    }
+    fn measure(&self, w: f64, h: f64, t: f64) -> anyhow::Result<(f64, f64)> {
+        self.reference.measure(w, h, t)
+    }
 }
 impl<'a> Debug for RenderableRef<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -81,7 +248,9 @@ pub struct BaseProperties {
     pub pos: ExprVector<2>,
     pub size: ExprVector<2>,
     pub color: ExprVector<4>,
-    pub alignment: Alignment
+    pub alignment: Alignment,
+    /// How the object gets combined with everything drawn beneath it.
+    pub blend: BlendMode
 }
 
 impl BaseProperties {
@@ -105,6 +274,7 @@ impl BaseProperties {
             size: util::parse_expression_list(size, util::DEFAULT_CONTEXT.clone()).map_err((err)("size"))?.try_into().map_err((err)("size"))?,
             color: util::parse_expression_list(color, util::DEFAULT_CONTEXT.clone()).map_err((err)("color"))?.try_into().map_err((err)("color"))?,
             alignment: Alignment::try_from(alignment.into())?,
+            blend: BlendMode::default()
         })
     }
 
@@ -128,37 +298,23 @@ pub struct ColoredRect {
 }
 impl Renderable for ColoredRect {
     fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
-        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
-
-        fn expr_to_f(e: util::ExprEval) -> Option<f64> {
-            match e {
-                util::ExprEval::F64(f) => Some(f),
-                util::ExprEval::String(_) => None
-            }
-        }
-
-        let view_size = context.get_view_size();
-        let color_eval = self.base.color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
-            .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
-        let pos_eval = self.base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
-            .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
-        let size_eval = self.base.size.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
-            .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
-        // Convert the alignment to scalar values.
-        //   Subtracting the size of the object multiplied by this value from the position of the
-        //   object correctly positions it relative to it's pivot.
-        let alignment: (f64, f64) = self.base.alignment.into();
-        graphics::rectangle(
-            [color_eval[0] as f32, color_eval[1] as f32, color_eval[2] as f32, color_eval[3] as f32],
-            [pos_eval[0]-size_eval[0]*alignment.0,pos_eval[1]-size_eval[1]*alignment.1,size_eval[0],size_eval[1]],
-            context.transform, opengl);
+        let shape = self.evaluate_shape(time, context.get_view_size())?;
+        graphics::rectangle(shape.color, shape.rect, context.transform, opengl);
         Ok(())
     }
 
+    fn shape_instance(&self, time: f64, view_size: [f64; 2]) -> Option<anyhow::Result<ShapeInstance>> {
+        Some(self.evaluate_shape(time, view_size))
+    }
+
     fn get_base_properties(&self) -> &BaseProperties {
         &self.base
     }
 
+    fn type_name(&self) -> &'static str {
+        "ColoredRect"
+    }
+
     fn copy<'b>(&self) -> Box<dyn Renderable + 'b>
     where Self: Sized {
         let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable) as *mut (dyn Renderable);
@@ -176,17 +332,8 @@ impl ColoredRect {
     pub fn new(base: BaseProperties) -> Self {
         ColoredRect { base }
     }
-}
-
-#[derive(Debug, Clone)]
-pub struct RoundedRect {
-    base: BaseProperties,
-    corner_rounding: util::ResolutionDependentExpr,
-}
 
-impl Renderable for RoundedRect {
-    fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
-        use graphics::Graphics;
+    fn evaluate_shape(&self, time: f64, view_size: [f64; 2]) -> anyhow::Result<ShapeInstance> {
         let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
 
         fn expr_to_f(e: util::ExprEval) -> Option<f64> {
@@ -196,29 +343,53 @@ impl Renderable for RoundedRect {
             }
         }
 
-        let view_size = context.get_view_size();
-        let color_arr = self.base.color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
+        let color_eval = self.base.color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
             .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
-        let mut pos_eval = self.base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
+        let pos_eval = self.base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
             .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
         let size_eval = self.base.size.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
             .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
-        let corner_rounding_eval = expr_to_f(self.corner_rounding.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
+        // Convert the alignment to scalar values.
+        //   Subtracting the size of the object multiplied by this value from the position of the
+        //   object correctly positions it relative to it's pivot.
         let alignment: (f64, f64) = self.base.alignment.into();
-        let arc_tri_count: u32 = (corner_rounding_eval as u32 / 2).max(6);
-        
-        pos_eval = [pos_eval[0] - size_eval[0] * alignment.0, pos_eval[1] - size_eval[1] * alignment.1];
+        Ok(ShapeInstance {
+            color: color_eval.map(|c| c as f32),
+            rect: [pos_eval[0]-size_eval[0]*alignment.0,pos_eval[1]-size_eval[1]*alignment.1,size_eval[0],size_eval[1]],
+            radii: [0.0; 4],
+            quality: Some(1)
+        })
+    }
+}
 
-        opengl.tri_list(&context.draw_state, &color_arr.map(|f| f as f32), |tri| {
-            graphics::triangulation::with_round_rectangle_tri_list(arc_tri_count, context.transform, [pos_eval[0],pos_eval[1],size_eval[0],size_eval[1]], corner_rounding_eval, tri);
-        });
+#[derive(Debug, Clone)]
+pub struct RoundedRect {
+    base: BaseProperties,
+    /// The radius of each corner, in the order top left, top right, bottom right, bottom left.
+    corner_rounding: ExprVector<4>,
+    /// The number of segments each corner's arc consists of, if set manually.
+    quality: Option<u32>
+}
+
+impl Renderable for RoundedRect {
+    fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
+        let shape = self.evaluate_shape(time, context.get_view_size())?;
+        ShapeInstance::draw_batch(&[shape], context, opengl);
         Ok(())
     }
 
+    fn shape_instance(&self, time: f64, view_size: [f64; 2]) -> Option<anyhow::Result<ShapeInstance>> {
+        Some(self.evaluate_shape(time, view_size))
+    }
+
     fn get_base_properties<'b>(&'b self) -> &'b BaseProperties {
         &self.base
     }
 
+    fn type_name(&self) -> &'static str {
+        "RoundedRect"
+    }
+
     fn copy<'b>(&self) -> Box<dyn Renderable + 'b> {
         let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable) as *mut (dyn Renderable);
         unsafe {
@@ -235,41 +406,186 @@ impl Renderable for RoundedRect {
     }
 }
 impl RoundedRect {
+    fn evaluate_shape(&self, time: f64, view_size: [f64; 2]) -> anyhow::Result<ShapeInstance> {
+        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
+
+        fn expr_to_f(e: util::ExprEval) -> Option<f64> {
+            match e {
+                util::ExprEval::F64(f) => Some(f),
+                util::ExprEval::String(_) => None
+            }
+        }
+
+        let color_arr = self.base.color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
+            .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
+        let pos_eval = self.base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
+            .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
+        let size_eval = self.base.size.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
+            .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
+        let corner_rounding_eval = self.corner_rounding.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?
+            .try_map(expr_to_f).ok_or(anyhow::anyhow!("Value returned from lua expression wasn't a number!"))?;
+        let alignment: (f64, f64) = self.base.alignment.into();
+
+        Ok(ShapeInstance {
+            color: color_arr.map(|f| f as f32),
+            rect: [pos_eval[0] - size_eval[0] * alignment.0, pos_eval[1] - size_eval[1] * alignment.1, size_eval[0], size_eval[1]],
+            radii: corner_rounding_eval,
+            quality: self.quality
+        })
+    }
+
+    /// Creates a new rounded rectangle.
+    /// 
+    /// The corner rounding is either a single expression used for all corners or four
+    /// expressions separated by semicolons (top left, top right, bottom right, bottom left).
     pub fn new<RoundingStr>(base: BaseProperties, corner_rounding: RoundingStr) -> Result<Self, PropertyError>
     where RoundingStr: Into<String> {
+        let corner_rounding: String = corner_rounding.into();
+        let mut radii = corner_rounding.split(';')
+            .map(|expr| util::res_dependent_expr(expr.to_owned(), util::DEFAULT_CONTEXT.clone(), util::ResExprType::HeightBased))
+            .collect::<Result<Vec<_>, PropertyError>>()?;
+
+        // A single radius gets used for all four corners
+        if radii.len() == 1 {
+            radii = vec![radii[0].clone(), radii[0].clone(), radii[0].clone(), radii[0].clone()];
+        }
+
         Ok(RoundedRect {
             base,
-            corner_rounding: util::res_dependent_expr(corner_rounding, util::DEFAULT_CONTEXT.clone(), util::ResExprType::HeightBased)?,
+            corner_rounding: radii.try_into()?,
+            quality: None
         })
     }
+
+    /// Sets the number of segments each corner's arc consists of.
+    /// 
+    /// When set to [`None`], it gets derived from the on-screen size of the corners, so large
+    /// radii look smooth while small ones stay cheap to draw.
+    pub fn set_quality(&mut self, quality: Option<u32>) {
+        self.quality = quality.map(|q| q.max(1));
+    }
 }
 
 use crate::render::font;
 
 pub struct TextFont {
     pub base_font: font::Font,
-    pub bold_font: font::Font
+    pub bold_font: font::Font,
+    /// The paths and face indices of the regular and bold font files, if the fonts were loaded
+    /// from files.
+    sources: Option<((String, isize), (String, isize))>,
+    /// The time the font files were last modified at when they got loaded.
+    modified: Option<SystemTime>
 }
 impl TextFont {
     /// Creates a new [`TextFont`]
+    /// 
+    /// Fonts that can't be loaded get replaced with the bundled default font.
     pub fn new<BaseStr, BoldStr>(base_font_path: BaseStr, bold_font_path: BoldStr) -> TextFont
     where BaseStr: Into<String>, BoldStr: Into<String> {
-        TextFont {
-            base_font: font::Font::new(<BaseStr as Into<String>>::into(base_font_path), 0).expect("invalid font path"),
-            bold_font: font::Font::new(<BoldStr as Into<String>>::into(bold_font_path), 0).expect("invalid font path")
-        }
+        Self::new_indexed((base_font_path, 0), (bold_font_path, 0))
     }
     /// Creates a new [`TextFont`] using fonts with a face index
+    /// 
+    /// Fonts that can't be loaded get replaced with the bundled default font.
     pub fn new_indexed<BaseStr, BoldStr>(base_font_path: (BaseStr, isize), bold_font_path: (BoldStr, isize)) -> TextFont
     where BaseStr: Into<String>, BoldStr: Into<String> {
+        let sources: ((String, isize), (String, isize)) = ((base_font_path.0.into(), base_font_path.1), (bold_font_path.0.into(), bold_font_path.1));
         TextFont {
-            base_font: font::Font::new(<BaseStr as Into<String>>::into(base_font_path.0), base_font_path.1).expect("invalid font path or face index"),
-            bold_font: font::Font::new(<BoldStr as Into<String>>::into(bold_font_path.0), bold_font_path.1).expect("invalid font path or face index")
+            base_font: font::Font::from_prepared(font::PreparedFont::new_or_fallback(&sources.0.0, sources.0.1, false)),
+            bold_font: font::Font::from_prepared(font::PreparedFont::new_or_fallback(&sources.1.0, sources.1.1, true)),
+            modified: Self::last_modified(&sources),
+            sources: Some(sources)
         }
     }
+    /// Creates a [`TextFont`] from the bundled default font.
+    pub fn fallback() -> TextFont {
+        TextFont {
+            base_font: font::Font::from_prepared(font::PreparedFont::fallback(false)),
+            bold_font: font::Font::from_prepared(font::PreparedFont::fallback(true)),
+            sources: None,
+            modified: None
+        }
+    }
+    /// Returns the time the most recently changed of the font files was modified at.
+    fn last_modified(sources: &((String, isize), (String, isize))) -> Option<SystemTime> {
+        [&sources.0.0, &sources.1.0].into_iter()
+            .filter_map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+            .max()
+    }
+    /// Returns whether the font files changed since they were loaded.
+    /// 
+    /// Always `false` for the bundled default font.
+    pub fn changed(&self) -> bool {
+        match &self.sources {
+            Some(sources) => Self::last_modified(sources) > self.modified,
+            None => false
+        }
+    }
+    /// Loads the fonts from their files again, replacing all cached glyphs.
+    /// 
+    /// Every text referencing this font uses the reloaded glyphs from then on. Does nothing for
+    /// the bundled default font.
+    pub fn reload(&mut self) {
+        if let Some(sources) = self.sources.clone() {
+            *self = Self::new_indexed(sources.0, sources.1);
+        }
+    }
+    /// Loads all supplied fonts (pairs of regular and bold font paths) concurrently and
+    /// pre-rasterizes their commonly used glyphs in background threads.
+    /// 
+    /// The glyphs get uploaded to the GPU afterwards, so this needs to be called on the thread
+    /// owning the OpenGL context.
+    pub fn load_all(fonts: HashMap<String, (String, String)>) -> HashMap<String, TextFont> {
+        let prepared = std::thread::scope(|scope| {
+            let handles = fonts.into_iter().map(|(name, (base_path, bold_path))| {
+                let load = |path: String, bold: bool| scope.spawn(move || {
+                    let mut font = font::PreparedFont::new_or_fallback(path, 0, bold);
+                    font.preload();
+                    font
+                });
+                let sources = ((base_path.clone(), 0), (bold_path.clone(), 0));
+                (name, load(base_path, false), load(bold_path, true), sources)
+            }).collect::<Vec<_>>();
+
+            handles.into_iter().map(|(name, base, bold, sources)| {
+                (name, base.join().expect("loading font failed"), bold.join().expect("loading font failed"), sources)
+            }).collect::<Vec<_>>()
+        });
+
+        prepared.into_iter().map(|(name, base, bold, sources)| {
+            (name, TextFont {
+                base_font: font::Font::from_prepared(base),
+                bold_font: font::Font::from_prepared(bold),
+                modified: Self::last_modified(&sources),
+                sources: Some(sources)
+            })
+        }).collect()
+    }
+}
+
+/// The time in seconds between checking whether any font files changed.
+pub const FONT_CHECK_INTERVAL: f64 = 1.0;
+
+/// Reloads the fonts in [`crate::FONTS`] whose files changed since they were loaded, or all fonts
+/// loaded from files if `force` is set.
+/// 
+/// The fonts get replaced in place, so all text uses the new glyphs right away. Returns the names
+/// of the reloaded fonts.
+pub fn reload_fonts(force: bool) -> Vec<String> {
+    let mut reloaded = Vec::new();
+    for (name, text_font) in crate::FONTS.get().unwrap().iter() {
+        let mut text_font = text_font.borrow_mut();
+        if force || text_font.changed() {
+            text_font.reload();
+            reloaded.push(name.clone());
+        }
+    }
+    reloaded
 }
 
 use std::cell::RefCell;
+use std::time::SystemTime;
 
 #[derive(Clone)]
 pub enum TextPart {
@@ -279,6 +595,8 @@ pub enum TextPart {
         italic: bool,
         color: util::ExprVector<4>,
         size: util::ResolutionDependentExpr,
+        /// How far the text is raised above the baseline (or lowered, if negative).
+        voffset: util::ResolutionDependentExpr,
         font: Rc<RefCell<TextFont>>
     },
     Tab,
@@ -287,31 +605,223 @@ pub enum TextPart {
         font: Rc<RefCell<TextFont>>
     },
     NewLine,
+    /// A place a word can be hyphenated at if it doesn't fit into the line, which is invisible
+    /// otherwise.
+    SoftHyphen {
+        bold: bool,
+        italic: bool,
+        color: util::ExprVector<4>,
+        size: util::ResolutionDependentExpr,
+        voffset: util::ResolutionDependentExpr,
+        font: Rc<RefCell<TextFont>>
+    },
     Placeholder {
         index: String,
-        pad_char: char,
-        pad_amount: i8,
+        format: PlaceholderFormat,
 
         bold: bool,
         italic: bool,
         color: util::ExprVector<4>,
         size: util::ResolutionDependentExpr,
+        voffset: util::ResolutionDependentExpr,
         font: Rc<RefCell<TextFont>>
     },
 }
 
+/// The format of a placeholder's value, written after it's name (e.g. `{{value:08.2}}`).
+/// 
+/// The format spec looks like `[[fill]align][sign][0][width][,][.precision][~step]`:
+/// - `fill` is the character used for padding (a space by default)
+/// - `align` is `<` (left), `>` (right) or `^` (centered); numbers are right-aligned by default
+/// - `sign` is `+` to write the sign of positive numbers too, or a space to write a space in
+///   place of it
+/// - `0` pads numbers with zeros after their sign
+/// - `width` is the minimum amount of characters
+/// - `,` groups the digits of numbers in thousands
+/// - `precision` is the amount of decimals numbers get rounded to
+/// - `step` rounds numbers to a multiple of it (e.g. `~0.5`), using it's decimals as the
+///   precision if there is none
+/// 
+/// Numbers get written with the separators of the document's locale (see [`super::locale`]).
+/// Numbers rounding to zero never get a minus sign, so values around zero don't change their
+/// width.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaceholderFormat {
+    /// The spec as it was written.
+    pub spec: String,
+    fill: char,
+    align: Option<char>,
+    sign: Option<char>,
+    zero: bool,
+    width: usize,
+    thousands: bool,
+    precision: Option<usize>,
+    /// The multiple numbers get rounded to and it's amount of decimals.
+    step: Option<(f64, usize)>
+}
+
+impl PlaceholderFormat {
+    /// Parses a format spec, returning [`None`] if it's invalid.
+    pub fn parse(spec: &str) -> Option<PlaceholderFormat> {
+        let mut format = PlaceholderFormat { spec: spec.to_owned(), fill: ' ', ..Default::default() };
+        let chars = spec.chars().collect::<Vec<_>>();
+        let mut i = 0;
+
+        // Fill and alignment
+        if chars.len() >= 2 && "<>^".contains(chars[1]) {
+            format.fill = chars[0];
+            format.align = Some(chars[1]);
+            i = 2;
+        } else if chars.first().is_some_and(|c| "<>^".contains(*c)) {
+            format.align = Some(chars[0]);
+            i = 1;
+        }
+
+        if chars.get(i).is_some_and(|c| "+ ".contains(*c)) {
+            format.sign = Some(chars[i]);
+            i += 1;
+        }
+
+        if chars.get(i) == Some(&'0') {
+            format.zero = true;
+            i += 1;
+        }
+
+        let digits = |i: &mut usize| {
+            let start = *i;
+            while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+                *i += 1;
+            }
+            chars[start..*i].iter().collect::<String>()
+        };
+
+        let width = digits(&mut i);
+        if !width.is_empty() {
+            format.width = width.parse().ok()?;
+        }
+
+        if chars.get(i) == Some(&',') {
+            format.thousands = true;
+            i += 1;
+        }
+
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            format.precision = Some(digits(&mut i).parse().ok()?);
+        }
+
+        if chars.get(i) == Some(&'~') {
+            let step = chars[i + 1..].iter().collect::<String>();
+            let decimals = step.split_once('.').map(|(_, decimals)| decimals.len()).unwrap_or(0);
+            format.step = Some((step.parse().ok().filter(|step: &f64| step.is_finite() && *step > 0.0)?, decimals));
+            i = chars.len();
+        }
+
+        match i == chars.len() {
+            true => Some(format),
+            false => None
+        }
+    }
+
+    /// Creates a format that leaves values unchanged, keeping the spec for the placeholder to
+    /// interpret (e.g. the chrono format string of the `date` placeholder).
+    pub fn verbatim(spec: &str) -> PlaceholderFormat {
+        PlaceholderFormat { spec: spec.to_owned(), fill: ' ', ..Default::default() }
+    }
+
+    /// Returns whether a string is a valid chrono format string, which can be formatted without
+    /// panicking.
+    pub fn is_valid_datetime_format(spec: &str) -> bool {
+        use chrono::format::{ StrftimeItems, Item };
+        !StrftimeItems::new(spec).any(|item| matches!(item, Item::Error))
+    }
+
+    /// Formats a placeholder's value.
+    /// 
+    /// The sign, rounding, thousands separators and zero padding only apply to numbers.
+    pub fn apply(&self, text: String) -> String {
+        let number = text.trim().parse::<f64>().ok().filter(|n| n.is_finite());
+        let number = match (number, self.step) {
+            (Some(n), Some((step, _))) => Some((n / step).round() * step),
+            (n, _) => n
+        };
+
+        let mut text = match (number, self.precision.or(self.step.map(|(_, decimals)| decimals))) {
+            (Some(n), Some(precision)) => format!("{n:.precision$}"),
+            (Some(n), None) if self.step.is_some() => n.to_string(),
+            _ => text
+        };
+        if number.is_some() {
+            // Values like -0.001 would otherwise be written as -0.00
+            if text.strip_prefix('-').is_some_and(|digits| digits.chars().all(|c| c == '0' || c == '.')) {
+                text.remove(0);
+            }
+            if self.thousands {
+                text = Self::group_thousands(&text);
+            }
+            if let (Some(sign), false) = (self.sign, text.starts_with('-')) {
+                text.insert(0, sign);
+            }
+        }
+        if number.is_some() {
+            text = super::locale::current().localize_number(&text);
+        }
+
+        let padding = self.width.saturating_sub(text.chars().count());
+        if padding == 0 {
+            return text
+        }
+
+        if self.zero && self.align.is_none() && number.is_some() {
+            let (sign, digits) = match text.chars().next() {
+                Some(sign) if "-+ ".contains(sign) => (sign.to_string(), text[1..].to_owned()),
+                _ => (String::new(), text.clone())
+            };
+            return format!("{sign}{}{digits}", "0".repeat(padding))
+        }
+
+        let fill = |amount: usize| std::iter::repeat(self.fill).take(amount).collect::<String>();
+        match self.align.unwrap_or(if number.is_some() { '>' } else { '<' }) {
+            '>' => format!("{}{text}", fill(padding)),
+            '^' => format!("{}{text}{}", fill(padding / 2), fill(padding - padding / 2)),
+            _ => format!("{text}{}", fill(padding))
+        }
+    }
+
+    /// Inserts a comma between every group of three digits in front of the decimal point.
+    fn group_thousands(number: &str) -> String {
+        let (sign, number) = match number.strip_prefix('-') {
+            Some(number) => ("-", number),
+            None => ("", number)
+        };
+        let (integer, fraction) = match number.split_once('.') {
+            Some((integer, fraction)) => (integer, format!(".{fraction}")),
+            None => (number, String::new())
+        };
+
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        format!("{sign}{grouped}{fraction}")
+    }
+}
+
 impl std::fmt::Debug for TextPart {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            TextPart::Text { text, bold, italic, color, size, font } => { write!(f, "\"{}\"", text) },
+            TextPart::Text { text, bold, italic, color, size, voffset, font } => { write!(f, "\"{}\"", text) },
             TextPart::Tab => { write!(f, "\\t") },
             TextPart::Space { size, font } => { write!(f, "\\s") },
             TextPart::NewLine => { write!(f, "\\n") },
-            TextPart::Placeholder { index, pad_char, pad_amount, bold, italic, color, size, font } => {
-                if *pad_amount<0 {
-                    write!(f, "{{{}<{}{{{}}}", pad_char, pad_amount.abs(), index)
-                } else {
-                    write!(f, "{{{}>{}{{{}}}", pad_amount, pad_amount, index)
+            TextPart::SoftHyphen { .. } => { write!(f, "\\-") },
+            TextPart::Placeholder { index, format, bold, italic, color, size, voffset, font } => {
+                match format.spec.is_empty() {
+                    true => write!(f, "{{{{{}}}}}", index),
+                    false => write!(f, "{{{{{}:{}}}}}", index, format.spec)
                 }
             },
         }
@@ -322,35 +832,42 @@ impl TextPart {
 
     pub fn set_bold(&mut self, set: bool) -> Result<(), PropertyError> {
         match self {
-            TextPart::Text { text, bold, italic, color, size, font } => *bold = set,
+            TextPart::Text { text, bold, italic, color, size, voffset, font } => *bold = set,
             _ => {}
         }
         Ok(())
     }
     pub fn set_italic(&mut self, set: bool) -> Result<(), PropertyError> {
         match self {
-            TextPart::Text { text, bold, italic, color, size, font } => *italic = set,
+            TextPart::Text { text, bold, italic, color, size, voffset, font } => *italic = set,
             _ => {}
         }
         Ok(())
     }
     pub fn set_color(&mut self, set: String) -> Result<(), PropertyError> {
         match self {
-            TextPart::Text { text, bold, italic, color, size, font } => *color = util::parse_expression_list(set, util::DEFAULT_CONTEXT.clone())?.try_into()?,
+            TextPart::Text { text, bold, italic, color, size, voffset, font } => *color = util::parse_expression_list(set, util::DEFAULT_CONTEXT.clone())?.try_into()?,
             _ => {}
         }
         Ok(())
     }
     pub fn set_size(&mut self, set: String) -> Result<(), PropertyError> {
         match self {
-            TextPart::Text { text, bold, italic, color, size, font } => *size = util::res_dependent_expr(set, util::DEFAULT_CONTEXT.clone(), util::ResExprType::HeightBased)?,
+            TextPart::Text { text, bold, italic, color, size, voffset, font } => *size = util::res_dependent_expr(set, util::DEFAULT_CONTEXT.clone(), util::ResExprType::HeightBased)?,
+            _ => {}
+        }
+        Ok(())
+    }
+    pub fn set_voffset(&mut self, set: String) -> Result<(), PropertyError> {
+        match self {
+            TextPart::Text { text, bold, italic, color, size, voffset, font } => *voffset = util::res_dependent_expr(set, util::DEFAULT_CONTEXT.clone(), util::ResExprType::HeightBased)?,
             _ => {}
         }
         Ok(())
     }
     pub fn set_font(&mut self, set: Rc<RefCell<TextFont>>) -> Result<(), PropertyError> {
         match self {
-            TextPart::Text { text, bold, italic, color, size, font } => *font = set,
+            TextPart::Text { text, bold, italic, color, size, voffset, font } => *font = set,
             _ => {}
         }
         Ok(())
@@ -364,6 +881,7 @@ impl mlua::UserData for TextPart {
         fields.add_field_method_get("type", |lua, s| {
             match *s {
                 TextPart::NewLine => "Newline".into_lua(lua),
+                TextPart::SoftHyphen { .. } => "SoftHyphen".into_lua(lua),
                 TextPart::Placeholder { .. } => "Placeholder".into_lua(lua),
                 TextPart::Space { .. } => "Space".into_lua(lua),
                 TextPart::Tab => "Tab".into_lua(lua),
@@ -376,21 +894,21 @@ impl mlua::UserData for TextPart {
             match s {
                 TextPart::Placeholder {
                     index,
-                    pad_char,
-                    pad_amount,
+                    format,
                     bold,
                     italic,
                     color,
                     size,
+                    voffset,
                     font
                 } => {
                     table.set("index", index.as_str());
-                    table.set("pad_char", String::from_utf8_lossy(&[*pad_char as u8]));
-                    table.set("pad_amount", *pad_amount);
+                    table.set("format", format.spec.as_str());
                     table.set("bold", *bold);
                     table.set("italic", *italic);
                     table.set("color", color.clone());
                     table.set("size", size.clone());
+                    table.set("voffset", voffset.clone());
                     table.set("font_name", font.borrow().base_font.name.as_str());
                     table.set("bold_font_name", font.borrow().bold_font.name.as_str());
                 },
@@ -408,6 +926,7 @@ impl mlua::UserData for TextPart {
                     italic,
                     color,
                     size,
+                    voffset,
                     font
                 } => {
                     table.set("text", text.as_str());
@@ -415,6 +934,7 @@ impl mlua::UserData for TextPart {
                     table.set("italic", *italic);
                     table.set("color", color.clone());
                     table.set("size", size.clone());
+                    table.set("voffset", voffset.clone());
                     table.set("font_name", font.borrow().base_font.name.as_str());
                     table.set("bold_font_name", font.borrow().bold_font.name.as_str());
                 },
@@ -426,22 +946,103 @@ impl mlua::UserData for TextPart {
     }
 }
 
+/// The text of a [`Text`] object in a single language.
 #[derive(Debug, Clone)]
-pub struct Text<'a> {
-    base: BaseProperties,
-    text: Vec<TextPart>,
-    text_alignment: util::Alignment,
-    placeholders: HashMap<String, TextPlaceholderExpr<'a>>
+pub struct TextVariant {
+    parts: Vec<TextPart>,
+    /// The lines of text as they were supplied, before parsing any markup.
+    raw_text: Vec<String>
 }
 
-pub struct TextPlaceholderExpr<'a> {
-    /// The function for evaluating the expression's value.
-    pub(self) expr: &'a mut dyn Fn(&[f64]) -> f64,
-    /// The string the expression was parsed from.
+/// The names of all undefined placeholders a warning has already been logged for.
+static WARNED_PLACEHOLDERS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+/// How far italic parts of text advance further than upright ones, relative to their font size.
+const ITALIC_ADVANCE_FAC: f64 = 0.10;
+
+/// The lines of a [`Text`] object, as laid out for a single frame.
+struct TextLayout {
+    /// The width lines get wrapped at.
+    max_width: f64,
+    /// The font size of the object, which is also the minimum height of a line.
+    default_size: f64,
+    /// The height of all lines combined.
+    height: f64,
+    line_widths: Vec<f64>,
+    line_heights: Vec<f64>,
+    /// The distance of each line's baseline from it's top.
+    line_baselines: Vec<f64>,
+    /// The indices of the soft hyphens at the end of a line, which get shown as a hyphen.
+    hyphenated: Vec<usize>,
+    /// The formatted text of each placeholder part by it's index.
     /// 
-    /// Used for debugging.
-    pub(self) base_string: String,
-    /// The context that was used to construct the evaluation function.
+    /// Placeholders only get evaluated once per frame, so the drawn text is always the one the
+    /// lines were laid out for, even when a live value changes in between.
+    placeholder_texts: HashMap<usize, String>
+}
+
+impl TextLayout {
+    /// Returns the left and right edge of the lines that contain anything, if there are any.
+    /// 
+    /// `left` is the left edge of the area lines get wrapped in.
+    fn horizontal_extent(&self, left: f64, text_align: f64) -> Option<(f64, f64)> {
+        let (min, max) = self.line_widths.iter()
+            .filter(|width| **width > 0.0)
+            .map(|width| (left + (self.max_width - width)*text_align, *width))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (x, width)| (min.min(x), max.max(x + width)));
+
+        match min < max {
+            true => Some((min, max)),
+            false => None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Text<'a> {
+    base: BaseProperties,
+    /// The text in each language, indexed by language code.
+    /// 
+    /// Text without any language variants gets stored under an empty language code. Contains at
+    /// least one variant.
+    variants: IndexMap<String, TextVariant>,
+    text_alignment: util::Alignment,
+    placeholders: HashMap<String, TextPlaceholderExpr<'a>>,
+    background: Option<TextBackground>
+}
+
+/// A card drawn behind a [`Text`] object, covering the extent of the laid out text.
+#[derive(Debug, Clone)]
+pub struct TextBackground {
+    color: ExprVector<4>,
+    /// The space between the text and the card's edges.
+    padding: util::ResolutionDependentExpr,
+    corner_radius: util::ResolutionDependentExpr
+}
+
+impl TextBackground {
+    pub fn new<CStr, PStr, RStr>(color: CStr, padding: PStr, corner_radius: RStr) -> Result<Self, PropertyError>
+    where
+        CStr: Into<String>,
+        PStr: Into<String>,
+        RStr: Into<String>
+    {
+        Ok(TextBackground {
+            color: util::parse_expression_list(color, util::DEFAULT_CONTEXT.clone())?.try_into()?,
+            padding: util::res_dependent_expr(padding, util::DEFAULT_CONTEXT.clone(), util::ResExprType::HeightBased)?,
+            corner_radius: util::res_dependent_expr(corner_radius, util::DEFAULT_CONTEXT.clone(), util::ResExprType::HeightBased)?
+        })
+    }
+}
+
+pub struct TextPlaceholderExpr<'a> {
+    /// The function for evaluating the expression's value.
+    pub(self) expr: Box<dyn Fn(&[f64]) -> f64 + 'a>,
+    /// The string the expression was parsed from.
+    /// 
+    /// Used for debugging.
+    pub(self) base_string: String,
+    /// The context that was used to construct the evaluation function.
     /// 
     /// Used to recreate the function when cloning.
     pub(self) base_context: &'a meval::Context<'a>,
@@ -451,13 +1052,6 @@ impl<'a> Clone for TextPlaceholderExpr<'a> {
         Self::parse(&self.base_string, self.base_context)
     }
 }
-impl<'a> Drop for TextPlaceholderExpr<'a> {
-    fn drop(&mut self) {
-        let boxed_expr = unsafe {
-            Box::from_raw(self.expr as *mut dyn Fn(&[f64]) -> f64)
-        };
-    }
-}
 impl<'a> Debug for TextPlaceholderExpr<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "TextPlaceholder({})", self.base_string)
@@ -487,7 +1081,7 @@ impl<'a> TextPlaceholderExpr<'a> {
         let expr: meval::Expr = expr_string.as_str().parse().unwrap();
         let ctx = Self::modify_context(context.clone());
         let func = expr.bindn_with_context(ctx, FUNC_VARS.as_slice()).unwrap();
-        TextPlaceholderExpr { expr: Box::leak(Box::new(func)), base_string: expr_string, base_context: context }
+        TextPlaceholderExpr { expr: Box::new(func), base_string: expr_string, base_context: context }
     }
 
     pub fn call(&self, width: f64, height: f64, time: f64) -> f64 {
@@ -509,156 +1103,303 @@ impl<'a> TextPlaceholderExpr<'a> {
 
 use regex::Regex;
 use once_cell::sync::Lazy;
+use indexmap::IndexMap;
 
-const PLACEHOLDER_REGEX: Lazy<Regex> = Lazy::new(||Regex::new(r"\{((?<padchar>[^:])(?<paddir>[<>])(?<padamount>\d+))?\{(?<name>[^}]*)\}\}").unwrap());
+const PLACEHOLDER_REGEX: Lazy<Regex> = Lazy::new(||Regex::new(r"\{((?<padchar>[^:])(?<paddir>[<>])(?<padamount>\d+))?\{(?<name>[^}:]*)(:(?<spec>[^}]*))?\}\}").unwrap());
 
-impl<'a> Text<'a> {
-    pub const PLACEHOLDER_AMOUNT: usize = 64;
+/// The characters that can be escaped with a backslash in text markup (e.g. `\*` for a literal
+/// asterisk).
+const ESCAPABLE_CHARS: [char; 8] = ['\\', '*', '_', '`', '~', '^', '{', '}'];
 
-    fn parse<S: AsRef<str>>(string: String, base_size: util::ResolutionDependentExpr, base_font: S, bold: bool, italic: bool, color: util::ExprVector<4>, font_list: &'static HashMap<String, Rc<RefCell<TextFont>>>) -> Result<Vec<TextPart>, PropertyError> {
-        use regex::Captures;
-        use std::sync::OnceLock;
-        lazy_static::lazy_static! {
-            static ref BOLD_REGEX: Regex = Regex::new(r"\*\*(?<content>.+?)\*\*").unwrap();
-            static ref ITALIC_REGEX: Regex = Regex::new(r"\*(?<content>.+?)\*").unwrap();
-            static ref FONT_REGEX: Regex = Regex::new(r"_(?<font>.+?)_(?<content>.+?)__").unwrap();
-            static ref COLOR_REGEX: Regex = Regex::new(r"`(?<r>[^;`]+);\s*(?<g>[^;`]+);\s*(?<b>[^;`]+)(;\s*(?<a>[^;`]+))?`(?<content>.+?)``").unwrap();
-            static ref SIZE_REGEX: Regex = Regex::new(r"~(?<size>[^~]+?)~(?<content>.+?)~~").unwrap();
-        }
-        static REGEXES: OnceLock<[Regex; 5]> = OnceLock::new();
-        if REGEXES.get().is_none() {
-            REGEXES.set([
-                SIZE_REGEX.clone(),
-                COLOR_REGEX.clone(),
-                FONT_REGEX.clone(),
-                BOLD_REGEX.clone(),
-                ITALIC_REGEX.clone(), 
-            ]).map_err(|_| "error initializing regex list").unwrap();
-        }
-
-        let regex_error_fn = |str: &'static str| { PropertyError::SyntaxError(
-            "Text".to_owned(),
-            "text".to_owned(),
-            Some(str.to_owned())) };
-
-        let regex_fns: [Box<dyn Fn(&mut TextPart, &Captures, &'static HashMap<String, Rc<RefCell<TextFont>>>) -> Result<(), PropertyError>>; 5] = [
-            Box::new(|part, captures, fonts| {
-                let size = captures.name("size")
-                    .ok_or((regex_error_fn)("No size expression in size redefinition!"))?
-                    .as_str().to_string();
-                part.set_size(size)
-            }),
-            Box::new(|part, captures, fonts| {
+/// The character marking a place a word can be hyphenated at.
+const SOFT_HYPHEN: char = '\u{AD}';
 
-                let error_msg = (regex_error_fn)("Invalid or missing color tuple in color redefinition!");
+/// Returns the character an escape sequence at the start of a string stands for, together with
+/// the sequence's length in bytes.
+/// 
+/// Besides the [`ESCAPABLE_CHARS`], manual line breaks (`\n`) and soft hyphens (`\-`) can be
+/// written as escape sequences.
+fn escape_sequence(text: &str) -> Option<(char, usize)> {
+    let c = text.strip_prefix('\\')?.chars().next()?;
+    let escaped = match c {
+        'n' => '\n',
+        '-' => SOFT_HYPHEN,
+        c if ESCAPABLE_CHARS.contains(&c) => c,
+        _ => return None
+    };
+    Some((escaped, 1 + c.len_utf8()))
+}
 
-                let alpha = match part {
-                    TextPart::Text { text: _, bold: _, italic: _, color, size: _, font: _ } => {
-                        match &color.list[3] {
-                            util::ResolutionDependentExpr::MathExpr { expr, base_string, base_context, base_expr_type } => base_string.clone(),
-                            util::ResolutionDependentExpr::LuaExpr(f, s) => s.clone()
-                        }
-                    },
-                    _ => "1.0".to_owned()
-                };
+/// Returns the position of the first occurrence of a character that isn't part of an escape
+/// sequence, in bytes.
+fn find_unescaped(text: &str, needle: char) -> Option<usize> {
+    let mut pos = 0;
+    while let Some(c) = text[pos..].chars().next() {
+        match escape_sequence(&text[pos..]) {
+            Some((_, len)) => pos += len,
+            None if c == needle => return Some(pos),
+            None => pos += c.len_utf8()
+        }
+    }
+    None
+}
+
+/// Replaces the escape sequences in a string with the characters they stand for.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(c) = text[pos..].chars().next() {
+        let (c, len) = escape_sequence(&text[pos..]).unwrap_or((c, c.len_utf8()));
+        unescaped.push(c);
+        pos += len;
+    }
+    unescaped
+}
 
-                let r = captures.name("r").ok_or(error_msg.clone())?.as_str();
-                let g = captures.name("g").ok_or(error_msg.clone())?.as_str();
-                let b = captures.name("b").ok_or(error_msg)?.as_str();
-                let a = captures.name("a").map(|m|m.as_str()).unwrap_or(alpha.as_str());
+/// Matches a placeholder at the start of a string.
+static PLACEHOLDER_START_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(&format!("^(?:{})", PLACEHOLDER_REGEX.as_str())).unwrap());
 
-                part.set_color(format!("{};{};{};{}",r,g,b,a))
-            }),
-            Box::new(|part, captures, fonts| {
-                let f = fonts
-                    .get(captures.name("font")
-                        .ok_or((regex_error_fn)("No font name in font redefinition!"))?
-                        .as_str()
-                    ).ok_or((regex_error_fn)("Invalid font name in font redefinition!"))?;
-                part.set_font(f.clone())
-            }),
-            Box::new(|part, captures, fonts| part.set_bold(true)),
-            Box::new(|part, captures, fonts| part.set_italic(true)),
-        ];
+/// The maximum depth styles in text markup can be nested to.
+const MAX_MARKUP_DEPTH: usize = 32;
 
-        let mut vec = vec![ TextPart::Text { text: string.as_str().into(), bold, italic, color, size: base_size, font: font_list.get(base_font.as_ref()).unwrap().clone() } ];
+/// Applies the header of a styled section (e.g. the font name of a font change) to a style.
+type MarkupStyleFn = fn(&mut TextPart, &str, &'static HashMap<String, Rc<RefCell<TextFont>>>) -> Result<(), PropertyError>;
 
-        let mut construct_vec = Vec::new();
+/// A recursive parser for the markup of a line of text.
+/// 
+/// Styled sections can be nested in any order (e.g. ``**bold `1;0;0`and red``**``). An opening
+/// sequence without a matching closing sequence is kept as literal text, while invalid sizes,
+/// colors or font names are errors containing their position in the line. Escape sequences (see
+/// [`escape_sequence`]) are turned into the characters they stand for while parsing, so they
+/// never start or end a section or placeholder.
+struct MarkupParser<'s> {
+    text: &'s str,
+    /// The current position in the text in bytes.
+    pos: usize,
+    depth: usize,
+    fonts: &'static HashMap<String, Rc<RefCell<TextFont>>>
+}
 
-        for (i, regex) in REGEXES.get().unwrap().iter().enumerate() {
-            for text_part in vec.into_iter() {
-                match text_part {
-                    TextPart::Text { ref text, bold, italic, color, size, font } => {
-                        let mut last_match_end: usize = 0;
-                        for text_captures in regex.captures_iter(text) {
-                            let text_match = text_captures.get(0).unwrap();
-                            let text_content = text_captures.name("content").expect("No content matched! This shouldn't happen!");
-                            construct_vec.push(TextPart::Text { text: text[last_match_end..text_match.start()].into(), bold, italic, color: color.clone(), size: size.clone(), font: font.clone() });
-                            let mut modified = TextPart::Text { text: text[text_content.start()..text_content.end()].into(), bold, italic, color: color.clone(), size: size.clone(), font: font.clone() };
-                            (regex_fns[i])(&mut modified, &text_captures, font_list)?;
-                            construct_vec.push(modified);
-                            last_match_end = text_match.end();
-                        }
-                        construct_vec.push(TextPart::Text { text: text[last_match_end..].into(), bold, italic, color: color.clone(), size: size.clone(), font })
-                    },
-                    _ => construct_vec.push(text_part)
-                }
-            }
-            vec = std::mem::replace(&mut construct_vec, Vec::new());
+impl<'s> MarkupParser<'s> {
+    fn new(text: &'s str, fonts: &'static HashMap<String, Rc<RefCell<TextFont>>>) -> Self {
+        MarkupParser { text, pos: 0, depth: 0, fonts }
+    }
+
+    /// Parses the whole text into parts, starting out with the given style (a [`TextPart::Text`]
+    /// whose text gets ignored).
+    fn parse(mut self, style: &TextPart) -> Result<Vec<TextPart>, PropertyError> {
+        Ok(self.parse_until(style, None)?.unwrap_or_default())
+    }
+
+    fn rest(&self) -> &'s str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, pos: usize, message: String) -> PropertyError {
+        let column = self.text[..pos].chars().count() + 1;
+        PropertyError::SyntaxError("Text".to_owned(), "text".to_owned(), Some(format!("{message} (at column {column})")))
+    }
+
+    /// Creates a part containing text in a style.
+    fn styled_text(style: &TextPart, string: String) -> TextPart {
+        let mut part = style.clone();
+        if let TextPart::Text { text, .. } = &mut part {
+            *text = string;
         }
+        part
+    }
 
-        // Find any placeholders and split them from the rest of the text.
-        for text_part in vec.into_iter() {
-            match text_part {
-                TextPart::Text { text, bold, italic, color, size, font } => {
-                    let mut leftover_text = text.clone();
-                    let mut placeholders_exist = true;
-                    while placeholders_exist {
-                        if let Some(capture) = PLACEHOLDER_REGEX.captures_iter(leftover_text.clone().leak()).next() {
-                            let placeholder_match = capture.get(0).unwrap();
-                            let index = capture.name("name").expect("No placeholder name matched! This shouldn't happen!").as_str();
-                            let padchar = capture.name("padchar").map(|m| m.as_str()).unwrap_or(" ");
-                            let padamount = capture.name("padamount").map(|m| {
-                                m.as_str().parse::<i32>().map_err(|_| {
-                                    (regex_error_fn)("Invalid placeholder padding amount!")
-                                })
-                            }).unwrap_or(Ok(0))?;
-                            let paddir = capture.name("paddir").map(|m| m.as_str()).unwrap_or("<");
-                            
-                            let (before, after) = (&leftover_text[..placeholder_match.start()], &leftover_text[placeholder_match.end()..]);
-    
-                            construct_vec.push(TextPart::Text { text: before.to_owned(), bold, italic, color: color.clone(), size: size.clone(), font: font.clone() });
-    
-                            construct_vec.push(TextPart::Placeholder {
-                                index: index.to_owned(),
-                                pad_char: padchar.chars().next().unwrap_or(' '),
-                                pad_amount: padamount as i8,
-                                bold,
-                                italic,
-                                color: color.clone(),
-                                size: size.clone(),
-                                font: font.clone()
-                            });
-
-                            leftover_text = after.to_owned();
-                        } else {
-                            placeholders_exist = false;
+    /// Creates a placeholder in a style from it's match of [`PLACEHOLDER_START_REGEX`] at the
+    /// current position.
+    fn placeholder(&self, style: &TextPart, capture: &regex::Captures) -> Result<TextPart, PropertyError> {
+        let index = capture.name("name").expect("No placeholder name matched! This shouldn't happen!").as_str();
+        // The older '{0>5{name}}' syntax only specifies the padding, which gets converted into an
+        // equivalent format spec
+        let spec = match (capture.name("spec"), capture.name("padchar"), capture.name("paddir"), capture.name("padamount")) {
+            (Some(spec), _, _, _) => spec.as_str().to_owned(),
+            (None, Some(padchar), Some(paddir), Some(padamount)) => format!("{}{}{}", padchar.as_str(), paddir.as_str(), padamount.as_str()),
+            _ => String::new()
+        };
+        // Some placeholders take their own kind of format instead (e.g. the chrono format string
+        // of the date and time placeholders)
+        let format = match PlaceholderFormat::parse(&spec) {
+            Some(format) => format,
+            None if super::placeholders::accepts_format(index, &spec) => PlaceholderFormat::verbatim(&spec),
+            None => return Err(self.error(self.pos, format!("Invalid placeholder format '{spec}'")))
+        };
+
+        match style.clone() {
+            TextPart::Text { bold, italic, color, size, voffset, font, .. } => Ok(TextPart::Placeholder { index: index.to_owned(), format, bold, italic, color, size, voffset, font }),
+            _ => unreachable!("styles are always text parts")
+        }
+    }
+
+    /// Parses text in a style until the closing sequence (or the end of the text if there is
+    /// none).
+    /// 
+    /// Returns [`None`] if the text ends before the closing sequence.
+    fn parse_until(&mut self, style: &TextPart, closing: Option<&str>) -> Result<Option<Vec<TextPart>>, PropertyError> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+
+        loop {
+            let rest = self.rest();
+
+            let end = match closing {
+                // `**` starts a bold section instead of ending the italic one if it gets closed
+                // later on, like the longer sequence gets matched first when opening a section
+                Some("*") if rest.starts_with("**") => {
+                    let start = self.pos;
+                    match self.section(style)? {
+                        Some(section) => {
+                            if !literal.is_empty() {
+                                parts.push(Self::styled_text(style, std::mem::take(&mut literal)));
+                            }
+                            parts.extend(section);
+                            continue
+                        },
+                        None => {
+                            self.pos = start + 1;
+                            true
                         }
                     }
-                    if leftover_text.len()>0 {
-                        construct_vec.push(TextPart::Text { text: leftover_text, bold, italic, color: color.clone(), size: size.clone(), font: font.clone() });
+                },
+                Some(closing) if rest.starts_with(closing) => {
+                    self.pos += closing.len();
+                    true
+                },
+                _ => rest.is_empty()
+            };
+            if end {
+                if !literal.is_empty() {
+                    parts.push(Self::styled_text(style, literal));
+                }
+                return Ok(match (rest.is_empty(), closing) {
+                    (true, Some(_)) => None,
+                    _ => Some(parts)
+                })
+            }
+
+            if let Some((escaped, len)) = escape_sequence(rest) {
+                literal.push(escaped);
+                self.pos += len;
+                continue
+            }
+
+            if let Some(capture) = PLACEHOLDER_START_REGEX.captures(rest) {
+                if !literal.is_empty() {
+                    parts.push(Self::styled_text(style, std::mem::take(&mut literal)));
+                }
+                parts.push(self.placeholder(style, &capture)?);
+                self.pos += capture.get(0).unwrap().end();
+                continue
+            }
+
+            let start = self.pos;
+            let c = rest.chars().next().unwrap();
+            match self.section(style)? {
+                Some(section) => {
+                    if !literal.is_empty() {
+                        parts.push(Self::styled_text(style, std::mem::take(&mut literal)));
                     }
+                    parts.extend(section);
                 },
-                _ => construct_vec.push(text_part)
+                None => {
+                    self.pos = start + c.len_utf8();
+                    literal.push(c);
+                }
             }
         }
-        vec = std::mem::replace(&mut construct_vec, Vec::new());
+    }
+
+    /// Parses a styled section at the current position.
+    /// 
+    /// Returns [`None`] if there's no (complete) styled section there.
+    fn section(&mut self, style: &TextPart) -> Result<Option<Vec<TextPart>>, PropertyError> {
+        let rest = self.rest();
+
+        // The opening sequence, the character ending the section's header (if it has one), the
+        // closing sequence, what the header contains and how the style gets changed
+        let (opening, header_end, closing, what, apply): (&str, Option<char>, &str, &str, MarkupStyleFn) = match rest.chars().next() {
+            Some('*') if rest.starts_with("**") => ("**", None, "**", "bold", |part, _, _| part.set_bold(true)),
+            Some('*') => ("*", None, "*", "italic", |part, _, _| part.set_italic(true)),
+            Some('~') => ("~", Some('~'), "~~", "size", |part, size, _| part.set_size(size.to_owned())),
+            Some('^') => ("^", Some('^'), "^^", "vertical offset", |part, offset, _| part.set_voffset(offset.to_owned())),
+            Some('`') => ("`", Some('`'), "``", "color", |part, header, _| {
+                // Keep the current alpha value if the color doesn't have one
+                let alpha = match part {
+                    TextPart::Text { color, .. } => match &color.list[3] {
+                        util::ResolutionDependentExpr::MathExpr { base_string, .. } => base_string.clone(),
+                        util::ResolutionDependentExpr::LuaExpr(_, s, _) => s.clone()
+                    },
+                    _ => "1.0".to_owned()
+                };
+
+                let components = header.split(';').map(|c| c.trim()).collect::<Vec<_>>();
+                match components.len() {
+                    3 => part.set_color(format!("{};{};{};{alpha}", components[0], components[1], components[2])),
+                    4 => part.set_color(components.join(";")),
+                    _ => Err(PropertyError::MismatchedExprCount)
+                }
+            }),
+            Some('_') => ("_", Some('_'), "__", "font name", |part, name, fonts| {
+                part.set_font(fonts.get(name).ok_or(PropertyError::UnknownFont(name.to_owned()))?.clone())
+            }),
+            _ => return Ok(None)
+        };
+
+        let start = self.pos;
+        self.pos += opening.len();
+
+        let header = match header_end {
+            Some(header_end) => match find_unescaped(self.rest(), header_end) {
+                Some(end) if end > 0 => {
+                    let header = unescape(&self.rest()[..end]);
+                    self.pos += end + header_end.len_utf8();
+                    header
+                },
+                _ => return Ok(None)
+            },
+            None => String::new()
+        };
+
+        // Sections need some content and a closing sequence; checking for the closing sequence
+        // first avoids parsing the rest of the line again for every unclosed opening sequence
+        if !self.rest().contains(closing) || self.rest().starts_with(closing) {
+            return Ok(None)
+        }
+
+        if self.depth >= MAX_MARKUP_DEPTH {
+            return Err(self.error(start, "Styles are nested too deeply".to_owned()))
+        }
+
+        let mut inner = style.clone();
+        apply(&mut inner, &header, self.fonts).map_err(|_| self.error(start, format!("Invalid {what} '{header}'")))?;
+
+        self.depth += 1;
+        let parts = self.parse_until(&inner, Some(closing))?;
+        self.depth -= 1;
+
+        Ok(parts)
+    }
+}
+
+impl<'a> Text<'a> {
+    pub const PLACEHOLDER_AMOUNT: usize = 64;
+
+    fn parse<S: AsRef<str>>(string: String, base_size: util::ResolutionDependentExpr, base_font: S, bold: bool, italic: bool, color: util::ExprVector<4>, font_list: &'static HashMap<String, Rc<RefCell<TextFont>>>) -> Result<Vec<TextPart>, PropertyError> {
+        let string = string.replace("&shy;", &SOFT_HYPHEN.to_string());
+
+        let font = font_list.get(base_font.as_ref()).ok_or(PropertyError::UnknownFont(base_font.as_ref().to_owned()))?.clone();
+        let voffset = util::res_dependent_expr("0", util::DEFAULT_CONTEXT.clone(), util::ResExprType::HeightBased)?;
+        let style = TextPart::Text { text: String::new(), bold, italic, color, size: base_size, voffset, font };
+        let mut vec = MarkupParser::new(&string, font_list).parse(&style)?;
 
         // Add tabs
-        construct_vec = Vec::new();
+        let mut construct_vec = Vec::new();
         for text_part in vec.into_iter() {
             match text_part {
-                TextPart::Text { text, bold, italic, color, size, font } => {
+                TextPart::Text { text, bold, italic, color, size, voffset, font } => {
                     let mut new_text_parts = vec![text.clone()];
                     while new_text_parts[new_text_parts.len()-1].find('\t').is_some() && new_text_parts[new_text_parts.len()-1].len()>1 {
                         let i = new_text_parts[new_text_parts.len()-1].find('\t').unwrap();
@@ -673,7 +1414,29 @@ impl<'a> Text<'a> {
                         if &txt == "\t" {
                             construct_vec.push(TextPart::Tab);
                         } else {
-                            construct_vec.push(TextPart::Text { text: txt, bold, italic, color: color.clone(), size: size.clone(), font: font.clone() });
+                            construct_vec.push(TextPart::Text { text: txt, bold, italic, color: color.clone(), size: size.clone(), voffset: voffset.clone(), font: font.clone() });
+                        }
+                    }
+                },
+                _ => construct_vec.push(text_part)
+            }
+        }
+        vec = std::mem::replace(&mut construct_vec, Vec::new());
+
+        // Add manual line breaks and soft hyphens
+        construct_vec = Vec::new();
+        for text_part in vec.into_iter() {
+            match text_part {
+                TextPart::Text { text, bold, italic, color, size, voffset, font } => {
+                    for (i, line) in text.split('\n').enumerate() {
+                        if i>0 {
+                            construct_vec.push(TextPart::NewLine);
+                        }
+                        for (j, txt) in line.split(SOFT_HYPHEN).enumerate() {
+                            if j>0 {
+                                construct_vec.push(TextPart::SoftHyphen { bold, italic, color: color.clone(), size: size.clone(), voffset: voffset.clone(), font: font.clone() });
+                            }
+                            construct_vec.push(TextPart::Text { text: txt.to_owned(), bold, italic, color: color.clone(), size: size.clone(), voffset: voffset.clone(), font: font.clone() });
                         }
                     }
                 },
@@ -687,11 +1450,11 @@ impl<'a> Text<'a> {
             construct_vec = Vec::new();
             for text_part in vec.into_iter() {
                 match text_part {
-                    TextPart::Text { text, bold, italic, color, size, font } => {
+                    TextPart::Text { text, bold, italic, color, size, voffset, font } => {
                         let split = text.split(c).collect::<Vec<&str>>();
 
                         for (i, &txt) in split.iter().enumerate() {
-                            construct_vec.push(TextPart::Text { text: txt.into(), bold, italic, color: color.clone(), size: size.clone(), font: font.clone() });
+                            construct_vec.push(TextPart::Text { text: txt.into(), bold, italic, color: color.clone(), size: size.clone(), voffset: voffset.clone(), font: font.clone() });
                             if i<split.len()-1 {
                                 construct_vec.push(TextPart::Space { size: size.clone(), font: font.clone() });
                             }
@@ -705,7 +1468,7 @@ impl<'a> Text<'a> {
 
         // Remove any strings of zero length
         vec = vec.into_iter().filter(|p| match &p {
-            TextPart::Text { text, bold, italic, color, size, font } => text.len()>0,
+            TextPart::Text { text, bold, italic, color, size, voffset, font } => text.len()>0,
             _ => true
         }).collect();
 
@@ -723,75 +1486,92 @@ impl<'a> Text<'a> {
     where
         TextStr: Into<String>,
         TxtAlignStr: Into<String>, {
-        let mut text_parts = Vec::new();
+        Self::with_variants(base, vec![(String::new(), text)], base_font, font_list, placeholders, text_alignment)
+    }
+
+    /// Creates a text object with variants of it's text in multiple languages.
+    /// 
+    /// Takes a list of language codes and the lines of text in that language. The variant of the
+    /// active language (see [`super::set_language`]) gets shown, falling back to the first one.
+    pub fn with_variants<TextStr, TxtAlignStr>(
+        base: BaseProperties,
+        variants: Vec<(String, Vec<TextStr>)>,
+        base_font: String,
+        font_list: &'static HashMap<String, Rc<RefCell<TextFont>>>,
+        placeholders: HashMap<String, TextPlaceholderExpr<'a>>,
+        text_alignment: TxtAlignStr
+    ) -> Result<Text<'a>, PropertyError>
+    where
+        TextStr: Into<String>,
+        TxtAlignStr: Into<String>, {
+        if variants.is_empty() {
+            return Err(PropertyError::SyntaxError("Text".to_owned(), "text".to_owned(), Some("No text specified!".to_owned())))
+        }
 
         let size_expr = &base.size.list[1];
 
         let col_expr = &base.color;
 
-        for into_string in text {
-            let string: String = into_string.into();
+        let mut text_variants = IndexMap::with_capacity(variants.len());
+
+        for (language, text) in variants {
+            let mut text_parts = Vec::new();
+            let mut raw_text = Vec::with_capacity(text.len());
+
+            for into_string in text {
+                let string: String = into_string.into();
+                raw_text.push(string.clone());
+
+                for part in Text::parse(string, size_expr.clone(), base_font.clone(), false, false, col_expr.clone(), font_list)? {
+                    text_parts.push(part);
+                }
 
-            for part in Text::parse(string, size_expr.clone(), base_font.clone(), false, false, col_expr.clone(), font_list)? {
-                text_parts.push(part);
+                text_parts.push(TextPart::NewLine);
             }
 
-            text_parts.push(TextPart::NewLine);
+            text_variants.insert(language, TextVariant { parts: text_parts, raw_text });
         }
 
-        // DEBUG: Check if the parsed text actually got parsed correctly
-        // println!("{:?}",text_parts);
-
-        // Text {
-        //     pos: util::parse_expression_list(<PosStr as Into<String>>::into(pos), &util::DEFAULT_CONTEXT).try_into().unwrap(),
-        //     text: text_parts,
-        //     wrapping_width: util::res_dependent_expr(<WidthStr as Into<String>>::into(wrapping_width), &util::DEFAULT_CONTEXT, util::ResExprType::WidthBased),
-        //     size: size_expr,
-        //     alignment: <AlignStr as Into<String>>::into(alignment).into(),
-        //     text_alignment: format!("TOP_{}",<TxtAlignStr as Into<String>>::into(text_alignment)).into(),
-        //     placeholders, }
         Ok(Text {
             base,
-            text: text_parts,
+            variants: text_variants,
             text_alignment: format!("TOP_{}",<TxtAlignStr as Into<String>>::into(text_alignment)).try_into()?,
-            placeholders
+            placeholders,
+            background: None
         })
     }
 
-    fn pad_num<'b>(num: f64, pad_amount: i8, pad_char: char, pad_dir_str: &str) -> &'b str {
-        let numstr = num.to_string();
-        let mut padstr = String::new();
-        if pad_amount-numstr.len() as i8 > 0 {
-            for _ in 0..pad_amount as usize-numstr.len() {
-                padstr.push(pad_char);
-            }
-        }
-        match pad_dir_str {
-            "<" => format!("{padstr}{numstr}").leak(),
-            ">" => format!("{numstr}{padstr}").leak(),
-            _ => padstr.leak()
-        }
+    /// Sets the card drawn behind the text.
+    pub fn set_background(&mut self, background: Option<TextBackground>) {
+        self.background = background;
     }
-}
 
-impl<'a> Renderable for Text<'a> {
-    fn get_base_properties(&self) -> &BaseProperties {
-        &self.base
+    /// Returns the variant of the text in the active language, or the first variant if there is
+    /// none in that language.
+    fn active_variant(&self) -> &TextVariant {
+        super::language()
+            .and_then(|language| self.variants.get(&language))
+            .or_else(|| self.variants.first().map(|(_, variant)| variant))
+            .expect("text objects always have at least one variant")
     }
 
-    fn copy<'b>(&self) -> Box<dyn Renderable + 'b> {
-        let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable + 'a) as *mut (dyn Renderable + 'a);
-        unsafe {
-            let result_ptr = std::mem::transmute::<*mut (dyn Renderable + 'a), *mut (dyn Renderable + 'b)>(leaked);
-            Box::from_raw(result_ptr)
+    /// Returns the names of all placeholders used in the text that are neither defined nor
+    /// built-in.
+    pub fn undefined_placeholders(&self) -> Vec<&str> {
+        let mut undefined: Vec<&str> = Vec::new();
+        for part in self.variants.values().flat_map(|variant| variant.parts.iter()) {
+            if let TextPart::Placeholder { index, .. } = part {
+                let defined = self.placeholders.contains_key(index) || super::placeholders::provided(index);
+                if !defined && !undefined.contains(&index.as_str()) {
+                    undefined.push(index);
+                }
+            }
         }
+        undefined
     }
 
-    fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
-        const ITALIC_ADVANCE_FAC: f64 = 0.10;
-
-        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
-
+    /// Lays out the lines of the active variant for a frame, without drawing them.
+    fn layout(&self, time: f64, view_size: [f64; 2], object_repr: &HashMap<String, mlua::Value>) -> anyhow::Result<TextLayout> {
         fn expr_to_f(e: util::ExprEval) -> Option<f64> {
             match e {
                 util::ExprEval::F64(f) => Some(f),
@@ -799,22 +1579,34 @@ impl<'a> Renderable for Text<'a> {
             }
         }
 
-        let view_size = context.get_view_size();
-        let max_width = expr_to_f(self.base.size.list[0].evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
-        let mut current_pos = self.base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
-        let alignment: (f64, f64) = self.base.alignment.into();
-        let text_align: f64 = self.text_alignment.multipliers().0;
-        
-        let default_size = expr_to_f(self.base.size.list[1].evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let max_width = expr_to_f(self.base.size.list[0].evaluate(view_size[0], view_size[1], time, object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let default_size = expr_to_f(self.base.size.list[1].evaluate(view_size[0], view_size[1], time, object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+
+        let text_parts = &self.active_variant().parts;
+
+        /// Returns the height of a line and the distance of it's baseline from it's top.
+        fn line_extent(ascent: f64, descent: f64, min_height: f64) -> (f64, f64) {
+            let height = (ascent + descent).max(min_height);
+            (height, height - descent)
+        }
 
         let mut height = 0.0;
-        let mut line_widths: Vec<f64> = Vec::with_capacity(self.text.len()/2+4);
-        let mut line_heights: Vec<f64> = Vec::with_capacity(self.text.len()/8);
+        let mut line_widths: Vec<f64> = Vec::with_capacity(text_parts.len()/2+4);
+        let mut line_heights: Vec<f64> = Vec::with_capacity(text_parts.len()/8);
+        let mut line_baselines: Vec<f64> = Vec::with_capacity(text_parts.len()/8);
         let mut curr_width = 0.0;
-        let mut curr_max_height = default_size;
+        // How far the parts of the current line reach above and below the baseline
+        let mut curr_ascent: f64 = 0.0;
+        let mut curr_descent: f64 = 0.0;
+        // The index and width of the soft hyphen directly in front of the current part
+        let mut soft_hyphen: Option<(usize, f64)> = None;
+        // The soft hyphens at the end of a line, which get shown as a hyphen
+        let mut hyphenated: Vec<usize> = Vec::new();
+        let mut placeholder_texts: HashMap<usize, String> = HashMap::new();
 
         // Calculate the dimensions of the object for the alignment
-        for part in self.text.iter() {
+        for (i, part) in text_parts.iter().enumerate() {
+            let previous_soft_hyphen = soft_hyphen.take();
             match part {
                 TextPart::Tab => {
                     let size_incs = default_size*12.0;
@@ -823,26 +1615,40 @@ impl<'a> Renderable for Text<'a> {
                     }
                 },
                 TextPart::NewLine => {
+                    let (line_height, baseline) = line_extent(curr_ascent, curr_descent, default_size);
                     line_widths.push(curr_width);
-                    line_heights.push(curr_max_height);
+                    line_heights.push(line_height);
+                    line_baselines.push(baseline);
 
-                    height += curr_max_height;
+                    height += line_height;
                     curr_width = 0.0;
-                    curr_max_height = default_size;
+                    curr_ascent = 0.0;
+                    curr_descent = 0.0;
+                },
+                TextPart::SoftHyphen { bold, italic, color, size, voffset, font } => {
+                    let part_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+                    let hyphen_width = match bold {
+                        false => font.borrow_mut().base_font.size("-", part_size).0,
+                        true => font.borrow_mut().bold_font.size("-", part_size).0
+                    };
+                    soft_hyphen = Some((i, hyphen_width));
                 },
                 TextPart::Space { size, font } => {
-                    let part_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;;
-                    if part_size>curr_max_height { curr_max_height = part_size; }
+                    let part_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;;
+
+                    let (ascent, descent) = font.borrow().base_font.line_metrics(part_size);
+                    curr_ascent = curr_ascent.max(ascent);
+                    curr_descent = curr_descent.max(descent);
 
                     let width = font.borrow_mut().base_font.size(" ", part_size).0;
                     if curr_width+width<=max_width {
                         curr_width += width as f64;
                     }
                 },
-                TextPart::Text { text, bold, italic, color, size, font } => {
+                TextPart::Text { text, bold, italic, color, size, voffset, font } => {
 
-                    let part_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
-                    if part_size>curr_max_height { curr_max_height = part_size; }
+                    let part_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+                    let part_voffset = expr_to_f(voffset.evaluate(view_size[0], view_size[1], time, object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
                     let mut part_width;
                     match bold {
                         false => { part_width = font.borrow_mut().base_font.size(text, part_size).0 },
@@ -852,34 +1658,39 @@ impl<'a> Renderable for Text<'a> {
                         part_width += part_size * ITALIC_ADVANCE_FAC;
                     }
                     if curr_width+part_width>max_width {
-                        height += curr_max_height;
+                        if let Some((index, hyphen_width)) = previous_soft_hyphen {
+                            curr_width += hyphen_width;
+                            hyphenated.push(index);
+                        }
+                        let (line_height, baseline) = line_extent(curr_ascent, curr_descent, default_size);
+                        height += line_height;
                         line_widths.push(curr_width);
-                        line_heights.push(curr_max_height);
+                        line_heights.push(line_height);
+                        line_baselines.push(baseline);
                         curr_width = 0.0;
-                        curr_max_height = default_size;
+                        curr_ascent = 0.0;
+                        curr_descent = 0.0;
                     }
                     curr_width += part_width;
+
+                    let (ascent, descent) = font.borrow().base_font.line_metrics(part_size);
+                    curr_ascent = curr_ascent.max(ascent + part_voffset);
+                    curr_descent = curr_descent.max(descent - part_voffset);
                 },
-                TextPart::Placeholder { index, pad_char, pad_amount, bold, italic, color, size, font } => {
-                    match self.placeholders.get(index) {
-                        Some(expr) => {
-                            let part_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;;
-                            if curr_max_height<part_size { curr_max_height = part_size; }
+                TextPart::Placeholder { index, format, bold, italic, color, size, voffset, font } => {
+                    match self.placeholder_text(index, format, view_size[0], view_size[1], time) {
+                        Some(text) => {
+                            let part_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;;
+                            let part_voffset = expr_to_f(voffset.evaluate(view_size[0], view_size[1], time, object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
 
                             let mut part_width;
                             
-                            let val = expr.call(view_size[0], view_size[1], time);
-
-                            let pad_dir_str = if *pad_amount<0 {
-                                "<"
-                            } else {
-                                ">"
-                            };
+                            let text = format.apply(text);
 
                             part_width = if *bold {
-                                font.borrow_mut().bold_font.size(Self::pad_num(val, pad_amount.abs(), *pad_char, pad_dir_str), part_size).0
+                                font.borrow_mut().bold_font.size(&text, part_size).0
                             } else {
-                                font.borrow_mut().base_font.size(Self::pad_num(val, pad_amount.abs(), *pad_char, pad_dir_str), part_size).0
+                                font.borrow_mut().base_font.size(&text, part_size).0
                             };
                             
                             if *italic {
@@ -887,13 +1698,26 @@ impl<'a> Renderable for Text<'a> {
                             }
 
                             if curr_width+part_width>max_width {
-                                height += curr_max_height;
+                                if let Some((index, hyphen_width)) = previous_soft_hyphen {
+                                    curr_width += hyphen_width;
+                                    hyphenated.push(index);
+                                }
+                                let (line_height, baseline) = line_extent(curr_ascent, curr_descent, default_size);
+                                height += line_height;
                                 line_widths.push(curr_width);
-                                line_heights.push(curr_max_height);
+                                line_heights.push(line_height);
+                                line_baselines.push(baseline);
                                 curr_width = 0.0;
-                                curr_max_height = default_size;
+                                curr_ascent = 0.0;
+                                curr_descent = 0.0;
                             }
                             curr_width += part_width;
+
+                            let (ascent, descent) = font.borrow().base_font.line_metrics(part_size);
+                            curr_ascent = curr_ascent.max(ascent + part_voffset);
+                            curr_descent = curr_descent.max(descent - part_voffset);
+
+                            placeholder_texts.insert(i, text);
                         },
                         None => {}
                     }
@@ -903,14 +1727,95 @@ impl<'a> Renderable for Text<'a> {
 
         line_widths.push(0.0);
         line_heights.push(default_size);
+        line_baselines.push(default_size);
+
+        Ok(TextLayout { max_width, default_size, height, line_widths, line_heights, line_baselines, hyphenated, placeholder_texts })
+    }
+
+    /// Returns the text a placeholder gets replaced with.
+    /// 
+    /// Placeholders defined in the document get evaluated by the object itself, all others come
+    /// from the registered placeholder sources (see [`super::placeholders`]). Undefined
+    /// placeholders get replaced with a visible `⟨missing: name⟩` marker and a warning gets
+    /// logged once.
+    fn placeholder_text(&self, index: &str, format: &PlaceholderFormat, width: f64, height: f64, time: f64) -> Option<String> {
+        if let Some(expr) = self.placeholders.get(index) {
+            return Some(expr.call(width, height, time).to_string())
+        }
+
+        let context = super::placeholders::PlaceholderContext { format, view_size: [width, height], time };
+        super::placeholders::text(index, &context).or_else(|| {
+            let mut warned = WARNED_PLACEHOLDERS.lock().unwrap();
+            if !warned.iter().any(|w| w == index) {
+                log_warn!("Text uses the undefined placeholder '{index}'");
+                warned.push(index.to_owned());
+            }
+            Some(format!("⟨missing: {index}⟩"))
+        })
+    }
+}
+
+impl<'a> Renderable for Text<'a> {
+    fn get_base_properties(&self) -> &BaseProperties {
+        &self.base
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Text"
+    }
+
+    fn copy<'b>(&self) -> Box<dyn Renderable + 'b> {
+        let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable + 'a) as *mut (dyn Renderable + 'a);
+        unsafe {
+            let result_ptr = std::mem::transmute::<*mut (dyn Renderable + 'a), *mut (dyn Renderable + 'b)>(leaked);
+            Box::from_raw(result_ptr)
+        }
+    }
+
+    fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
+        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
+
+        fn expr_to_f(e: util::ExprEval) -> Option<f64> {
+            match e {
+                util::ExprEval::F64(f) => Some(f),
+                util::ExprEval::String(_) => None
+            }
+        }
+
+        let view_size = context.get_view_size();
+        let mut current_pos = self.base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let alignment: (f64, f64) = self.base.alignment.into();
+        let text_align: f64 = self.text_alignment.multipliers().0;
+
+        let text_parts = &self.active_variant().parts;
+        let layout = self.layout(time, view_size, &object_repr)?;
+        let TextLayout { max_width, default_size, height, ref line_widths, ref line_heights, ref line_baselines, ref hyphenated, ref placeholder_texts } = layout;
 
         let mut current_line: usize = 0;
 
         let starting_pos = (current_pos[0] - max_width*alignment.0, current_pos[1] - height*alignment.1);
         current_pos = ([starting_pos.0 + (max_width - line_widths[current_line])*text_align, starting_pos.1]);
 
+        // Draw the background card around the lines that contain anything
+        if let Some(background) = &self.background {
+            use graphics::Graphics;
+
+            if let Some((left, right)) = layout.horizontal_extent(starting_pos.0, text_align) {
+                let padding = expr_to_f(background.padding.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+                let radius = expr_to_f(background.corner_radius.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+                let color_eval = background.color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+
+                let rect = [left - padding, starting_pos.1 - padding, right - left + padding*2.0, height + padding*2.0];
+                let arc_tri_count = crate::render::shapes::arc_resolution(radius * crate::render::shapes::pixels_per_unit(&context));
+
+                opengl.tri_list(&context.draw_state, &color_eval.map(|f| f as f32), |tri| {
+                    crate::render::shapes::with_rounded_rectangle_tri_list(arc_tri_count, context.transform, rect, [radius; 4], tri);
+                });
+            }
+        }
+
         // Draw the text
-        for part in self.text.iter() {
+        for (i, part) in text_parts.iter().enumerate() {
             match part {
                 TextPart::Tab => {
                     // current_pos.0 += default_size*4.0;
@@ -929,13 +1834,35 @@ impl<'a> Renderable for Text<'a> {
                     current_pos[1] += line_heights[current_line];
                     current_line += 1;
                 },
+                TextPart::SoftHyphen { bold, italic, color, size, voffset, font } => {
+                    // Soft hyphens only get shown if the line gets wrapped after them
+                    if hyphenated.contains(&i) {
+                        let part_font_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+                        let part_voffset = expr_to_f(voffset.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+                        let color_eval = color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+
+                        let mut font_borrow = font.borrow_mut();
+                        let font_instance = match bold {
+                            true => &mut font_borrow.bold_font,
+                            false => &mut font_borrow.base_font
+                        };
+
+                        let ctx = context.trans(current_pos[0], current_pos[1] + line_baselines[current_line] - part_voffset - part_font_size);
+
+                        font_instance.draw("-", part_font_size, (color_eval[0] as f32, color_eval[1] as f32, color_eval[2] as f32, color_eval[3] as f32), *italic, &ctx, opengl);
+                        super::selection::record_text(font_instance, "-", part_font_size, current_pos, line_heights[current_line]);
+                    }
+                },
                 TextPart::Space { size, font } => {
                     let part_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;;
-                    let width = font.borrow_mut().base_font.size(" ", part_size).0;
+                    let mut font_borrow = font.borrow_mut();
+                    let width = font_borrow.base_font.size(" ", part_size).0;
+                    super::selection::record_text(&mut font_borrow.base_font, " ", part_size, current_pos, line_heights[current_line]);
                     current_pos[0] += width as f64;
                 },
-                TextPart::Text { text, bold, italic, color, size, font } => {
+                TextPart::Text { text, bold, italic, color, size, voffset, font } => {
                     let part_font_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+                    let part_voffset = expr_to_f(voffset.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
                     let color_eval = color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
 
                     let mut font_borrow = font.borrow_mut();
@@ -957,27 +1884,22 @@ impl<'a> Renderable for Text<'a> {
                         current_line += 1;
                     }
 
-                    let ctx = context.trans(current_pos[0], current_pos[1] + line_heights[current_line] - part_font_size);
+                    // Glyphs are drawn with their baseline one font size below the context's origin
+                    let ctx = context.trans(current_pos[0], current_pos[1] + line_baselines[current_line] - part_voffset - part_font_size);
 
                     font_instance.draw(text, part_font_size, (color_eval[0] as f32, color_eval[1] as f32, color_eval[2] as f32, color_eval[3] as f32), *italic, &ctx, opengl);
+                    super::selection::record_text(font_instance, text, part_font_size, current_pos, line_heights[current_line]);
 
                     current_pos[0] += part_size.0;
                 },
-                TextPart::Placeholder { index, pad_char, pad_amount, bold, italic, color, size, font } => {
-                    match self.placeholders.get(index) {
-                        Some(expr) => {
+                TextPart::Placeholder { bold, italic, color, size, voffset, font, .. } => {
+                    match placeholder_texts.get(&i) {
+                        Some(text) => {
                             let part_font_size = expr_to_f(size.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;;
+                            let part_voffset = expr_to_f(voffset.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
                             let color_eval = color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
 
-                            let val = expr.call(view_size[0], view_size[1], time);
-
-                            let pad_dir_str = if *pad_amount<0 {
-                                "<"
-                            } else {
-                                ">"
-                            };
-
-                            let text = Self::pad_num(val, pad_amount.abs(), *pad_char, pad_dir_str);
+                            let text = text.as_str();
 
                             let mut font_borrow = font.borrow_mut();
                             let font_instance;
@@ -998,9 +1920,10 @@ impl<'a> Renderable for Text<'a> {
                                 current_line += 1;
                             }
 
-                            let ctx = context.trans(current_pos[0], current_pos[1] + line_heights[current_line] - part_font_size);
+                            let ctx = context.trans(current_pos[0], current_pos[1] + line_baselines[current_line] - part_voffset - part_font_size);
 
                             font_instance.draw(text, part_font_size, (color_eval[0] as f32, color_eval[1] as f32, color_eval[2] as f32, color_eval[3] as f32), *italic, &ctx, opengl);
+                            super::selection::record_text(font_instance, text, part_font_size, current_pos, line_heights[current_line]);
 
                             current_pos[0] += part_size.0;
                         },
@@ -1016,24 +1939,70 @@ impl<'a> Renderable for Text<'a> {
         use mlua::IntoLua;
         let mut ret = self.base.to_lua(lua)?;
 
-        ret.insert("text".to_owned(), self.text.iter().map(|r|r.clone()).collect::<Vec<_>>().into_lua(lua)?);
+        ret.insert("text".to_owned(), self.active_variant().parts.iter().map(|r|r.clone()).collect::<Vec<_>>().into_lua(lua)?);
 
         Ok(ret)
     }
-}
 
-use graphics::Image as ImageRect;
-use opengl_graphics::Texture;
-use std::path::Path;
+    fn raw_text(&self) -> Option<&[String]> {
+        Some(&self.active_variant().raw_text)
+    }
+
+    fn languages(&self) -> Vec<&str> {
+        self.variants.keys().map(|language| language.as_str()).filter(|language| !language.is_empty()).collect()
+    }
+
+    /// Covers the area lines get wrapped in and the laid out lines, which can reach beyond it if
+    /// a word is too long to be wrapped.
+    fn bounds(&self, time: f64, view_size: [f64; 2]) -> anyhow::Result<([f64; 4], [f64; 2])> {
+        fn expr_to_f(e: util::ExprEval) -> Option<f64> {
+            match e {
+                util::ExprEval::F64(f) => Some(f),
+                util::ExprEval::String(_) => None
+            }
+        }
+
+        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
+        let pos = self.base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let alignment: (f64, f64) = self.base.alignment.into();
+        let layout = self.layout(time, view_size, &object_repr)?;
+
+        let (left, top) = (pos[0] - layout.max_width*alignment.0, pos[1] - layout.height*alignment.1);
+        let (right, bottom) = (left + layout.max_width, top + layout.height);
+        let (left, right) = match layout.horizontal_extent(left, self.text_alignment.multipliers().0) {
+            Some((min, max)) => (left.min(min), right.max(max)),
+            None => (left, right)
+        };
+
+        Ok(([left, top, right - left, bottom - top], pos))
+    }
+
+    /// Measures the laid out lines: the width of the widest line and the height of all lines.
+    fn measure(&self, w: f64, h: f64, t: f64) -> anyhow::Result<(f64, f64)> {
+        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
+        let layout = self.layout(t, [w, h], &object_repr)?;
+
+        Ok((layout.line_widths.iter().fold(0.0, |a: f64, b| a.max(*b)), layout.height))
+    }
+}
+
+use graphics::Image as ImageRect;
+use opengl_graphics::{ Texture, TextureSettings };
+use std::path::Path;
 
 use std::sync::RwLock;
 static IMAGE_TEXTURES: RwLock<Vec<Texture>> = RwLock::new(Vec::new());
 
+/// The screen height at which images are measured at their size in pixels.
+const NATURAL_SIZE_REFERENCE_HEIGHT: f64 = 1080.0;
+
 #[derive(Clone)]
 pub struct Image {
     base: BaseProperties,
     texture_path: String,
-    texture: usize
+    texture: usize,
+    /// The alternative text describing the image.
+    alt: Option<String>
 }
 
 impl Debug for Image {
@@ -1043,16 +2012,18 @@ impl Debug for Image {
 }
 
 impl Image {
-    pub fn new<P: AsRef<Path>>(base: BaseProperties, path: P) -> Result<Self, PropertyError> {
-        use crate::render::sprite::DEFAULT_TEXTURE_SETTINGS;
-
+    /// Loads an image file.
+    /// 
+    /// The texture settings define how the image gets filtered when scaled (use
+    /// [`crate::render::sprite::DEFAULT_TEXTURE_SETTINGS`] unless the document specifies otherwise).
+    pub fn new<P: AsRef<Path>>(base: BaseProperties, path: P, alt: Option<String>, settings: &TextureSettings) -> Result<Self, PropertyError> {
         let texture_path = path.as_ref().to_str()
             .ok_or(PropertyError::SyntaxError(
                 "Image".to_owned(),
                 "path".to_owned(),
                 Some("Path isn't valid unicode!".to_owned())))?
             .to_owned();
-        let texture = Texture::from_path(path, &DEFAULT_TEXTURE_SETTINGS)
+        let texture = Texture::from_path(path, settings)
             .map_err(|e|PropertyError::SyntaxError(
                 "Image".to_owned(),
                 "path".to_owned(),
@@ -1060,14 +2031,12 @@ impl Image {
         
         IMAGE_TEXTURES.write().unwrap().push(texture);
 
-        Ok(Self { base, texture: IMAGE_TEXTURES.read().unwrap().len()-1, texture_path })
+        Ok(Self { base, texture: IMAGE_TEXTURES.read().unwrap().len()-1, texture_path, alt })
     }
 }
 
 impl Renderable for Image {
     fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
-        use graphics::DrawState;
-
         let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
 
         fn expr_to_f(e: util::ExprEval) -> Option<f64> {
@@ -1086,9 +2055,9 @@ impl Renderable for Image {
         let rect = ImageRect::new().rect([pos_eval[0]-size_eval[0]*alignment.0,pos_eval[1]-size_eval[1]*alignment.1,size_eval[0],size_eval[1]]).color(col_eval);
 
         let lock = IMAGE_TEXTURES.read().unwrap();
-        let texture = lock.get(self.texture).unwrap();
+        let texture = lock.get(self.texture).ok_or(anyhow::anyhow!("Texture of image {} doesn't exist!", self.texture_path))?;
 
-        rect.draw(texture, &DrawState::default(), context.transform, opengl);
+        rect.draw(texture, &context.draw_state, context.transform, opengl);
 
         Ok(())
     }
@@ -1097,6 +2066,10 @@ impl Renderable for Image {
         &self.base
     }
 
+    fn type_name(&self) -> &'static str {
+        "Image"
+    }
+
     fn copy<'b>(&self) -> Box<dyn Renderable + 'b> {
         let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable) as *mut (dyn Renderable);
         unsafe {
@@ -1110,7 +2083,568 @@ impl Renderable for Image {
         let mut ret = self.base.to_lua(lua)?;
 
         ret.insert("texture".to_owned(), (self.texture_path.as_str()).into_lua(lua)?);
+        if let Some(alt) = &self.alt {
+            ret.insert("alt".to_owned(), alt.as_str().into_lua(lua)?);
+        }
+
+        Ok(ret)
+    }
+
+    fn alt_text(&self) -> Option<&str> {
+        self.alt.as_deref()
+    }
+
+    fn source_size(&self) -> Option<(u32, u32)> {
+        use graphics::ImageSize;
+        IMAGE_TEXTURES.read().unwrap().get(self.texture).map(|texture| texture.get_size())
+    }
+
+    fn asset_path(&self) -> Option<&str> {
+        Some(&self.texture_path)
+    }
+    /// Measures the image's size in pixels, scaled from a 1080p screen to the given one.
+    fn measure(&self, _w: f64, h: f64, _t: f64) -> anyhow::Result<(f64, f64)> {
+        let (width, height) = self.source_size().ok_or(anyhow::anyhow!("Texture of image {} doesn't exist!", self.texture_path))?;
+        let scale = h / NATURAL_SIZE_REFERENCE_HEIGHT;
+        Ok((width as f64 * scale, height as f64 * scale))
+    }
+}
+use crate::render::video::VideoStream;
+
+/// A video file that gets played behind or between other objects.
+/// 
+/// Playback follows the slide's time, so pausing, scrubbing and restarting the slide also apply
+/// to the video. Can also be used as a slide's background by specifying the path of a video file
+/// as the `background` of the slide.
+/// 
+/// The decoder is shared by all copies of the object and stops once the last of them (usually
+/// together with the presentation) gets dropped.
+#[derive(Clone)]
+pub struct Video {
+    base: BaseProperties,
+    video_path: String,
+    stream: Rc<RefCell<VideoStream>>
+}
+
+impl Debug for Video {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Video{{ pos: {:?}, size: {:?}, alignment: {:?}, video: {} }}",self.base.pos,self.base.size,self.base.alignment,self.video_path)
+    }
+}
+
+impl Video {
+    pub fn new<P: AsRef<Path>>(base: BaseProperties, path: P, looped: bool) -> Result<Self, PropertyError> {
+        let video_path = path.as_ref().to_str()
+            .ok_or(PropertyError::SyntaxError(
+                "Video".to_owned(),
+                "path".to_owned(),
+                Some("Path isn't valid unicode!".to_owned())))?
+            .to_owned();
+        // ffmpeg would stream remote videos
+        util::check_remote_path(&video_path)?;
+        let stream = VideoStream::new(path, looped)
+            .map_err(|e|PropertyError::SyntaxError(
+                "Video".to_owned(),
+                "path".to_owned(),
+                Some(format!("Opening video at path {video_path} failed: {e}"))))?;
+
+        Ok(Self { base, stream: Rc::new(RefCell::new(stream)), video_path })
+    }
+
+    /// Creates a video filling the whole screen, for usage as a slide's background.
+    pub fn background<P: AsRef<Path>>(path: P) -> Result<Self, PropertyError> {
+        Self::new(BaseProperties::new("0;0", "w;h", "1;1;1;1", "TOP_LEFT")?, path, true)
+    }
+}
+
+impl Renderable for Video {
+    fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
+        use crate::render::sprite::DEFAULT_TEXTURE_SETTINGS;
+
+        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
+
+        fn expr_to_f(e: util::ExprEval) -> Option<f64> {
+            match e {
+                util::ExprEval::F64(f) => Some(f),
+                util::ExprEval::String(_) => None
+            }
+        }
+
+        let view_size = context.get_view_size();
+        let pos_eval = self.base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let size_eval = self.base.size.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let col_eval = self.base.color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?.map(|f|f as f32);
+        let alignment: (f64, f64) = self.base.alignment.into();
+
+        let rect = ImageRect::new().rect([pos_eval[0]-size_eval[0]*alignment.0,pos_eval[1]-size_eval[1]*alignment.1,size_eval[0],size_eval[1]]).color(col_eval);
+
+        let mut stream = self.stream.borrow_mut();
+
+        // Nothing gets drawn until the first frame has been decoded
+        if let Some(texture) = stream.texture(time, &DEFAULT_TEXTURE_SETTINGS) {
+            rect.draw(texture, &context.draw_state, context.transform, opengl);
+        }
+
+        Ok(())
+    }
+
+    fn get_base_properties(&self) -> &BaseProperties {
+        &self.base
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Video"
+    }
+
+    fn asset_path(&self) -> Option<&str> {
+        Some(&self.video_path)
+    }
+
+    fn copy<'b>(&self) -> Box<dyn Renderable + 'b> {
+        let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable) as *mut (dyn Renderable);
+        unsafe {
+            let result_ptr = std::mem::transmute::<*mut (dyn Renderable), *mut (dyn Renderable + 'b)>(leaked);
+            Box::from_raw(result_ptr)
+        }
+    }
+
+    fn to_lua<'lua>(&self, lua: &'lua mlua::Lua) -> anyhow::Result<HashMap<String, mlua::Value<'lua>>> {
+        use mlua::IntoLua;
+        let mut ret = self.base.to_lua(lua)?;
+
+        ret.insert("video".to_owned(), (self.video_path.as_str()).into_lua(lua)?);
+
+        Ok(ret)
+    }
+}
+
+/// The last blurred content of a [`Blur`], which gets reused while the content beneath it doesn't
+/// change.
+struct BlurCache {
+    texture: Texture,
+    /// The framebuffer region the content was captured from, in pixels.
+    region: (i32, i32, u32, u32),
+    radius: f64,
+    /// The hash of the captured pixels.
+    content: u64,
+    /// The time the content was captured at.
+    time: f64
+}
+
+impl Debug for BlurCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BlurCache{{ region: {:?}, radius: {} }}", self.region, self.radius)
+    }
+}
+
+/// Blurs everything that has been rendered beneath it's rect, e.g. for frosted-glass panels.
+/// 
+/// The blurred content gets tinted using the object's color, so a partially transparent white
+/// results in a frosted-glass look.
+/// 
+/// The blurred texture is owned by the object (and shared by it's copies), so it gets freed
+/// together with the slide. While the slide's time stands still the cached texture gets drawn
+/// without reading back the framebuffer, otherwise it only gets blurred again if the captured
+/// content changed.
+#[derive(Debug, Clone)]
+pub struct Blur {
+    base: BaseProperties,
+    /// The blur radius (standard deviation) in pixels.
+    radius: util::ResolutionDependentExpr,
+    cache: Rc<RefCell<Option<BlurCache>>>
+}
+
+impl Blur {
+    /// The factor the captured content gets downscaled by before blurring it.
+    /// 
+    /// Blurring is done on the CPU, which would be way too slow at full resolution.
+    const DOWNSCALE: u32 = 4;
+
+    pub fn new<RadiusStr>(base: BaseProperties, radius: RadiusStr) -> Result<Self, PropertyError>
+    where RadiusStr: Into<String> {
+        Ok(Blur {
+            base,
+            radius: util::res_dependent_expr(radius, util::DEFAULT_CONTEXT.clone(), util::ResExprType::HeightBased)?,
+            cache: Rc::new(RefCell::new(None))
+        })
+    }
+}
+
+impl Renderable for Blur {
+    fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
+        use graphics::ImageSize;
+        use graphics::triangulation::{ tx, ty };
+        use crate::render::sprite::DEFAULT_TEXTURE_SETTINGS;
+
+        let object_repr = self.to_lua(crate::LUA_INSTANCE.get().unwrap())?;
+
+        fn expr_to_f(e: util::ExprEval) -> Option<f64> {
+            match e {
+                util::ExprEval::F64(f) => Some(f),
+                util::ExprEval::String(_) => None
+            }
+        }
+
+        let view_size = context.get_view_size();
+        let pos_eval = self.base.pos.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let size_eval = self.base.size.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let col_eval = self.base.color.evaluate_arr(view_size[0], view_size[1], time, &object_repr)?.try_map(expr_to_f).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?.map(|f|f as f32);
+        let radius = expr_to_f(self.radius.evaluate(view_size[0], view_size[1], time, &object_repr)?).ok_or(anyhow::anyhow!("Lua expression didn't return a number!"))?;
+        let alignment: (f64, f64) = self.base.alignment.into();
 
+        let rect = [pos_eval[0]-size_eval[0]*alignment.0, pos_eval[1]-size_eval[1]*alignment.1, size_eval[0], size_eval[1]];
+
+        // Without a viewport, the rect's position in the framebuffer is unknown
+        let viewport = match context.viewport {
+            Some(v) => v,
+            None => return Ok(())
+        };
+
+        // Find the rect's region in the framebuffer (in pixels, measured from the bottom left)
+        let m = context.transform;
+        let corners = [(rect[0], rect[1]), (rect[0]+rect[2], rect[1]+rect[3])]
+            .map(|(x, y)| (tx(m, x, y) as f64, ty(m, x, y) as f64));
+        let to_px = |ndc: f64, size: u32| ((ndc + 1.0) / 2.0 * size as f64).clamp(0.0, size as f64);
+        let (x0, x1) = (to_px(corners[0].0.min(corners[1].0), viewport.draw_size[0]), to_px(corners[0].0.max(corners[1].0), viewport.draw_size[0]));
+        let (y0, y1) = (to_px(corners[0].1.min(corners[1].1), viewport.draw_size[1]), to_px(corners[0].1.max(corners[1].1), viewport.draw_size[1]));
+
+        let (width, height) = ((x1 - x0) as u32, (y1 - y0) as u32);
+        if width < Self::DOWNSCALE || height < Self::DOWNSCALE {
+            return Ok(())
+        }
+
+        let region = (x0 as i32, y0 as i32, width, height);
+        let mut cache = self.cache.borrow_mut();
+        let unchanged = |cache: &BlurCache| cache.region == region && cache.radius == radius;
+
+        // Everything beneath only changes over time, so the framebuffer doesn't need to be read
+        // back while the time stands still
+        let reusable = cache.as_ref().is_some_and(|cache| unchanged(cache) && cache.time == time);
+        if !reusable {
+            // Everything rendered so far needs to be in the framebuffer before reading it back
+            opengl.draw_end();
+            opengl.draw_begin(viewport);
+
+            let captured = crate::render::capture::capture_region(region.0, region.1, width, height);
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            std::hash::Hash::hash(captured.as_raw(), &mut hasher);
+            let content = std::hash::Hasher::finish(&hasher);
+
+            match cache.as_mut() {
+                Some(cache) if unchanged(cache) && cache.content == content => cache.time = time,
+                _ => {
+                    let small = image::imageops::resize(&captured, width / Self::DOWNSCALE, height / Self::DOWNSCALE, image::imageops::FilterType::Triangle);
+                    let blurred = image::imageops::blur(&small, (radius / Self::DOWNSCALE as f64).max(0.0) as f32);
+
+                    let texture = match cache.take() {
+                        Some(mut cache) if cache.texture.get_size() == blurred.dimensions() => {
+                            cache.texture.update(&blurred);
+                            cache.texture
+                        },
+                        _ => Texture::from_image(&blurred, &DEFAULT_TEXTURE_SETTINGS)
+                    };
+                    *cache = Some(BlurCache { texture, region, radius, content, time });
+                }
+            }
+        }
+
+        let texture = &cache.as_ref().unwrap().texture;
+        ImageRect::new().rect(rect).draw(texture, &context.draw_state, context.transform, opengl);
+
+        // Tint the blurred content
+        graphics::Rectangle::new(col_eval).draw(rect, &context.draw_state, context.transform, opengl);
+
+        Ok(())
+    }
+
+    fn get_base_properties(&self) -> &BaseProperties {
+        &self.base
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Blur"
+    }
+
+    fn copy<'b>(&self) -> Box<dyn Renderable + 'b> {
+        let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable) as *mut (dyn Renderable);
+        unsafe {
+            let result_ptr = std::mem::transmute::<*mut (dyn Renderable), *mut (dyn Renderable + 'b)>(leaked);
+            Box::from_raw(result_ptr)
+        }
+    }
+
+    fn to_lua<'lua>(&self, lua: &'lua mlua::Lua) -> anyhow::Result<HashMap<String, mlua::Value<'lua>>> {
+        use mlua::IntoLua;
+        let mut ret = self.base.to_lua(lua)?;
+        ret.insert("radius".to_owned(), self.radius.clone().into_lua(lua)?);
+        Ok(ret)
+    }
+}
+
+/// Wraps an object that has an id, so it can be shown and hidden at runtime.
+/// 
+/// The visibility of all objects sharing an id gets toggled together (see
+/// [`super::toggle_object`]).
+#[derive(Debug)]
+pub struct Named {
+    id: String,
+    object: Box<dyn Renderable>
+}
+
+impl Named {
+    pub fn new<IdStr>(id: IdStr, object: Box<dyn Renderable>) -> Self
+    where IdStr: Into<String> {
+        Named { id: id.into(), object }
+    }
+
+    /// Returns the id of the object.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Clone for Named {
+    fn clone(&self) -> Self {
+        Named { id: self.id.clone(), object: self.object.copy() }
+    }
+}
+
+impl Renderable for Named {
+    fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
+        match super::object_visible(&self.id) {
+            true => self.object.render(time, context, opengl),
+            false => Ok(())
+        }
+    }
+
+    fn get_base_properties(&self) -> &BaseProperties {
+        self.object.get_base_properties()
+    }
+
+    fn type_name(&self) -> &'static str {
+        self.object.type_name()
+    }
+
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn copy<'b>(&self) -> Box<dyn Renderable + 'b> {
+        let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable) as *mut (dyn Renderable);
+        unsafe {
+            let result_ptr = std::mem::transmute::<*mut (dyn Renderable), *mut (dyn Renderable + 'b)>(leaked);
+            Box::from_raw(result_ptr)
+        }
+    }
+
+    fn to_lua<'lua>(&self, lua: &'lua mlua::Lua) -> anyhow::Result<HashMap<String, mlua::Value<'lua>>> {
+        use mlua::IntoLua;
+        let mut ret = self.object.to_lua(lua)?;
+        ret.insert("id".to_owned(), self.id.as_str().into_lua(lua)?);
+        Ok(ret)
+    }
+
+    fn raw_text(&self) -> Option<&[String]> {
+        self.object.raw_text()
+    }
+
+    fn languages(&self) -> Vec<&str> {
+        self.object.languages()
+    }
+
+    fn alt_text(&self) -> Option<&str> {
+        self.object.alt_text()
+    }
+
+    fn source_size(&self) -> Option<(u32, u32)> {
+        self.object.source_size()
+    }
+
+    fn asset_path(&self) -> Option<&str> {
+        self.object.asset_path()
+    }
+
+    fn bounds(&self, time: f64, view_size: [f64; 2]) -> anyhow::Result<([f64; 4], [f64; 2])> {
+        self.object.bounds(time, view_size)
+    }
+
+    fn measure(&self, w: f64, h: f64, t: f64) -> anyhow::Result<(f64, f64)> {
+        self.object.measure(w, h, t)
+    }
+}
+
+/// Cycles through a list of entries (e.g. headlines on digital signage), showing each one for a
+/// fixed amount of time.
+/// 
+/// Every entry is a separate object (usually a [`Text`]), which gets rendered with the time since
+/// it was shown, so it's animations (e.g. fading in and out) restart for every entry. Entries
+/// larger than the ticker get scaled down around it's position until they fit into it's size.
+/// 
+/// The entries can also come from a [`TickerFeed`], which replaces them whenever it fetched new
+/// lines.
+#[derive(Debug)]
+pub struct Ticker {
+    base: BaseProperties,
+    entries: RefCell<Vec<Box<dyn Renderable>>>,
+    /// The lines of text of all entries from the document.
+    /// 
+    /// Empty for tickers showing a feed, as it's lines aren't part of the document.
+    lines: Vec<String>,
+    /// How long each entry is shown, in seconds.
+    dwell: f64,
+    feed: Option<Rc<TickerFeed>>,
+    /// How many times the feed was fetched when the entries were last created from it.
+    feed_generation: std::cell::Cell<u64>
+}
+
+impl Ticker {
+    pub fn new(base: BaseProperties, entries: Vec<Box<dyn Renderable>>, lines: Vec<String>, dwell: f64, feed: Option<TickerFeed>) -> Self {
+        Ticker { base, entries: RefCell::new(entries), lines, dwell: dwell.max(f64::EPSILON), feed: feed.map(Rc::new), feed_generation: Default::default() }
+    }
+
+    /// Splits a text into the lines shown as entries, skipping empty ones.
+    pub fn split_lines(text: &str) -> Vec<String> {
+        text.lines().map(|line| line.trim()).filter(|line| !line.is_empty()).map(|line| line.to_owned()).collect()
+    }
+
+    /// Replaces the entries with ones showing the feed's lines, if it fetched new ones since the
+    /// last call.
+    fn update_feed(&self) {
+        let Some(feed) = &self.feed else {
+            return
+        };
+        let fetched = feed.lines.lock().unwrap();
+        if fetched.0 == self.feed_generation.get() {
+            return
+        }
+        self.feed_generation.set(fetched.0);
+
+        *self.entries.borrow_mut() = fetched.1.iter()
+            .filter_map(|line| (feed.entry)(line).map_err(|e| log_warn!("Creating the ticker entry '{line}' failed: {e}")).ok())
+            .collect();
+    }
+}
+
+impl Clone for Ticker {
+    fn clone(&self) -> Self {
+        Ticker {
+            base: self.base.clone(),
+            entries: RefCell::new(self.entries.borrow().iter().map(|entry| entry.copy()).collect()),
+            lines: self.lines.clone(),
+            dwell: self.dwell,
+            feed: self.feed.clone(),
+            feed_generation: self.feed_generation.clone()
+        }
+    }
+}
+
+/// The lines of a [`Ticker`], fetched from a URL (or read from a file) in the background and
+/// refreshed periodically.
+/// 
+/// Shared by all copies of the ticker. The thread fetching the lines stops once the last copy
+/// (usually together with the presentation) gets dropped.
+pub struct TickerFeed {
+    url: String,
+    /// How many times the lines were fetched and the latest fetched lines.
+    lines: std::sync::Arc<std::sync::Mutex<(u64, Vec<String>)>>,
+    /// Creates the entry showing a line.
+    entry: Box<dyn Fn(&str) -> anyhow::Result<Box<dyn Renderable>>>
+}
+
+impl Debug for TickerFeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TickerFeed{{ url: {} }}", self.url)
+    }
+}
+
+impl TickerFeed {
+    /// Starts fetching the lines from the URL every `refresh` seconds.
+    pub fn new(url: String, refresh: f64, entry: Box<dyn Fn(&str) -> anyhow::Result<Box<dyn Renderable>>>) -> Self {
+        let lines = std::sync::Arc::new(std::sync::Mutex::new((0, Vec::new())));
+
+        let shared = std::sync::Arc::downgrade(&lines);
+        let source = url.clone();
+        std::thread::spawn(move || loop {
+            let result = super::data::fetch_text(&source);
+            let Some(lines) = shared.upgrade() else {
+                return
+            };
+            match result {
+                Ok(text) => {
+                    let mut lines = lines.lock().unwrap();
+                    lines.0 += 1;
+                    lines.1 = Ticker::split_lines(&text);
+                },
+                Err(e) => log_warn!("Fetching the ticker entries from {source} failed: {e}")
+            }
+            drop(lines);
+            std::thread::sleep(std::time::Duration::from_secs_f64(refresh.max(1.0)));
+        });
+
+        TickerFeed { url, lines, entry }
+    }
+}
+
+impl Renderable for Ticker {
+    fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) -> anyhow::Result<()> {
+        self.update_feed();
+        let entries = self.entries.borrow();
+        if entries.is_empty() {
+            return Ok(())
+        }
+
+        let index = (time.max(0.0) / self.dwell) as usize % entries.len();
+        let entry_time = time.max(0.0) % self.dwell;
+
+        // Scale entries that don't fit down around the ticker's position
+        let view_size = context.get_view_size();
+        let ([_, _, width, height], pivot) = self.bounds(entry_time, view_size)?;
+        let (entry_width, entry_height) = entries[index].measure(view_size[0], view_size[1], entry_time)?;
+        let scale = [width / entry_width, height / entry_height].into_iter()
+            .filter(|scale| scale.is_finite() && *scale > 0.0)
+            .fold(1.0, f64::min);
+        let context = match scale < 1.0 {
+            true => context.trans(pivot[0], pivot[1]).scale(scale, scale).trans(-pivot[0], -pivot[1]),
+            false => context
+        };
+
+        entries[index].render(entry_time, context, opengl)
+    }
+
+    fn get_base_properties(&self) -> &BaseProperties {
+        &self.base
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Ticker"
+    }
+
+    fn copy<'b>(&self) -> Box<dyn Renderable + 'b> {
+        let leaked = Box::leak(Box::new(<Self as Clone>::clone(self))) as &mut (dyn Renderable) as *mut (dyn Renderable);
+        unsafe {
+            let result_ptr = std::mem::transmute::<*mut (dyn Renderable), *mut (dyn Renderable + 'b)>(leaked);
+            Box::from_raw(result_ptr)
+        }
+    }
+
+    fn to_lua<'lua>(&self, lua: &'lua mlua::Lua) -> anyhow::Result<HashMap<String, mlua::Value<'lua>>> {
+        use mlua::IntoLua;
+        let mut ret = self.base.to_lua(lua)?;
+        ret.insert("dwell".to_owned(), self.dwell.into_lua(lua)?);
         Ok(ret)
     }
-}
\ No newline at end of file
+
+    fn raw_text(&self) -> Option<&[String]> {
+        Some(&self.lines)
+    }
+    /// Measures the entry shown at the given time.
+    fn measure(&self, w: f64, h: f64, t: f64) -> anyhow::Result<(f64, f64)> {
+        let entries = self.entries.borrow();
+        if entries.is_empty() {
+            return Ok((0.0, 0.0))
+        }
+
+        let index = (t.max(0.0) / self.dwell) as usize % entries.len();
+        entries[index].measure(w, h, t.max(0.0) % self.dwell)
+    }
+}
@@ -0,0 +1,117 @@
+use std::ops::RangeInclusive;
+use std::sync::Mutex;
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+use crate::render::font::Font;
+
+/// A rendered character and the rectangle (`[x, y, width, height]`) it occupies on the screen.
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphRect {
+    pub ch: char,
+    pub rect: [f64; 4]
+}
+
+/// The characters rendered in the current frame, in reading order.
+static GLYPHS: Mutex<Vec<GlyphRect>> = Mutex::new(Vec::new());
+
+/// Whether rendered characters currently get recorded.
+///
+/// Only the slide shown in the main view gets recorded, not thumbnails or captions.
+static RECORDING: AtomicBool = AtomicBool::new(false);
+
+/// Forgets the characters of the previous frame and starts recording rendered characters.
+pub fn begin_frame() {
+    GLYPHS.lock().unwrap().clear();
+    RECORDING.store(true, Ordering::Relaxed);
+}
+
+/// Stops recording rendered characters.
+pub fn end_frame() {
+    RECORDING.store(false, Ordering::Relaxed);
+}
+
+/// Records the characters of a piece of text rendered at `pos` (the top left corner of the line
+/// it's on).
+pub fn record_text(font: &mut Font, text: &str, size: f64, pos: [f64; 2], line_height: f64) {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return
+    }
+
+    let offsets = font.char_offsets(text, size);
+
+    let mut glyphs = GLYPHS.lock().unwrap();
+    for (i, ch) in text.chars().enumerate() {
+        glyphs.push(GlyphRect { ch, rect: [pos[0] + offsets[i], pos[1], offsets[i+1] - offsets[i], line_height] });
+    }
+}
+
+/// Returns the index of the character at a point on the screen, or the closest one if there's no
+/// character at that point.
+///
+/// Returns [`None`] if no text got rendered.
+pub fn glyph_at(point: [f64; 2]) -> Option<usize> {
+    let glyphs = GLYPHS.lock().unwrap();
+
+    let distance = |rect: &[f64; 4]| {
+        let dx = (rect[0] - point[0]).max(point[0] - (rect[0] + rect[2])).max(0.0);
+        let dy = (rect[1] - point[1]).max(point[1] - (rect[1] + rect[3])).max(0.0);
+        dx * dx + dy * dy
+    };
+
+    glyphs.iter().enumerate()
+        .min_by(|(_, a), (_, b)| distance(&a.rect).total_cmp(&distance(&b.rect)))
+        .map(|(i, _)| i)
+}
+
+/// Returns the rectangles of the characters in a range.
+pub fn rects(range: RangeInclusive<usize>) -> Vec<[f64; 4]> {
+    let glyphs = GLYPHS.lock().unwrap();
+    glyphs.iter().skip(*range.start()).take(range.end() + 1 - range.start()).map(|g| g.rect).collect()
+}
+
+/// Returns the text of the characters in a range.
+///
+/// Characters on different lines get separated by a line break.
+pub fn text(range: RangeInclusive<usize>) -> String {
+    let glyphs = GLYPHS.lock().unwrap();
+
+    let mut text = String::new();
+    let mut last_line = None;
+    for glyph in glyphs.iter().skip(*range.start()).take(range.end() + 1 - range.start()) {
+        if last_line.is_some_and(|y| y != glyph.rect[1]) {
+            text.push('\n');
+        }
+        last_line = Some(glyph.rect[1]);
+        text.push(glyph.ch);
+    }
+    text
+}
+
+/// Returns all words rendered in the current frame and the rectangles they occupy.
+pub fn words() -> Vec<(String, [f64; 4])> {
+    let glyphs = GLYPHS.lock().unwrap();
+
+    let mut words: Vec<(String, [f64; 4])> = Vec::new();
+    let mut current: Option<(String, [f64; 4])> = None;
+
+    for glyph in glyphs.iter() {
+        let is_word_char = glyph.ch.is_alphanumeric() || glyph.ch == '\'';
+
+        // Continue the current word if the character is on the same line
+        let continues = matches!(&current, Some((_, rect)) if is_word_char && rect[1] == glyph.rect[1]);
+
+        if let (true, Some((word, rect))) = (continues, &mut current) {
+            word.push(glyph.ch);
+            rect[2] = glyph.rect[0] + glyph.rect[2] - rect[0];
+            continue
+        }
+
+        words.extend(current.take());
+        if is_word_char {
+            current = Some((glyph.ch.to_string(), glyph.rect));
+        }
+    }
+    words.extend(current);
+
+    words
+}
@@ -1,6 +1,10 @@
 #![allow(dead_code)]
 
 use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::sync::atomic::{ AtomicU32, Ordering };
+use std::time::{ Duration, Instant };
 
 use opengl_graphics::GlGraphics;
 use graphics::Context;
@@ -10,8 +14,26 @@ use crate::util::DefaultingOption;
 use crate::presentation::renderable;
 
 use renderable::{ Renderable, BaseProperties };
+use crate::render::blend::BlendMode;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
 
 use once_cell::sync::Lazy;
+
+/// The lowest and highest z-index an object can have.
+pub const Z_INDEX_RANGE: (i16, i16) = (-255, 255);
+
+/// The offset subtracted from the z-index of objects rendered beneath the slide's background.
+/// 
+/// It moves their layers below all other layers while keeping their order among each other.
+pub const UNDER_BACKGROUND_OFFSET: i16 = 512;
+
+/// Returns whether the layer with the given key gets rendered beneath the slide's background.
+fn is_under_background(z: i16) -> bool {
+    z < Z_INDEX_RANGE.0
+}
+
 /// An instance of the default background for slides:
 /// A white rectangle
 pub const DEFAULT_BACKGROUND_RENDERABLE: Lazy<renderable::ColoredRect> = Lazy::new(|| {
@@ -24,6 +46,7 @@ pub const DEFAULT_BACKGROUND_RENDERABLE: Lazy<renderable::ColoredRect> = Lazy::n
         Ok(p) => p,
         Err(e) => match e {
             PropertyError::BadAlignment => panic!("{err_begin} Invalid alignment! {err_end}"),
+            PropertyError::UnknownFont(name) => panic!("{err_begin} Unknown font {name}! {err_end}"),
             PropertyError::MismatchedExprCount => panic!("{err_begin} Invalid expression count! {err_end}"),
             PropertyError::SyntaxError(_, prop, spec) => panic!("{err_begin} Error in field {prop}: {} {err_end}",spec.unwrap_or("No furhter information given.".to_owned())),
             PropertyError::LuaError(e) => panic!("{err_begin} Lua error: {e} {err_end}"),
@@ -36,10 +59,177 @@ pub const DEFAULT_BACKGROUND_RENDERABLE: Lazy<renderable::ColoredRect> = Lazy::n
     renderable::ColoredRect::new(properties)
 });
 
+/// The minimum time between logging the same render error again.
+const RENDER_ERROR_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The time each render error was last logged at.
+/// 
+/// Failing objects usually fail on every frame, so repeated errors only get logged every
+/// [`RENDER_ERROR_LOG_INTERVAL`].
+static LOGGED_RENDER_ERRORS: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The amount of times objects failed to render, including errors that weren't logged.
+static RENDER_ERRORS: AtomicU32 = AtomicU32::new(0);
+
+/// Returns how often objects failed to render since the application was started.
+pub fn render_error_count() -> u32 {
+    RENDER_ERRORS.load(Ordering::Relaxed)
+}
+
+/// Forgets which render errors were logged, so the errors of a reloaded presentation get logged
+/// right away even if the previous version failed the same way.
+pub fn clear_logged_render_errors() {
+    LOGGED_RENDER_ERRORS.lock().unwrap().clear();
+}
+
+/// Renders an object, drawing a red placeholder box containing the error instead if that fails.
+/// 
+/// A failing object (e.g. because of a Lua runtime error) doesn't stop the rest of the slide from
+/// being rendered.
+fn render_or_placeholder(renderable: &dyn Renderable, time: f64, context: Context, opengl: &mut GlGraphics) {
+    use graphics::Transformed;
+
+    #[cfg(feature = "render_tracing")]
+    let _span = tracing::info_span!("Renderable::render", object = renderable.type_name(), id = renderable.id().unwrap_or_default()).entered();
+
+    let blend = renderable.get_base_properties().blend;
+    let error = match blend.draw(context, opengl, |context, opengl| renderable.render(time, context, opengl)) {
+        Ok(()) => return,
+        Err(e) => e.to_string()
+    };
+    RENDER_ERRORS.fetch_add(1, Ordering::Relaxed);
+
+    {
+        let mut logged = LOGGED_RENDER_ERRORS.lock().unwrap();
+        let now = Instant::now();
+        if logged.get(&error).map(|last| now - *last >= RENDER_ERROR_LOG_INTERVAL).unwrap_or(true) {
+            log_err!("Error rendering {renderable:?}: {error}");
+            logged.insert(error.clone(), now);
+        }
+    }
+
+    // Draw the placeholder where the object would have been, if at least it's bounds can be
+    // evaluated
+    let view_size = context.get_view_size();
+    let rect = match renderable.bounds(time, view_size) {
+        Ok((rect, _)) if rect[2] > 1.0 && rect[3] > 1.0 => rect,
+        _ => [0.0, 0.0, view_size[0] * 0.25, view_size[1] * 0.1]
+    };
+
+    graphics::rectangle([1.0, 0.0, 0.0, 0.3], rect, context.transform, opengl);
+    graphics::Rectangle::new_border([1.0, 0.0, 0.0, 1.0], 2.0).draw(rect, &context.draw_state, context.transform, opengl);
+
+    let fonts = crate::FONTS.get().unwrap();
+    let mut font = fonts.get("Default").unwrap().borrow_mut();
+    let size = (view_size[1] * 0.02).max(crate::render::font::FONT_SCALE.0 as f64);
+    let first_line = error.lines().next().unwrap_or("");
+    font.base_font.draw(first_line, size, (1.0, 0.0, 0.0, 1.0), false, &context.trans(rect[0] + 4.0, rect[1] + 4.0), opengl);
+}
+
+/// Evaluates an object as a shape that can be drawn in a batch with others.
+/// 
+/// Returns [`None`] for objects that need to go through [`render_or_placeholder`] instead: objects
+/// that aren't shapes, shapes using a blend mode other than alpha blending and shapes that can't
+/// be evaluated (so their error gets shown).
+fn batched_shape(renderable: &dyn Renderable, time: f64, view_size: [f64; 2]) -> Option<renderable::ShapeInstance> {
+    if renderable.get_base_properties().blend != BlendMode::Alpha {
+        return None
+    }
+    renderable.shape_instance(time, view_size)?.ok()
+}
+
+/// Counts a render error that happened outside of [`render_or_placeholder`].
+pub(super) fn count_render_error() {
+    RENDER_ERRORS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Draws the card shown in place of a slide that panicked while rendering, containing the slide's
+/// number (starting at 1) and the panic's message.
+pub fn render_error_card(number: usize, error: &str, context: Context, opengl: &mut GlGraphics) {
+    use graphics::Transformed;
+
+    graphics::clear([0.15, 0.0, 0.0, 1.0], opengl);
+
+    let view_size = context.get_view_size();
+    let fonts = crate::FONTS.get().unwrap();
+    let mut font = fonts.get("Default").unwrap().borrow_mut();
+    let size = (view_size[1] * 0.03).max(crate::render::font::FONT_SCALE.0 as f64);
+    let lines = std::iter::once(format!("Slide #{number} failed to render"))
+        .chain(error.lines().map(str::to_owned));
+    for (i, line) in lines.enumerate() {
+        let color = match i { 0 => (1.0, 0.3, 0.3, 1.0), _ => (1.0, 1.0, 1.0, 0.8) };
+        font.base_font.draw(&line, size, color, false, &context.trans(view_size[0] * 0.05, view_size[1] * 0.1 + i as f64 * size * 1.5), opengl);
+    }
+}
+
+/// How the presentation switches to a slide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transition {
+    /// Switches to the slide instantly.
+    Cut,
+    /// Fades the previous slide's background into the slide's background over `duration` seconds,
+    /// if both are plain colors.
+    Fade { duration: f64 }
+}
+
+/// A narration audio file of a slide.
+/// 
+/// When present, the viewer plays the file after `pre_delay` seconds and automatically advances
+/// to the next slide `post_delay` seconds after it finished playing.
+#[derive(Debug, Clone)]
+pub struct Narration {
+    pub path: String,
+    pub pre_delay: f64,
+    pub post_delay: f64
+}
+
 /// Contains all the objects (including a background object) used for rendering a slide.
+/// 
+/// The background object is reference counted, so multiple consecutive slides can share the same
+/// background (see [`Slide::with_shared_background`]).
 pub struct Slide {
-    objects: IndexMap<u8, Vec<Box<dyn Renderable>>>,
-    background: Box<dyn Renderable>
+    objects: IndexMap<i16, Vec<Box<dyn Renderable>>>,
+    background: Rc<dyn Renderable>,
+    narration: Option<Narration>,
+    /// The aspect ratio of the slide's safe area, if it has one.
+    safe_area: Option<f64>,
+    /// The planned duration of the slide in seconds, if it has one.
+    duration: Option<f64>,
+    /// The title of the slide, if it has one.
+    title: Option<String>,
+    /// The name the slide can be jumped to by, if it has one.
+    bookmark: Option<String>,
+    /// How the presentation switches to the slide, if it differs from the presentation's default.
+    transition: Option<Transition>,
+    /// The speaker notes of the slide, if it has any.
+    notes: Option<String>,
+    /// The hash of the content the slide was parsed from, if it was parsed from a document.
+    content_hash: Option<u64>,
+    /// Whether the slide was added by the application instead of being part of the document.
+    generated: bool,
+    /// The message the slide panicked with while rendering, if it did. Broken slides get
+    /// replaced with an error card (see [`render_error_card`]) instead of being rendered again.
+    error: Option<String>
+}
+
+impl Clone for Slide {
+    /// Copies the slide's objects, while the copy keeps sharing the background object.
+    fn clone(&self) -> Self {
+        Slide {
+            objects: self.objects.iter().map(|(z, vec)| (*z, vec.iter().map(|renderable| renderable.copy()).collect())).collect(),
+            background: self.background.clone(),
+            narration: self.narration.clone(),
+            safe_area: self.safe_area,
+            duration: self.duration,
+            title: self.title.clone(),
+            bookmark: self.bookmark.clone(),
+            transition: self.transition,
+            notes: self.notes.clone(),
+            content_hash: self.content_hash,
+            generated: self.generated,
+            error: self.error.clone()
+        }
+    }
 }
 
 impl Slide {
@@ -51,17 +241,58 @@ impl Slide {
         let bg: DefaultingOption<Box<dyn Renderable>> = background.into();
         Slide {
             objects: IndexMap::new(),
-            background: bg.consume(Box::new(DEFAULT_BACKGROUND_RENDERABLE.clone()))
+            background: Rc::from(bg.consume(Box::new(DEFAULT_BACKGROUND_RENDERABLE.clone()))),
+            narration: None,
+            safe_area: None,
+            duration: None,
+            title: None,
+            bookmark: None,
+            transition: None,
+            notes: None,
+            content_hash: None,
+            generated: false,
+            error: None
+        }
+    }
+
+    /// Creates a new slide that uses the same background object as another slide.
+    /// 
+    /// Consecutive slides sharing a background keep it's animation running continuously instead
+    /// of restarting it on every slide.
+    pub fn with_shared_background(background: Rc<dyn Renderable>) -> Slide {
+        Slide {
+            objects: IndexMap::new(),
+            background,
+            narration: None,
+            safe_area: None,
+            duration: None,
+            title: None,
+            bookmark: None,
+            transition: None,
+            notes: None,
+            content_hash: None,
+            generated: false,
+            error: None
         }
     }
 
     /// Creates a new slide from a hashmap containing layers of objects (sorted by z-index) and a background object.
-    pub fn with_objects_ordered(objects: HashMap<u8, Vec<Box<dyn Renderable>>>, background: Box<dyn Renderable>) -> Slide {
+    pub fn with_objects_ordered(objects: HashMap<i16, Vec<Box<dyn Renderable>>>, background: Box<dyn Renderable>) -> Slide {
         let mut slide = Slide {
             // Convert from HashMap to IndexMap
             //   The contained object also get sorted by z-index.
-            objects: objects.into_iter().collect::<IndexMap<u8, Vec<Box<dyn Renderable>>>>(),
-            background: background.into()
+            objects: objects.into_iter().collect::<IndexMap<i16, Vec<Box<dyn Renderable>>>>(),
+            background: Rc::from(background),
+            narration: None,
+            safe_area: None,
+            duration: None,
+            title: None,
+            bookmark: None,
+            transition: None,
+            notes: None,
+            content_hash: None,
+            generated: false,
+            error: None
         };
 
         slide.objects.sort_by(|a,_,b,_| a.cmp(b));
@@ -74,17 +305,231 @@ impl Slide {
     where B: Into< Box<dyn Renderable> > {
         let mut objects = IndexMap::new();
         objects.insert(0, vec);
-        Slide { objects, background: background.into() }
+        Slide { objects, background: Rc::from(background.into()), narration: None, safe_area: None, duration: None, title: None, bookmark: None, transition: None, notes: None, content_hash: None, generated: false, error: None }
+    }
+
+    /// Sets the aspect ratio (width / height) of the slide's safe area.
+    /// 
+    /// The safe area is a region of the screen content can be kept inside of, so it stays visible
+    /// on projectors with a different aspect ratio.
+    pub fn set_safe_area(&mut self, ratio: Option<f64>) {
+        self.safe_area = ratio;
+    }
+
+    /// Returns the aspect ratio of the slide's safe area, if it has one.
+    pub fn safe_area(&self) -> Option<f64> {
+        self.safe_area
+    }
+
+    /// Sets the planned duration of the slide in seconds.
+    /// 
+    /// When auto-advancing (e.g. when exporting a video), the viewer switches to the next slide
+    /// once the duration has passed.
+    pub fn set_duration(&mut self, duration: Option<f64>) {
+        self.duration = duration;
+    }
+
+    /// Returns the planned duration of the slide in seconds, if it has one.
+    pub fn duration(&self) -> Option<f64> {
+        self.duration
+    }
+
+    /// Sets the title of the slide.
+    /// 
+    /// Titles aren't rendered, but get used for navigating exported documents.
+    pub fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+    }
+
+    /// Returns the title of the slide, if it has one.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Sets the speaker notes of the slide.
+    /// 
+    /// Notes aren't rendered on the slide, but can be printed next to it on exported handouts.
+    pub fn set_notes(&mut self, notes: Option<String>) {
+        self.notes = notes;
+    }
+
+    /// Sets the hash of the content the slide was parsed from.
+    pub fn set_content_hash(&mut self, hash: Option<u64>) {
+        self.content_hash = hash;
+    }
+
+    /// Returns the hash of the content the slide was parsed from, which stays the same across
+    /// runs as long as the slide doesn't change. Slides created by the application itself don't
+    /// have one.
+    pub fn content_hash(&self) -> Option<u64> {
+        self.content_hash
+    }
+
+    /// Marks the slide as added by the application (like the 'End of presentation' slide), so
+    /// exports and analyses of the document skip it.
+    pub fn set_generated(&mut self, generated: bool) {
+        self.generated = generated;
+    }
+
+    /// Returns whether the slide was added by the application instead of being part of the
+    /// document.
+    pub fn is_generated(&self) -> bool {
+        self.generated
+    }
+
+    /// Marks the slide as broken by the message it panicked with while rendering, or clears the
+    /// mark to render it again.
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    /// Returns the message the slide panicked with while rendering, if it did.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns the speaker notes of the slide, if it has any.
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    /// Sets the name the slide can be jumped to by using the keys bound to it.
+    pub fn set_bookmark(&mut self, bookmark: Option<String>) {
+        self.bookmark = bookmark;
+    }
+
+    /// Returns the slide's bookmark, if it has one.
+    pub fn bookmark(&self) -> Option<&str> {
+        self.bookmark.as_deref()
+    }
+
+    /// Sets how the presentation switches to the slide.
+    /// 
+    /// Slides without a transition use the presentation's default transition.
+    pub fn set_transition(&mut self, transition: Option<Transition>) {
+        self.transition = transition;
+    }
+
+    /// Returns how the presentation switches to the slide, if it differs from the presentation's
+    /// default.
+    pub fn transition(&self) -> Option<Transition> {
+        self.transition
+    }
+
+    /// Returns the slide's background object.
+    pub fn background(&self) -> &Rc<dyn Renderable> {
+        &self.background
+    }
+
+    /// Returns whether this slide uses the same background object as another slide.
+    pub fn shares_background_with(&self, other: &Slide) -> bool {
+        Rc::ptr_eq(&self.background, &other.background)
+    }
+
+    /// Returns the color of the slide's background at a point in time if it's a plain colored
+    /// rectangle covering the whole screen.
+    pub fn plain_background_color(&self, time: f64, view_size: [f64; 2]) -> Option<[f64; 4]> {
+        if self.background.type_name() != "ColoredRect" {
+            return None
+        }
+
+        let (rect, _) = self.background.bounds(time, view_size).ok()?;
+        if rect[0] > 0.0 || rect[1] > 0.0 || rect[0] + rect[2] < view_size[0] || rect[1] + rect[3] < view_size[1] {
+            return None
+        }
+
+        let object_repr = self.background.to_lua(crate::LUA_INSTANCE.get().unwrap()).ok()?;
+        self.background.get_base_properties().color.evaluate_arr(view_size[0], view_size[1], time, &object_repr).ok()?
+            .try_map(|e| match e {
+                super::util::ExprEval::F64(f) => Some(f),
+                super::util::ExprEval::String(_) => None
+            })
+    }
+
+    /// Sets the narration of the slide.
+    pub fn set_narration(&mut self, narration: Option<Narration>) {
+        self.narration = narration;
+    }
+
+    /// Returns the narration of the slide, if it has one.
+    pub fn narration(&self) -> Option<&Narration> {
+        self.narration.as_ref()
+    }
+
+    /// Returns an iterator over all lines of text on the slide.
+    pub fn text_lines(&self) -> impl Iterator<Item = &String> {
+        self.objects.values()
+            .flat_map(|vec| vec.iter())
+            .filter_map(|renderable| renderable.raw_text())
+            .flat_map(|lines| lines.iter())
+    }
+
+    /// Returns the amount of words in all text on the slide.
+    pub fn word_count(&self) -> usize {
+        self.text_lines().map(|line| line.split_whitespace().count()).sum()
+    }
+
+    /// Estimates how long presenting the slide takes in seconds, based on it's amount of words
+    /// and a speaking rate.
+    pub fn estimated_speaking_time(&self, words_per_minute: f64) -> f64 {
+        self.word_count() as f64 / words_per_minute * 60.0
+    }
+
+    /// Returns the alternative texts of all objects on the slide that have one.
+    pub fn alt_texts(&self) -> impl Iterator<Item = &str> {
+        self.objects.values()
+            .flat_map(|vec| vec.iter())
+            .filter_map(|renderable| renderable.alt_text())
+    }
+
+    /// Returns the paths of all files shown or played on the slide, including it's background and
+    /// narration.
+    pub fn asset_paths(&self) -> impl Iterator<Item = &str> {
+        self.background.asset_path().into_iter()
+            .chain(self.narration.as_ref().map(|narration| narration.path.as_str()))
+            .chain(self.objects.values().flat_map(|vec| vec.iter()).filter_map(|renderable| renderable.asset_path()))
+    }
+
+    /// Returns the codes of all languages objects on the slide have variants for.
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.objects.values()
+            .flat_map(|vec| vec.iter())
+            .flat_map(|renderable| renderable.languages())
+    }
+
+    /// Returns the slide's layers of objects, sorted by z-index.
+    pub fn layers(&self) -> impl Iterator<Item = (i16, &[Box<dyn Renderable>])> {
+        self.objects.iter().map(|(z, vec)| (*z, vec.as_slice()))
+    }
+
+    /// Returns an object of the layer with the given z-index, if it exists.
+    pub fn object(&self, z_index: i16, index: usize) -> Option<&dyn Renderable> {
+        self.objects.get(&z_index)?.get(index).map(|renderable| &**renderable)
+    }
+
+    /// Moves an object to another layer, placing it above the objects already in that layer.
+    /// 
+    /// Returns the object's new index inside that layer, or [`None`] if the object doesn't exist.
+    /// Layers left empty get removed.
+    pub fn move_object(&mut self, z_index: i16, index: usize, new_z_index: i16) -> Option<usize> {
+        let vec = self.objects.get_mut(&z_index).filter(|vec| index < vec.len())?;
+        let object = vec.remove(index);
+        if vec.is_empty() {
+            self.objects.shift_remove(&z_index);
+        }
+
+        self.add_boxed(object, new_z_index);
+        Some(self.objects.get(&new_z_index).unwrap().len() - 1)
     }
 
     /// Adds an object to the slide.
     pub fn add<B, Z>(&mut self, obj: B, z_index: Z)
     where
         B: Renderable + 'static,
-        Z: Into< DefaultingOption<u8> > {
+        Z: Into< DefaultingOption<i16> > {
         // The user can pass anything to the function that can be converted into this type
-        //   That could be a `u8`, this type directly or an `Option<u8>`.
-        let z: DefaultingOption<u8> = z_index.into();
+        //   That could be an `i16`, this type directly or an `Option<i16>`.
+        let z: DefaultingOption<i16> = z_index.into();
         if self.objects.contains_key(z.get(&0)) {
             // We can safely unwrap here as we know that the indexed entry exists because of the
             // if-statement
@@ -100,11 +545,11 @@ impl Slide {
     /// 
     /// Useful when dealing with trait objects.
     pub fn add_boxed<Z>(&mut self, obj: Box<dyn renderable::Renderable>, z_index: Z)
-    where Z: Into< DefaultingOption<u8> >{
+    where Z: Into< DefaultingOption<i16> >{
         // See the `add()` function for some comments; this function is basically the same, just
         // without the conversion from implicit type to boxed trait
 
-        let z: DefaultingOption<u8> = z_index.into();
+        let z: DefaultingOption<i16> = z_index.into();
         if self.objects.contains_key(z.get(&0)) {
             self.objects.get_mut(z.get(&0)).unwrap().push(obj)
         } else {
@@ -115,19 +560,104 @@ impl Slide {
     }
 
     /// Renders the slide.
-    pub fn render(&self, time: f64, context: Context, opengl: &mut GlGraphics) {
+    /// 
+    /// The background gets rendered using `background_time`, which differs from `time` when the
+    /// background is shared with the previous slides. Objects on the layer beneath the background
+    /// (see [`UNDER_BACKGROUND_OFFSET`]) get rendered before it.
+    #[cfg_attr(feature = "render_tracing", tracing::instrument(name = "Slide::render", skip_all, fields(title = self.title().unwrap_or_default())))]
+    pub fn render(&self, time: f64, background_time: f64, context: Context, opengl: &mut GlGraphics) {
+        self.render_layers(time, context, opengl, is_under_background);
+
         // Render the background
-        self.background.render(time, context, opengl);
+        render_or_placeholder(&*self.background, background_time, context, opengl);
+
+        self.render_layers(time, context, opengl, |z| !is_under_background(z));
+    }
+
+    /// Renders the slide with a plain color instead of it's background.
+    /// 
+    /// Gets used for fading between the plain colored backgrounds of two slides.
+    #[cfg_attr(feature = "render_tracing", tracing::instrument(name = "Slide::render", skip_all, fields(title = self.title().unwrap_or_default())))]
+    pub fn render_with_background_color(&self, time: f64, color: [f64; 4], context: Context, opengl: &mut GlGraphics) {
+        self.render_layers(time, context, opengl, is_under_background);
+
+        let view_size = context.get_view_size();
+        graphics::rectangle(color.map(|c| c as f32), [0.0, 0.0, view_size[0], view_size[1]], context.transform, opengl);
 
+        self.render_layers(time, context, opengl, |z| !is_under_background(z));
+    }
+
+    /// Renders all objects of the slide without it's background.
+    /// 
+    /// Gets used when the window is transparent, so the slide's content can be composited on top
+    /// of other applications (e.g. as an overlay in OBS).
+    #[cfg_attr(feature = "render_tracing", tracing::instrument(name = "Slide::render", skip_all, fields(title = self.title().unwrap_or_default())))]
+    pub fn render_objects(&self, time: f64, context: Context, opengl: &mut GlGraphics) {
+        self.render_layers(time, context, opengl, |_| true);
+    }
+
+    /// Draws the bounding box, pivot point and z-index of every object on the slide.
+    /// 
+    /// Gets used for debugging the layout of slides.
+    pub fn render_debug_outlines(&self, time: f64, context: Context, opengl: &mut GlGraphics) {
+        use graphics::Transformed;
+
+        const OUTLINE_COLOR: [f32; 4] = [1.0, 0.0, 1.0, 1.0];
+        const PIVOT_COLOR: [f32; 4] = [0.0, 1.0, 1.0, 1.0];
+
+        let view_size = context.get_view_size();
+        let label_size = (view_size[1] * 0.02).max(crate::render::font::FONT_SCALE.0 as f64);
+
+        let fonts = crate::FONTS.get().unwrap();
+        let mut font = fonts.get("Default").unwrap().borrow_mut();
+
+        for (z, vec) in self.objects.iter() {
+            for renderable in vec.iter() {
+                let (rect, pivot) = match renderable.bounds(time, view_size) {
+                    Ok(b) => b,
+                    Err(_) => continue
+                };
+
+                graphics::Rectangle::new_border(OUTLINE_COLOR, 1.0).draw(rect, &context.draw_state, context.transform, opengl);
+
+                // Draw a small cross at the pivot point
+                graphics::line(PIVOT_COLOR, 1.0, [pivot[0] - 6.0, pivot[1], pivot[0] + 6.0, pivot[1]], context.transform, opengl);
+                graphics::line(PIVOT_COLOR, 1.0, [pivot[0], pivot[1] - 6.0, pivot[0], pivot[1] + 6.0], context.transform, opengl);
+
+                font.base_font.draw(format!("z={z}"), label_size, (1.0, 0.0, 1.0, 1.0), false, &context.trans(rect[0] + 2.0, rect[1] + 2.0), opengl);
+            }
+        }
+    }
+
+    /// Renders the objects of all layers whose z-index matches the filter.
+    fn render_layers<F: Fn(i16) -> bool>(&self, time: f64, context: Context, opengl: &mut GlGraphics, filter: F) {
         // Render all objects of the slide
         //   The order of objects when iterating needs to be based on the z-index, which is also
         //   used as an index to the `Vec`s. This order gets established through an IndexMap that
         //   has it's items sorted by z-index (it is sorted upon creationg and gets re-sorted when
         //   inserting an object with a new z-index).
-        for (_, vec) in self.objects.iter() {
+        //   Consecutive rectangles of the same color get collected and drawn in a single draw
+        //   call, which keeps grids of many shapes cheap without changing the drawing order.
+        let view_size = context.get_view_size();
+        let mut batch: Vec<renderable::ShapeInstance> = Vec::new();
+        for (_, vec) in self.objects.iter().filter(|(z, _)| filter(**z)) {
             for renderable in vec.iter() {
-                renderable.render(time, context, opengl);
+                match batched_shape(&**renderable, time, view_size) {
+                    Some(shape) => {
+                        if batch.first().map(|s| s.color != shape.color).unwrap_or(false) {
+                            renderable::ShapeInstance::draw_batch(&batch, context, opengl);
+                            batch.clear();
+                        }
+                        batch.push(shape);
+                    },
+                    None => {
+                        renderable::ShapeInstance::draw_batch(&batch, context, opengl);
+                        batch.clear();
+                        render_or_placeholder(&**renderable, time, context, opengl);
+                    }
+                }
             }
         }
+        renderable::ShapeInstance::draw_batch(&batch, context, opengl);
     }
-}
\ No newline at end of file
+}
@@ -5,6 +5,7 @@ use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_er
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{ AtomicBool, AtomicU64, Ordering };
 
 /// All errors that can happen while constructing, converting or using mainly [`ExprVector`]s and [`Alignment`]s.
 /// 
@@ -17,6 +18,8 @@ pub enum PropertyError {
     /// This error indicates that you supplied an invalid string while constructing an
     /// [`Alignment`] from a [`String`].
     BadAlignment,
+    /// This error indicates that a font was referenced by a name that isn't loaded.
+    UnknownFont(String),
     /// This error indicates a syntax error in the properties of a Renderable object that isn't
     /// covered in the other possible errors.
     /// 
@@ -58,6 +61,11 @@ impl PropertyError {
                 property.to_owned(),
                 "Invalid alignment string!".to_owned()
             ),
+            Self::UnknownFont(name) => (
+                rtype.to_owned(),
+                property.to_owned(),
+                format!("Unknown font '{name}'!")
+            ),
             Self::SyntaxError(t, p, d) => (
                 t.clone(),
                 p.clone(),
@@ -166,6 +174,81 @@ use meval::{ Expr, Context };
 use std::ops::Deref;
 use once_cell::sync::Lazy;
 
+/// The aspect ratio (width / height) of the safe area of the slide that's currently being rendered,
+/// stored as the bits of an [`f64`]. Zero if there is no safe area.
+static SAFE_AREA_RATIO: AtomicU64 = AtomicU64::new(0);
+
+/// Sets the aspect ratio of the safe area used by expressions that get evaluated from now on.
+pub fn set_safe_area(ratio: Option<f64>) {
+    SAFE_AREA_RATIO.store(ratio.unwrap_or(0.0).to_bits(), Ordering::Relaxed);
+}
+
+/// Returns the safe area of the slide that's currently being rendered inside a screen of the
+/// given size as `[x, y, width, height]`.
+/// 
+/// Returns the whole screen if there is no safe area.
+pub fn safe_area(width: f64, height: f64) -> [f64; 4] {
+    let ratio = f64::from_bits(SAFE_AREA_RATIO.load(Ordering::Relaxed));
+    if ratio <= 0.0 {
+        return [0.0, 0.0, width, height]
+    }
+
+    fit_safe_area(ratio, width, height)
+}
+
+/// Returns the largest centered rectangle with the given aspect ratio that fits inside a screen of
+/// the given size as `[x, y, width, height]`.
+pub fn fit_safe_area(ratio: f64, width: f64, height: f64) -> [f64; 4] {
+    if width / height > ratio {
+        let safe_width = height * ratio;
+        [(width - safe_width) / 2.0, 0.0, safe_width, height]
+    } else {
+        let safe_height = width / ratio;
+        [0.0, (height - safe_height) / 2.0, width, safe_height]
+    }
+}
+
+/// The time elapsed since the presentation was started in seconds, stored as the bits of an
+/// [`f64`].
+static PRESENTATION_TIME: AtomicU64 = AtomicU64::new(0);
+
+/// Sets the time elapsed since the presentation was started.
+pub fn set_presentation_time(time: f64) {
+    PRESENTATION_TIME.store(time.to_bits(), Ordering::Relaxed);
+}
+
+/// Returns the time elapsed since the presentation was started.
+pub fn presentation_time() -> f64 {
+    f64::from_bits(PRESENTATION_TIME.load(Ordering::Relaxed))
+}
+
+/// The seed of the `rand()` expression function for the slide that's currently being rendered.
+static RANDOM_SEED: AtomicU64 = AtomicU64::new(0);
+
+/// Sets the seed of the `rand()` expression function for expressions that get evaluated from now
+/// on.
+/// 
+/// The seed gets derived from the slide's index and the document's seed, so random values differ
+/// between slides, but stay the same every time a slide gets shown (and in exports).
+pub fn set_random_seed(slide: usize, document_seed: u64) {
+    RANDOM_SEED.store(splitmix64(document_seed ^ splitmix64(slide as u64)), Ordering::Relaxed);
+}
+
+/// Returns a pseudo-random number from 0 (inclusive) to 1 (exclusive), which only depends on the
+/// given seed and the seed of the current slide.
+pub fn random(seed: f64) -> f64 {
+    let bits = splitmix64(RANDOM_SEED.load(Ordering::Relaxed) ^ seed.to_bits());
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Scrambles the bits of a number (the output function of the SplitMix64 generator).
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
 /// The default context used for evaluating mathematical expressions.
 pub struct DefaultContext;
 impl DefaultContext {
@@ -209,6 +292,24 @@ impl DefaultContext {
                 isGreater(a,b)               - returns 1 if a is greater than b, otherwise returns 0
                 isLess(a,b)                  - returns 1 if a is less than b, otherwise returns 0
                 mod(a,b)                     - returns the the remainder of the division of a by b, also called the modulo of a and b
+                between(t,a,b)               - returns 1 if t is at least a and less than b, otherwise returns 0
+                stepAt(t,a)                  - returns 1 if t is at least a, otherwise returns 0
+                rand(seed)                   - returns a pseudo-random number from 0 to 1, which is the same every time the slide is shown (use different seeds for different numbers)
+
+                Polar functions: (angles are in radians, starting to the right and going clockwise, as y points down)
+                polarX(cx,r,angle)           - the x coordinate of the point on a circle around cx with radius r at the given angle
+                polarY(cy,r,angle)           - the y coordinate of the point on a circle around cy with radius r at the given angle
+
+                Safe area functions: (the variables safeX, safeY, safeW and safeH are shorthands for these)
+                safeX(w,h), safeY(w,h)       - the position of the top left corner of the slide's safe area
+                safeW(w,h), safeH(w,h)       - the size of the slide's safe area
+
+                Time functions: (the variable tg is a shorthand for this)
+                globalTime(t)                - the time since the presentation was started, which doesn't restart on slide changes
+
+                Data provider functions: (the variables sunrise, sunset, daylight and data_NAME are shorthands for these)
+                sunVariable(i)               - today's sunrise (0) and sunset (1) in hours or whether it's daylight (2)
+                dataValue(i)                 - the i-th data value configured by the user, sorted by name
             */
             
             // Easing functions
@@ -231,6 +332,28 @@ impl DefaultContext {
             ctx.func2("isGreater",|a,b|match a>b { true=>1.0, false=>0.0 });
             ctx.func2("isLess",|a,b|match a<b { true=>1.0, false=>0.0 });
             ctx.func2("mod", |a,b|a%b);
+            ctx.func3("between", |t,a,b|match t>=a && t<b { true=>1.0, false=>0.0 });
+            ctx.func2("stepAt", |t,a|match t>=a { true=>1.0, false=>0.0 });
+            ctx.func("rand", |seed|random(seed));
+
+            // Polar functions
+            ctx.func3("polarX", |cx,r,angle|cx + r*angle.cos());
+            ctx.func3("polarY", |cy,r,angle|cy + r*angle.sin());
+
+            // Safe area functions
+            ctx.func2("safeX", |w,h|safe_area(w,h)[0]);
+            ctx.func2("safeY", |w,h|safe_area(w,h)[1]);
+            ctx.func2("safeW", |w,h|safe_area(w,h)[2]);
+            ctx.func2("safeH", |w,h|safe_area(w,h)[3]);
+
+            // Time functions
+            //   The slide time argument is ignored; it's only there so the function can be
+            //   called in place of a variable.
+            ctx.func("globalTime", |_|presentation_time());
+
+            // Data provider functions
+            ctx.func("sunVariable", |i|super::data::sun_variable(i as usize));
+            ctx.func("dataValue", |i|super::data::number(i as usize));
 
             Arc::new(ctx)
         })
@@ -259,11 +382,16 @@ pub enum ResolutionDependentExpr {
         /// 
         /// Decides what the percent sign (`%`) gets replaced with.
         /// 
-        /// When this value equals [`ResExprType::WidthBased`], any percent sign gets replaced with `/100*w`.
-        /// When it equals [`ResExprType::HeightBased`], percent signs get replaced with `/100*h`.
+        /// When this value equals [`ResExprType::WidthBased`], percent signs without an explicit
+        /// axis (that aren't remainders) get replaced with `/100*w`. When it equals
+        /// [`ResExprType::HeightBased`], they get replaced with `/100*h`.
         base_expr_type: ResExprType
     },
-    LuaExpr(mlua::Function<'static>, String)
+    /// A Lua expression, the string it was parsed from and the properties of the object it reads.
+    /// 
+    /// When the read properties are declared (see [`lua_expr()`]), only those get passed to the
+    /// expression instead of the whole property map.
+    LuaExpr(mlua::Function<'static>, String, Option<Vec<String>>)
 }
 
 impl Clone for ResolutionDependentExpr {
@@ -273,8 +401,8 @@ impl Clone for ResolutionDependentExpr {
                 // Reconstruct the expression
                 res_dependent_expr(base_string.clone(), base_context.clone(), *base_expr_type).unwrap_or_else(|_|panic!("Reconstruction of an expression failed! This shouldn't happen!"))
             },
-            Self::LuaExpr(func, func_str) => {
-                Self::LuaExpr(func.clone(), func_str.clone())
+            Self::LuaExpr(func, func_str, reads) => {
+                Self::LuaExpr(func.clone(), func_str.clone(), reads.clone())
             }
         }
     }
@@ -303,7 +431,7 @@ impl Debug for ResolutionDependentExpr {
             Self::MathExpr { expr: _, base_string, base_context: _, base_expr_type: _ } => {
                 write!(f, "ResolutionDependentExpr({})", base_string)
             },
-            Self::LuaExpr(func, str) => {
+            Self::LuaExpr(func, str, _) => {
                 write!(f,"ResolutionDependentExpr({str})")
             }
         }
@@ -316,11 +444,23 @@ impl ResolutionDependentExpr {
             Self::MathExpr { expr, base_string: _, base_context: _, base_expr_type: _ } => {
                 Ok(ExprEval::F64((expr)(width,height,time)))
             },
-            Self::LuaExpr(func, _) => {
+            Self::LuaExpr(func, _, reads) => {
                 use mlua::FromLuaMulti;
 
-                // TODO: Replace this clone, as it's getting called every frame and clones a HashMap.
-                let val: mlua::MultiValue = func.call(object.clone())?;
+                let val: mlua::MultiValue = match reads {
+                    // Only marshal the properties the expression actually reads
+                    Some(keys) => {
+                        let table = crate::LUA_INSTANCE.get().unwrap().create_table_with_capacity(0, keys.len())?;
+                        for key in keys.iter() {
+                            if let Some(value) = object.get(key) {
+                                table.raw_set(key.as_str(), value.clone())?;
+                            }
+                        }
+                        func.call(table)?
+                    },
+                    // TODO: Replace this clone, as it's getting called every frame and clones a HashMap.
+                    None => func.call(object.clone())?
+                };
                 if let Ok(str) = String::from_lua_multi(val.clone(), crate::LUA_INSTANCE.get().unwrap()) {
                     Ok(ExprEval::String(str))
                 } else if let Ok(float) = f64::from_lua_multi(val, crate::LUA_INSTANCE.get().unwrap()) {
@@ -482,13 +622,22 @@ impl ResExprType {
 /// on the specified [`ResExprType`]).
 /// 
 /// Example: `50%` = `50/100*w` = `0.5*w` = half of the window's width
+/// 
+/// The axis of a percent sign after a number can also be chosen explicitly by following it with
+/// `w` or `h`, which allows mixing both axes in a single expression (e.g. `50%w-10%h`). Percent
+/// signs followed by a number, name or parenthesis are the remainder operator instead (e.g.
+/// `t%2` or `t%w`).
+/// 
+/// Numbers can also have one of these unit suffixes, which don't depend on the type of the
+/// expression:
+/// - `vw` / `vh`: percent of the window's width / height (`10vw` = `10/100*w`)
+/// - `vmin` / `vmax`: percent of the window's shorter / longer side
+/// - `px`: absolute pixels, which is what plain numbers are as well (`12px` = `12`)
 pub fn res_dependent_expr<S: Into<String>>(expr: S, context: Arc<Context<'static>>, expr_type: ResExprType) -> Result<ResolutionDependentExpr, PropertyError> {
     const EMPTY: String = String::new();
 
     let exprstr: String = expr.into();
-
-    // Replace percent sign to be able to parse it with meval's parser.
-    let mstring = exprstr.replace("%", &("/100*".to_owned()+expr_type.str()));
+    let mstring = expand_shorthands(&exprstr, expr_type);
     let lstring = exprstr;
 
     use meval::{ Error, FuncEvalError, ParseError, RPNError };
@@ -562,14 +711,131 @@ pub fn res_dependent_expr<S: Into<String>>(expr: S, context: Arc<Context<'static
     Err(PropertyError::MultiError(vec![math_error.unwrap(), lua_error.unwrap()]))
 }
 
+/// Helper function for replacing the shorthands of expressions (percent signs, unit suffixes and
+/// the variables standing for function calls) with what meval's parser understands.
+fn expand_shorthands(expr: &str, expr_type: ResExprType) -> String {
+    // Replace percent signs with an explicit axis first, then all others using the expression's
+    // type, to be able to parse them with meval's parser. The axis is only chosen after number
+    // literals and percent signs followed by an operand are the remainder operator, so `t%w`
+    // stays the remainder of t divided by the width.
+    lazy_static::lazy_static! {
+        static ref AXIS_PERCENT_REGEX: regex::Regex = regex::Regex::new(r"(^|[^\w.])(\d*\.?\d+)%([wh])\b").unwrap();
+        static ref PERCENT_REGEX: regex::Regex = regex::Regex::new(r"%(\s*[\w(.])?").unwrap();
+    }
+    let mstring = AXIS_PERCENT_REGEX.replace_all(expr, "${1}${2}/100*${3}").into_owned();
+    let mstring = PERCENT_REGEX.replace_all(&mstring, |captures: &regex::Captures| {
+        match captures.get(1) {
+            Some(_) => captures[0].to_owned(),
+            None => format!("/100*{}", expr_type.str())
+        }
+    }).into_owned();
+
+    // Replace the unit suffixes of numbers. Numbers that are part of a name (like the 2 in
+    // 'atan2') don't get replaced.
+    lazy_static::lazy_static! {
+        static ref UNIT_REGEX: regex::Regex = regex::Regex::new(r"(^|[^\w.])(\d*\.?\d+)(vmin|vmax|vw|vh|px)\b").unwrap();
+    }
+    let mstring = UNIT_REGEX.replace_all(&mstring, |captures: &regex::Captures| {
+        let (prefix, number) = (&captures[1], &captures[2]);
+        match &captures[3] {
+            "vw" => format!("{prefix}({number}/100*w)"),
+            "vh" => format!("{prefix}({number}/100*h)"),
+            "vmin" => format!("{prefix}({number}/100*min(w,h))"),
+            "vmax" => format!("{prefix}({number}/100*max(w,h))"),
+            _ => format!("{prefix}{number}")
+        }
+    }).into_owned();
+
+    // Replace the safe area variables with calls to their functions, as they depend on the
+    // window's size. The variables share the names of the functions, so they can't collide with
+    // any other name.
+    lazy_static::lazy_static! {
+        static ref SAFE_AREA_REGEX: regex::Regex = regex::Regex::new(r"\bsafe([XYWH])\b(\s*\()?").unwrap();
+    }
+    let mstring = SAFE_AREA_REGEX.replace_all(&mstring, |captures: &regex::Captures| {
+        match captures.get(2) {
+            // Calls of the functions are kept
+            Some(_) => captures[0].to_owned(),
+            None => format!("safe{}(w,h)", &captures[1])
+        }
+    }).into_owned();
+
+    // Replace the global time variable with a call to it's function, as the bound function only
+    // receives the slide time.
+    lazy_static::lazy_static! {
+        static ref GLOBAL_TIME_REGEX: regex::Regex = regex::Regex::new(r"\btg\b").unwrap();
+    }
+    let mstring = GLOBAL_TIME_REGEX.replace_all(&mstring, "globalTime(t)").into_owned();
+
+    // Replace the variables of the data providers with calls to their functions. Unknown data
+    // values are kept, so they get reported as unknown variables.
+    lazy_static::lazy_static! {
+        static ref SUN_REGEX: regex::Regex = regex::Regex::new(r"\b(sunrise|sunset|daylight)\b").unwrap();
+        static ref DATA_REGEX: regex::Regex = regex::Regex::new(r"\bdata_(\w+)\b").unwrap();
+    }
+    let mstring = SUN_REGEX.replace_all(&mstring, |captures: &regex::Captures| {
+        let index = super::data::SUN_VARIABLES.iter().position(|name| *name == &captures[1]).unwrap_or(0);
+        format!("sunVariable({index})")
+    }).into_owned();
+    let mstring = DATA_REGEX.replace_all(&mstring, |captures: &regex::Captures| {
+        match super::data::value_index(&captures[1]) {
+            Some(index) => format!("dataValue({index})"),
+            None => captures[0].to_owned()
+        }
+    }).into_owned();
+
+    mstring
+}
+
+/// Whether a Lua expression or remote file got refused since the last call of
+/// [`take_safe_mode_refusal()`].
+static SAFE_MODE_REFUSAL: AtomicBool = AtomicBool::new(false);
+
+/// Returns whether a Lua expression or remote file got refused because of safe mode since the
+/// last call.
+/// 
+/// Gets used for replacing objects that failed to load because of it with a warning.
+pub fn take_safe_mode_refusal() -> bool {
+    SAFE_MODE_REFUSAL.swap(false, Ordering::Relaxed)
+}
+
+/// Refuses paths of remote files (containing `://`) in safe mode, as nothing gets fetched over
+/// the network then.
+pub fn check_remote_path(path: &str) -> Result<(), PropertyError> {
+    if path.contains("://") && crate::CONFIG.get().is_some_and(|config| config.safe_mode) {
+        SAFE_MODE_REFUSAL.store(true, Ordering::Relaxed);
+        return Err(PropertyError::SyntaxError(String::new(), String::new(), Some("Remote files are disabled in safe mode".to_owned())))
+    }
+    Ok(())
+}
+
+/// Parses a Lua expression.
+/// 
+/// The properties of the object the expression reads can be declared in a comment on it's first
+/// line, e.g. `--@reads pos, size`. Only those properties get passed to the expression then,
+/// which avoids converting the whole property map every time it gets evaluated.
 pub fn lua_expr<S: Into<String>>(expr: S) -> Result<ResolutionDependentExpr, PropertyError> {
     let str = expr.into();
+
+    let reads = str.trim_start().lines().next()
+        .and_then(|line| line.strip_prefix("--@reads"))
+        .map(|keys| keys.split(',').map(|key| key.trim().to_owned()).filter(|key| !key.is_empty()).collect::<Vec<_>>());
+
+    // Documents opened in safe mode can't run any code
+    if crate::CONFIG.get().is_some_and(|config| config.safe_mode) {
+        SAFE_MODE_REFUSAL.store(true, Ordering::Relaxed);
+        return Err(PropertyError::SyntaxError(String::new(), String::new(), Some("Lua expressions are disabled in safe mode".to_owned())))
+    }
+
     crate::LUA_INSTANCE.get().unwrap().load(&str).into_function()
-        .map(|f| ResolutionDependentExpr::LuaExpr(f, str))
+        .map(|f| ResolutionDependentExpr::LuaExpr(f, str, reads))
         .map_err(|e| PropertyError::LuaError(e))
 }
 
 /// Parses a list of expressions separated by semicolons using the [`res_dependent_expr()`] function.
+/// 
+/// Percent signs without an explicit axis refer to the width in the first, third, ... expression
+/// and to the height in the second, fourth, ... expression.
 pub fn parse_expression_list<'a, S: Into<String>>(string: S, context: Arc<Context<'static>>) -> Result<Vec<ResolutionDependentExpr>, PropertyError> {
     let mut expr_vec = Vec::new();
 
@@ -578,4 +844,31 @@ pub fn parse_expression_list<'a, S: Into<String>>(string: S, context: Arc<Contex
     }
 
     Ok(expr_vec)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_area_variables_dont_collide_with_other_names() {
+        let expand = |expr: &str| expand_shorthands(expr, ResExprType::WidthBased);
+
+        assert_eq!(expand("safeX+safeW/2"), "safeX(w,h)+safeW(w,h)/2");
+        assert_eq!(expand("safeH(w,h)*2"), "safeH(w,h)*2");
+        // Short names and names starting like the variables are left to the user
+        assert_eq!(expand("sx+sw*sh"), "sx+sw*sh");
+        assert_eq!(expand("safeWidth"), "safeWidth");
+    }
+
+    #[test]
+    fn percent_signs_followed_by_an_operand_are_remainders() {
+        let expand = |expr: &str| expand_shorthands(expr, ResExprType::HeightBased);
+
+        assert_eq!(expand("t%w"), "t%w");
+        assert_eq!(expand("t % 2"), "t % 2");
+        assert_eq!(expand("a2%h"), "a2%h");
+        assert_eq!(expand("50%w-10%h"), "50/100*w-10/100*h");
+        assert_eq!(expand("50%+t%3"), "50/100*h+t%3");
+        assert_eq!(expand("(25%)"), "(25/100*h)");
+    }
+}
@@ -0,0 +1,31 @@
+use std::path::PathBuf;
+
+use tracing_chrome::{ ChromeLayerBuilder, FlushGuard };
+use tracing_subscriber::prelude::*;
+
+/// The environment variable setting the file the trace gets written to.
+const TRACE_FILE_ENV: &'static str = "TRACE_FILE";
+
+/// Starts recording the spans of rendered slides and objects into a trace file.
+///
+/// The trace is written in the Chrome trace event format, which can be opened as a flame chart in
+/// Perfetto (<https://ui.perfetto.dev>) or `chrome://tracing`. Every object's span carries it's
+/// type and id, so slow slides can be attributed to the objects that take the longest to render.
+/// The file gets set using the `TRACE_FILE` environment variable and defaults to
+/// `trace-<timestamp>.json` in the working directory.
+///
+/// The trace only gets written completely once the returned guard is dropped.
+pub fn init() -> FlushGuard {
+    let path = std::env::var_os(TRACE_FILE_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("trace-{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S"))));
+
+    let (layer, guard) = ChromeLayerBuilder::new()
+        .file(&path)
+        .include_args(true)
+        .build();
+    tracing_subscriber::registry().with(layer).init();
+
+    println!("Recording render spans to {}", path.display());
+    guard
+}
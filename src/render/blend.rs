@@ -0,0 +1,92 @@
+use opengl_graphics::GlGraphics;
+use graphics::{ Context, DrawState };
+use graphics::draw_state::Blend;
+
+/// How an object's colors get combined with everything drawn beneath it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// The object gets drawn over what's beneath it, according to it's transparency.
+    #[default]
+    Alpha,
+    /// The object's colors get added to the ones beneath it (weighted by it's transparency),
+    /// which only ever brightens them, e.g. for glows.
+    Add,
+    /// The colors beneath the object get multiplied with it's colors, which only ever darkens
+    /// them, e.g. for tinting.
+    Multiply,
+    /// The inverted colors beneath the object get multiplied with it's inverted colors, which
+    /// brightens them without blowing them out like [`BlendMode::Add`].
+    Screen
+}
+
+impl BlendMode {
+    /// All blend modes.
+    pub const ALL: [BlendMode; 4] = [BlendMode::Alpha, BlendMode::Add, BlendMode::Multiply, BlendMode::Screen];
+
+    /// Parses a blend mode from it's name as used in documents.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// Returns the name of the blend mode as used in documents.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlendMode::Alpha => "alpha",
+            BlendMode::Add => "add",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Screen => "screen"
+        }
+    }
+
+    /// Returns the draw state objects using this blend mode get drawn with.
+    pub fn draw_state(&self, draw_state: DrawState) -> DrawState {
+        let blend = match self {
+            BlendMode::Alpha => return draw_state,
+            BlendMode::Add => Blend::Lighter,
+            BlendMode::Multiply => Blend::Multiply,
+            // Gets replaced by the actual blend function while drawing (see `draw`)
+            BlendMode::Screen => Blend::Add
+        };
+        DrawState { blend: Some(blend), ..draw_state }
+    }
+
+    /// Draws using this blend mode.
+    ///
+    /// Multiply and screen use the object's colors regardless of it's transparency, so they're
+    /// meant for opaque objects and images on black (for screen) or white (for multiply)
+    /// backgrounds.
+    ///
+    /// The graphics backend doesn't know screen blending, so objects using it get drawn with a
+    /// placeholder draw state and the blend function gets replaced once the backend applied it.
+    /// The backend only sets the blend function again when the draw state changes, so everything
+    /// the object draws (e.g. every glyph texture of a text) gets screen blended, unless the object
+    /// changes the draw state itself.
+    /// Must be called while drawing (i.e. inside of a [`GlGraphics::draw`] call).
+    pub fn draw<T, F>(&self, context: Context, opengl: &mut GlGraphics, draw: F) -> T
+    where F: FnOnce(Context, &mut GlGraphics) -> T {
+        let context = Context { draw_state: self.draw_state(context.draw_state), ..context };
+
+        let viewport = match context.viewport {
+            Some(v) if *self == BlendMode::Screen => v,
+            _ => return draw(context, opengl)
+        };
+
+        // Drawing nothing makes the backend flush everything drawn before and apply the draw
+        // state, which can then be replaced for everything the object draws
+        {
+            use graphics::Graphics;
+            opengl.tri_list(&context.draw_state, &[0.0; 4], |_| {});
+        }
+        unsafe {
+            gl::BlendFuncSeparate(gl::ONE_MINUS_DST_COLOR, gl::ONE, gl::ZERO, gl::ONE);
+        }
+
+        let result = draw(context, opengl);
+
+        // Flush the object while the blend function is still replaced
+        opengl.draw_end();
+        opengl.draw_begin(viewport);
+
+        result
+    }
+}
@@ -0,0 +1,27 @@
+use image::RgbaImage;
+
+/// Reads the contents of the currently bound framebuffer into an image.
+/// 
+/// Needs to be called on the thread owning the OpenGL context, after rendering a frame.
+pub fn capture_frame(width: u32, height: u32) -> RgbaImage {
+    capture_region(0, 0, width, height)
+}
+
+/// Reads a region of the currently bound framebuffer into an image.
+/// 
+/// The position is measured in pixels from the bottom left corner of the framebuffer, as usual in
+/// OpenGL. Needs to be called on the thread owning the OpenGL context.
+pub fn capture_region(x: i32, y: i32, width: u32, height: u32) -> RgbaImage {
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(x, y, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut std::ffi::c_void);
+    }
+
+    // OpenGL stores the rows of the framebuffer from bottom to top, so the image needs to be
+    // flipped to be upright.
+    let mut image = RgbaImage::from_raw(width, height, pixels).expect("framebuffer size mismatch");
+    image::imageops::flip_vertical_in_place(&mut image);
+    image
+}
@@ -0,0 +1,147 @@
+use opengl_graphics::{ GlGraphics, Texture };
+use graphics::{ Context, DrawState, ImageSize };
+use image::RgbaImage;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use super::capture::capture_frame;
+use super::sprite::DEFAULT_TEXTURE_SETTINGS;
+
+/// The amount of entries in the table converting linear colors back to sRGB.
+const LINEAR_TABLE_SIZE: usize = 4096;
+
+/// Converts the channels of sRGB colors into linear light.
+static TO_LINEAR: Lazy<[f32; 256]> = Lazy::new(|| std::array::from_fn(|i| {
+    let c = i as f32 / 255.0;
+    match c <= 0.04045 {
+        true => c / 12.92,
+        false => ((c + 0.055) / 1.055).powf(2.4)
+    }
+}));
+
+/// Converts linear light (quantized to [`LINEAR_TABLE_SIZE`] steps) back into sRGB channels.
+static TO_SRGB: Lazy<Vec<u8>> = Lazy::new(|| (0..LINEAR_TABLE_SIZE).map(|i| {
+    let c = i as f32 / (LINEAR_TABLE_SIZE - 1) as f32;
+    let c = match c <= 0.0031308 {
+        true => c * 12.92,
+        false => 1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}).collect());
+
+/// A simulation of how people with a color vision deficiency see the slides, applied on top of
+/// the rendered frame so authors can check the readability of their slides.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorFilter {
+    /// The slides are shown unchanged.
+    #[default]
+    None,
+    /// Missing red cones (red-green color blindness).
+    Protanopia,
+    /// Missing green cones (the most common red-green color blindness).
+    Deuteranopia,
+    /// Missing blue cones (blue-yellow color blindness).
+    Tritanopia,
+    /// No color vision at all, only brightness differences remain.
+    Grayscale
+}
+
+impl ColorFilter {
+    /// All filters, in the order they get cycled through.
+    pub const ALL: [ColorFilter; 5] = [ColorFilter::None, ColorFilter::Protanopia, ColorFilter::Deuteranopia, ColorFilter::Tritanopia, ColorFilter::Grayscale];
+
+    /// Parses a filter from it's name as used on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|filter| filter.name() == name)
+    }
+
+    /// Returns the name of the filter as used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorFilter::None => "none",
+            ColorFilter::Protanopia => "protanopia",
+            ColorFilter::Deuteranopia => "deuteranopia",
+            ColorFilter::Tritanopia => "tritanopia",
+            ColorFilter::Grayscale => "grayscale"
+        }
+    }
+
+    /// Returns the filter following this one in [`ColorFilter::ALL`].
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|filter| filter == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Returns the matrix transforming linear RGB colors, or [`None`] if the filter doesn't
+    /// change anything.
+    ///
+    /// The deficiencies use the matrices of Machado et al. (2009) at full severity.
+    fn matrix(&self) -> Option<[[f32; 3]; 3]> {
+        match self {
+            ColorFilter::None => None,
+            ColorFilter::Protanopia => Some([
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998]
+            ]),
+            ColorFilter::Deuteranopia => Some([
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881]
+            ]),
+            ColorFilter::Tritanopia => Some([
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900]
+            ]),
+            ColorFilter::Grayscale => Some([[0.2126, 0.7152, 0.0722]; 3])
+        }
+    }
+
+    /// Applies the filter to every pixel of an image.
+    pub fn apply(&self, image: &mut RgbaImage) {
+        let matrix = match self.matrix() {
+            Some(m) => m,
+            None => return
+        };
+
+        for pixel in image.pixels_mut() {
+            let rgb = [0, 1, 2].map(|i| TO_LINEAR[pixel.0[i] as usize]);
+            for (channel, row) in pixel.0.iter_mut().zip(matrix.iter()) {
+                let linear = (row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]).clamp(0.0, 1.0);
+                *channel = TO_SRGB[(linear * (LINEAR_TABLE_SIZE - 1) as f32).round() as usize];
+            }
+        }
+    }
+
+    /// Applies the filter to everything drawn onto the view of the context so far.
+    ///
+    /// Reads the framebuffer back, so it's slow and only meant for previews. Must be called
+    /// while drawing (i.e. inside of a [`GlGraphics::draw`] call).
+    pub fn apply_to_frame(&self, texture: &mut Option<Texture>, context: Context, opengl: &mut GlGraphics) {
+        // Without a viewport, the size of the framebuffer is unknown
+        let viewport = match context.viewport {
+            Some(v) if *self != ColorFilter::None => v,
+            _ => return
+        };
+
+        // Everything rendered so far needs to be in the framebuffer before reading it back
+        opengl.draw_end();
+        opengl.draw_begin(viewport);
+
+        let mut frame = capture_frame(viewport.draw_size[0], viewport.draw_size[1]);
+        self.apply(&mut frame);
+
+        match texture {
+            Some(texture) if texture.get_size() == frame.dimensions() => texture.update(&frame),
+            _ => *texture = Some(Texture::from_image(&frame, &DEFAULT_TEXTURE_SETTINGS))
+        }
+
+        // The filtered frame replaces the framebuffer's contents, including their alpha
+        let view_size = context.get_view_size();
+        graphics::Image::new()
+            .rect([0.0, 0.0, view_size[0], view_size[1]])
+            .draw(texture.as_ref().unwrap(), &DrawState { blend: None, ..context.draw_state }, context.transform, opengl);
+    }
+}
@@ -16,92 +16,320 @@ const MAX_FONT_COUNT: usize = ((FONT_SCALE.1 - FONT_SCALE.0) / FONT_SCALE.2) as
 
 pub const ITALIC_FAC: f64 = 0.15;
 
+/// The amount of horizontal subpixel positions glyphs of small text get rasterized at.
+pub const SUBPIXEL_PHASES: u32 = 3;
+
+/// The largest font size glyphs get positioned with subpixel precision at.
+/// 
+/// Larger text doesn't visibly jitter when moving across pixel boundaries, so it's glyphs only get
+/// rasterized once.
+pub const SUBPIXEL_MAX_SIZE: f32 = 48.0;
+
+/// Identifies a rasterized glyph by it's character, it's font size and the subpixel position it
+/// got rasterized at (a multiple of `1 / SUBPIXEL_PHASES` pixels).
+type GlyphKey = (char, u32, u32);
+
+/// The maximum amount of laid out texts cached per font size, before the cache starts a new
+/// generation.
+/// 
+/// Placeholders showing the time or live data produce a new text every frame, so the cache
+/// needs to be bounded.
+const MAX_CACHED_RUNS: usize = 1024;
+
+/// A laid out text, ready to be drawn without looking up the glyphs' metrics again.
+struct TextRun {
+    /// Each character and the position of it's glyph, relative to the start of the text.
+    glyphs: Vec<(char, [f64; 2])>,
+    /// The horizontal advance of the whole text.
+    width: f64
+}
+
+/// The laid out texts of a single font size.
+/// 
+/// When the current generation is full, it replaces the previous one. Texts that get drawn again
+/// are moved back into the current generation, so the static text of a slide survives while
+/// dozens of live placeholders keep filling the cache with texts that are only drawn once.
+#[derive(Default)]
+struct RunCache {
+    current: HashMap<String, TextRun>,
+    previous: HashMap<String, TextRun>
+}
+
+impl RunCache {
+    /// Returns whether the text is cached, moving it into the current generation if it's only
+    /// in the previous one.
+    fn touch(&mut self, text: &str) -> bool {
+        if self.current.contains_key(text) {
+            return true
+        }
+        match self.previous.remove(text) {
+            Some(run) => {
+                self.insert(text.to_owned(), run);
+                true
+            },
+            None => false
+        }
+    }
+
+    fn insert(&mut self, text: String, run: TextRun) {
+        if self.current.len() >= MAX_CACHED_RUNS {
+            self.previous = std::mem::take(&mut self.current);
+        }
+        self.current.insert(text, run);
+    }
+
+    fn get(&self, text: &str) -> &TextRun {
+        &self.current[text]
+    }
+}
+
+/// The regular style of the default font that is bundled into the binary (DejaVu Sans).
+/// 
+/// Gets used as the `Default` font and as a fallback for fonts that couldn't be loaded.
+pub const DEFAULT_FONT: &'static [u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+/// The bold style of the default font that is bundled into the binary (DejaVu Sans Bold).
+pub const DEFAULT_BOLD_FONT: &'static [u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+
 // #[derive(Clone)]
 pub struct Font {
     pub bases: Vec<(fontdue::Font, f32)>,
     pub name: String,
-    cached_glyphs: HashMap<(char, u32), (Texture, Metrics)>
+    cached_glyphs: HashMap<GlyphKey, (Texture, Metrics)>,
+    /// The layouts of recently drawn texts by font size and text.
+    /// 
+    /// Text that doesn't change between frames (everything but placeholders, usually) only gets
+    /// laid out once.
+    cached_runs: HashMap<u32, RunCache>
 }
 
-#[allow(dead_code)]
-impl Font {
-    pub fn new<P: AsRef<Path>, F: Into<DefaultingOption<isize>>>(path: P, face_index: F) -> Option<Font> {
+/// The characters that get rasterized ahead of time when preloading a font.
+pub const PRELOAD_CHARS: std::ops::RangeInclusive<char> = ' '..='~';
+
+/// A rasterized glyph that hasn't been uploaded to the GPU yet.
+type GlyphBitmap = (Metrics, Vec<u8>);
+
+/// A font that got loaded and optionally pre-rasterized, but doesn't contain any GPU resources
+/// yet.
+/// 
+/// Unlike [`Font`], it can be created on any thread. It needs to be converted into a [`Font`]
+/// using [`Font::from_prepared`] on the thread owning the OpenGL context.
+pub struct PreparedFont {
+    bases: Vec<(fontdue::Font, f32)>,
+    name: String,
+    glyphs: Vec<((char, u32), GlyphBitmap)>
+}
+
+impl PreparedFont {
+    /// Loads a font from a file.
+    pub fn new<P: AsRef<Path>, F: Into<DefaultingOption<isize>>>(path: P, face_index: F) -> Option<PreparedFont> {
         let face_index_option: DefaultingOption<isize> = face_index.into();
 
         let name = path.as_ref().to_string_lossy().to_string();
 
         let bytes = std::fs::read(path.as_ref()).ok()?;
-        let face_ind = face_index_option.consume(0);
 
+        Self::from_bytes(bytes, face_index_option.consume(0), name)
+    }
+
+    /// Loads a font from the contents of a font file.
+    pub fn from_bytes(bytes: Vec<u8>, face_index: isize, name: String) -> Option<PreparedFont> {
         let face_sizes: [f32; (FONT_SCALE.1 - FONT_SCALE.0) as usize] = std::array::from_fn(|i| FONT_SCALE.0 + i as f32);
         let faces: Vec<(fontdue::Font, f32)> = face_sizes.into_iter().step_by(FONT_SCALE.2 as usize).filter_map(|size| {
-            fontdue::Font::from_bytes(bytes.as_slice(), fontdue::FontSettings { collection_index: face_ind as u32, scale: size }).ok().map(|font| (font, size))
+            fontdue::Font::from_bytes(bytes.as_slice(), fontdue::FontSettings { collection_index: face_index as u32, scale: size }).ok().map(|font| (font, size))
         }).collect();
 
         match faces.len() {
             0 => None,
-            _ => Some(Font { bases: faces, name, cached_glyphs: HashMap::with_capacity(MAX_FONT_COUNT * 40) })
+            _ => Some(PreparedFont { bases: faces, name, glyphs: Vec::new() })
         }
     }
 
+    /// Loads the bundled default font.
+    pub fn fallback(bold: bool) -> PreparedFont {
+        let (bytes, name) = match bold {
+            false => (DEFAULT_FONT, "Default (bundled)"),
+            true => (DEFAULT_BOLD_FONT, "Default Bold (bundled)")
+        };
+
+        Self::from_bytes(bytes.to_vec(), 0, name.to_owned()).expect("couldn't parse default font's data")
+    }
+
+    /// Loads a font from a file, falling back to the bundled default font if that fails.
+    pub fn new_or_fallback<P: AsRef<Path>>(path: P, face_index: isize, bold: bool) -> PreparedFont {
+        Self::new(path.as_ref(), face_index).unwrap_or_else(|| {
+            log::warn!("Loading font '{}' failed, using the bundled default font instead", path.as_ref().display());
+            Self::fallback(bold)
+        })
+    }
+
+    /// Rasterizes all [`PRELOAD_CHARS`] at every size step of [`FONT_SCALE`].
+    pub fn preload(&mut self) {
+        for &(_, size) in self.bases.iter() {
+            for ch in PRELOAD_CHARS {
+                let glyph = Font::rasterize(&self.bases, ch, size);
+                self.glyphs.push(((ch, size as u32), glyph));
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl Font {
+    pub fn new<P: AsRef<Path>, F: Into<DefaultingOption<isize>>>(path: P, face_index: F) -> Option<Font> {
+        PreparedFont::new(path, face_index).map(Self::from_prepared)
+    }
+
     pub fn from_bytes(bytes: Vec<u8>, face_index: isize, name: String) -> Option<Font> {
-        let face_sizes: [f32; (FONT_SCALE.1 - FONT_SCALE.0) as usize] = std::array::from_fn(|i| FONT_SCALE.0 + i as f32);
-        let faces: Vec<(fontdue::Font, f32)> = face_sizes.into_iter().step_by(FONT_SCALE.2 as usize).filter_map(|size| {
-            fontdue::Font::from_bytes(bytes.as_slice(), fontdue::FontSettings { collection_index: face_index as u32, scale: size }).ok().map(|font| (font, size))
-        }).collect();
+        PreparedFont::from_bytes(bytes, face_index, name).map(Self::from_prepared)
+    }
 
-        match faces.len() {
-            0 => None,
-            _ => Some(Font { bases: faces, name, cached_glyphs: HashMap::with_capacity(MAX_FONT_COUNT * 40) })
+    /// Creates a font from a [`PreparedFont`], uploading all of it's pre-rasterized glyphs.
+    pub fn from_prepared(prepared: PreparedFont) -> Font {
+        let mut cached_glyphs = HashMap::with_capacity(MAX_FONT_COUNT * 40);
+
+        for ((ch, size), (metrics, bitmap)) in prepared.glyphs {
+            cached_glyphs.insert((ch, size, 0), (Self::upload_glyph(&bitmap, &metrics), metrics));
         }
+
+        Font { bases: prepared.bases, name: prepared.name, cached_glyphs, cached_runs: HashMap::new() }
     }
 
-    fn glyphs(&mut self, text: &str, size: f32) -> (Vec<(&Texture, [f64; 2])>, f64) {
-        let base_index = self.bases.binary_search_by(|(_, font_size)| {
+    /// Rasterizes a character using the base font closest to the given size.
+    fn rasterize(bases: &[(fontdue::Font, f32)], ch: char, size: f32) -> GlyphBitmap {
+        let base_index = bases.binary_search_by(|(_, font_size)| {
             font_size.total_cmp(&size)
         }).unwrap_or_else(|i|i);
-        let base = &self.bases[base_index.min(self.bases.len()-1)].0;
+        let base = &bases[base_index.min(bases.len()-1)].0;
 
-        let mut x = 0.0;
-        let mut y = 0.0;
-        let height = size;
-        let mut res = Vec::with_capacity(text.len());
+        let g = base.rasterize_subpixel(ch, size);
+
+        let mut bitmap = Vec::with_capacity(g.1.len()/3+1);
+        for col in g.1.chunks_exact(3) {
+            let (r,g,b) = (col[0],col[1],col[2]);
+            bitmap.push(((r as f64 + g as f64 + b as f64)/3.0) as u8);
+        }
+
+        (g.0, bitmap)
+    }
 
+    /// Creates a texture from the bitmap of a rasterized glyph.
+    fn upload_glyph(bitmap: &[u8], metrics: &Metrics) -> Texture {
+        Texture::from_memory_alpha(
+            bitmap,
+            metrics.width as u32,
+            metrics.height as u32,
+            &TextureSettings::new()
+        ).unwrap()
+    }
+
+    /// Lays out a text at a font size, rasterizing all glyphs that aren't cached yet.
+    /// 
+    /// Does nothing if the text was already laid out at that size.
+    fn layout(&mut self, text: &str, size: f32) {
         let size_ind: u32 = size as u32;
+        if self.cached_runs.get_mut(&size_ind).is_some_and(|runs| runs.touch(text)) {
+            return
+        }
 
         for ch in text.chars() {
-            let ind = (ch, size_ind);
+            let ind = (ch, size_ind, 0);
             if self.cached_glyphs.get(&ind).is_none() {
                 log::debug!("Rasterizing character '{ch}'");
-                let g = base.rasterize_subpixel(ch, size);
-                
-                let mut bitmap = Vec::with_capacity(g.1.len()/3+1);
-                for col in g.1.chunks_exact(3) {
-                    let (r,g,b) = (col[0],col[1],col[2]);
-                    bitmap.push(((r as f64 + g as f64 + b as f64)/3.0) as u8);
-                }
-
-                let texture = Texture::from_memory_alpha(
-                    bitmap.as_slice(),
-                    g.0.width as u32,
-                    g.0.height as u32,
-                    &TextureSettings::new()
-                ).unwrap();
-
-                self.cached_glyphs.insert(ind, (texture, g.0));
+                let (metrics, bitmap) = Self::rasterize(&self.bases, ch, size);
+
+                let texture = Self::upload_glyph(&bitmap, &metrics);
+
+                self.cached_glyphs.insert(ind, (texture, metrics));
             }
         }
 
+        let (mut x, mut y) = (0.0, 0.0);
+        let mut glyphs = Vec::with_capacity(text.len());
         for ch in text.chars() {
-            let glyph = self.cached_glyphs.get(&(ch, size_ind)).unwrap();
-            let metrics = glyph.1;
+            let metrics = self.cached_glyphs.get(&(ch, size_ind, 0)).unwrap().1;
+
+            glyphs.push((ch, [(x + metrics.xmin as f32) as f64, (y + size - metrics.height as f32 - metrics.ymin as f32) as f64]));
 
-            res.push((&glyph.0, [(x + metrics.xmin as f32) as f64, (y + height - metrics.height as f32 - metrics.ymin as f32) as f64]));
-    
             x += metrics.advance_width;
             y += metrics.advance_height;
         }
-        (res, x as f64)
+
+        self.cached_runs.entry(size_ind).or_default().insert(text.to_owned(), TextRun { glyphs, width: x as f64 });
+    }
+
+    fn glyphs(&mut self, text: &str, size: f32) -> (Vec<(&Texture, [f64; 2])>, f64) {
+        let size_ind: u32 = size as u32;
+        self.layout(text, size);
+
+        let run = self.cached_runs[&size_ind].get(text);
+        let glyphs = run.glyphs.iter().map(|(ch, pos)| (&self.cached_glyphs.get(&(*ch, size_ind, 0)).unwrap().0, *pos)).collect();
+        (glyphs, run.width)
+    }
+
+    /// Lays out glyphs like [`Font::glyphs`], but moves each glyph to the closest subpixel position
+    /// on the screen instead of wherever the context's transformation puts it.
+    /// 
+    /// Each glyph gets snapped to a whole pixel and drawn using a bitmap rasterized at the
+    /// remaining fraction of a pixel, so text moving slowly doesn't jitter between pixels.
+    fn subpixel_glyphs(&mut self, text: &str, size: f32, italic: bool, context: &Context) -> Vec<(&Texture, [f64; 2])> {
+        let size_ind: u32 = size as u32;
+
+        // The layout is based on the unshifted glyphs
+        self.layout(text, size);
+        let run = self.cached_runs[&size_ind].get(text).glyphs.clone();
+
+        let m = context.transform;
+        let width_px = context.viewport.map(|v| v.draw_size[0] as f64).unwrap_or(context.get_view_size()[0]);
+        let px_per_unit = m[0][0] * width_px / 2.0;
+
+        let mut placed = Vec::with_capacity(run.len());
+        for (ch, mut pos) in run {
+            // Rotated text can't be snapped to pixels horizontally
+            if px_per_unit.abs() < f64::EPSILON || m[1][0] != 0.0 {
+                placed.push((ch, 0, pos));
+                continue
+            }
+
+            let sheared_x = if italic { pos[0] - ITALIC_FAC * pos[1] } else { pos[0] };
+            let pixel_x = (m[0][0] * sheared_x + m[0][1] * pos[1] + m[0][2] + 1.0) / 2.0 * width_px;
+            let steps = ((pixel_x - pixel_x.floor()) * SUBPIXEL_PHASES as f64).round() as u32;
+            let snapped = pixel_x.floor() + (steps / SUBPIXEL_PHASES) as f64;
+            let phase = steps % SUBPIXEL_PHASES;
+            pos[0] += (snapped - pixel_x) / px_per_unit;
+
+            if !self.cached_glyphs.contains_key(&(ch, size_ind, phase)) {
+                let (metrics, bitmap) = Self::shift_glyph(Self::rasterize(&self.bases, ch, size), phase);
+                let texture = Self::upload_glyph(&bitmap, &metrics);
+                self.cached_glyphs.insert((ch, size_ind, phase), (texture, metrics));
+            }
+
+            placed.push((ch, phase, pos));
+        }
+
+        placed.into_iter().map(|(ch, phase, pos)| (&self.cached_glyphs.get(&(ch, size_ind, phase)).unwrap().0, pos)).collect()
+    }
+
+    /// Shifts the bitmap of a glyph to the right by `phase / SUBPIXEL_PHASES` pixels, making it one
+    /// pixel wider.
+    fn shift_glyph((metrics, bitmap): GlyphBitmap, phase: u32) -> GlyphBitmap {
+        if phase == 0 {
+            return (metrics, bitmap)
+        }
+
+        let offset = phase as f32 / SUBPIXEL_PHASES as f32;
+        let (width, height) = (metrics.width, metrics.height);
+
+        let mut shifted = vec![0; (width + 1) * height];
+        for y in 0..height {
+            for x in 0..=width {
+                let current = if x < width { bitmap[y * width + x] as f32 } else { 0.0 };
+                let previous = if x > 0 { bitmap[y * width + x - 1] as f32 } else { 0.0 };
+                shifted[y * (width + 1) + x] = (current * (1.0 - offset) + previous * offset).round() as u8;
+            }
+        }
+
+        (Metrics { width: width + 1, ..metrics }, shifted)
     }
 
     fn render_text<G, T>(glyphs: &[(&T, [f64; 2])], c: &Context, gl: &mut G, color: [f32;4], italic: bool)
@@ -128,11 +356,42 @@ impl Font {
         text_string.push(' ');
         // self.base.set_pixel_sizes(0, size)?;
         
-        let glyphs = self.glyphs(&text_string, size as f32).0;
+        let glyphs = match size as f32 <= SUBPIXEL_MAX_SIZE {
+            true => self.subpixel_glyphs(&text_string, size as f32, italic, context),
+            false => self.glyphs(&text_string, size as f32).0
+        };
 
         Self::render_text(&glyphs, context, opengl_backend, [color.0,color.1,color.2,color.3], italic);
     }
 
+    /// Returns the horizontal offset of each character of the text from it's start, followed by
+    /// the width of the whole text.
+    pub fn char_offsets<Str: Into<String>>(&mut self, text: Str, size: f64) -> Vec<f64> {
+        let size = size as u32;
+        let text_string: String = text.into();
+
+        // Makes sure all glyphs are cached
+        self.layout(&text_string, size as f32);
+
+        let mut x = 0.0;
+        let mut offsets = Vec::with_capacity(text_string.len() + 1);
+        for ch in text_string.chars() {
+            offsets.push(x);
+            x += self.cached_glyphs.get(&(ch, size, 0)).map(|glyph| glyph.1.advance_width as f64).unwrap_or(0.0);
+        }
+        offsets.push(x);
+        offsets
+    }
+
+    /// Returns how far the font reaches above (the ascent) and below (the descent) the baseline
+    /// at a font size.
+    pub fn line_metrics(&self, size: f64) -> (f64, f64) {
+        match self.bases.first().and_then(|(base, _)| base.horizontal_line_metrics(size as u32 as f32)) {
+            Some(metrics) => (metrics.ascent as f64, -metrics.descent as f64),
+            None => (size * 0.8, size * 0.2)
+        }
+    }
+
     pub fn size<Str: Into<String>>(&mut self, text: Str, size: f64) -> (f64, f64) {
         let size = size as u32;
         let text_string: String = text.into();
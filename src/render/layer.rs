@@ -0,0 +1,128 @@
+use opengl_graphics::{ GlGraphics, Texture };
+use graphics::{ Context, Viewport, Transformed };
+use image::RgbaImage;
+
+use super::capture::capture_frame;
+
+/// An offscreen render target that slides get drawn into before being composited onto the window
+/// (or read back into an image).
+///
+/// Drawing slides into layers instead of directly onto the window is what allows blending
+/// between slides, applying effects to a whole slide and rendering thumbnails or exports using
+/// the same code path.
+pub struct Layer {
+    framebuffer: u32,
+    texture: Texture,
+    size: (u32, u32)
+}
+
+impl Layer {
+    /// Creates a transparent layer of the given size in pixels.
+    pub fn new(size: (u32, u32)) -> Self {
+        let (width, height) = (size.0.max(1), size.1.max(1));
+
+        let (mut previous_framebuffer, mut previous_texture) = (0, 0);
+        let (mut framebuffer, mut texture) = (0, 0);
+
+        unsafe {
+            // Remember the currently bound framebuffer and texture, so they can be restored afterwards
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+            gl::GetIntegerv(gl::TEXTURE_BINDING_2D, &mut previous_texture);
+
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA8 as i32, width as i32, height as i32, 0, gl::RGBA, gl::UNSIGNED_BYTE, std::ptr::null());
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+
+            gl::GenFramebuffers(1, &mut framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, texture, 0);
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as u32);
+            gl::BindTexture(gl::TEXTURE_2D, previous_texture as u32);
+        }
+
+        Layer { framebuffer, texture: Texture::new(texture, width, height), size: (width, height) }
+    }
+
+    /// Returns the size of the layer in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Recreates the layer if it's size differs from the given one, e.g. after the window was
+    /// resized. The contents of the layer get lost when it gets recreated.
+    pub fn resize(&mut self, size: (u32, u32)) {
+        if (size.0.max(1), size.1.max(1)) != self.size {
+            *self = Layer::new(size);
+        }
+    }
+
+    /// Clears the layer and draws into it.
+    ///
+    /// The [`Context`] passed to `draw` uses `window_size` as it's view size, so content laid out
+    /// for the window ends up at the same place in the layer, regardless of the display's pixel
+    /// density. Must not be called while the supplied [`GlGraphics`] instance is already drawing
+    /// (i.e. inside of a [`GlGraphics::draw`] call), as it starts a draw call on it's own.
+    pub fn draw<F>(&mut self, opengl: &mut GlGraphics, window_size: [f64; 2], draw: F)
+    where F: FnOnce(Context, &mut GlGraphics) {
+        let (width, height) = self.size;
+        let viewport = Viewport { rect: [0, 0, width as i32, height as i32], draw_size: [width, height], window_size };
+
+        self.bound(|| {
+            opengl.draw(viewport, |c, gl| {
+                graphics::clear([0.0, 0.0, 0.0, 0.0], gl);
+                draw(c, gl);
+            });
+        });
+    }
+
+    /// Reads the contents of the layer into an image.
+    pub fn capture(&self) -> RgbaImage {
+        self.bound(|| capture_frame(self.size.0, self.size.1))
+    }
+
+    /// Draws the layer onto the whole view of the given context.
+    ///
+    /// At full opacity, the layer's pixels (including their alpha) replace the ones beneath
+    /// them instead of getting blended, so a layer with transparent areas stays transparent when
+    /// composited onto a transparent window.
+    pub fn composite(&self, opacity: f32, context: Context, opengl: &mut GlGraphics) {
+        let view_size = context.get_view_size();
+        let draw_state = match opacity >= 1.0 {
+            true => graphics::DrawState { blend: None, ..context.draw_state },
+            false => context.draw_state
+        };
+
+        // The rows of the layer are stored from bottom to top, so it needs to be flipped
+        graphics::Image::new_color([1.0, 1.0, 1.0, opacity.clamp(0.0, 1.0)])
+            .rect([0.0, 0.0, view_size[0], view_size[1]])
+            .draw(&self.texture, &draw_state, context.transform.trans(0.0, view_size[1]).flip_v(), opengl);
+    }
+
+    /// Runs `f` with the layer's framebuffer bound, restoring the previous framebuffer afterwards.
+    fn bound<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        let mut previous_framebuffer = 0;
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+        }
+
+        let result = f();
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as u32);
+        }
+        result
+    }
+}
+
+impl Drop for Layer {
+    fn drop(&mut self) {
+        // The texture gets deleted by it's own destructor
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+        }
+    }
+}
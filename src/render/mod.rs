@@ -1,2 +1,14 @@
 pub mod font;
-pub mod sprite;
\ No newline at end of file
+pub mod sprite;
+pub mod capture;
+pub mod output;
+pub mod offscreen;
+pub mod layer;
+pub mod video;
+pub mod shapes;
+pub mod slide_view;
+pub mod pointer;
+pub mod teleprompter;
+pub mod overview;
+pub mod color_filter;
+pub mod blend;
\ No newline at end of file
@@ -0,0 +1,16 @@
+use opengl_graphics::GlGraphics;
+use graphics::Context;
+use image::RgbaImage;
+
+use super::layer::Layer;
+
+/// Renders into an offscreen framebuffer of the given size and returns the result as an image.
+/// 
+/// Must not be called while the supplied [`GlGraphics`] instance is already drawing (i.e. inside
+/// of a [`GlGraphics::draw`] call), as it starts a draw call on it's own.
+pub fn render_offscreen<F>(opengl: &mut GlGraphics, size: (u32, u32), draw: F) -> RgbaImage
+where F: FnOnce(Context, &mut GlGraphics) {
+    let mut layer = Layer::new(size);
+    layer.draw(opengl, [size.0 as f64, size.1 as f64], draw);
+    layer.capture()
+}
@@ -0,0 +1,52 @@
+use std::fs::File;
+use std::io::{ BufWriter, Write };
+use std::path::Path;
+
+use image::RgbaImage;
+
+/// A destination that rendered frames get published to, so the presentation can be ingested by
+/// other software without capturing the window.
+/// 
+/// The only backend is [`RawVideoOutput`]. Publishing frames as an NDI, Spout or Syphon source
+/// directly is out of scope, as each of them needs it's platform's SDK.
+pub trait FrameOutput {
+    /// Publishes a single rendered frame.
+    fn publish(&mut self, frame: &RgbaImage) -> anyhow::Result<()>;
+}
+
+/// Writes every frame as raw, tightly packed RGBA8 pixels to a file or named pipe.
+/// 
+/// The stream can be consumed by tools like ffmpeg
+/// (`ffmpeg -f rawvideo -pix_fmt rgba -s WIDTHxHEIGHT -i PIPE ...`), which can in turn publish it
+/// to vision mixers, e.g. as an NDI source.
+pub struct RawVideoOutput {
+    writer: BufWriter<File>,
+    /// The size of the first published frame. All further frames need to have the same size, as
+    /// the raw stream doesn't contain any size information.
+    size: Option<(u32, u32)>
+}
+
+impl RawVideoOutput {
+    /// Opens the file or named pipe at the given path for writing frames to.
+    pub fn new<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        Ok(RawVideoOutput { writer: BufWriter::new(File::create(path)?), size: None })
+    }
+}
+
+impl FrameOutput for RawVideoOutput {
+    fn publish(&mut self, frame: &RgbaImage) -> anyhow::Result<()> {
+        let size = *self.size.get_or_insert(frame.dimensions());
+
+        // Frames with a different size (e.g. after resizing the window) get scaled to the size of
+        // the first frame to keep the stream valid.
+        if frame.dimensions() != size {
+            let resized = image::imageops::resize(frame, size.0, size.1, image::imageops::FilterType::Triangle);
+            self.writer.write_all(resized.as_raw())?;
+        } else {
+            self.writer.write_all(frame.as_raw())?;
+        }
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use opengl_graphics::{ GlGraphics, Texture, TextureSettings };
+use graphics::{ Context, Transformed };
+
+use crate::presentation::Presentation;
+use super::slide_view::SlideView;
+
+/// The amount of thumbnails in each row of the overview.
+const COLUMNS: usize = 4;
+
+/// The space between the thumbnails, relative to their width.
+const GAP: f64 = 0.08;
+
+/// The amount of thumbnails rendered per frame, so opening the overview of a long presentation
+/// doesn't freeze the window.
+const THUMBNAILS_PER_FRAME: usize = 2;
+
+/// The color of the frame around the selected thumbnail.
+const SELECTED_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+
+/// Shows thumbnails of all slides in a grid, for jumping to any slide of the presentation.
+///
+/// The thumbnails get rendered using [`Presentation::render_thumbnail`] while the overview is
+/// open, a few of them every frame. The grid can be zoomed and panned like the editor's preview.
+pub struct Overview {
+    /// The rendered thumbnails by the index of their slide, [`None`] until they're rendered.
+    thumbnails: Vec<Option<Texture>>,
+    /// The size in pixels the thumbnails get rendered at.
+    thumbnail_size: (u32, u32),
+    /// The index of the selected slide.
+    selected: usize,
+    /// The zoomable and pannable view onto the grid.
+    view: SlideView
+}
+
+impl Overview {
+    /// Creates an overview of all slides of the presentation, selecting the current one.
+    pub fn new(presentation: &Presentation) -> Self {
+        let count = presentation.slide_count();
+        Overview {
+            thumbnails: (0..count).map(|_| None).collect(),
+            thumbnail_size: (0, 0),
+            selected: presentation.current_slide_index().min(count.saturating_sub(1)),
+            view: SlideView::new(1.0)
+        }
+    }
+
+    /// Returns the index of the selected slide.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Moves the selection by the given amount of columns and rows, stopping at the first and
+    /// last slide.
+    pub fn move_selection(&mut self, columns: isize, rows: isize) {
+        let last = self.thumbnails.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + columns + rows * COLUMNS as isize).clamp(0, last) as usize;
+    }
+
+    /// Zooms the grid when scrolling vertically and pans it when scrolling horizontally.
+    pub fn scroll(&mut self, amount: [f64; 2], cursor: [f64; 2], window_size: [f64; 2]) {
+        self.view.scroll(amount, cursor, window_size);
+    }
+
+    /// Renders the thumbnails that aren't rendered yet, re-rendering all of them if the size of
+    /// the window changed.
+    ///
+    /// `draw_size` is the size of the window in pixels. Must not be called while already drawing
+    /// with the supplied [`GlGraphics`] instance.
+    pub fn update_thumbnails(&mut self, presentation: &Presentation, draw_size: (u32, u32), opengl: &mut GlGraphics) {
+        let (width, _) = Self::cell_size([draw_size.0 as f64, draw_size.1 as f64]);
+        let size = (width.round().max(1.0) as u32, (width * draw_size.1 as f64 / draw_size.0.max(1) as f64).round().max(1.0) as u32);
+        if size != self.thumbnail_size {
+            self.thumbnail_size = size;
+            self.thumbnails.iter_mut().for_each(|thumbnail| *thumbnail = None);
+        }
+
+        // The selected slide and the ones around it get rendered first
+        let mut order = (0..self.thumbnails.len()).collect::<Vec<_>>();
+        order.sort_by_key(|index| index.abs_diff(self.selected));
+
+        let missing = order.into_iter().filter(|index| self.thumbnails[*index].is_none()).take(THUMBNAILS_PER_FRAME).collect::<Vec<_>>();
+        for index in missing {
+            if let Some(image) = presentation.render_thumbnail(index, size, opengl) {
+                self.thumbnails[index] = Some(Texture::from_image(&image, &TextureSettings::new()));
+            }
+        }
+    }
+
+    /// Returns the index of the slide whose thumbnail is at the given point of the window, if
+    /// there is one.
+    pub fn slide_at(&self, point: [f64; 2], view_size: [f64; 2]) -> Option<usize> {
+        let point = self.view.to_slide(point, view_size);
+        let (width, height) = Self::cell_size(view_size);
+        let gap = width * GAP;
+        let scroll = self.scroll(view_size);
+
+        let column = ((point[0] - gap) / (width + gap)).floor();
+        let row = ((point[1] + scroll - gap) / (height + gap)).floor();
+        if column < 0.0 || column >= COLUMNS as f64 || row < 0.0 {
+            return None
+        }
+
+        // Points in the gaps between the thumbnails don't belong to any slide
+        let inside = point[0] - gap - column * (width + gap) <= width && point[1] + scroll - gap - row * (height + gap) <= height;
+        let index = row as usize * COLUMNS + column as usize;
+        (inside && index < self.thumbnails.len()).then_some(index)
+    }
+
+    /// Draws the overview over the whole window.
+    pub fn render(&self, context: Context, opengl: &mut GlGraphics) {
+        let view_size = context.get_view_size();
+        let (width, height) = Self::cell_size(view_size);
+        let gap = width * GAP;
+        let scroll = self.scroll(view_size);
+
+        graphics::clear([0.1, 0.1, 0.1, 1.0], opengl);
+        let context = self.view.transform(context, view_size);
+
+        let fonts = crate::FONTS.get().unwrap();
+        let mut font = fonts.get("Default").unwrap().borrow_mut();
+        let font_size = (gap * 0.45).max(crate::render::font::FONT_SCALE.0 as f64);
+
+        for (index, thumbnail) in self.thumbnails.iter().enumerate() {
+            let x = gap + (index % COLUMNS) as f64 * (width + gap);
+            let y = gap + (index / COLUMNS) as f64 * (height + gap) - scroll;
+            if y + height + gap < 0.0 || y > view_size[1] {
+                continue
+            }
+
+            if index == self.selected {
+                let border = gap * 0.15;
+                graphics::rectangle(SELECTED_COLOR, [x - border, y - border, width + border * 2.0, height + border * 2.0], context.transform, opengl);
+            }
+            match thumbnail {
+                Some(texture) => graphics::Image::new().rect([x, y, width, height]).draw(texture, &context.draw_state, context.transform, opengl),
+                None => graphics::rectangle([0.2, 0.2, 0.2, 1.0], [x, y, width, height], context.transform, opengl)
+            }
+
+            font.base_font.draw(format!("{}", index + 1), font_size, (0.8, 0.8, 0.8, 1.0), false, &context.trans(x, y + height + gap * 0.1), opengl);
+        }
+    }
+
+    /// Returns the size of each thumbnail in the window, for a window of the given size.
+    fn cell_size(view_size: [f64; 2]) -> (f64, f64) {
+        let width = view_size[0] / (COLUMNS as f64 + (COLUMNS + 1) as f64 * GAP);
+        (width, width * view_size[1] / view_size[0].max(1.0))
+    }
+
+    /// Returns how far the grid is scrolled down, so the selected thumbnail is visible.
+    fn scroll(&self, view_size: [f64; 2]) -> f64 {
+        let (width, height) = Self::cell_size(view_size);
+        let gap = width * GAP;
+        let row = (self.selected / COLUMNS) as f64;
+        ((row + 1.0) * (height + gap) + gap - view_size[1]).max(0.0)
+    }
+}
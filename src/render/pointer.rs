@@ -0,0 +1,74 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use opengl_graphics::GlGraphics;
+use graphics::Context;
+
+use crate::config::PointerEffects;
+
+/// The width of the pointer trail at it's newest end, relative to the height of the window.
+const TRAIL_WIDTH: f64 = 0.006;
+
+/// The width of a click ripple's ring, relative to the height of the window.
+const RIPPLE_WIDTH: f64 = 0.004;
+
+/// The amount of segments a click ripple's ring consists of.
+const RIPPLE_RESOLUTION: u32 = 48;
+
+/// Effects drawn on top of the slides following the mouse cursor, making it easier to follow
+/// the presenter's pointer on recordings.
+///
+/// Times are measured in real time, so the effects keep fading out while the slide time is
+/// paused.
+pub struct PointerOverlay {
+    /// The recent positions of the cursor and when it was moved there, oldest first.
+    trail: VecDeque<(Instant, [f64; 2])>,
+    /// The positions of recent clicks and when they happened.
+    ripples: Vec<(Instant, [f64; 2])>
+}
+
+impl PointerOverlay {
+    pub fn new() -> Self {
+        PointerOverlay { trail: VecDeque::new(), ripples: Vec::new() }
+    }
+
+    /// Records a movement of the cursor.
+    pub fn moved(&mut self, position: [f64; 2], settings: &PointerEffects) {
+        if settings.trail {
+            self.trail.push_back((Instant::now(), position));
+        }
+    }
+
+    /// Records a click at the given position.
+    pub fn clicked(&mut self, position: [f64; 2], settings: &PointerEffects) {
+        if settings.ripple {
+            self.ripples.push((Instant::now(), position));
+        }
+    }
+
+    /// Draws the trail and ripples, dropping the parts that have faded out completely.
+    pub fn render(&mut self, settings: &PointerEffects, context: Context, opengl: &mut GlGraphics) {
+        let view_height = context.get_view_size()[1];
+        let [r, g, b, a] = settings.color;
+
+        self.trail.retain(|(instant, _)| instant.elapsed().as_secs_f64() < settings.trail_duration);
+        self.ripples.retain(|(instant, _)| instant.elapsed().as_secs_f64() < settings.ripple_duration);
+
+        // The trail gets thinner and more transparent towards it's older end
+        for ((_, from), (instant, to)) in self.trail.iter().zip(self.trail.iter().skip(1)) {
+            let remaining = 1.0 - instant.elapsed().as_secs_f64() / settings.trail_duration;
+            let radius = TRAIL_WIDTH * view_height * remaining * 0.5;
+            graphics::Line::new_round([r, g, b, a * remaining as f32], radius)
+                .draw_from_to(*from, *to, &context.draw_state, context.transform, opengl);
+        }
+
+        // Ripples grow from the clicked position while fading out
+        for (instant, [x, y]) in self.ripples.iter() {
+            let progress = instant.elapsed().as_secs_f64() / settings.ripple_duration;
+            let radius = settings.ripple_radius * view_height * progress;
+            graphics::Ellipse::new_border([r, g, b, a * (1.0 - progress) as f32], RIPPLE_WIDTH * view_height * 0.5)
+                .resolution(RIPPLE_RESOLUTION)
+                .draw([x - radius, y - radius, radius * 2.0, radius * 2.0], &context.draw_state, context.transform, opengl);
+        }
+    }
+}
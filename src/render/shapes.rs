@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::{ Arc, Mutex };
+
+use graphics::math::Matrix2d;
+use graphics::triangulation::{ tx, ty };
+use once_cell::sync::Lazy;
+
+/// The maximum number of triangulated rounded rectangles kept in [`TESSELLATION_CACHE`].
+/// 
+/// Shapes with animated sizes or radii produce a new entry on most frames, so the cache simply
+/// gets cleared once it is full.
+const MAX_CACHED_TESSELLATIONS: usize = 256;
+
+/// The resolution, size and (already limited) corner radii of a rounded rectangle, with the
+/// floating point values stored as their bit patterns so they can be hashed.
+type TessellationKey = (u32, [u64; 2], [u64; 4]);
+
+/// The triangles of recently drawn rounded rectangles, relative to their top left corner and
+/// without any transformation applied.
+static TESSELLATION_CACHE: Lazy<Mutex<HashMap<TessellationKey, Arc<[[f64; 2]]>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The approximate length (in pixels) of each segment of a rounded corner's arc.
+const ARC_SEGMENT_LENGTH: f64 = 3.0;
+/// The range the number of segments of a rounded corner's arc gets limited to.
+const ARC_SEGMENTS: (u32, u32) = (2, 64);
+
+/// Returns the number of segments a rounded corner with the given on-screen radius (in pixels)
+/// needs to look smooth.
+pub fn arc_resolution(radius_px: f64) -> u32 {
+    let arc_length = radius_px.abs() * std::f64::consts::FRAC_PI_2;
+    ((arc_length / ARC_SEGMENT_LENGTH).ceil() as u32).clamp(ARC_SEGMENTS.0, ARC_SEGMENTS.1)
+}
+
+/// Returns how many pixels on screen one unit of the context's coordinate system spans
+/// horizontally.
+pub fn pixels_per_unit(context: &graphics::Context) -> f64 {
+    let m = context.transform;
+    let width_px = context.viewport.map(|v| v.draw_size[0] as f64).unwrap_or(context.get_view_size()[0]);
+    m[0][0].hypot(m[1][0]) * width_px / 2.0
+}
+
+/// Triangulates a rectangle with an individual radius for each of it's corners.
+/// 
+/// The radii are given in the order top left, top right, bottom right, bottom left and get
+/// limited to half of the rectangle's smaller side. Each corner's arc consists of `resolution`
+/// segments. The triangles get passed to `f` in chunks.
+pub fn with_rounded_rectangle_tri_list<F>(resolution: u32, m: Matrix2d, rect: [f64; 4], radii: [f64; 4], mut f: F)
+where F: FnMut(&[[f32; 2]]) {
+    let mut vertices: Vec<[f32; 2]> = Vec::new();
+    push_rounded_rectangle(resolution, m, rect, radii, &mut vertices);
+
+    for chunk in vertices.chunks(graphics::BACK_END_MAX_VERTEX_COUNT - graphics::BACK_END_MAX_VERTEX_COUNT % 3) {
+        f(chunk);
+    }
+}
+
+/// Triangulates multiple rectangles, each with it's own corner radii and arc resolution, into
+/// one list of triangles.
+/// 
+/// Lets many shapes of the same color be drawn with as few draw calls as possible instead of one
+/// (or more) per shape. Rectangles without any rounding only take up two triangles each. The
+/// triangles get passed to `f` in chunks.
+pub fn with_rounded_rectangles_tri_list<I, F>(shapes: I, m: Matrix2d, mut f: F)
+where I: IntoIterator<Item = (u32, [f64; 4], [f64; 4])>, F: FnMut(&[[f32; 2]]) {
+    let mut vertices: Vec<[f32; 2]> = Vec::new();
+    for (resolution, rect, radii) in shapes {
+        push_rounded_rectangle(resolution, m, rect, radii, &mut vertices);
+    }
+
+    for chunk in vertices.chunks(graphics::BACK_END_MAX_VERTEX_COUNT - graphics::BACK_END_MAX_VERTEX_COUNT % 3) {
+        f(chunk);
+    }
+}
+
+/// Appends the triangles of a single (rounded) rectangle to `vertices`.
+fn push_rounded_rectangle(resolution: u32, m: Matrix2d, rect: [f64; 4], radii: [f64; 4], vertices: &mut Vec<[f32; 2]>) {
+    let [x, y, w, h] = rect;
+    let max_radius = (w.min(h) / 2.0).max(0.0);
+    let radii = radii.map(|r| r.clamp(0.0, max_radius));
+    let resolution = resolution.max(1);
+
+    if radii.iter().all(|r| *r == 0.0) {
+        let corners = [(x, y), (x + w, y), (x + w, y + h), (x, y + h)].map(|(px, py)| [tx(m, px, py), ty(m, px, py)]);
+        vertices.extend_from_slice(&[corners[0], corners[1], corners[2], corners[0], corners[2], corners[3]]);
+        return;
+    }
+
+    // Only the position and transformation change between frames for most shapes, so the
+    // triangulation itself gets reused as long as the size and radii stay the same
+    let key: TessellationKey = (resolution, [w.to_bits(), h.to_bits()], radii.map(f64::to_bits));
+    let local = {
+        let mut cache = TESSELLATION_CACHE.lock().unwrap();
+        match cache.get(&key) {
+            Some(local) => local.clone(),
+            None => {
+                if cache.len() >= MAX_CACHED_TESSELLATIONS {
+                    cache.clear();
+                }
+                let local: Arc<[[f64; 2]]> = tessellate_rounded_rectangle(resolution, w, h, radii).into();
+                cache.insert(key, local.clone());
+                local
+            }
+        }
+    };
+
+    vertices.extend(local.iter().map(|[px, py]| [tx(m, x + px, y + py), ty(m, x + px, y + py)]));
+}
+
+/// Triangulates a rounded rectangle with it's top left corner at the origin.
+fn tessellate_rounded_rectangle(resolution: u32, w: f64, h: f64, radii: [f64; 4]) -> Vec<[f64; 2]> {
+    use std::f64::consts::{ PI, FRAC_PI_2 };
+
+    // The center of each corner's arc and the angle it starts at
+    let corners = [
+        (radii[0], radii[0], PI),
+        (w - radii[1], radii[1], PI + FRAC_PI_2),
+        (w - radii[2], h - radii[2], 0.0),
+        (radii[3], h - radii[3], FRAC_PI_2)
+    ];
+
+    // Build the outline of the shape in clockwise order
+    let mut outline: Vec<[f64; 2]> = Vec::with_capacity(4 * (resolution as usize + 1));
+    for ((cx, cy, start), radius) in corners.into_iter().zip(radii) {
+        for i in 0..=resolution {
+            let angle = start + FRAC_PI_2 * i as f64 / resolution as f64;
+            outline.push([cx + radius * angle.cos(), cy + radius * angle.sin()]);
+        }
+    }
+
+    // The shape is convex, so it can be triangulated as a fan around it's center
+    let center = [w / 2.0, h / 2.0];
+    let mut vertices = Vec::with_capacity(outline.len() * 3);
+    for i in 0..outline.len() {
+        vertices.push(center);
+        vertices.push(outline[i]);
+        vertices.push(outline[(i + 1) % outline.len()]);
+    }
+    vertices
+}
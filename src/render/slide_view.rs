@@ -0,0 +1,90 @@
+use graphics::{ Context, Transformed };
+
+/// The smallest zoom factor a [`SlideView`] can be zoomed out to.
+pub const MIN_ZOOM: f64 = 0.05;
+
+/// The largest zoom factor a [`SlideView`] can be zoomed in to.
+pub const MAX_ZOOM: f64 = 16.0;
+
+/// The factor a [`SlideView`] gets zoomed by for every step of the mouse wheel.
+pub const ZOOM_STEP: f64 = 1.1;
+
+/// The amount of pixels a [`SlideView`] gets panned by for every step of horizontal scrolling.
+pub const PAN_STEP: f64 = 40.0;
+
+/// A zoomable and pannable view onto a slide, e.g. for the editor's preview or the viewer's
+/// overview.
+///
+/// The slide gets rendered at the size of the window, then scaled by the zoom factor around the
+/// window's center and moved by the pan offset.
+#[derive(Debug, Clone, Copy)]
+pub struct SlideView {
+    /// The zoom factor the view gets reset to when fitting it to the window.
+    fit_zoom: f64,
+    zoom: f64,
+    /// The offset of the slide's center from the window's center in pixels.
+    pan: [f64; 2]
+}
+
+impl SlideView {
+    /// Creates a view showing the slide centered in the window, scaled by `fit_zoom`.
+    ///
+    /// A `fit_zoom` of `1.0` fills the whole window.
+    pub fn new(fit_zoom: f64) -> SlideView {
+        SlideView { fit_zoom, zoom: fit_zoom, pan: [0.0, 0.0] }
+    }
+
+    /// Resets the zoom and pan, so the whole slide fits into the window again.
+    pub fn fit(&mut self) {
+        self.zoom = self.fit_zoom;
+        self.pan = [0.0, 0.0];
+    }
+
+    /// Moves the slide by the given amount of pixels.
+    pub fn pan_by(&mut self, delta: [f64; 2]) {
+        self.pan[0] += delta[0];
+        self.pan[1] += delta[1];
+    }
+
+    /// Zooms the view when scrolling vertically and pans it when scrolling horizontally, keeping
+    /// the point beneath `cursor` (in window coordinates) in place while zooming.
+    pub fn scroll(&mut self, amount: [f64; 2], cursor: [f64; 2], window_size: [f64; 2]) {
+        if amount[1] != 0.0 {
+            self.zoom_at(ZOOM_STEP.powf(amount[1]), cursor, window_size);
+        }
+        self.pan_by([amount[0] * PAN_STEP, 0.0]);
+    }
+
+    /// Multiplies the zoom factor by `factor`, keeping the point of the slide beneath `cursor`
+    /// (in window coordinates) in place.
+    pub fn zoom_at(&mut self, factor: f64, cursor: [f64; 2], window_size: [f64; 2]) {
+        let point = self.to_slide(cursor, window_size);
+
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+
+        // Move the slide so the point ends up beneath the cursor again
+        let origin = self.origin(window_size);
+        self.pan[0] += cursor[0] - (origin[0] + point[0] * self.zoom);
+        self.pan[1] += cursor[1] - (origin[1] + point[1] * self.zoom);
+    }
+
+    /// Converts a point in window coordinates into the slide's coordinates.
+    pub fn to_slide(&self, point: [f64; 2], window_size: [f64; 2]) -> [f64; 2] {
+        let origin = self.origin(window_size);
+        [(point[0] - origin[0]) / self.zoom, (point[1] - origin[1]) / self.zoom]
+    }
+
+    /// Applies the view to a context, so everything drawn with it ends up inside the view.
+    pub fn transform(&self, context: Context, window_size: [f64; 2]) -> Context {
+        let origin = self.origin(window_size);
+        context.trans(origin[0], origin[1]).scale(self.zoom, self.zoom)
+    }
+
+    /// Returns the position of the slide's top left corner in window coordinates.
+    fn origin(&self, window_size: [f64; 2]) -> [f64; 2] {
+        [
+            window_size[0] * (1.0 - self.zoom) / 2.0 + self.pan[0],
+            window_size[1] * (1.0 - self.zoom) / 2.0 + self.pan[1]
+        ]
+    }
+}
@@ -0,0 +1,149 @@
+use opengl_graphics::GlGraphics;
+use graphics::{ Context, Transformed };
+
+/// The font size of the notes, relative to the height of the window.
+const TEXT_SIZE: f64 = 0.075;
+
+/// The space between the notes and the left and right edges of the window, relative to it's
+/// width.
+const MARGIN: f64 = 0.08;
+
+/// The distance of the line currently being read from the top of the window, relative to it's
+/// height.
+const READING_LINE: f64 = 0.3;
+
+/// The factor the scrolling speed gets multiplied or divided by when it's changed.
+const SPEED_STEP: f64 = 1.1;
+
+/// The slowest and fastest scrolling speed in words per minute.
+const SPEED_RANGE: (f64, f64) = (20.0, 600.0);
+
+/// Shows the speaker notes of the current slide in large, automatically scrolling text instead
+/// of the slide, for script-driven talks and recordings.
+///
+/// The notes scroll at a speaking rate in words per minute, so the line that should be read right
+/// now stays at the reading line marked on the left.
+pub struct Teleprompter {
+    /// The speaking rate the notes scroll at, in words per minute.
+    speed: f64,
+    /// How far the notes have scrolled past the reading line, in lines.
+    scrolled: f64,
+    /// The average amount of words per line of the last laid out notes, which converts the
+    /// speaking rate into lines.
+    words_per_line: f64,
+    /// The amount of lines of the last laid out notes.
+    line_count: usize
+}
+
+impl Teleprompter {
+    /// Creates a teleprompter scrolling at the given speaking rate.
+    pub fn new(words_per_minute: f64) -> Self {
+        Teleprompter { speed: words_per_minute.clamp(SPEED_RANGE.0, SPEED_RANGE.1), scrolled: 0.0, words_per_line: 1.0, line_count: 0 }
+    }
+
+    /// Returns the speaking rate the notes scroll at, in words per minute.
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Scrolls faster by one step.
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * SPEED_STEP).min(SPEED_RANGE.1);
+    }
+
+    /// Scrolls slower by one step.
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / SPEED_STEP).max(SPEED_RANGE.0);
+    }
+
+    /// Scrolls back to the start of the notes, e.g. after switching to another slide.
+    pub fn restart(&mut self) {
+        self.scrolled = 0.0;
+    }
+
+    /// Scrolls the notes by the amount of words spoken within `dt` seconds, stopping at their
+    /// last line.
+    pub fn advance(&mut self, dt: f64) {
+        let lines = dt * self.speed / 60.0 / self.words_per_line;
+        self.scrolled = (self.scrolled + lines).min(self.line_count.saturating_sub(1) as f64);
+    }
+
+    /// Draws the notes over the whole window.
+    ///
+    /// `paused` only changes the status shown in the corner, scrolling gets held by not calling
+    /// [`Teleprompter::advance`].
+    pub fn render(&mut self, notes: Option<&str>, paused: bool, context: Context, opengl: &mut GlGraphics) {
+        let view_size = context.get_view_size();
+        let font_size = (view_size[1] * TEXT_SIZE).max(crate::render::font::FONT_SCALE.0 as f64);
+        let line_height = font_size * 1.35;
+        let reading_line = view_size[1] * READING_LINE;
+
+        graphics::clear([0.0, 0.0, 0.0, 1.0], opengl);
+
+        let fonts = crate::FONTS.get().unwrap();
+        let mut font = fonts.get("Default").unwrap().borrow_mut();
+
+        let notes = notes.map(str::trim).filter(|notes| !notes.is_empty()).unwrap_or("This slide has no notes.");
+        let lines = Self::wrap(notes, view_size[0] * (1.0 - MARGIN * 2.0), |text| font.base_font.size(text, font_size).0);
+
+        let words = lines.iter().map(|line| line.split_whitespace().count()).sum::<usize>();
+        self.words_per_line = (words as f64 / lines.len().max(1) as f64).max(1.0);
+        self.line_count = lines.len();
+        self.scrolled = self.scrolled.min(lines.len().saturating_sub(1) as f64);
+
+        // Lines that were already read get dimmed
+        let top = reading_line - self.scrolled * line_height;
+        for (i, line) in lines.iter().enumerate() {
+            let y = top + i as f64 * line_height;
+            if y + line_height < 0.0 || y > view_size[1] {
+                continue
+            }
+            let color = match (i as f64) < self.scrolled.floor() {
+                true => (0.45, 0.45, 0.45, 1.0),
+                false => (1.0, 1.0, 1.0, 1.0)
+            };
+            font.base_font.draw(line.as_str(), font_size, color, false, &context.trans(view_size[0] * MARGIN, y), opengl);
+        }
+
+        // Mark the reading line with a triangle pointing at it
+        let marker = font_size * 0.4;
+        let marker_x = view_size[0] * MARGIN * 0.5;
+        let marker_y = reading_line + line_height * 0.5;
+        graphics::polygon([1.0, 0.25, 0.2, 1.0], &[
+            [marker_x - marker, marker_y - marker],
+            [marker_x + marker * 0.5, marker_y],
+            [marker_x - marker, marker_y + marker]
+        ], context.transform, opengl);
+
+        let status_size = (view_size[1] * 0.03).max(crate::render::font::FONT_SCALE.0 as f64);
+        let status = match paused {
+            true => format!("{:.0} wpm (paused)", self.speed),
+            false => format!("{:.0} wpm", self.speed)
+        };
+        let status_width = font.base_font.size(status.as_str(), status_size).0;
+        font.base_font.draw(status, status_size, (0.6, 0.6, 0.6, 1.0), false, &context.trans(view_size[0] - status_width - status_size, view_size[1] - status_size * 1.6), opengl);
+    }
+
+    /// Wraps the lines of the notes at word boundaries, so no line gets wider than `max_width`.
+    ///
+    /// Empty lines are kept, so paragraphs of the notes stay separated.
+    fn wrap<F: FnMut(&str) -> f64>(notes: &str, max_width: f64, mut measure: F) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in notes.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = match line.is_empty() {
+                    true => word.to_owned(),
+                    false => format!("{line} {word}")
+                };
+                if !line.is_empty() && measure(&candidate) > max_width {
+                    lines.push(std::mem::replace(&mut line, word.to_owned()));
+                } else {
+                    line = candidate;
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
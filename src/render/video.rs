@@ -0,0 +1,215 @@
+use std::io::Read;
+use std::path::{ Path, PathBuf };
+use std::process::{ Child, Command, Stdio };
+use std::sync::mpsc::{ self, Receiver, TryRecvError };
+
+use image::RgbaImage;
+use opengl_graphics::{ Texture, TextureSettings };
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+/// The amount of decoded frames buffered ahead of the shown one.
+///
+/// The decoder blocks once the buffer is full, so it never runs ahead of the presentation's time.
+const FRAME_BUFFER: usize = 2;
+
+/// How far in seconds the time may jump ahead between two rendered frames before the decoder gets
+/// restarted at the new time instead of decoding all frames in between.
+const SEEK_THRESHOLD: f64 = 1.0;
+
+/// The properties of a video file that are needed for decoding it.
+#[derive(Clone, Copy, Debug)]
+pub struct VideoInfo {
+    /// The resolution of the video.
+    pub size: (u32, u32),
+    /// The amount of frames per second.
+    pub frame_rate: f64,
+    /// The length of the video in seconds, if it's known.
+    pub duration: Option<f64>
+}
+
+impl VideoInfo {
+    /// Gets the resolution, frame rate and length of a video file using `ffprobe`.
+    pub fn probe<P: AsRef<Path>>(path: P) -> anyhow::Result<VideoInfo> {
+        let path = path.as_ref();
+        let output = Command::new("ffprobe")
+            .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "stream=width,height,r_frame_rate:format=duration", "-of", "default=noprint_wrappers=1"])
+            .arg(path)
+            .output()
+            .map_err(|e| anyhow::anyhow!("couldn't start ffprobe: {e}"))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let value = |key: &str| stdout.lines().find_map(|line| line.strip_prefix(key)?.strip_prefix('='));
+
+        let size = value("width").zip(value("height"))
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+            .ok_or(anyhow::anyhow!("couldn't determine the resolution of video {}", path.display()))?;
+
+        // The frame rate is given as a fraction, like `30000/1001`
+        let frame_rate = value("r_frame_rate")
+            .and_then(|rate| match rate.split_once('/') {
+                Some((num, den)) => Some(num.parse::<f64>().ok()? / den.parse::<f64>().ok()?),
+                None => rate.parse().ok()
+            })
+            .filter(|rate| rate.is_finite() && *rate > 0.0)
+            .ok_or(anyhow::anyhow!("couldn't determine the frame rate of video {}", path.display()))?;
+
+        let duration = value("duration").and_then(|d| d.parse::<f64>().ok()).filter(|d| d.is_finite() && *d > 0.0);
+
+        Ok(VideoInfo { size, frame_rate, duration })
+    }
+}
+
+/// Decodes a video file in the background using an external `ffmpeg` process.
+///
+/// Frames get decoded as fast as they are taken, starting at the given time of the video. The
+/// `ffmpeg` process gets stopped when the decoder is dropped.
+pub struct VideoDecoder {
+    frames: Receiver<RgbaImage>,
+    /// The presentation's time the decoder was started at, which the first frame belongs to.
+    start: f64,
+    frame_rate: f64,
+    /// The amount of frames taken so far.
+    taken: u64,
+    process: Child
+}
+
+impl VideoDecoder {
+    /// Opens a video file starting at `offset` seconds into it, optionally restarting it from the
+    /// beginning whenever it ends.
+    ///
+    /// `start` is the presentation's time the first decoded frame gets shown at. Requires
+    /// `ffmpeg` to be available on the `PATH`.
+    pub fn open<P: AsRef<Path>>(path: P, info: &VideoInfo, looped: bool, offset: f64, start: f64) -> anyhow::Result<VideoDecoder> {
+        let path = path.as_ref();
+        let size = info.size;
+
+        let mut command = Command::new("ffmpeg");
+        command.args(["-v", "error"]);
+        if looped {
+            command.args(["-stream_loop", "-1"]);
+        }
+        if offset > 0.0 {
+            command.args(["-ss", &format!("{offset:.3}")]);
+        }
+        let mut process = command.arg("-i").arg(path)
+            .args(["-an", "-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("couldn't start ffmpeg: {e}"))?;
+
+        let mut stdout = process.stdout.take().ok_or(anyhow::anyhow!("couldn't read the output of ffmpeg"))?;
+        let (sender, frames) = mpsc::sync_channel(FRAME_BUFFER);
+
+        // Read the decoded frames on a separate thread, so rendering never waits for the decoder.
+        // The thread stops once the decoder (and with it the receiver) gets dropped.
+        let path_str = path.display().to_string();
+        std::thread::spawn(move || {
+            let mut buffer = vec![0u8; size.0 as usize * size.1 as usize * 4];
+            while stdout.read_exact(&mut buffer).is_ok() {
+                let frame = match RgbaImage::from_raw(size.0, size.1, buffer.clone()) {
+                    Some(frame) => frame,
+                    None => break
+                };
+                if sender.send(frame).is_err() {
+                    break
+                }
+            }
+            log_dbg!("Video {path_str} finished decoding");
+        });
+
+        Ok(VideoDecoder { frames, start, frame_rate: info.frame_rate, taken: 0, process })
+    }
+
+    /// Returns the presentation's time up to which frames were taken.
+    fn position(&self) -> f64 {
+        self.start + self.taken as f64 / self.frame_rate
+    }
+
+    /// Takes the decoded frames up to the given time of the presentation, returning the last of
+    /// them.
+    ///
+    /// Returns [`None`] if the frame shown at that time was already taken or isn't decoded yet.
+    pub fn frame_at(&mut self, time: f64) -> Option<RgbaImage> {
+        let mut frame = None;
+        while self.position() <= time {
+            match self.frames.try_recv() {
+                Ok(next) => {
+                    frame = Some(next);
+                    self.taken += 1;
+                },
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break
+            }
+        }
+        frame
+    }
+}
+
+impl Drop for VideoDecoder {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// A video file together with the texture it's frames get uploaded to.
+///
+/// The video follows the time it gets rendered at: it starts decoding when it's first shown and
+/// gets restarted at the new time whenever the time jumps back (e.g. after restarting the slide)
+/// or far ahead (e.g. after scrubbing). While the time stands still (e.g. while paused), the
+/// current frame stays visible.
+pub struct VideoStream {
+    path: PathBuf,
+    info: VideoInfo,
+    looped: bool,
+    decoder: Option<VideoDecoder>,
+    texture: Option<Texture>,
+    /// The time the video was last rendered at.
+    last_time: f64,
+    /// Whether starting the decoder failed, so it doesn't get retried on every frame.
+    failed: bool
+}
+
+impl VideoStream {
+    /// Probes a video file, without decoding it yet.
+    pub fn new<P: AsRef<Path>>(path: P, looped: bool) -> anyhow::Result<VideoStream> {
+        let info = VideoInfo::probe(path.as_ref())?;
+        Ok(VideoStream { path: path.as_ref().to_path_buf(), info, looped, decoder: None, texture: None, last_time: 0.0, failed: false })
+    }
+
+    /// Uploads the frame shown at the given time, returning the texture containing it.
+    ///
+    /// Returns [`None`] if no frame has been decoded yet. Needs to be called on the thread owning
+    /// the OpenGL context.
+    pub fn texture(&mut self, time: f64, settings: &TextureSettings) -> Option<&Texture> {
+        let jumped = match &self.decoder {
+            Some(_) => time < self.last_time || time > self.last_time + SEEK_THRESHOLD,
+            None => !self.failed
+        };
+        self.last_time = time;
+
+        if jumped {
+            // Looped videos continue at the same point of their current repetition
+            let offset = match (self.looped, self.info.duration) {
+                (true, Some(duration)) => time.max(0.0) % duration,
+                _ => time.max(0.0)
+            };
+            // Stop the old decoder before starting the new one
+            self.decoder = None;
+            self.decoder = VideoDecoder::open(&self.path, &self.info, self.looped, offset, time)
+                .map_err(|e| log_err!("Decoding video {} failed: {e}", self.path.display())).ok();
+            self.failed = self.decoder.is_none();
+        }
+
+        if let Some(frame) = self.decoder.as_mut().and_then(|decoder| decoder.frame_at(time)) {
+            match &mut self.texture {
+                Some(texture) => texture.update(&frame),
+                None => self.texture = Some(Texture::from_image(&frame, settings))
+            }
+        }
+
+        self.texture.as_ref()
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+use crate::parse::Parser;
+use crate::presentation::Presentation;
+use crate::state::RehearsalState;
+
+/// The name of the baseline referring to the last rehearsal instead of a git revision.
+pub const REHEARSAL_BASELINE: &'static str = "rehearsal";
+
+/// How long a document needs to be presented in seconds before it counts as rehearsed while the
+/// viewer is still open. Otherwise the rehearsal only gets recorded once the viewer gets closed,
+/// so a viewer that gets killed or restarted right away doesn't count.
+pub const MIN_REHEARSAL_TIME: f64 = 60.0;
+
+/// What the slides of a document get compared against to find the ones that changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Baseline {
+    /// The document as it was committed in a git revision (e.g. `HEAD~3` or a tag).
+    Revision(String),
+    /// The document as it was when the viewer last presented it (see [`MIN_REHEARSAL_TIME`]).
+    Rehearsal
+}
+
+impl Baseline {
+    /// Returns the baseline with the given name, which is either [`REHEARSAL_BASELINE`] or a git
+    /// revision.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            REHEARSAL_BASELINE => Baseline::Rehearsal,
+            revision => Baseline::Revision(revision.to_owned())
+        }
+    }
+
+    /// Describes the baseline for listing the changes since it.
+    pub fn describe(&self) -> String {
+        match self {
+            Baseline::Revision(revision) => format!("revision {revision}"),
+            Baseline::Rehearsal => "the last rehearsal".to_owned()
+        }
+    }
+
+    /// Returns the content hashes of the document's slides at the baseline.
+    ///
+    /// A revision of the document gets read using `git show` and parsed like the current one, so
+    /// the fonts it uses need to be loaded already.
+    pub fn hashes(&self, document: &Path) -> anyhow::Result<HashSet<u64>> {
+        match self {
+            Baseline::Rehearsal => RehearsalState::load(document)?.hashes.iter()
+                .map(|hash| u64::from_str_radix(hash, 16).map_err(|_| anyhow::anyhow!("invalid slide hash '{hash}' in the rehearsal file")))
+                .collect(),
+            Baseline::Revision(revision) => {
+                // Running git inside of the document's directory finds the repository it's in
+                let directory = document.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+                let name = document.file_name().ok_or(anyhow::anyhow!("{} isn't a file", document.display()))?;
+
+                let output = Command::new("git")
+                    .current_dir(directory)
+                    .arg("show")
+                    .arg(format!("{revision}:./{}", name.to_string_lossy()))
+                    .output()
+                    .map_err(|e| anyhow::anyhow!("couldn't run git: {e}"))?;
+                if !output.status.success() {
+                    anyhow::bail!("git couldn't read {} at {revision}: {}", document.display(), String::from_utf8_lossy(&output.stderr).trim());
+                }
+                let contents = String::from_utf8(output.stdout)?;
+
+                let mut parser = crate::parse::get_parser(document).ok_or(anyhow::anyhow!("No parser found for file type!"))?;
+                let slides = parser.parse(&contents)
+                    .map_err(|e| anyhow::anyhow!("couldn't parse {} at {revision}: {e}", document.display()))?;
+                Ok(slides.iter().map(|slide| slide.content_hash).collect())
+            }
+        }
+    }
+}
+
+/// Returns the indices of the slides whose content isn't part of the baseline's slides.
+///
+/// Slides get compared by their content only, so moved slides don't count as changed. Slides
+/// without a content hash (which weren't parsed from the document) never count as changed.
+pub fn changed_slides(presentation: &Presentation, baseline: &HashSet<u64>) -> HashSet<usize> {
+    (0..presentation.document_slide_count())
+        .filter(|index| presentation.slide(*index)
+            .and_then(|slide| slide.content_hash())
+            .is_some_and(|hash| !baseline.contains(&hash)))
+        .collect()
+}
+
+/// Formats a content hash as a hexadecimal number, the way it gets saved and printed.
+pub fn format_hash(hash: u64) -> String {
+    format!("{hash:016x}")
+}
+
+/// Saves the content hashes of the presentation's slides as the document's last rehearsal.
+pub fn record_rehearsal(document: &Path, presentation: &Presentation) -> anyhow::Result<()> {
+    let hashes = (0..presentation.document_slide_count())
+        .filter_map(|index| presentation.slide(index)?.content_hash())
+        .map(format_hash)
+        .collect();
+    RehearsalState { hashes }.save(document)
+}
@@ -0,0 +1,341 @@
+use std::collections::{ HashMap, HashSet };
+use std::path::{ Path, PathBuf };
+
+#[allow(unused)]
+use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
+
+/// The directory dictionaries get searched in if none is configured.
+pub const DEFAULT_DICTIONARY_DIR: &'static str = "/usr/share/hunspell";
+
+/// The maximum amount of suggestions returned for a misspelled word.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// How the affix flags of a Hunspell dictionary are written, set by the `FLAG` option of it's
+/// affix file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FlagType {
+    /// Every character is a flag (the default).
+    Char,
+    /// Every two characters are a flag.
+    Long,
+    /// The flags are comma separated numbers.
+    Numeric
+}
+
+impl FlagType {
+    /// Splits the flags of a word or affix into the single flags.
+    fn split(&self, flags: &str) -> Vec<String> {
+        match self {
+            FlagType::Char => flags.chars().map(String::from).collect(),
+            FlagType::Long => flags.chars().collect::<Vec<_>>().chunks(2).map(|flag| flag.iter().collect()).collect(),
+            FlagType::Numeric => flags.split(',').map(|flag| flag.trim().to_owned()).filter(|flag| !flag.is_empty()).collect()
+        }
+    }
+}
+
+/// A single character of the condition of an affix rule.
+#[derive(Clone, Debug)]
+enum Condition {
+    /// Any character (`.`).
+    Any,
+    /// One of the characters (`[abc]` or a single character), or any other character if negated
+    /// (`[^abc]`).
+    Set(Vec<char>, bool)
+}
+
+impl Condition {
+    /// Parses a condition like `[^aeiou]y`.
+    fn parse(condition: &str) -> Vec<Condition> {
+        let mut result = Vec::new();
+        let mut chars = condition.chars();
+        while let Some(c) = chars.next() {
+            result.push(match c {
+                '.' => Condition::Any,
+                '[' => {
+                    let set = chars.by_ref().take_while(|c| *c != ']').collect::<Vec<_>>();
+                    match set.first() {
+                        Some('^') => Condition::Set(set[1..].to_vec(), true),
+                        _ => Condition::Set(set, false)
+                    }
+                },
+                c => Condition::Set(vec![c], false)
+            });
+        }
+        result
+    }
+
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Condition::Any => true,
+            Condition::Set(set, negated) => set.contains(&c) != *negated
+        }
+    }
+}
+
+/// A prefix or suffix rule of a Hunspell affix file, like `SFX D y ied [^aeiou]y`.
+#[derive(Clone, Debug)]
+struct AffixRule {
+    /// The flag words need to have for the rule to apply to them.
+    flag: String,
+    /// Whether the rule can be combined with a rule of the other kind (a prefix with a suffix).
+    cross_product: bool,
+    /// The characters removed from the word before adding the affix.
+    strip: String,
+    /// The affix added to the word.
+    add: String,
+    /// The characters the word needs to start (prefixes) or end (suffixes) with.
+    condition: Vec<Condition>
+}
+
+impl AffixRule {
+    /// Removes a prefix added by this rule from a word, returning the word it was added to.
+    fn remove_prefix(&self, word: &str) -> Option<String> {
+        let rest = word.strip_prefix(self.add.as_str()).filter(|rest| !rest.is_empty())?;
+        let stem = format!("{}{rest}", self.strip);
+        let matches = stem.chars().count() >= self.condition.len()
+            && self.condition.iter().zip(stem.chars()).all(|(condition, c)| condition.matches(c));
+        matches.then_some(stem)
+    }
+
+    /// Removes a suffix added by this rule from a word, returning the word it was added to.
+    fn remove_suffix(&self, word: &str) -> Option<String> {
+        let rest = word.strip_suffix(self.add.as_str()).filter(|rest| !rest.is_empty())?;
+        let stem = format!("{rest}{}", self.strip);
+        let matches = stem.chars().count() >= self.condition.len()
+            && self.condition.iter().rev().zip(stem.chars().rev()).all(|(condition, c)| condition.matches(c));
+        matches.then_some(stem)
+    }
+}
+
+/// The prefix and suffix rules of a Hunspell dictionary, read from it's `.aff` file.
+#[derive(Debug, Default)]
+struct Affixes {
+    prefixes: Vec<AffixRule>,
+    suffixes: Vec<AffixRule>,
+    flag_type: Option<FlagType>,
+    /// The flag sets `AF` aliases refer to by their position, starting at 1.
+    aliases: Vec<Vec<String>>
+}
+
+impl Affixes {
+    /// Parses the `FLAG`, `AF`, `PFX` and `SFX` options of an affix file, ignoring all others.
+    fn parse(contents: &str) -> Affixes {
+        let mut affixes = Affixes::default();
+        // Whether the rules of a flag can be combined, from the header line each group of rules
+        // starts with (e.g. `SFX D Y 4`)
+        let mut headers = HashMap::new();
+        let mut aliases_started = false;
+
+        for line in contents.lines() {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            match fields.as_slice() {
+                ["FLAG", flag_type, ..] => affixes.flag_type = match *flag_type {
+                    "long" => Some(FlagType::Long),
+                    "num" => Some(FlagType::Numeric),
+                    _ => Some(FlagType::Char)
+                },
+                // The first `AF` line only contains the amount of aliases
+                ["AF", flags, ..] => match aliases_started {
+                    true => affixes.aliases.push(affixes.flag_type().split(flags)),
+                    false => aliases_started = true
+                },
+                [kind @ ("PFX" | "SFX"), flag, rest @ ..] => {
+                    let cross_product = match headers.get(&(*kind, *flag)) {
+                        Some(cross_product) => *cross_product,
+                        None => {
+                            headers.insert((*kind, *flag), rest.first() == Some(&"Y"));
+                            continue
+                        }
+                    };
+                    let (strip, add) = match rest {
+                        [strip, add, ..] => (*strip, *add),
+                        _ => continue
+                    };
+                    let empty = |affix: &str| match affix {
+                        "0" => String::new(),
+                        affix => affix.to_lowercase()
+                    };
+                    let rule = AffixRule {
+                        flag: flag.to_string(),
+                        cross_product,
+                        strip: empty(strip),
+                        // The flags of twofold affixes aren't supported
+                        add: empty(add.split('/').next().unwrap_or("")),
+                        condition: Condition::parse(rest.get(2).copied().unwrap_or("."))
+                    };
+                    match *kind {
+                        "PFX" => affixes.prefixes.push(rule),
+                        _ => affixes.suffixes.push(rule)
+                    }
+                },
+                _ => ()
+            }
+        }
+
+        affixes
+    }
+
+    fn flag_type(&self) -> FlagType {
+        self.flag_type.unwrap_or(FlagType::Char)
+    }
+
+    /// Parses the flags following the slash of a dictionary entry.
+    fn flags(&self, flags: &str) -> Vec<String> {
+        match self.aliases.is_empty() {
+            true => self.flag_type().split(flags),
+            false => flags.parse::<usize>().ok()
+                .and_then(|alias| self.aliases.get(alias.checked_sub(1)?))
+                .cloned()
+                .unwrap_or_default()
+        }
+    }
+}
+
+/// Decodes a dictionary or affix file, which are often encoded in ISO-8859-1 instead of UTF-8.
+fn decode(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| e.into_bytes().into_iter().map(char::from).collect())
+}
+
+/// A simple dictionary based spell checker.
+///
+/// Reads word lists and Hunspell `.dic` files. If a `.aff` file with the same name exists next to
+/// a `.dic` file, it's prefix and suffix rules are applied to the words, so inflected forms like
+/// plurals are known as well. Suggestions are the known words one edit away from the misspelled
+/// word.
+pub struct SpellChecker {
+    /// The known words with the affix flags they have.
+    words: HashMap<String, HashSet<String>>,
+    affixes: Affixes,
+    /// The results of previous checks, as the same words get checked every frame.
+    cache: HashMap<String, Option<Vec<String>>>
+}
+
+impl SpellChecker {
+    /// Loads the dictionary of a language from a directory.
+    ///
+    /// Uses `<language>.dic` if it exists or the first file for a regional variant of the
+    /// language (e.g. `en_US.dic` for `en`) otherwise.
+    pub fn for_language<P: AsRef<Path>>(directory: P, language: &str) -> anyhow::Result<SpellChecker> {
+        let language = language.replace('-', "_");
+
+        let exact = directory.as_ref().join(format!("{language}.dic"));
+        let path = match exact.exists() {
+            true => exact,
+            false => {
+                let mut variants = std::fs::read_dir(directory.as_ref())?
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "dic"))
+                    .filter(|path| path.file_stem().is_some_and(|stem| stem.to_string_lossy().starts_with(&format!("{language}_"))))
+                    .collect::<Vec<PathBuf>>();
+                variants.sort();
+                variants.into_iter().next().ok_or(anyhow::anyhow!("no dictionary for language {language} found in {}", directory.as_ref().display()))?
+            }
+        };
+
+        Self::load(path)
+    }
+
+    /// Loads a dictionary file containing one word per line, together with the affix file next to
+    /// it if there is one.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<SpellChecker> {
+        let contents = decode(std::fs::read(path.as_ref())?);
+
+        let affix_path = path.as_ref().with_extension("aff");
+        let affixes = match affix_path.exists() {
+            true => Affixes::parse(&decode(std::fs::read(&affix_path)?)),
+            false => Affixes::default()
+        };
+
+        let mut words: HashMap<String, HashSet<String>> = HashMap::new();
+        for line in contents.lines() {
+            // Hunspell dictionaries start with the amount of words...
+            if line.chars().all(|c| c.is_ascii_digit()) {
+                continue
+            }
+            // ...and may have affix flags after a slash, followed by morphological fields
+            let entry = line.split_whitespace().next().unwrap_or("");
+            let (word, flags) = entry.split_once('/').unwrap_or((entry, ""));
+            let word = word.to_lowercase();
+            if !word.is_empty() {
+                words.entry(word).or_default().extend(affixes.flags(flags));
+            }
+        }
+
+        log_info!("Loaded {} words and {} affix rules for spell checking from {}", words.len(), affixes.prefixes.len() + affixes.suffixes.len(), path.as_ref().display());
+        Ok(SpellChecker { words, affixes, cache: HashMap::new() })
+    }
+
+    /// Checks a single word, returning suggestions if it's misspelled.
+    ///
+    /// Returns [`None`] if the word is spelled correctly or isn't a word (e.g. a number).
+    pub fn check(&mut self, word: &str) -> Option<&Vec<String>> {
+        if !self.cache.contains_key(word) {
+            let result = self.check_uncached(word);
+            self.cache.insert(word.to_owned(), result);
+        }
+        self.cache.get(word).unwrap().as_ref()
+    }
+
+    fn check_uncached(&self, word: &str) -> Option<Vec<String>> {
+        let lower = word.to_lowercase();
+        if !lower.chars().any(|c| c.is_alphabetic()) || lower.chars().any(|c| c.is_ascii_digit()) || self.is_known(&lower) {
+            return None
+        }
+
+        let mut suggestions = Self::edits(&lower).into_iter()
+            .filter(|candidate| self.is_known(candidate))
+            .collect::<Vec<_>>();
+        suggestions.sort();
+        suggestions.dedup();
+        suggestions.truncate(MAX_SUGGESTIONS);
+        Some(suggestions)
+    }
+
+    /// Returns whether a word is listed in the dictionary or can be formed from a listed word
+    /// using it's prefix and suffix rules.
+    fn is_known(&self, word: &str) -> bool {
+        let has_flags = |stem: &str, flags: &[&str]| self.words.get(stem)
+            .is_some_and(|stem_flags| flags.iter().all(|flag| stem_flags.contains(*flag)));
+
+        if self.words.contains_key(word) {
+            return true
+        }
+
+        let suffixed = self.affixes.suffixes.iter().any(|suffix| match suffix.remove_suffix(word) {
+            Some(stem) => has_flags(&stem, &[suffix.flag.as_str()]) || (suffix.cross_product && self.affixes.prefixes.iter()
+                .filter(|prefix| prefix.cross_product)
+                .any(|prefix| prefix.remove_prefix(&stem).is_some_and(|stem| has_flags(&stem, &[suffix.flag.as_str(), prefix.flag.as_str()])))),
+            None => false
+        });
+
+        suffixed || self.affixes.prefixes.iter()
+            .any(|prefix| prefix.remove_prefix(word).is_some_and(|stem| has_flags(&stem, &[prefix.flag.as_str()])))
+    }
+
+    /// Returns all strings one deletion, transposition, replacement or insertion away from a word.
+    fn edits(word: &str) -> Vec<String> {
+        let chars = word.chars().collect::<Vec<_>>();
+        let letters = ('a'..='z').chain("äöüßéèàç".chars()).collect::<Vec<_>>();
+        let mut edits = Vec::new();
+
+        for i in 0..=chars.len() {
+            let (left, right) = chars.split_at(i);
+            let left: String = left.iter().collect();
+
+            if !right.is_empty() {
+                edits.push(format!("{left}{}", right[1..].iter().collect::<String>()));
+            }
+            if right.len() > 1 {
+                edits.push(format!("{left}{}{}{}", right[1], right[0], right[2..].iter().collect::<String>()));
+            }
+            for letter in letters.iter() {
+                if !right.is_empty() {
+                    edits.push(format!("{left}{letter}{}", right[1..].iter().collect::<String>()));
+                }
+                edits.push(format!("{left}{letter}{}", right.iter().collect::<String>()));
+            }
+        }
+
+        edits
+    }
+}
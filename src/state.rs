@@ -0,0 +1,83 @@
+use std::path::{ Path, PathBuf };
+
+use serde::Deserialize;
+
+/// The extension appended to the name of a document to get the name of it's state file.
+const STATE_EXTENSION: &'static str = "state";
+
+/// The state of the viewer for a single document, which persists across restarts.
+///
+/// Gets saved to a hidden file next to the document whenever the slide changes, so presenting
+/// can be continued using the `--resume` flag after the viewer was closed by accident or crashed.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ViewerState {
+    /// The index of the last viewed slide.
+    pub slide: usize,
+}
+
+impl ViewerState {
+    /// Returns the path of the state file belonging to a document.
+    ///
+    /// For `talk.hjson`, this is `.talk.hjson.state` in the same directory.
+    pub fn path(document: &Path) -> PathBuf {
+        let name = document.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or("presentation".to_owned());
+        document.with_file_name(format!(".{name}.{STATE_EXTENSION}"))
+    }
+
+    /// Loads the state saved for a document.
+    pub fn load(document: &Path) -> anyhow::Result<Self> {
+        let path = Self::path(document);
+        let contents = std::fs::read_to_string(&path)?;
+
+        deser_hjson::from_str(contents.as_str())
+            .map_err(|e| anyhow::anyhow!("invalid state file at {}: {e}", path.display()))
+    }
+
+    /// Saves the state for a document, replacing any previously saved state.
+    pub fn save(&self, document: &Path) -> anyhow::Result<()> {
+        std::fs::write(Self::path(document), format!("{{\n    slide: {}\n}}\n", self.slide))?;
+        Ok(())
+    }
+}
+
+/// The extension appended to the name of a document to get the name of it's rehearsal file.
+const REHEARSAL_EXTENSION: &'static str = "rehearsal";
+
+/// The content of a document's slides when it was last presented.
+///
+/// Gets saved to a hidden file next to the document whenever the viewer presents it, so the slides
+/// that changed since the last rehearsal can be found (see [`crate::review`]).
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct RehearsalState {
+    /// The content hashes of the slides as hexadecimal numbers, in the order of the slides.
+    pub hashes: Vec<String>,
+}
+
+impl RehearsalState {
+    /// Returns the path of the rehearsal file belonging to a document.
+    ///
+    /// For `talk.hjson`, this is `.talk.hjson.rehearsal` in the same directory.
+    pub fn path(document: &Path) -> PathBuf {
+        let name = document.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or("presentation".to_owned());
+        document.with_file_name(format!(".{name}.{REHEARSAL_EXTENSION}"))
+    }
+
+    /// Loads the rehearsal saved for a document.
+    pub fn load(document: &Path) -> anyhow::Result<Self> {
+        let path = Self::path(document);
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("the document wasn't rehearsed yet ({}: {e})", path.display()))?;
+
+        deser_hjson::from_str(contents.as_str())
+            .map_err(|e| anyhow::anyhow!("invalid rehearsal file at {}: {e}", path.display()))
+    }
+
+    /// Saves the rehearsal for a document, replacing any previously saved one.
+    pub fn save(&self, document: &Path) -> anyhow::Result<()> {
+        let hashes = self.hashes.iter().map(|hash| format!("        \"{hash}\"\n")).collect::<String>();
+        std::fs::write(Self::path(document), format!("{{\n    hashes: [\n{hashes}    ]\n}}\n"))?;
+        Ok(())
+    }
+}
@@ -1,22 +1,60 @@
-use std::collections::HashMap;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::path::{ Path, PathBuf };
 use std::time::Instant;
 
-use opengl_graphics::{ GlGraphics, OpenGL };
-use piston::{RenderArgs, UpdateArgs, ButtonArgs, Button, ButtonState, Key};
+use opengl_graphics::{ GlGraphics, OpenGL, Texture };
+use piston::{RenderArgs, UpdateArgs, ButtonArgs, Button, ButtonState, Key, MouseButton};
 use piston_window::PistonWindow;
 
 #[allow(unused)]
 use log::{ debug as log_dbg, info as log_info, warn as log_warn, error as log_err };
 
-use super::util::{ PanickingOption, AssumeThreadSafe };
+use super::util::PanickingOption;
 use super::presentation;
+use super::engine;
+use super::state::ViewerState;
+use super::chapters::ChapterLog;
+use super::render::output::FrameOutput;
+use super::render::layer::Layer;
+use super::render::pointer::PointerOverlay;
+use super::render::teleprompter::Teleprompter;
+use super::render::overview::Overview;
+use super::render::color_filter::ColorFilter;
+use super::audio::{ AudioPlayer, SoundEffects };
+use super::midi::{ MidiListener, MidiBinding, MidiAction };
+use super::messages::MessageListener;
+#[cfg(target_os = "linux")]
+use super::mpris::{ MediaControls, MediaCommand };
 
 // Gets used for automatic links in comments.
 #[allow(unused)]
 use crate::presentation::Renderable;
-use crate::presentation::renderable::BaseProperties;
+
+/// The time in seconds after which an unmoved mouse cursor gets hidden.
+pub const CURSOR_HIDE_DELAY: f64 = 2.0;
+
+/// The color of the highlight behind selected text.
+const SELECTION_COLOR: [f32; 4] = [0.2, 0.45, 1.0, 0.35];
+
+/// The amount of seconds the slide time gets moved by when scrubbing.
+pub const TIME_SCRUB_STEP: f64 = 0.5;
+
+/// The amount of seconds the slide time gets moved by when stepping a single frame.
+pub const FRAME_STEP: f64 = 1.0 / 60.0;
+
+/// The speeds the slide time can run at, cycled through using the slow motion key.
+const TIME_SCALES: [f64; 3] = [1.0, 0.5, 0.1];
+
+/// The interval in seconds at which dropped-frame statistics get logged, if enabled.
+const FRAME_STATS_INTERVAL: f64 = 5.0;
+
+/// How much longer than expected a frame may take before it counts as dropped.
+const DROPPED_FRAME_TOLERANCE: f64 = 1.5;
+
+/// The time in seconds a quit key needs to be pressed again within to confirm quitting.
+const QUIT_CONFIRM_TIMEOUT: f64 = 2.0;
+
+/// The time in seconds a message to the presenter stays on the window.
+const MESSAGE_DURATION: f64 = 8.0;
 
 pub struct Application {
     pub opengl_version: OpenGL,
@@ -46,77 +84,251 @@ pub struct AppData {
     /// Only enabled in debug relases or with the 'debug_features' feature-flag.
     #[cfg(any(debug_features))]
     frames: u32,
-    /// Captures the state for the left/A, right/D and F11 keys.
-    last_press: (bool, bool, bool)
+    /// Captures the state for the previous slide, next slide and fullscreen keys.
+    last_press: (bool, bool, bool),
+    /// The last time the mouse cursor was moved.
+    /// 
+    /// Gets used for hiding the cursor after it wasn't moved for [`CURSOR_HIDE_DELAY`] seconds.
+    /// Is [`None`] if the cursor should be hidden until it gets moved again.
+    last_mouse_move: Option<Instant>,
+    /// The output the rendered frames get published to, if enabled in the user configuration.
+    video_output: Option<Box<dyn FrameOutput>>,
+    /// The path of the presentation file.
+    /// 
+    /// Screenshots get saved next to it.
+    document_path: PathBuf,
+    /// Whether a screenshot should be taken after rendering the next frame.
+    screenshot_requested: bool,
+    /// The audio player used for playing the narration of slides.
+    /// 
+    /// Is [`None`] if no audio output device is available.
+    audio: Option<AudioPlayer>,
+    /// The playback state of the current slide's narration.
+    narration_state: NarrationState,
+    /// The sound effects configured in the document.
+    sounds: SoundEffects,
+    /// The state of the search overlay, if it's open.
+    search: Option<Search>,
+    /// Whether a control key is currently being held down.
+    ctrl_held: bool,
+    /// The connection to the MIDI controller.
+    /// 
+    /// Is [`None`] if the document doesn't bind any MIDI messages or no controller is available.
+    midi: Option<MidiListener>,
+    /// The MIDI bindings configured in the document.
+    midi_bindings: Vec<CompiledMidiBinding>,
+    /// The media player registered on the D-Bus session bus.
+    /// 
+    /// Is [`None`] if it's disabled in the user configuration or no session bus is available.
+    #[cfg(target_os = "linux")]
+    media_controls: Option<MediaControls>,
+    /// Whether the bounding boxes of all objects get drawn on top of the slide.
+    debug_outlines: bool,
+    /// The color vision deficiency simulated on top of the slide.
+    color_filter: ColorFilter,
+    /// The texture the filtered frame gets drawn with, reused between frames.
+    color_filter_texture: Option<Texture>,
+    /// Whether the slide time is paused, holding all time-based animations in their current state.
+    paused: bool,
+    /// The factor the slide time advances by relative to real time, for slow motion.
+    time_scale: f64,
+    /// The last known position of the mouse cursor in window coordinates.
+    cursor: [f64; 2],
+    /// The size of the window in window coordinates, as of the last rendered frame.
+    window_size: [f64; 2],
+    /// The indices of the first and last selected characters (see [`presentation::selection`]).
+    /// 
+    /// The first index is where the selection was started, so it can be larger than the second.
+    selection: Option<(usize, usize)>,
+    /// Whether the selection is currently being dragged.
+    selecting: bool,
+    /// The system clipboard selected text gets copied to.
+    /// 
+    /// Gets created when copying for the first time and kept alive afterwards, as the copied text
+    /// would get lost on some platforms otherwise.
+    clipboard: Option<arboard::Clipboard>,
+    /// The last time the font files were checked for changes.
+    last_font_check: Instant,
+    /// Statistics about the timing of rendered frames.
+    frame_stats: FrameStats,
+    /// When a quit key was pressed, if quitting still needs to be confirmed by pressing it again.
+    quit_pending: Option<Instant>,
+    /// Whether the window should be closed.
+    quit: bool,
+    /// The layer the current slide gets rendered into before being composited onto the window.
+    /// 
+    /// Gets created when rendering the first frame, as the window's size isn't known before.
+    slide_layer: Option<Layer>,
+    /// The trail and click ripples following the mouse cursor.
+    pointer: PointerOverlay,
+    /// The log of slide changes written while presenting, if enabled.
+    chapters: Option<ChapterLog>,
+    /// The teleprompter shown instead of the slides, if it's enabled.
+    teleprompter: Option<Teleprompter>,
+    /// The overview of all slides, if it's open.
+    overview: Option<Overview>,
+    /// The source of messages to the presenter, if enabled in the user configuration.
+    messages: Option<MessageListener>,
+    /// The last message to the presenter and when it was received.
+    message: Option<(String, Instant)>
+}
+
+/// Counts frames that took longer than the display (or frame limiter) allows.
+struct FrameStats {
+    /// The time in seconds a frame is expected to take, based on the monitor's refresh rate and
+    /// the configured frame limit. Frames can't be counted as dropped if it is unknown.
+    target_interval: Option<f64>,
+    /// The amount of frames rendered since the statistics were last logged.
+    frames: u32,
+    /// The amount of frames dropped since the statistics were last logged.
+    dropped: u32,
+    /// The longest time between two frames since the statistics were last logged.
+    longest: f64,
+    /// The last time the statistics were logged.
+    last_report: Instant
 }
-impl AppData {
-    pub fn create(filepath: String) -> AppData {
-        use crate::parse::{ self, Parser };
 
-        // Read the contents of the presentation file
-        let filecontents: String = std::fs::read_to_string(filepath.as_str()).unwrap();
+impl FrameStats {
+    fn new() -> Self {
+        FrameStats { target_interval: None, frames: 0, dropped: 0, longest: 0.0, last_report: Instant::now() }
+    }
 
-        // Create an instance of a parser (which parser gets instantiated depends on the file extension)
-        let mut parser = parse::get_parser(filepath.as_str()).expect("No parser found for file type!");
+    /// Sets the expected time between frames from the refresh rate of the monitor and the
+    /// configured frame limit, whichever is lower.
+    fn set_refresh_rate(&mut self, refresh_rate: Option<f64>) {
+        let max_fps = crate::CONFIG.get().unwrap().max_fps.map(|fps| fps as f64);
+        self.target_interval = match (refresh_rate.filter(|hz| *hz > 0.0), max_fps) {
+            (Some(hz), Some(fps)) => Some(1.0 / hz.min(fps)),
+            (Some(rate), None) | (None, Some(rate)) => Some(1.0 / rate),
+            (None, None) => None
+        };
+    }
 
-        let document_fonts = parser.parse_fonts(filecontents.as_str()).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
-        crate::FONTS.set({
-            let mut map = HashMap::new();
+    /// Records a frame that was rendered `dt` seconds after the previous one, logging the
+    /// statistics every [`FRAME_STATS_INTERVAL`] seconds.
+    fn record(&mut self, dt: f64) {
+        self.frames += 1;
+        self.longest = self.longest.max(dt);
 
-            // Adds the default font in case it was included into the binary at compile time.
-            #[cfg(default_font)]
-            {
-                let bytes = include_bytes!("OpenSans.ttf") as &[u8];
+        if let Some(target) = self.target_interval {
+            if dt > target * DROPPED_FRAME_TOLERANCE {
+                self.dropped += (dt / target).round() as u32 - 1;
+            }
+        }
 
-                // let face = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default()).expect("couldn't parse default font's data");
+        let elapsed = self.last_report.elapsed().as_secs_f64();
+        if elapsed >= FRAME_STATS_INTERVAL {
+            let target = self.target_interval.map(|t| format!("{:.1}", 1.0 / t)).unwrap_or("unknown".to_owned());
+            log_info!("{:.1} FPS (target: {target}), {} dropped frames, longest frame took {:.1} ms",
+                self.frames as f64 / elapsed, self.dropped, self.longest * 1000.0);
 
-                let base_font = crate::render::font::Font::from_bytes(bytes.to_vec(), 0, "Default (bundled)".to_owned()).expect("couldn't parse default font's data");
-                let bold_font = crate::render::font::Font::from_bytes(bytes.to_vec(), 0, "Default (bundled)".to_owned()).expect("couldn't parse default font's data");
+            self.frames = 0;
+            self.dropped = 0;
+            self.longest = 0.0;
+            self.last_report = Instant::now();
+        }
+    }
+}
 
-                map.insert("Default".to_owned(), Rc::new(RefCell::new(presentation::TextFont { base_font, bold_font })));
-            }
+/// A MIDI binding with it's Lua handler already compiled.
+struct CompiledMidiBinding {
+    binding: MidiBinding,
+    handler: Option<mlua::Function<'static>>
+}
 
-            for (name, path) in document_fonts {
-                map.insert(name, Rc::new(RefCell::new(presentation::renderable::TextFont::new(path.0, path.1))));
-            }
+/// The state of the search overlay.
+struct Search {
+    /// The text that is being searched for.
+    query: String,
+    /// All lines of text matching the query.
+    results: Vec<presentation::SearchResult>,
+    /// The index of the currently selected result.
+    selected: usize
+}
 
-            AssumeThreadSafe(map)
-        }).ok().expect("error initializing fonts");
+/// The maximum amount of search results shown at once.
+const MAX_SEARCH_RESULTS: usize = 10;
 
-        let document = parser.parse(filecontents.as_str()).unwrap_or_else(|e| { parser.handle_error(e); unreachable!() });
+/// The playback state of a slide's narration.
+#[derive(Clone, Copy, Debug)]
+enum NarrationState {
+    /// Waiting for the narration's pre-delay to pass.
+    PreDelay,
+    /// The narration is currently playing.
+    Playing,
+    /// The narration finished playing at the contained slide time; waiting for the post-delay to
+    /// pass before advancing to the next slide.
+    PostDelay(f64),
+    /// The narration was played or couldn't be played.
+    Done
+}
+impl AppData {
+    pub fn create(filepath: String) -> AppData {
+        let engine::Document { mut presentation, sounds, midi_bindings } = engine::load_document(&filepath);
 
-        let mut presentation = presentation::Presentation::new();
+        // Compile the Lua handlers of the MIDI bindings
+        let midi_bindings = midi_bindings.into_iter().filter_map(|binding| {
+            let handler = match &binding.action {
+                // Documents opened in safe mode can't run any code
+                MidiAction::Lua(_) if crate::CONFIG.get().unwrap().safe_mode => {
+                    log_warn!("Lua handlers of MIDI bindings are disabled in safe mode");
+                    None
+                },
+                MidiAction::Lua(code) => {
+                    let lua: &'static mlua::Lua = &*crate::LUA_INSTANCE.get().unwrap();
+                    match lua.load(format!("local number, value = ...\n{code}")).into_function() {
+                        Ok(function) => Some(function),
+                        // Bindings with invalid code get skipped, the other bindings keep working
+                        Err(e) => {
+                            log_err!("Invalid Lua code in MIDI binding, skipping it: {e}");
+                            return None
+                        }
+                    }
+                },
+                _ => None
+            };
+            Some(CompiledMidiBinding { binding, handler })
+        }).collect::<Vec<_>>();
 
-        for slide_data in document {
-            let mut slide = presentation::Slide::new(slide_data.background);
-            for (z, content) in slide_data.content {
-                for renderable in content {
-                    slide.add_boxed(renderable, z);
-                }
-            }
-            presentation.add_slide(slide);
-        }
+        let midi = match midi_bindings.is_empty() {
+            true => None,
+            false => MidiListener::connect(crate::CONFIG.get().unwrap().midi_port.as_deref())
+                .map_err(|e| log_warn!("No MIDI input available, MIDI bindings are disabled: {e}")).ok()
+        };
 
-        // Adds an 'End of presentation' slide. This can only be done when including the default
-        // font though, as the text needs a font to render itself.
-        #[cfg(default_font)]
-        {
-            let bg = presentation::ColoredRect::new(BaseProperties::new("0;0", "w;h", "0;0;0;1", "TOP_LEFT").map_err(|_|()).unwrap());
-            let mut last_slide = presentation::Slide::new(Box::new(bg) as Box<dyn presentation::Renderable>);
+        #[cfg(target_os = "linux")]
+        let media_controls = match crate::CONFIG.get().unwrap().media_controls {
+            true => MediaControls::connect().map_err(|e| log_warn!("Registering as a media player failed, media keys are disabled: {e}")).ok(),
+            false => None
+        };
 
-            let text = presentation::Text::new(
-                BaseProperties::new("0;0","w;4%","1;1;1;1","TOP_LEFT").map_err(|_|()).unwrap(),
-                vec!["End of presentation"],
-                "Default".to_owned(),
-                &*crate::FONTS.get().unwrap(),
-                HashMap::new(),
-                "LEFT").map_err(|_|()).unwrap();
-            last_slide.add(text, 0);
+        presentation.set_transparent(crate::CONFIG.get().unwrap().transparent);
+        presentation.set_default_transition(match crate::CONFIG.get().unwrap().background_fade {
+            duration if duration > 0.0 => presentation::Transition::Fade { duration },
+            _ => presentation::Transition::Cut
+        });
 
-            presentation.add_slide(last_slide);
+        // Continue on the slide that was shown when the viewer was last closed
+        if crate::CONFIG.get().unwrap().resume {
+            match ViewerState::load(Path::new(&filepath)) {
+                Ok(state) => {
+                    log_info!("Resuming on slide #{}", state.slide);
+                    presentation.go_to_slide(state.slide);
+                },
+                Err(e) => log_warn!("Can't resume the presentation, starting on the first slide: {e}")
+            }
         }
 
-        AppData {
+        // Start logging chapters with the first shown slide
+        let chapters = crate::CONFIG.get().unwrap().chapters.as_ref().and_then(|path| {
+            ChapterLog::create(path, presentation.title())
+                .map_err(|e| log_err!("Creating the chapter log at {} failed: {e}", path.display())).ok()
+        });
+
+        let words_per_minute = crate::CONFIG.get().unwrap().words_per_minute;
+        log_info!("The presentation contains {} words, presenting it takes about {:.0} minutes", presentation.word_count(), presentation.estimated_duration(words_per_minute) / 60.0);
+
+        let mut data = AppData {
             presentation,
             time: 0.0,
             last_frame: Instant::now(),
@@ -124,7 +336,56 @@ impl AppData {
             timeint: 0,
             #[cfg(any(debug_features))]
             frames: 0,
-            last_press: (false, false, false)
+            last_press: (false, false, false),
+            last_mouse_move: Some(Instant::now()),
+            video_output: None,
+            document_path: PathBuf::from(filepath),
+            screenshot_requested: false,
+            audio: AudioPlayer::new().map_err(|e| log_warn!("No audio output available, narrations are disabled: {e}")).ok(),
+            narration_state: NarrationState::PreDelay,
+            sounds,
+            search: None,
+            ctrl_held: false,
+            midi,
+            midi_bindings,
+            #[cfg(target_os = "linux")]
+            media_controls,
+            debug_outlines: crate::CONFIG.get().unwrap().debug_outlines,
+            color_filter: crate::CONFIG.get().unwrap().color_filter,
+            color_filter_texture: None,
+            paused: false,
+            time_scale: 1.0,
+            cursor: [0.0, 0.0],
+            window_size: [0.0, 0.0],
+            selection: None,
+            selecting: false,
+            clipboard: None,
+            last_font_check: Instant::now(),
+            frame_stats: FrameStats::new(),
+            quit_pending: None,
+            quit: false,
+            slide_layer: None,
+            pointer: PointerOverlay::new(),
+            chapters,
+            teleprompter: crate::CONFIG.get().unwrap().teleprompter.then(|| Teleprompter::new(words_per_minute)),
+            overview: None,
+            messages: crate::CONFIG.get().unwrap().messages.as_deref().map(MessageListener::open),
+            message: None
+        };
+        data.log_chapter();
+        data
+    }
+
+    /// Adds the current slide to the chapter log, if it's enabled.
+    fn log_chapter(&mut self) {
+        let slide = self.presentation.current_slide_index();
+        let title = self.presentation.current_slide().and_then(|s| s.title()).map(|t| t.to_owned()).unwrap_or(format!("Slide {}", slide + 1));
+
+        if let Some(chapters) = &mut self.chapters {
+            if let Err(e) = chapters.slide_shown(slide, title) {
+                log_err!("Writing to the chapter log failed, disabling it: {e}");
+                self.chapters = None;
+            }
         }
     }
 }
@@ -137,30 +398,47 @@ impl Application {
         Application { opengl_version, opengl_backend: PanickingOption::None, data: PanickingOption::None }
     }
     /// Initializes all the data and state of the application.
-    pub fn init<Str: Into<String>>(&mut self, title: Str, resolution: (u32, u32), vsync: bool, resizable: bool, decoration: bool, filepath: String) -> PistonWindow {
-        // Initialize the logging backend
-        pretty_env_logger::try_init_timed_custom_env("LOG").unwrap();
-
-        // Create the window
-        let window = piston::window::WindowSettings::new(title.into(), [resolution.0,resolution.1])
-            .graphics_api(self.opengl_version)
-            .exit_on_esc(true)
-            .vsync(vsync)
-            .resizable(resizable)
-            .decorated(decoration)
-            .samples(0)
-            .srgb(true)
-            .build()
-            .unwrap();
+    pub fn init<Str: Into<String>>(&mut self, title: Str, resolution: (u32, u32), vsync: bool, resizable: bool, decoration: bool, transparent: bool, filepath: String) -> PistonWindow {
+        // Esc gets handled like the other quit keys, so it can close the search overlay and
+        // quitting can be confirmed
+        let title: String = title.into();
+        // The window gets shown once the document is loaded and warmed up (see
+        // [`Application::warm_up`]), other commands only need it for it's OpenGL context
+        let window = engine::create_window(title.as_str(), self.opengl_version, resolution, vsync, resizable, decoration, transparent, false, false);
         // Create the OpenGL context
         self.opengl_backend = PanickingOption::Some(GlGraphics::new(self.opengl_version));
 
         // Create the application's data
         self.data = PanickingOption::Some(AppData::create(filepath));
+        engine::set_document_title(&window, &title, &self.data.presentation);
 
         window
     }
 
+    /// Renders the current slide once without showing it, so compiling shaders, uploading
+    /// textures and rasterizing glyphs doesn't happen during the first visible frame.
+    /// 
+    /// `size` is the size of the window.
+    pub fn warm_up(&mut self, size: (u32, u32)) {
+        let start = Instant::now();
+        let index = self.data.presentation.current_slide_index();
+        match self.data.presentation.render_thumbnail(index, size, &mut self.opengl_backend) {
+            Some(_) => log_dbg!("Warmed up slide #{index} in {:.1} ms", start.elapsed().as_secs_f64() * 1000.0),
+            None => log_dbg!("Nothing to warm up, the presentation has no slides")
+        }
+    }
+
+    /// Sets the refresh rate of the monitor the window is on, which dropped frames get measured
+    /// against.
+    pub fn set_refresh_rate(&mut self, refresh_rate: Option<f64>) {
+        self.data.frame_stats.set_refresh_rate(refresh_rate);
+    }
+
+    /// Sets the output every rendered frame gets published to.
+    pub fn set_video_output(&mut self, output: Box<dyn FrameOutput>) {
+        self.data.video_output = Some(output);
+    }
+
     /// Renders the application
     pub fn render(&mut self, args: &RenderArgs) {
         // Increase the 'frames' counter if debugging
@@ -172,26 +450,122 @@ impl Application {
 
         // Calculate how much time has passed since rendering the last frame, then set
         // self.data.last_frame to the current point in time for the next frame.
+        //   The slide time doesn't advance while it's paused and advances slower in slow motion.
         let now = Instant::now();
         let dt = self.data.last_frame.elapsed().as_secs_f64();
-        self.data.time += dt;
+        if !self.data.paused {
+            self.data.time += dt * self.data.time_scale;
+        }
         self.data.last_frame = now;
 
-        // Draw the presentation
+        if crate::CONFIG.get().unwrap().frame_stats {
+            self.data.frame_stats.record(dt);
+        }
+
+        // The teleprompter scrolls in real time, but holds still while the slide time is paused
+        if let Some(teleprompter) = &mut self.data.teleprompter {
+            if !self.data.paused {
+                teleprompter.advance(dt);
+            }
+        }
+
+        // Draw the slide into it's layer
+        //   The layer matches the window's size in pixels, so compositing it doesn't lose any
+        //   sharpness. The video output keeps showing the slide while the teleprompter or the
+        //   overview replaces it on the window.
+        let time = self.data.time;
+        let draw_size = (args.draw_size[0], args.draw_size[1]);
+        self.data.window_size = args.window_size;
+        {
+            let data = &mut *self.data;
+            if (data.teleprompter.is_none() && data.overview.is_none()) || data.video_output.is_some() {
+                let layer = data.slide_layer.get_or_insert_with(|| Layer::new(draw_size));
+                layer.resize(draw_size);
+                layer.draw(&mut self.opengl_backend, args.window_size, |c, gl| data.presentation.render(time, c, gl));
+            }
+            if let Some(overview) = &mut data.overview {
+                overview.update_thumbnails(&data.presentation, draw_size, &mut self.opengl_backend);
+            }
+        }
+
+        // Composite the slide onto the window and draw the overlays on top of it
+        let quit_pending = self.quit_pending();
         self.opengl_backend.draw(args.viewport(), |c, gl| {
-            // We need to set a local variable here to copy the value, because we already mutably
-            // borrowed 'self' in the call above and would immutably borrow it by directly passing
-            // the value into the function call below, which we aren't allowed to do.
-            let time = self.data.time;
+            // The overview and the teleprompter replace the slide completely
+            if let Some(overview) = &self.data.overview {
+                overview.render(c, gl);
+            } else if self.data.teleprompter.is_some() {
+                let data = &mut *self.data;
+                let notes = data.presentation.current_slide().and_then(|s| s.notes());
+                data.teleprompter.as_mut().unwrap().render(notes, data.paused, c, gl);
+            } else if let Some(layer) = &self.data.slide_layer {
+                layer.composite(1.0, c, gl);
+            }
+
+            // The overlays belonging to the slide aren't shown on the overview
+            if self.data.overview.is_none() {
+                // Simulate color vision deficiencies only on the slide, keeping the overlays readable
+                self.data.color_filter.apply_to_frame(&mut self.data.color_filter_texture, c, gl);
+
+                // Highlight the selected text
+                if let Some((start, end)) = self.data.selection {
+                    for rect in presentation::selection::rects(start.min(end)..=start.max(end)) {
+                        graphics::rectangle(SELECTION_COLOR, rect, c.transform, gl);
+                    }
+                }
+
+                if self.data.debug_outlines {
+                    self.data.presentation.render_debug_outlines(time, c, gl);
+                }
+            }
 
-            self.data.presentation.render(time, c, gl);
+            if let Some(search) = &self.data.search {
+                Self::render_search(search, c, gl);
+            }
+
+            self.data.pointer.render(&crate::CONFIG.get().unwrap().pointer_effects, c, gl);
+
+            if quit_pending {
+                Self::render_quit_hint(c, gl);
+            }
+
+            if let Some((message, _)) = &self.data.message {
+                Self::render_message(message, c, gl);
+            }
         });
+
+        if self.data.screenshot_requested {
+            self.data.screenshot_requested = false;
+            self.save_screenshot(args.draw_size[0], args.draw_size[1]);
+        }
+
+        // Publish the rendered slide
+        //   It gets read from the slide's layer, so the overlays only meant for the presenter
+        //   (like the search box or the laser pointer) don't end up in the video.
+        let frame = match (&self.data.video_output, &self.data.slide_layer) {
+            (Some(_), Some(layer)) => Some(layer.capture()),
+            _ => None
+        };
+        if let (Some(output), Some(frame)) = (&mut self.data.video_output, frame) {
+            if let Err(e) = output.publish(&frame) {
+                log_err!("Publishing frame to video output failed, disabling it: {e}");
+                self.data.video_output = None;
+            }
+        }
     }
 
     /// Updates the application.
     /// 
-    /// Currently only used for measuring FPS if debugging is enabled.
+    /// Plays the narrations of slides and measures FPS if debugging is enabled.
     pub fn update(&mut self, _args: &UpdateArgs) {
+        self.update_narration();
+        self.update_auto_advance();
+        self.update_midi();
+        self.update_messages();
+        #[cfg(target_os = "linux")]
+        self.update_media_controls();
+        self.update_fonts();
+
         // self.data.time += args.dt;
         #[cfg(any(debug_features))]
         if self.data.time>= self.data.timeint as f64 + 1.0 {
@@ -202,33 +576,593 @@ impl Application {
         }
     }
 
+    /// Resets all slide-specific state after switching to another slide.
+    fn slide_changed(&mut self) {
+        self.data.time = 0.0;
+        self.data.selection = None;
+        self.data.selecting = false;
+        self.data.narration_state = NarrationState::PreDelay;
+        if let Some(audio) = &mut self.data.audio {
+            audio.stop();
+        }
+        if let Some(teleprompter) = &mut self.data.teleprompter {
+            teleprompter.restart();
+        }
+
+        self.data.log_chapter();
+
+        let state = ViewerState { slide: self.data.presentation.current_slide_index() };
+        if let Err(e) = state.save(&self.data.document_path) {
+            log_warn!("Saving the viewer's state failed: {e}");
+        }
+    }
+
+    /// Reloads fonts whose files changed, so edited fonts show up without restarting.
+    fn update_fonts(&mut self) {
+        if self.data.last_font_check.elapsed().as_secs_f64() < presentation::renderable::FONT_CHECK_INTERVAL {
+            return
+        }
+        self.data.last_font_check = Instant::now();
+
+        for name in presentation::renderable::reload_fonts(false) {
+            log_info!("Reloaded font '{name}'");
+        }
+    }
+
+    /// Shows the last message to the presenter received since the last update, hiding the shown
+    /// one after [`MESSAGE_DURATION`] seconds.
+    fn update_messages(&mut self) {
+        if let Some(message) = self.data.messages.as_ref().and_then(|messages| messages.poll().last()) {
+            log_info!("Message to the presenter: {message}");
+            self.data.message = Some((message, Instant::now()));
+        }
+        if self.data.message.as_ref().is_some_and(|(_, received)| received.elapsed().as_secs_f64() >= MESSAGE_DURATION) {
+            self.data.message = None;
+        }
+    }
+
+    /// Handles all MIDI messages received since the last update.
+    fn update_midi(&mut self) {
+        let messages = match &self.data.midi {
+            Some(midi) => midi.poll().collect::<Vec<_>>(),
+            None => return
+        };
+
+        for message in messages {
+            let mut navigation = None;
+
+            for compiled in self.data.midi_bindings.iter().filter(|b| b.binding.kind == message.kind && b.binding.number == message.number) {
+                match (&compiled.binding.action, &compiled.handler) {
+                    (MidiAction::Lua(_), Some(handler)) => {
+                        if let Err(e) = handler.call::<_, ()>((message.number, message.value)) {
+                            log_err!("Error in Lua handler of MIDI binding: {e}");
+                        }
+                    },
+                    (action, _) if message.is_press() => navigation = Some(action.clone()),
+                    _ => {}
+                }
+            }
+
+            match navigation {
+                Some(MidiAction::NextSlide) => {
+                    self.data.presentation.next_slide();
+                    self.slide_changed();
+                },
+                Some(MidiAction::PreviousSlide) => {
+                    self.data.presentation.previous_slide();
+                    self.slide_changed();
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// Handles all commands received from media player controllers since the last update and
+    /// publishes the current state to them.
+    #[cfg(target_os = "linux")]
+    fn update_media_controls(&mut self) {
+        let commands = match &self.data.media_controls {
+            Some(controls) => controls.poll().collect::<Vec<_>>(),
+            None => return
+        };
+
+        for command in commands {
+            match command {
+                MediaCommand::NextSlide => {
+                    self.data.presentation.next_slide();
+                    self.slide_changed();
+                },
+                MediaCommand::PreviousSlide => {
+                    self.data.presentation.previous_slide();
+                    self.slide_changed();
+                },
+                MediaCommand::Pause => self.data.paused = true,
+                MediaCommand::Play => self.data.paused = false,
+                MediaCommand::PlayPause => self.data.paused = !self.data.paused,
+                MediaCommand::Quit => self.data.quit = true
+            }
+        }
+
+        let slide = self.data.presentation.current_slide_index();
+        let title = self.data.presentation.current_slide().and_then(|s| s.title()).map(|t| t.to_owned()).unwrap_or(format!("Slide {}", slide + 1));
+        let paused = self.data.paused;
+        if let Some(controls) = &mut self.data.media_controls {
+            if let Err(e) = controls.publish(paused, &title) {
+                log_err!("Publishing the media player state failed, disabling media keys: {e}");
+                self.data.media_controls = None;
+            }
+        }
+    }
+
+    /// Plays a sound effect, if it's configured and audio output is available.
+    fn play_sound_effect(&mut self, path: Option<String>) {
+        if let (Some(audio), Some(path)) = (&mut self.data.audio, path) {
+            if let Err(e) = audio.play_effect(&path) {
+                log_err!("Playing sound effect {path} failed: {e}");
+            }
+        }
+    }
+
+    /// Switches to the next slide once the current slide's duration has passed.
+    /// 
+    /// Only enabled in the user configuration or when writing to a video output, so exported
+    /// videos show each slide for it's planned duration.
+    fn update_auto_advance(&mut self) {
+        let config = crate::CONFIG.get().unwrap();
+        if !config.auto_advance && self.data.video_output.is_none() {
+            return
+        }
+
+        let duration = match self.data.presentation.current_slide().and_then(|s| s.duration()) {
+            Some(d) => d,
+            None => return
+        };
+
+        if self.data.time >= duration {
+            self.data.presentation.next_slide();
+            self.slide_changed();
+        }
+    }
+
+    /// Plays the current slide's narration and advances to the next slide once it's finished.
+    fn update_narration(&mut self) {
+        let narration = match self.data.presentation.current_slide().and_then(|s| s.narration()) {
+            Some(n) => n.clone(),
+            None => return
+        };
+        let audio = match &mut self.data.audio {
+            Some(a) => a,
+            None => return
+        };
+
+        match self.data.narration_state {
+            NarrationState::PreDelay if self.data.time >= narration.pre_delay => {
+                match audio.play(&narration.path) {
+                    Ok(()) => self.data.narration_state = NarrationState::Playing,
+                    Err(e) => {
+                        log_err!("Playing narration {} failed: {e}", narration.path);
+                        self.data.narration_state = NarrationState::Done;
+                    }
+                }
+            },
+            NarrationState::Playing if !audio.is_playing() => {
+                self.data.narration_state = NarrationState::PostDelay(self.data.time);
+            },
+            NarrationState::PostDelay(finished) if self.data.time - finished >= narration.post_delay => {
+                self.data.presentation.next_slide();
+                self.slide_changed();
+            },
+            _ => {}
+        }
+    }
+
+    /// Saves the currently rendered frame to a timestamped PNG file next to the presentation file.
+    fn save_screenshot(&self, width: u32, height: u32) {
+        let frame = crate::render::capture::capture_frame(width, height);
+
+        let stem = self.data.document_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or("presentation".to_owned());
+        let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+        let path = self.data.document_path.with_file_name(format!("{stem}_{timestamp}.png"));
+
+        match frame.save(&path) {
+            Ok(()) => log_info!("Saved screenshot to {}", path.display()),
+            Err(e) => log_err!("Saving screenshot to {} failed: {e}", path.display())
+        }
+    }
+
+    /// Notifies the application that the mouse cursor has been moved.
+    /// 
+    /// Extends the text selection while it's being dragged.
+    pub fn mouse_moved(&mut self, position: [f64; 2]) {
+        self.data.last_mouse_move = Some(Instant::now());
+        self.data.cursor = position;
+        self.data.pointer.moved(position, &crate::CONFIG.get().unwrap().pointer_effects);
+
+        if self.data.selecting {
+            if let (Some((start, _)), Some(end)) = (self.data.selection, presentation::selection::glyph_at(position)) {
+                self.data.selection = Some((start, end));
+            }
+        }
+    }
+
+    /// Zooms and pans the overview, if it's open.
+    pub fn scroll(&mut self, amount: [f64; 2]) {
+        let data = &mut *self.data;
+        if let Some(overview) = &mut data.overview {
+            overview.scroll(amount, data.cursor, data.window_size);
+        }
+    }
+
+    /// Copies the selected text to the clipboard.
+    fn copy_selection(&mut self) {
+        let text = match self.data.selection {
+            Some((start, end)) => presentation::selection::text(start.min(end)..=start.max(end)),
+            None => return
+        };
+
+        if self.data.clipboard.is_none() {
+            self.data.clipboard = arboard::Clipboard::new().map_err(|e| log_err!("Clipboard isn't available: {e}")).ok();
+        }
+        if let Some(clipboard) = &mut self.data.clipboard {
+            match clipboard.set_text(text) {
+                Ok(()) => log_info!("Copied selected text to the clipboard"),
+                Err(e) => log_err!("Copying selected text to the clipboard failed: {e}")
+            }
+        }
+    }
+
+    /// Hides the mouse cursor until it gets moved again.
+    pub fn hide_cursor(&mut self) {
+        self.data.last_mouse_move = None;
+    }
+
+    /// Returns whether the mouse cursor should currently be visible.
+    pub fn cursor_visible(&self) -> bool {
+        match self.data.last_mouse_move {
+            Some(instant) => instant.elapsed().as_secs_f64() < CURSOR_HIDE_DELAY,
+            None => false
+        }
+    }
+
+    /// Renders the search overlay on top of the presentation.
+    fn render_search(search: &Search, context: graphics::Context, opengl: &mut GlGraphics) {
+        use graphics::Transformed;
+
+        let view_size = context.get_view_size();
+        let font_size = (view_size[1] * 0.035).max(crate::render::font::FONT_SCALE.0 as f64);
+        let line_height = font_size * 1.4;
+
+        // Only show a window of results around the selected one
+        let first = search.selected.saturating_sub(MAX_SEARCH_RESULTS - 1);
+        let shown = search.results.iter().enumerate().skip(first).take(MAX_SEARCH_RESULTS);
+
+        let lines = shown.len() + 1;
+        graphics::rectangle([0.0, 0.0, 0.0, 0.8], [0.0, 0.0, view_size[0], line_height * lines as f64 + font_size * 0.5], context.transform, opengl);
+
+        let fonts = crate::FONTS.get().unwrap();
+        let mut font = fonts.get("Default").unwrap().borrow_mut();
+
+        let header = match search.results.len() {
+            0 => format!("Find: {}_", search.query),
+            n => format!("Find: {}_   ({n} results)", search.query)
+        };
+        font.bold_font.draw(header, font_size, (1.0, 1.0, 1.0, 1.0), false, &context.trans(font_size * 0.5, font_size * 0.25), opengl);
+
+        for (row, (index, result)) in shown.enumerate() {
+            let color = if index == search.selected { (1.0, 0.85, 0.3, 1.0) } else { (0.85, 0.85, 0.85, 1.0) };
+            let ctx = context.trans(font_size * 0.5, font_size * 0.25 + line_height * (row + 1) as f64);
+            font.base_font.draw(format!("Slide {}: {}", result.slide + 1, result.line), font_size, color, false, &ctx, opengl);
+        }
+    }
+
+    /// Draws a hint asking to press the quit key again at the bottom of the window.
+    fn render_quit_hint(context: graphics::Context, opengl: &mut GlGraphics) {
+        use graphics::Transformed;
+
+        let view_size = context.get_view_size();
+        let font_size = (view_size[1] * 0.035).max(crate::render::font::FONT_SCALE.0 as f64);
+        let height = font_size * 1.9;
+
+        graphics::rectangle([0.0, 0.0, 0.0, 0.8], [0.0, view_size[1] - height, view_size[0], height], context.transform, opengl);
+
+        let fonts = crate::FONTS.get().unwrap();
+        let mut font = fonts.get("Default").unwrap().borrow_mut();
+        font.bold_font.draw("Press again to quit", font_size, (1.0, 1.0, 1.0, 1.0), false, &context.trans(font_size * 0.5, view_size[1] - height + font_size * 0.45), opengl);
+    }
+
+    /// Draws a message to the presenter in a bar at the top of the window.
+    /// 
+    /// Only gets drawn onto the window, so it never ends up in the video output.
+    fn render_message(message: &str, context: graphics::Context, opengl: &mut GlGraphics) {
+        use graphics::Transformed;
+
+        let view_size = context.get_view_size();
+        let font_size = (view_size[1] * 0.035).max(crate::render::font::FONT_SCALE.0 as f64);
+        let height = font_size * 1.9;
+
+        graphics::rectangle([0.55, 0.1, 0.1, 0.9], [0.0, 0.0, view_size[0], height], context.transform, opengl);
+
+        let fonts = crate::FONTS.get().unwrap();
+        let mut font = fonts.get("Default").unwrap().borrow_mut();
+        font.bold_font.draw(message, font_size, (1.0, 1.0, 1.0, 1.0), false, &context.trans(font_size * 0.5, font_size * 0.45), opengl);
+    }
+
+    /// Returns whether a quit key was pressed and quitting still needs to be confirmed.
+    fn quit_pending(&self) -> bool {
+        self.data.quit_pending.is_some_and(|pressed| pressed.elapsed().as_secs_f64() < QUIT_CONFIRM_TIMEOUT)
+    }
+
+    /// Returns whether the window should be closed.
+    pub fn should_quit(&self) -> bool {
+        self.data.quit
+    }
+
+    /// Handles text typed by the user.
+    /// 
+    /// Only used for entering the query of the search overlay.
+    pub fn text_input(&mut self, text: &str) {
+        // Borrow the app's data once, so the search state and the presentation can be borrowed
+        // at the same time.
+        let data = &mut *self.data;
+
+        if let Some(search) = &mut data.search {
+            search.query.extend(text.chars().filter(|c| !c.is_control()));
+            search.results = data.presentation.search(&search.query);
+            search.selected = 0;
+        }
+    }
+
+    /// Handles keys pressed while the search overlay is open.
+    fn search_input(&mut self, key: Key) {
+        let data = &mut *self.data;
+
+        let search = match &mut data.search {
+            Some(s) => s,
+            None => return
+        };
+
+        match key {
+            Key::Backspace => {
+                search.query.pop();
+                search.results = data.presentation.search(&search.query);
+                search.selected = 0;
+            },
+            Key::Up => search.selected = search.selected.saturating_sub(1),
+            Key::Down => search.selected = (search.selected + 1).min(search.results.len().saturating_sub(1)),
+            Key::Return => {
+                if let Some(result) = search.results.get(search.selected) {
+                    let slide = result.slide;
+                    self.data.presentation.go_to_slide(slide);
+                    self.slide_changed();
+                }
+                self.data.search = None;
+            },
+            Key::Escape => self.data.search = None,
+            _ => {}
+        }
+    }
+
+    /// Handles keys pressed while the overview is open.
+    fn overview_input(&mut self, key: Key) {
+        let bindings = &crate::CONFIG.get().unwrap().keybindings;
+        let overview = match &mut self.data.overview {
+            Some(o) => o,
+            None => return
+        };
+
+        match key {
+            Key::Left => overview.move_selection(-1, 0),
+            Key::Right => overview.move_selection(1, 0),
+            Key::Up => overview.move_selection(0, -1),
+            Key::Down => overview.move_selection(0, 1),
+            Key::Return => {
+                let slide = overview.selected();
+                self.data.overview = None;
+                self.data.presentation.go_to_slide(slide);
+                self.slide_changed();
+            },
+            key if key == Key::Escape || bindings.overview.contains(&key) => self.data.overview = None,
+            _ => {}
+        }
+    }
+
     /// Checks for input and updates the applications state accordingly.
+    /// 
+    /// The keys for each action are taken from the user configuration.
     pub fn input(&mut self, args: &ButtonArgs) -> bool {
-        match (args.button, args.state, self.data.last_press) {
-            (Button::Keyboard(Key::A | Key::Left), ButtonState::Press, (false, _, _)) => {
+        let config = crate::CONFIG.get().unwrap();
+        let bindings = &config.keybindings;
+
+        // Clicking a slide of the overview jumps to it
+        if let (Button::Mouse(MouseButton::Left), ButtonState::Press, Some(overview)) = (args.button, args.state, &self.data.overview) {
+            if let Some(index) = overview.slide_at(self.data.cursor, self.data.window_size) {
+                self.data.overview = None;
+                self.data.presentation.go_to_slide(index);
+                self.slide_changed();
+            }
+            return false
+        }
+
+        // Dragging with the left mouse button selects text
+        if let Button::Mouse(MouseButton::Left) = args.button {
+            match args.state {
+                ButtonState::Press => {
+                    self.data.pointer.clicked(self.data.cursor, &config.pointer_effects);
+                    self.data.selection = presentation::selection::glyph_at(self.data.cursor).map(|i| (i, i));
+                    self.data.selecting = self.data.selection.is_some();
+                },
+                ButtonState::Release => self.data.selecting = false
+            }
+            return false
+        }
+
+        let key = match args.button {
+            Button::Keyboard(key) => key,
+            _ => return false
+        };
+
+        if let Key::LCtrl | Key::RCtrl = key {
+            self.data.ctrl_held = args.state == ButtonState::Press;
+        }
+
+        // Ctrl+F opens or closes the search overlay
+        if key == Key::F && self.data.ctrl_held {
+            if args.state == ButtonState::Press {
+                self.data.search = match self.data.search {
+                    Some(_) => None,
+                    None => Some(Search { query: String::new(), results: Vec::new(), selected: 0 })
+                };
+            }
+            return false
+        }
+
+        // Ctrl+Q quits without asking for confirmation
+        if key == Key::Q && self.data.ctrl_held {
+            if args.state == ButtonState::Press {
+                self.data.quit = true;
+            }
+            return false
+        }
+
+        // Ctrl+C copies the selected text
+        if key == Key::C && self.data.ctrl_held {
+            if args.state == ButtonState::Press {
+                self.copy_selection();
+            }
+            return false
+        }
+
+        // While searching, all keys are used for the search overlay
+        if self.data.search.is_some() {
+            if args.state == ButtonState::Press {
+                self.search_input(key);
+            }
+            return false
+        }
+
+        // While the overview is open, the keys are used for selecting a slide in it
+        if self.data.overview.is_some() {
+            if args.state == ButtonState::Press {
+                self.overview_input(key);
+            }
+            return false
+        }
+
+        match (args.state, self.data.last_press) {
+            (ButtonState::Press, (false, _, _)) if bindings.previous_slide.contains(&key) => {
                 self.data.presentation.previous_slide();
-                self.data.time = 0.0;
+                self.slide_changed();
+                self.play_sound_effect(self.data.sounds.previous.clone());
                 self.data.last_press.0 = true;
             },
-            (Button::Keyboard(Key::A | Key::Left), ButtonState::Release, (true, _, _)) => {
+            (ButtonState::Release, (true, _, _)) if bindings.previous_slide.contains(&key) => {
                 self.data.last_press.0 = false;
             },
 
-            (Button::Keyboard(Key::D | Key::Right), ButtonState::Press, (_, false, _)) => {
+            (ButtonState::Press, (_, false, _)) if bindings.next_slide.contains(&key) => {
                 self.data.presentation.next_slide();
-                self.data.time = 0.0;
+                self.slide_changed();
+                self.play_sound_effect(self.data.sounds.next.clone());
                 self.data.last_press.1 = true;
             },
-            (Button::Keyboard(Key::D | Key::Right), ButtonState::Release, (_, true, _)) => {
+            (ButtonState::Release, (_, true, _)) if bindings.next_slide.contains(&key) => {
                 self.data.last_press.1 = false;
             },
-            (Button::Keyboard(Key::F11), ButtonState::Press, (_, _, false)) => {
+            (ButtonState::Press, (_, _, false)) if bindings.fullscreen.contains(&key) => {
                 self.data.last_press.2 = true;
                 return true
             },
-            (Button::Keyboard(Key::F11), ButtonState::Release, (_, _, true)) => {
+            (ButtonState::Release, (_, _, true)) if bindings.fullscreen.contains(&key) => {
                 self.data.last_press.2 = false;
             },
+            (ButtonState::Press, _) if bindings.quit.contains(&key) || (key == Key::Escape && config.exit_on_esc) => {
+                // Quit keys are easily hit by accident, so they always need to be confirmed
+                let confirm = bindings.quit.contains(&key) || config.confirm_quit;
+                match confirm && !self.quit_pending() {
+                    true => self.data.quit_pending = Some(Instant::now()),
+                    false => self.data.quit = true
+                }
+            },
+            (ButtonState::Press, _) if bindings.screenshot.contains(&key) => {
+                self.data.screenshot_requested = true;
+            },
+            (ButtonState::Press, _) if bindings.captions.contains(&key) => {
+                self.data.presentation.toggle_captions();
+            },
+            (ButtonState::Press, _) if bindings.pause_time.contains(&key) => {
+                self.data.paused = !self.data.paused;
+                log_info!("Slide time {} at {:.2}s", if self.data.paused { "paused" } else { "resumed" }, self.data.time);
+            },
+            (ButtonState::Press, _) if bindings.scrub_forward.contains(&key) => {
+                self.data.time += TIME_SCRUB_STEP;
+            },
+            (ButtonState::Press, _) if bindings.scrub_backward.contains(&key) => {
+                self.data.time = (self.data.time - TIME_SCRUB_STEP).max(0.0);
+            },
+            (ButtonState::Press, _) if bindings.restart_slide.contains(&key) => {
+                self.data.time = 0.0;
+            },
+            (ButtonState::Press, _) if bindings.step_frame.contains(&key) => {
+                self.data.paused = true;
+                self.data.time += FRAME_STEP;
+                log_info!("Slide time stepped to {:.3}s", self.data.time);
+            },
+            (ButtonState::Press, _) if bindings.slow_motion.contains(&key) => {
+                let current = TIME_SCALES.iter().position(|s| *s == self.data.time_scale).unwrap_or(0);
+                self.data.time_scale = TIME_SCALES[(current + 1) % TIME_SCALES.len()];
+                log_info!("Slide time running at {}x speed", self.data.time_scale);
+            },
+            (ButtonState::Press, _) if bindings.debug_outlines.contains(&key) => {
+                self.data.debug_outlines = !self.data.debug_outlines;
+            },
+            (ButtonState::Press, _) if bindings.color_filter.contains(&key) => {
+                self.data.color_filter = self.data.color_filter.next();
+                log_info!("Color filter: {}", self.data.color_filter.name());
+            },
+            (ButtonState::Press, _) if bindings.language.contains(&key) => {
+                self.data.presentation.next_language();
+            },
+            (ButtonState::Press, _) if bindings.teleprompter.contains(&key) => {
+                self.data.teleprompter = match self.data.teleprompter {
+                    Some(_) => None,
+                    None => Some(Teleprompter::new(config.words_per_minute))
+                };
+            },
+            (ButtonState::Press, _) if self.data.teleprompter.is_some() && bindings.teleprompter_faster.contains(&key) => {
+                let teleprompter = self.data.teleprompter.as_mut().unwrap();
+                teleprompter.faster();
+                log_info!("Teleprompter scrolling at {:.0} words per minute", teleprompter.speed());
+            },
+            (ButtonState::Press, _) if self.data.teleprompter.is_some() && bindings.teleprompter_slower.contains(&key) => {
+                let teleprompter = self.data.teleprompter.as_mut().unwrap();
+                teleprompter.slower();
+                log_info!("Teleprompter scrolling at {:.0} words per minute", teleprompter.speed());
+            },
+            (ButtonState::Press, _) if bindings.overview.contains(&key) => {
+                self.data.overview = Some(Overview::new(&self.data.presentation));
+            },
+            (ButtonState::Press, _) if bindings.mute.contains(&key) => {
+                if let Some(audio) = &mut self.data.audio {
+                    audio.toggle_mute();
+                }
+            },
+            (ButtonState::Press, _) => {
+                for (id, _) in bindings.toggle_objects.iter().filter(|(_, keys)| keys.contains(&key)) {
+                    presentation::toggle_object(id);
+                }
+
+                // Jump to a bookmarked slide, either by it's name or it's position
+                let bookmark = match bindings.bookmark_slots.iter().position(|k| *k == key) {
+                    Some(slot) => self.data.presentation.bookmarks().get(slot).map(|b| b.to_string()),
+                    None => bindings.bookmarks.iter().find(|(_, keys)| keys.contains(&key)).map(|(name, _)| name.clone())
+                };
+                if let Some(bookmark) = bookmark {
+                    match self.data.presentation.go_to_bookmark(&bookmark) {
+                        true => self.slide_changed(),
+                        false => log_warn!("There's no slide with the bookmark '{bookmark}'")
+                    }
+                }
+            },
             _ => {}
         }
 